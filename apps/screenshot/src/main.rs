@@ -0,0 +1,122 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+use alloc::format;
+use alloc::string::String;
+use core::time::Duration;
+use std::fs::File;
+use std::io::Write;
+use std::time::Instant;
+
+const OUTPUT_DIR: &str = "@0xE0/tmp";
+const DEFAULT_FRAMES: usize = 30;
+const DEFAULT_INTERVAL_MS: u64 = 100;
+
+struct Options {
+    window: Option<usize>,
+    record: bool,
+    frames: usize,
+    interval_ms: u64,
+}
+
+fn parse_args(argc: i32, argv: *const *const u8) -> Options {
+    let mut opts = Options { window: None, record: false, frames: DEFAULT_FRAMES, interval_ms: DEFAULT_INTERVAL_MS };
+
+    let mut args = alloc::vec::Vec::new();
+    for i in 1..argc {
+        let arg_ptr = unsafe { *argv.add(i as usize) };
+        let c_str = unsafe { core::ffi::CStr::from_ptr(arg_ptr as *const i8) };
+        args.push(String::from(c_str.to_string_lossy()));
+    }
+
+    let mut i = 0;
+    while i < args.len() {
+        let value = args.get(i + 1).and_then(|v| v.parse::<u64>().ok());
+        match args[i].as_str() {
+            "--window" => {
+                opts.window = value.map(|v| v as usize).filter(|&v| v != 0);
+                i += 1;
+            }
+            "--record" => opts.record = true,
+            "--frames" => {
+                opts.frames = value.unwrap_or(DEFAULT_FRAMES as u64) as usize;
+                i += 1;
+            }
+            "--interval" => {
+                opts.interval_ms = value.unwrap_or(DEFAULT_INTERVAL_MS);
+                i += 1;
+            }
+            other => std::println!("screenshot: ignoring unknown argument {}", other),
+        }
+        i += 1;
+    }
+    opts
+}
+
+fn timestamp() -> String {
+    let (d, m, y) = std::os::get_date();
+    let (hh, mm, ss) = std::os::get_time();
+    format!("{:04}{:02}{:02}-{:02}{:02}{:02}", y, m, d, hh, mm, ss)
+}
+
+fn save(path: &str, window: Option<usize>) -> bool {
+    let capture = match std::graphics::capture(window) {
+        Some(capture) => capture,
+        None => {
+            std::println!("screenshot: nothing to capture");
+            return false;
+        }
+    };
+
+    // Window buffers carry real transparency; the composed screen is always opaque.
+    let png = capture.to_png(window.is_some());
+    match File::create(path).and_then(|mut file| file.write_all(&png)) {
+        Ok(()) => true,
+        Err(_) => {
+            std::println!("screenshot: could not write {}", path);
+            false
+        }
+    }
+}
+
+/// Saves `frames` numbered PNGs into a new directory. Encoding takes time, so frames are
+/// paced against the start time and the rate drops rather than drifting when it falls behind.
+fn record(opts: &Options) {
+    let dir = format!("{}/recording-{}", OUTPUT_DIR, timestamp());
+    let _ = std::fs::create_dir(&dir);
+
+    let start = Instant::now();
+    let mut saved = 0;
+    for frame in 0..opts.frames {
+        let due = Duration::from_millis(opts.interval_ms * frame as u64);
+        let elapsed = start.elapsed();
+        if elapsed < due {
+            std::time::sleep(due - elapsed);
+        }
+
+        if save(&format!("{}/frame-{:04}.png", dir, frame), opts.window) {
+            saved += 1;
+        }
+    }
+    std::println!("screenshot: recorded {} frames to {}", saved, dir);
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn main(argc: i32, argv: *const *const u8) -> i32 {
+    let opts = parse_args(argc, argv);
+    let _ = std::fs::create_dir(OUTPUT_DIR);
+
+    if opts.record {
+        record(&opts);
+        return 0;
+    }
+
+    let path = format!("{}/screenshot-{}.png", OUTPUT_DIR, timestamp());
+    if save(&path, opts.window) {
+        std::println!("screenshot: saved {}", path);
+        0
+    } else {
+        1
+    }
+}
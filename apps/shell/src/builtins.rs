@@ -6,7 +6,7 @@ use std::io::{Read, Write};
 
 pub fn execute_builtin(cmd: &str, args: &[String], cwd: &mut String, path_env: &mut String, in_fd: usize, out_fd: usize) -> i32 {
     if cmd == "help" {
-        std::os::file_write(out_fd, b"Available commands: help, clear, ls, cd, pwd, touch, mkdir, rm, mv, cp, cat, sleep, osfetch, echo, export\n");
+        std::os::file_write(out_fd, b"Available commands: help, clear, ls, cd, pwd, touch, mkdir, rm, mv, cp, cat, sleep, osfetch, echo, export, mode, theme\n");
         return 0;
     } else if cmd == "export" {
         if !args.is_empty() {
@@ -172,6 +172,49 @@ pub fn execute_builtin(cmd: &str, args: &[String], cwd: &mut String, path_env: &
         }
         std::os::file_write(out_fd, b"\n");
         return 0;
+    } else if cmd == "mode" {
+        use std::graphics::ScanoutMode;
+
+        // mode [WIDTHxHEIGHT] [mirror|span]
+        let mut size = (0, 0);
+        let mut scanouts = ScanoutMode::Primary;
+        for arg in args {
+            match arg.as_str() {
+                "mirror" => scanouts = ScanoutMode::Mirror,
+                "span" => scanouts = ScanoutMode::Span,
+                _ => match arg.split_once('x').and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?))) {
+                    Some(parsed) => size = parsed,
+                    None => {
+                        std::os::file_write(out_fd, b"usage: mode [WIDTHxHEIGHT] [mirror|span]\n");
+                        return 1;
+                    }
+                },
+            }
+        }
+
+        if !std::graphics::set_display_mode(size.0, size.1, scanouts) {
+            std::os::file_write(out_fd, b"mode: could not set display mode\n");
+            return 1;
+        }
+        let current = format!("{}x{}\n", std::graphics::get_screen_width(), std::graphics::get_screen_height());
+        std::os::file_write(out_fd, current.as_bytes());
+        return 0;
+    } else if cmd == "theme" {
+        // theme [NAME]
+        let Some(name) = args.first() else {
+            let current = format!("{}\n", std::graphics::get_theme());
+            std::os::file_write(out_fd, current.as_bytes());
+            return 0;
+        };
+        if std::fs::File::open(&format!("/sys/themes/{}.theme", name)).is_err() {
+            std::os::file_write(out_fd, format!("theme: no theme named {}\n", name).as_bytes());
+            return 1;
+        }
+        if !std::graphics::set_theme(name) {
+            std::os::file_write(out_fd, b"theme: could not set theme\n");
+            return 1;
+        }
+        return 0;
     } else if cmd == "sleep" {
         if !args.is_empty() {
             if let Ok(ms) = args[0].parse::<u64>() {
@@ -3,10 +3,35 @@
 
 extern crate alloc;
 use alloc::format;
-use inkui::{Color, Display, Size, Widget, Window};
+use alloc::string::String;
+use core::time::Duration;
+use inkui::{Color, Display, Event, Size, State, Widget, Window};
 use std::fs::File;
+use std::graphics::Items;
 use std::io::Read;
 
+const TASKS_ID: usize = 4;
+const WORKSPACES_ID: usize = 5;
+/// Task buttons use this plus the id of the window they stand for.
+const TASK_ID_BASE: usize = 1000;
+/// Workspace buttons use this plus the workspace index.
+const WORKSPACE_ID_BASE: usize = 100;
+const WORKSPACE_WIDTH: usize = 22;
+const TASK_WIDTH: usize = 160;
+const TASK_GAP: usize = 6;
+const TASK_TITLE_LEN: usize = 18;
+
+#[derive(Clone, Copy, Default)]
+struct Bar {
+    font_size: f32,
+    /// Window Alt+Tab is about to activate, highlighted until the switch ends.
+    switch_target: usize,
+    /// Last app window with focus. Clicking the taskbar focuses the bar itself, so the
+    /// `focused` flag of the window list is no help here.
+    active_task: usize,
+    workspace: usize,
+}
+
 fn open_start_menu(_win: &mut Window, _id: usize) {
     std::os::print("Start Menu Clicked\n");
 }
@@ -19,6 +44,134 @@ fn wifi_status(_win: &mut Window, _id: usize) {
     std::os::print("Wifi Clicked\n");
 }
 
+/// Clicking a task focuses its window, or minimizes it when it already has focus.
+fn task_clicked(bar: &State<Bar>, wid: usize) {
+    let minimized = std::graphics::list_windows().iter().any(|w| w.id == wid && w.minimized);
+    if wid == bar.get().active_task && !minimized {
+        std::graphics::minimize_window(wid);
+    } else {
+        std::graphics::activate_window(wid);
+    }
+}
+
+fn task_label(title: &str) -> String {
+    let title = if title.is_empty() { "Untitled" } else { title };
+    if title.chars().count() > TASK_TITLE_LEN {
+        let mut short: String = title.chars().take(TASK_TITLE_LEN - 1).collect();
+        short.push('…');
+        short
+    } else {
+        String::from(title)
+    }
+}
+
+fn rebuild_tasks(win: &mut Window, bar: &State<Bar>) {
+    let own_id = win.id;
+    let Bar { font_size, switch_target, active_task: active, workspace } = bar.get();
+
+    let all: alloc::vec::Vec<_> = std::graphics::list_windows()
+        .into_iter()
+        .filter(|w| w.w_type == Items::Window && w.id != own_id)
+        .collect();
+
+    if let Some(children) = win.find_widget_by_id_mut(WORKSPACES_ID).and_then(|strip| strip.get_children_mut()) {
+        children.clear();
+        for n in 0..std::graphics::WORKSPACE_COUNT {
+            let background = if n == workspace { Color::rgb(90, 70, 200) } else { Color::rgba(0, 0, 0, 0) };
+            let occupied = all.iter().any(|w| w.workspace == n);
+            let text_color = if occupied || n == workspace { Color::rgb(255, 255, 255) } else { Color::rgb(110, 110, 110) };
+
+            let button = Widget::button(WORKSPACE_ID_BASE + n, &format!("{}", n + 1))
+                .x(Size::Absolute(n * WORKSPACE_WIDTH))
+                .width(Size::Absolute(WORKSPACE_WIDTH))
+                .height(Size::Relative(100))
+                .background_color(background)
+                .set_text_color(text_color)
+                .set_text_size(font_size)
+                .on_click(move |_, _| {
+                    std::graphics::switch_workspace(n);
+                });
+            children.push(button);
+        }
+    }
+
+    let mut windows: alloc::vec::Vec<_> = all.into_iter().filter(|w| w.workspace == workspace).collect();
+    windows.sort_by_key(|w| w.id);
+
+    let strip_width = match win.find_widget_by_id(TASKS_ID) {
+        Some(strip) => strip.get_width(),
+        None => return,
+    };
+    let count = windows.len().max(1);
+    let width = TASK_WIDTH.min(strip_width.saturating_sub(TASK_GAP * (count - 1)) / count);
+
+    if let Some(children) = win.find_widget_by_id_mut(TASKS_ID).and_then(|strip| strip.get_children_mut()) {
+        children.clear();
+        for (i, w) in windows.iter().enumerate() {
+            let background = if w.id == switch_target {
+                Color::rgb(90, 70, 200)
+            } else if w.id == active {
+                Color::rgb(70, 70, 70)
+            } else {
+                Color::rgb(40, 40, 40)
+            };
+            let text_color = if w.minimized { Color::rgb(140, 140, 140) } else { Color::rgb(255, 255, 255) };
+
+            let wid = w.id;
+            let state = bar.clone();
+            let button = Widget::button(TASK_ID_BASE + w.id, &task_label(w.title()))
+                .x(Size::Absolute(i * (width + TASK_GAP)))
+                .width(Size::Absolute(width))
+                .height(Size::Relative(100))
+                .background_color(background)
+                .set_text_color(text_color)
+                .set_text_size(font_size)
+                .on_click(move |_, _| task_clicked(&state, wid));
+            children.push(button);
+        }
+    }
+
+    win.draw();
+    win.update();
+}
+
+fn on_window_event(win: &mut Window, event: &Event, bar: &State<Bar>) {
+    match event {
+        Event::WindowSwitch(e) => {
+            bar.update(|b| b.switch_target = e.subject as usize);
+            rebuild_tasks(win, bar);
+        }
+        Event::WindowFocused(e) => {
+            if e.subject as usize != win.id {
+                bar.update(|b| b.active_task = e.subject as usize);
+                rebuild_tasks(win, bar);
+            }
+        }
+        Event::WindowDestroyed(e) => {
+            bar.update(|b| {
+                if b.active_task == e.subject as usize {
+                    b.active_task = 0;
+                }
+            });
+            rebuild_tasks(win, bar);
+        }
+        Event::WindowCreated(_) | Event::WindowChanged(_) => {
+            rebuild_tasks(win, bar);
+        }
+        Event::WorkspaceChanged(e) => {
+            bar.update(|b| b.workspace = e.subject as usize);
+            rebuild_tasks(win, bar);
+        }
+        Event::ScreenResized(e) => {
+            // Only the width follows the screen; the layout inside is sized off the height.
+            let height = win.height;
+            win.set_bounds(0, 0, e.width, height);
+            rebuild_tasks(win, bar);
+        }
+        _ => {}
+    }
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn main() -> i32 {
     let screen_w = std::graphics::get_screen_width();
@@ -51,6 +204,7 @@ pub extern "C" fn main() -> i32 {
 
     let unit = screen_h as f32 / 8.0;
     let font_size = unit * 4.0;
+    let bar = State::new(Bar { font_size, ..Bar::default() });
 
     let l = Widget::label(2, " \u{E8F0}  Guest | ")
         .y(Size::Absolute((unit) as usize))
@@ -68,25 +222,54 @@ pub extern "C" fn main() -> i32 {
         .set_text_size(font_size);
 
     root = root.add_child(clock);
+
+    let workspaces = Widget::frame(WORKSPACES_ID)
+        .x(Size::Relative(24))
+        .y(Size::Absolute(unit as usize))
+        .width(Size::Absolute(WORKSPACE_WIDTH * std::graphics::WORKSPACE_COUNT))
+        .height(Size::Absolute((unit * 6.0) as usize))
+        .background_color(Color::rgba(0, 0, 0, 0));
+
+    root = root.add_child(workspaces);
+
+    let tasks = Widget::frame(TASKS_ID)
+        .x(Size::Relative(56))
+        .y(Size::Absolute(unit as usize))
+        .width(Size::Relative(42))
+        .height(Size::Absolute((unit * 6.0) as usize))
+        .background_color(Color::rgba(0, 0, 0, 0));
+
+    root = root.add_child(tasks);
     win.children.push(root);
+    let state = bar.clone();
+    win.set_on_event(move |win, event| on_window_event(win, event, &state));
     win.show();
+    win.watch_windows(true);
+    if let Some(focused) = std::graphics::list_windows().iter().find(|w| w.focused) {
+        bar.update(|b| b.active_task = focused.id);
+    }
+    bar.update(|b| b.workspace = std::graphics::current_workspace());
+    win.draw();
+    rebuild_tasks(&mut win, &bar);
 
-    let mut last_minute = 99;
+    update_clock(&mut win);
+    win.present();
+    win.set_interval(Duration::from_secs(1), update_clock);
 
     loop {
-        let (h, m, _) = std::os::get_time();
-        if m != last_minute {
-            last_minute = m;
-            let time_str = format!("{:02}:{:02}", h, m);
-
-            if let Some(widget) = win.find_widget_by_id_mut(3) {
-                if let Widget::Label { text, .. } = widget {
-                    text.text = time_str;
-                }
-            }
-            win.draw();
-            win.update();
+        win.event_loop();
+    }
+}
+
+fn update_clock(win: &mut Window) {
+    let (h, m, _) = std::os::get_time();
+    let time_str = format!("{:02}:{:02}", h, m);
+
+    if let Some(Widget::Label { text, .. }) = win.find_widget_by_id_mut(3) {
+        if text.text == time_str {
+            return;
         }
-        std::os::sleep(1000);
+        text.text = time_str;
     }
+    win.invalidate(3);
 }
\ No newline at end of file
@@ -217,6 +217,20 @@ impl TerminalBuffer {
         s
     }
 
+    /// Plain text of the active screen, without colours or trailing blanks.
+    pub fn text(&self) -> String {
+        let current = if self.is_alt { &self.alt_lines } else { &self.lines };
+        let mut s = String::new();
+        for (i, line) in current.iter().enumerate() {
+            if i > 0 {
+                s.push('\n');
+            }
+            let text: String = line.iter().map(|cell| cell.c).collect();
+            s.push_str(text.trim_end());
+        }
+        s
+    }
+
     pub fn switch_screen(&mut self, alt: bool) {
         if self.is_alt != alt {
             std::debugln!("[term] Switching to {} screen", if alt { "alternate" } else { "main" });
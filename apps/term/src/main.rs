@@ -18,6 +18,12 @@ use crate::types::{Cell, TermAction};
 static mut TERM_READ_FD: usize = 0;
 static mut TERM_WRITE_FD: usize = 0;
 
+const KEY_SHIFT: u32 = 0x110007;
+/// Ctrl+C and Ctrl+V as the keyboard driver reports them; with Shift held they copy and
+/// paste instead of reaching the shell.
+const CTRL_C: u32 = 0x03;
+const CTRL_V: u32 = 0x16;
+
 fn update_term_size(win: &Window) {
     if let Some(widget) = win.find_widget_by_id(2) {
         if let inkui::widget::Widget::Label { text, geometry, .. } = widget {
@@ -139,6 +145,7 @@ pub extern "C" fn main() -> i32 {
 
     let mut term_buffer = TerminalBuffer::new();
     let mut pipe_buf = [0u8; 4096];
+    let mut shift_held = false;
 
     loop {
         let mut did_work = false;
@@ -157,7 +164,15 @@ pub extern "C" fn main() -> i32 {
         for event in events.iter() {
             match event {
                 Event::Keyboard(e) => {
-                    if e.pressed {
+                    if e.key == KEY_SHIFT {
+                        shift_held = e.pressed;
+                    } else if e.pressed && shift_held && e.key == CTRL_C {
+                        win.copy_text(&term_buffer.text());
+                    } else if e.pressed && shift_held && e.key == CTRL_V {
+                        if let Some(text) = win.paste_text() {
+                            std::os::file_write(unsafe { TERM_WRITE_FD }, text.as_bytes());
+                        }
+                    } else if e.pressed {
                         if let Some(c) = core::char::from_u32(e.key) {
                             for _ in 0..e.repeat {
                                 let mut buf = [0u8; 4];
@@ -196,6 +211,9 @@ pub extern "C" fn main() -> i32 {
                     update_term_size(&win);
                     needs_redraw = true;
                 }
+                Event::CloseRequested(_) | Event::Close(_) => {
+                    win.close();
+                }
                 _ => {}
             }
         }
@@ -424,7 +442,8 @@ pub extern "C" fn main() -> i32 {
         }
 
         if !did_work {
-            std::os::yield_task();
+            // The shell's output arrives over a pipe, so only sleep briefly on window input.
+            std::graphics::wait_events(win.id, 0, 0, Some(10_000_000));
         }
     }
 }
@@ -0,0 +1,53 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+use alloc::string::String;
+use alloc::vec::Vec;
+use std::audio::AudioStream;
+use std::math::FloatMath;
+
+const RATE: u32 = 48000;
+const DEFAULT_FREQUENCY: u32 = 440;
+const DEFAULT_SECONDS: u32 = 2;
+const AMPLITUDE: f64 = 8000.0;
+const PI: f64 = 3.14159265358979323846;
+
+#[unsafe(no_mangle)]
+pub extern "C" fn main(argc: i32, argv: *const *const u8) -> i32 {
+    let mut args = Vec::new();
+    for i in 1..argc {
+        let arg_ptr = unsafe { *argv.add(i as usize) };
+        let c_str = unsafe { core::ffi::CStr::from_ptr(arg_ptr as *const i8) };
+        args.push(String::from(c_str.to_string_lossy()));
+    }
+    let frequency = args.first().and_then(|a| a.parse::<u32>().ok()).unwrap_or(DEFAULT_FREQUENCY);
+    let seconds = args.get(1).and_then(|a| a.parse::<u32>().ok()).unwrap_or(DEFAULT_SECONDS);
+
+    let mut stream = match AudioStream::open(RATE, 2) {
+        Ok(stream) => stream,
+        Err(_) => {
+            std::println!("tone: no audio device");
+            return 1;
+        }
+    };
+
+    // One second at a time, left and right interleaved.
+    let mut samples = Vec::with_capacity(RATE as usize * 2);
+    let mut n: u64 = 0;
+    for _ in 0..seconds {
+        samples.clear();
+        for _ in 0..RATE {
+            let t = (n % RATE as u64) as f64 / RATE as f64;
+            let sample = ((2.0 * PI * frequency as f64 * t).sin() * AMPLITUDE) as i16;
+            samples.push(sample);
+            samples.push(sample);
+            n += 1;
+        }
+        if stream.write_samples(&samples).is_err() {
+            std::println!("tone: write failed");
+            return 1;
+        }
+    }
+    0
+}
@@ -0,0 +1,33 @@
+use inkui::{Headless, Theme, Widget, Window};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+/// The font KrakeOS ships, for tests that look at text.
+pub const FONT: &[u8] = include_bytes!("../../../tree/sys/fonts/CaskaydiaNerd.ttf");
+
+/// A window on its own headless screen, in the built-in theme whatever the host has.
+pub fn window(width: usize, height: usize, children: Vec<Widget>) -> Window {
+    let mut win = Window::with_platform("test", width, height, Rc::new(Headless::new(800, 600)));
+    win.set_theme(Theme::default());
+    win.children = children;
+    win
+}
+
+/// Lays out and paints the whole window.
+pub fn render(win: &mut Window) -> &[u32] {
+    win.draw();
+    win.pixels()
+}
+
+/// Renders `win` and compares it with `tests/golden/<name>.ppm`.
+pub fn assert_golden(name: &str, win: &mut Window) {
+    let (width, height) = (win.width, win.height);
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    golden::assert_golden(&dir, name, width, height, render(win));
+}
+
+/// Where widget `id` was laid out, as `(x, y, width, height)`.
+pub fn bounds(win: &Window, id: usize) -> (usize, usize, usize, usize) {
+    let geometry = win.find_widget_by_id(id).unwrap_or_else(|| panic!("no widget {}", id)).geometry();
+    (geometry.x, geometry.y, geometry.width, geometry.height)
+}
@@ -0,0 +1,106 @@
+use inkui::event::{Event, MouseEvent};
+use inkui::{FileChooser, Headless, Platform, Theme, Window};
+use inkui_host_tests::FONT;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Where the first row of the file list starts, inside its border.
+const LIST_TOP: usize = 69;
+const ROW_HEIGHT: usize = 22;
+/// The id the headless platform gives the dialog, the second window shown.
+const DIALOG: u32 = 2;
+
+fn app(headless: &Rc<Headless>) -> Window {
+    let mut win = Window::with_platform("test", 200, 100, headless.clone());
+    win.set_theme(Theme::default());
+    win.load_font(FONT);
+    win.show();
+    win
+}
+
+/// Opens `chooser` over `win`, returning where its answer will be.
+fn choose(win: &mut Window, chooser: FileChooser) -> Rc<RefCell<Option<Option<String>>>> {
+    let answer = Rc::new(RefCell::new(None));
+    let slot = answer.clone();
+    win.choose_file(chooser, move |_, path| *slot.borrow_mut() = Some(path));
+    answer
+}
+
+fn click(win: &mut Window, headless: &Headless, x: usize, y: usize) {
+    for down in [true, false] {
+        headless.push_event(Event::Mouse(MouseEvent { wid: DIALOG, x, y, buttons: [down, false, false], scroll: 0 }));
+    }
+    let events = win.poll_events();
+    win.dispatch(&events);
+}
+
+/// Selects row `row` of the dialog's list.
+fn click_row(win: &mut Window, headless: &Headless, row: usize) {
+    click(win, headless, 60, LIST_TOP + row * ROW_HEIGHT + ROW_HEIGHT / 2);
+}
+
+/// Clicks the dialog's Open button, at the right of its bottom row.
+fn click_open(win: &mut Window, headless: &Headless) {
+    click(win, headless, 296, 356);
+}
+
+#[test]
+fn file_chooser_lists_folders_then_matching_files() {
+    let headless = Rc::new(Headless::new(800, 600));
+    for path in ["/home/user/b.txt", "/home/user/a.png", "/home/user/c.PNG", "/home/user/docs/notes.txt"] {
+        headless.add_file(path, b"");
+    }
+    let mut win = app(&headless);
+    let answer = choose(&mut win, FileChooser::open().directory("/home/user").filter(&["png"]));
+
+    // "../", "docs/", "a.png", then "c.PNG" with b.txt filtered out.
+    click_row(&mut win, &headless, 3);
+    click_open(&mut win, &headless);
+    assert_eq!(*answer.borrow(), Some(Some(String::from("/home/user/c.PNG"))));
+}
+
+#[test]
+fn file_chooser_opens_folders() {
+    let headless = Rc::new(Headless::new(800, 600));
+    headless.add_file("/home/user/docs/notes.txt", b"");
+    assert!(headless.create_dir("/home/user/archive"));
+    assert!(!headless.create_dir("/home/user/archive"), "the folder is already there");
+    assert!(!headless.create_dir("/tmp/a/b"), "there is no /tmp/a to make it in");
+    let mut win = app(&headless);
+    let answer = choose(&mut win, FileChooser::open().directory("/home/user"));
+
+    // "../", "archive/", "docs/", then inside docs "../" and "notes.txt".
+    click_row(&mut win, &headless, 2);
+    click_open(&mut win, &headless);
+    assert_eq!(*answer.borrow(), None, "opening a folder doesn't answer");
+    click_row(&mut win, &headless, 1);
+    click_open(&mut win, &headless);
+    assert_eq!(*answer.borrow(), Some(Some(String::from("/home/user/docs/notes.txt"))));
+}
+
+#[test]
+fn file_chooser_saves_the_typed_name_over_a_highlighted_folder() {
+    let headless = Rc::new(Headless::new(800, 600));
+    headless.add_file("/home/user/docs/notes.txt", b"");
+    let mut win = app(&headless);
+    let answer = choose(&mut win, FileChooser::save("report.txt").directory("/home/user"));
+
+    // "../", then "docs/".
+    click_row(&mut win, &headless, 1);
+    click_open(&mut win, &headless);
+    assert_eq!(*answer.borrow(), Some(Some(String::from("/home/user/report.txt"))));
+}
+
+#[test]
+fn file_chooser_saves_into_a_double_clicked_folder() {
+    let headless = Rc::new(Headless::new(800, 600));
+    headless.add_file("/home/user/docs/notes.txt", b"");
+    let mut win = app(&headless);
+    let answer = choose(&mut win, FileChooser::save("report.txt").directory("/home/user"));
+
+    click_row(&mut win, &headless, 1);
+    click_row(&mut win, &headless, 1);
+    assert_eq!(*answer.borrow(), None, "opening a folder doesn't answer");
+    click_open(&mut win, &headless);
+    assert_eq!(*answer.borrow(), Some(Some(String::from("/home/user/docs/report.txt"))));
+}
@@ -0,0 +1,113 @@
+use inkui::{Display, FlexDirection, Size, Widget};
+use inkui_host_tests::{bounds, render, window};
+
+fn boxed(id: usize, width: usize, height: usize) -> Widget {
+    Widget::frame(id).width(Size::Absolute(width)).height(Size::Absolute(height))
+}
+
+/// A frame filling its parent, which widgets are not by default.
+fn filling(id: usize) -> Widget {
+    Widget::frame(id).width(Size::Auto).height(Size::Auto)
+}
+
+fn flex(direction: FlexDirection, wrap: bool, children: Vec<Widget>) -> Widget {
+    children.into_iter().fold(filling(1).set_display(Display::Flex { direction, wrap }), Widget::add_child)
+}
+
+#[test]
+fn flex_row_places_children_side_by_side() {
+    let mut win = window(300, 100, vec![flex(FlexDirection::Row, false, vec![
+        boxed(2, 80, 30),
+        boxed(3, 60, 40),
+        boxed(4, 80, 20),
+    ])]);
+    render(&mut win);
+
+    assert_eq!(bounds(&win, 2), (0, 0, 80, 30));
+    assert_eq!(bounds(&win, 3), (80, 0, 60, 40));
+    assert_eq!(bounds(&win, 4), (140, 0, 80, 20));
+}
+
+#[test]
+fn flex_row_wraps_below_the_tallest_child() {
+    let mut win = window(200, 100, vec![flex(FlexDirection::Row, true, vec![
+        boxed(2, 80, 30),
+        boxed(3, 80, 40),
+        boxed(4, 80, 20),
+    ])]);
+    render(&mut win);
+
+    assert_eq!(bounds(&win, 2), (0, 0, 80, 30));
+    assert_eq!(bounds(&win, 3), (80, 0, 80, 40));
+    assert_eq!(bounds(&win, 4), (0, 40, 80, 20));
+}
+
+#[test]
+fn flex_column_stacks_children() {
+    let mut win = window(200, 100, vec![flex(FlexDirection::Column, false, vec![
+        boxed(2, 80, 30),
+        boxed(3, 120, 20),
+    ])]);
+    render(&mut win);
+
+    assert_eq!(bounds(&win, 2), (0, 0, 80, 30));
+    assert_eq!(bounds(&win, 3), (0, 30, 120, 20));
+}
+
+#[test]
+fn flex_spaces_children_by_margin_and_padding() {
+    let container = flex(FlexDirection::Row, false, vec![
+        boxed(2, 50, 20).margin(Size::Absolute(4)),
+        boxed(3, 50, 20).margin(Size::Absolute(4)),
+    ]).padding(Size::Absolute(10));
+    let mut win = window(200, 100, vec![container]);
+    render(&mut win);
+
+    assert_eq!(bounds(&win, 2), (14, 14, 50, 20));
+    assert_eq!(bounds(&win, 3), (72, 14, 50, 20));
+}
+
+#[test]
+fn grid_splits_the_frame_into_cells() {
+    let grid = (2..6).fold(
+        filling(1).set_display(Display::Grid { rows: 2, cols: 2 }),
+        |grid, id| grid.add_child(filling(id)),
+    );
+    let mut win = window(200, 100, vec![grid]);
+    render(&mut win);
+
+    assert_eq!(bounds(&win, 2), (0, 0, 100, 50));
+    assert_eq!(bounds(&win, 3), (100, 0, 100, 50));
+    assert_eq!(bounds(&win, 4), (0, 50, 100, 50));
+    assert_eq!(bounds(&win, 5), (100, 50, 100, 50));
+}
+
+#[test]
+fn grid_sizes_children_within_their_cells() {
+    let grid = filling(1)
+        .padding(Size::Absolute(10))
+        .set_display(Display::Grid { rows: 1, cols: 3 })
+        .add_child(filling(2).width(Size::Relative(50)))
+        .add_child(boxed(3, 20, 10))
+        .add_child(filling(4).x(Size::FromRight(0)).width(Size::Absolute(30)));
+    let mut win = window(320, 100, vec![grid]);
+    render(&mut win);
+
+    assert_eq!(bounds(&win, 2), (10, 10, 50, 80));
+    assert_eq!(bounds(&win, 3), (110, 10, 20, 10));
+    assert_eq!(bounds(&win, 4), (280, 10, 30, 80));
+}
+
+#[test]
+fn edges_and_percentages_place_children() {
+    let mut win = window(300, 100, vec![
+        Widget::frame(1).x(Size::FromRight(10)).y(Size::FromDown(5)).width(Size::Absolute(50)).height(Size::Absolute(20)),
+        Widget::frame(2).x(Size::Relative(50)).y(Size::Relative(25)).width(Size::Relative(10)).height(Size::Relative(50)),
+        Widget::frame(3).x(Size::FromLeft(7)).y(Size::FromUp(3)).width(Size::Absolute(1000)).height(Size::Auto),
+    ]);
+    render(&mut win);
+
+    assert_eq!(bounds(&win, 1), (240, 75, 50, 20));
+    assert_eq!(bounds(&win, 2), (150, 25, 30, 50));
+    assert_eq!(bounds(&win, 3), (7, 3, 300, 100));
+}
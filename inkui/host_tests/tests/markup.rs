@@ -0,0 +1,114 @@
+use inkui::markup::{load, parse};
+use inkui::{Bindings, Color, Display, FlexDirection, Headless, MarkupError, Size, State, Widget};
+use inkui_host_tests::{bounds, render, window};
+
+fn headless() -> Headless {
+    Headless::new(800, 600)
+}
+
+fn tree(source: &str) -> Vec<Widget> {
+    parse(&headless(), source, &Bindings::new()).unwrap_or_else(|e| panic!("{}", e))
+}
+
+fn error(source: &str) -> MarkupError {
+    match parse(&headless(), source, &Bindings::new()) {
+        Ok(_) => panic!("`{}` parsed", source),
+        Err(e) => e,
+    }
+}
+
+fn assert_error(source: &str, line: usize, column: usize, message: &str) {
+    let e = error(source);
+    assert_eq!((e.line, e.column, e.message.as_str()), (line, column, message), "{}", source);
+}
+
+#[test]
+fn nested_elements_become_nested_widgets() {
+    let widgets = tree(r#"
+        <!-- the toolbar -->
+        <frame id="1" display="flex" direction="column">
+            <text-area id="2">Name &amp; path</text-area>
+            <frame id="3">
+                <button id="4" text="Go" />
+            </frame>
+        </frame>
+        <list id="5" selected="1"><option>a</option><option> b </option></list>
+    "#);
+
+    assert_eq!(widgets.iter().map(Widget::get_id).collect::<Vec<_>>(), [1, 5]);
+    let outer = widgets[0].get_children().unwrap();
+    assert_eq!(outer.iter().map(Widget::get_id).collect::<Vec<_>>(), [2, 3]);
+    assert_eq!(outer[0].get_text(), "Name & path");
+    let inner = outer[1].get_children().unwrap();
+    assert_eq!(inner.iter().map(Widget::get_id).collect::<Vec<_>>(), [4]);
+    assert!(matches!(widgets[0].get_display(), Display::Flex { direction: FlexDirection::Column, wrap: false }));
+    assert_eq!(widgets[1].selected(), Some(1));
+}
+
+#[test]
+fn attributes_set_sizes_and_styles() {
+    let widgets = tree(r##"
+        <label id="1" x="10" y="20px" width="120" height="50%" background="#ff0000" text-color="#00ff00">Hi</label>
+    "##);
+    let label = &widgets[0];
+    assert!(matches!(label.get_user_width(), Size::Absolute(120)));
+    assert!(matches!(label.get_user_height(), Size::Relative(50)));
+    assert_eq!(label.background(), Some(Color::rgb(255, 0, 0)));
+    assert_eq!(label.text_color(), Some(Color::rgb(0, 255, 0)));
+
+    let mut win = window(300, 200, widgets);
+    render(&mut win);
+    assert_eq!(bounds(&win, 1), (10, 20, 120, 100));
+}
+
+#[test]
+fn handlers_are_bound_by_name() {
+    let clicks = State::new(0);
+    let counter = clicks.clone();
+    let bindings = Bindings::new().action("save", move |_, id| counter.update(|n| *n += id));
+    let widgets = parse(&headless(), r#"<button id="7" on-click="save">Save</button>"#, &bindings).unwrap();
+
+    let mut win = window(100, 50, widgets);
+    let click = win.find_widget_by_id(7).unwrap().handlers().click.clone().expect("no click handler");
+    click.call(&mut win, 7, ());
+    assert_eq!(clicks.get(), 7);
+
+    let e = parse(&headless(), r#"<button on-click="load" />"#, &bindings).err().unwrap();
+    assert_eq!((e.line, e.column, e.message.as_str()), (1, 9, "no handler named `load`"));
+    assert!(parse(&headless(), r#"<button on-click="load" />"#, &Bindings::new().lenient()).is_ok());
+}
+
+#[test]
+fn unclosed_tags_point_at_their_opening() {
+    assert_error("<frame>\n  <frame>\n  </frame>\n", 1, 1, "`<frame>` is never closed");
+    assert_error("<frame>\n  <label>hi</frame>", 2, 12, "expected `</label>`, found `</frame>`");
+    assert_error("\n<!-- left open", 2, 1, "comment is never closed");
+    assert_error("<button text=\"Go />", 1, 9, "value of `text` is never closed");
+}
+
+#[test]
+fn unknown_attributes_point_at_the_attribute() {
+    assert_error("<frame>\n    <button id=\"1\" colour=\"red\" />\n</frame>", 2, 20, "`<button>` has no attribute `colour`");
+    assert_error("<frame width=\"wide\" />", 1, 8, "`wide` isn't a size");
+    assert_error("<blink />", 1, 1, "unknown widget `<blink>`");
+}
+
+#[test]
+fn bad_entities_point_at_the_entity() {
+    assert_error("<label>\n  a &bogus; b</label>", 2, 5, "unknown entity `&bogus;`");
+    assert_error("<label>a & b</label>", 1, 10, "`&` must start an entity such as `&amp;`");
+    assert_error("<button text=\"a &lt; &nope;\" />", 1, 22, "unknown entity `&nope;`");
+    assert_error("<label id=\"1\"\n  text=\"x\ny &zz;\" />", 3, 3, "unknown entity `&zz;`");
+}
+
+#[test]
+fn files_are_read_through_the_platform() {
+    let platform = headless();
+    platform.add_file("/apps/ui/main.ui", br#"<frame id="1"><button id="2" text="Go" /></frame>"#);
+    let widgets = load(&platform, "/apps/ui/main.ui", &Bindings::new()).unwrap();
+    assert_eq!(widgets[0].get_children().unwrap()[0].get_id(), 2);
+
+    let e = load(&platform, "/apps/ui/missing.ui", &Bindings::new()).err().unwrap();
+    assert_eq!(e.to_string(), "can't read /apps/ui/missing.ui");
+    assert_error("<frame>\n  <image src=\"/apps/ui/logo.png\" />\n</frame>", 2, 10, "can't read /apps/ui/logo.png");
+}
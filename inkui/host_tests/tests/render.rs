@@ -0,0 +1,155 @@
+use inkui::{Align, Color, Display, FlexDirection, GradientDirection, LinearGradient, Size, Widget};
+use inkui_host_tests::{assert_golden, bounds, render, window, FONT};
+
+const RED: Color = Color::rgb(208, 64, 64);
+const BLUE: Color = Color::rgb(64, 96, 208);
+const GREEN: Color = Color::rgb(48, 160, 80);
+const YELLOW: Color = Color::rgb(224, 192, 48);
+
+fn rgb(pixel: u32) -> u32 {
+    pixel & 0x00FF_FFFF
+}
+
+fn hex(color: Color) -> u32 {
+    (color.r as u32) << 16 | (color.g as u32) << 8 | color.b as u32
+}
+
+fn panel(id: usize, x: usize, y: usize, width: usize, height: usize) -> Widget {
+    Widget::frame(id)
+        .x(Size::Absolute(x))
+        .y(Size::Absolute(y))
+        .width(Size::Absolute(width))
+        .height(Size::Absolute(height))
+}
+
+#[test]
+fn gradients_run_in_each_direction() {
+    let directions = [
+        GradientDirection::Horizontal,
+        GradientDirection::Vertical,
+        GradientDirection::Diagonal,
+        GradientDirection::DiagonalAlt,
+        GradientDirection::Custom { angle: 30.0 },
+    ];
+    let children = directions.iter().enumerate().map(|(i, &direction)| {
+        panel(i + 1, 4 + (i % 3) * 30, 4 + (i / 3) * 30, 26, 26)
+            .background_gradient(LinearGradient::new(RED, BLUE, direction))
+    }).collect();
+    let mut win = window(96, 64, children);
+
+    let pixels = render(&mut win);
+    let at = |x: usize, y: usize| rgb(pixels[y * 96 + x]);
+    assert_eq!(at(4, 4), at(4, 29), "a horizontal gradient is the same down each column");
+    assert_ne!(at(4, 4), at(29, 4));
+    assert_eq!(at(34, 4), at(59, 4), "a vertical gradient is the same along each row");
+    assert_ne!(at(34, 4), at(34, 29));
+    assert_golden("gradients", &mut win);
+}
+
+#[test]
+fn border_radius_rounds_corners_and_borders() {
+    let mut win = window(96, 64, vec![
+        panel(1, 4, 4, 40, 40).background_color(YELLOW).set_border_radius(Size::Absolute(10)),
+        panel(2, 50, 4, 40, 40)
+            .background_color(GREEN)
+            .set_border_radius(Size::Absolute(16))
+            .set_border_size(Size::Absolute(3))
+            .set_border_color(BLUE),
+        panel(3, 4, 48, 86, 12)
+            .background_gradient(LinearGradient::new(RED, YELLOW, GradientDirection::Horizontal))
+            .set_border_radius(Size::Relative(50)),
+    ]);
+
+    let pixels = render(&mut win);
+    let at = |x: usize, y: usize| pixels[y * 96 + x];
+    assert_eq!(at(4, 4), 0, "corners outside the radius stay clear");
+    assert_eq!(rgb(at(24, 24)), hex(YELLOW));
+    assert_eq!(rgb(at(70, 5)), hex(BLUE), "the border runs along the edge");
+    assert_eq!(rgb(at(70, 24)), hex(GREEN));
+    assert_golden("border_radius", &mut win);
+}
+
+#[test]
+fn flex_and_grid_boxes() {
+    let colors = [RED, GREEN, BLUE, YELLOW];
+    let cell = |id: usize, width: usize, height: usize| {
+        Widget::frame(id)
+            .width(Size::Absolute(width))
+            .height(Size::Absolute(height))
+            .margin(Size::Absolute(2))
+            .background_color(colors[id % 4])
+    };
+    let row = (10..15).fold(
+        panel(1, 0, 0, 96, 30).set_display(Display::Flex { direction: FlexDirection::Row, wrap: true }),
+        |row, id| row.add_child(cell(id, 20 + id % 3 * 6, 10)),
+    );
+    let grid = (20..26).fold(
+        panel(2, 0, 32, 96, 32).padding(Size::Absolute(2)).set_display(Display::Grid { rows: 2, cols: 3 }),
+        |grid, id| grid.add_child(Widget::frame(id).margin(Size::Absolute(1)).background_color(colors[id % 4])),
+    );
+    let mut win = window(96, 64, vec![row, grid]);
+
+    render(&mut win);
+    assert_eq!(bounds(&win, 14).1, bounds(&win, 10).1 + 14, "the last box wraps to a second row");
+    assert_golden("flex_grid", &mut win);
+}
+
+#[test]
+fn partial_repaints_match_a_full_paint() {
+    let mut win = window(96, 64, vec![
+        panel(1, 4, 4, 50, 40).background_color(YELLOW).set_border_radius(Size::Absolute(10)),
+        panel(2, 30, 20, 50, 40).background_color(GREEN).set_border_size(Size::Absolute(2)).set_border_color(BLUE),
+        panel(3, 40, 10, 20, 20).background_gradient(LinearGradient::new(RED, BLUE, GradientDirection::Diagonal)),
+    ]);
+    let full = render(&mut win).to_vec();
+
+    // Crosses the first two panels but not the third, which the second covers outside it.
+    win.invalidate_area(28, 40, 20, 20);
+    win.present();
+    assert!(win.pixels() == full.as_slice(), "repainting part of the window changed it");
+}
+
+/// Columns of `label` holding dark pixels, the first and last.
+fn ink(pixels: &[u32], width: usize, label: (usize, usize, usize, usize)) -> (usize, usize) {
+    let (x, y, w, h) = label;
+    let dark = |px: usize| (y..y + h).any(|py| (pixels[py * width + px] >> 16) & 0xFF < 128);
+    let first = (x..x + w).find(|&px| dark(px)).expect("label has no text");
+    let last = (x..x + w).rev().find(|&px| dark(px)).unwrap();
+    (first, last)
+}
+
+#[test]
+fn labels_align_their_text() {
+    let label = |id: usize, y: usize, align: Align| {
+        Widget::label(id, "Ink")
+            .x(Size::Absolute(10))
+            .y(Size::Absolute(y))
+            .width(Size::Absolute(180))
+            .height(Size::Absolute(24))
+            .padding(Size::Absolute(4))
+            .background_color(Color::rgb(255, 255, 255))
+            .set_text_color(Color::rgb(0, 0, 0))
+            .set_text_size(16.0)
+            .set_text_align(align)
+    };
+    let mut win = window(200, 90, vec![
+        label(1, 0, Align::Left),
+        label(2, 30, Align::Center),
+        label(3, 60, Align::Right),
+    ]);
+    win.load_font(FONT);
+    let pixels = render(&mut win).to_vec();
+
+    // Glyphs sit a bearing away from where their advance starts and ends.
+    let slack = 4;
+    let (left, _) = ink(&pixels, 200, bounds(&win, 1));
+    assert!((14..14 + slack).contains(&left), "left-aligned text starts at {}", left);
+
+    let (first, last) = ink(&pixels, 200, bounds(&win, 2));
+    let middle = (first + last) / 2;
+    assert!(middle.abs_diff(100) <= slack / 2, "centred text is around {}", middle);
+    assert_eq!(last - first, ink(&pixels, 200, bounds(&win, 1)).1 - left, "alignment moves text without changing it");
+
+    let (_, right) = ink(&pixels, 200, bounds(&win, 3));
+    assert!((186 - slack..186).contains(&right), "right-aligned text ends at {}", right);
+}
@@ -0,0 +1,58 @@
+use inkui::event::{Event, KeyboardEvent};
+use inkui::{Headless, Size, Theme, Widget, Window};
+use inkui_host_tests::{render, FONT};
+use std::rc::Rc;
+
+const CTRL_A: u32 = 0x01;
+const CTRL_Z: u32 = 0x1A;
+const BACKSPACE: u32 = 0x08;
+const KEY_DOWN: u32 = 0x110004;
+const KEY_END: u32 = 0x11000A;
+
+/// Sends `keys` to the focused text area in one batch, as the compositor queues them
+/// between two layouts.
+fn type_keys(win: &mut Window, headless: &Headless, keys: &[u32]) {
+    for &key in keys {
+        headless.push_event(Event::Keyboard(KeyboardEvent { wid: win.id as u32, key, pressed: true, repeat: 1 }));
+    }
+    let events = win.poll_events();
+    win.dispatch(&events);
+}
+
+/// A shown window holding a focused text area with `text`.
+fn text_area(text: &str) -> (Window, Rc<Headless>) {
+    let headless = Rc::new(Headless::new(800, 600));
+    let mut win = Window::with_platform("test", 200, 100, headless.clone());
+    win.set_theme(Theme::default());
+    win.load_font(FONT);
+
+    let mut area = Widget::text_area(1).width(Size::Absolute(200)).height(Size::Absolute(100));
+    area.append_text(text);
+    area.set_focused(true);
+    win.children.push(area);
+    win.focus = 1;
+    win.show();
+    (win, headless)
+}
+
+#[test]
+fn moving_after_an_edit_uses_the_new_rows() {
+    let (mut win, headless) = text_area("a\nb\nc");
+    type_keys(&mut win, &headless, &[CTRL_A, BACKSPACE, KEY_DOWN]);
+
+    let editor = win.find_widget_by_id(1).and_then(|w| w.editor()).unwrap();
+    assert_eq!(editor.text(), "");
+    assert_eq!(editor.caret(), 0);
+}
+
+#[test]
+fn moving_after_undo_stays_on_char_boundaries() {
+    let (mut win, headless) = text_area("ab\ncd");
+    type_keys(&mut win, &headless, &[CTRL_A, 'é' as u32, 'é' as u32, 'é' as u32]);
+    render(&mut win);
+    type_keys(&mut win, &headless, &[CTRL_Z, KEY_END, KEY_DOWN]);
+
+    let editor = win.find_widget_by_id(1).and_then(|w| w.editor()).unwrap();
+    assert_eq!(editor.text(), "ab\ncd");
+    assert_eq!(editor.caret(), 5);
+}
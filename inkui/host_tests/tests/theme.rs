@@ -0,0 +1,31 @@
+use inkui::{Color, Headless, Platform, Theme};
+
+#[test]
+fn shipped_themes_parse() {
+    let themes = [
+        ("light", include_str!("../../../tree/sys/themes/light.theme")),
+        ("dark", include_str!("../../../tree/sys/themes/dark.theme")),
+    ];
+    for (name, source) in themes {
+        if let Err(e) = Theme::parse(name, source) {
+            panic!("{}.theme: {}", name, e);
+        }
+    }
+}
+
+#[test]
+fn the_built_in_theme_is_light() {
+    assert_eq!(Theme::default().color("accent"), Some(Color::rgb(70, 110, 200)));
+}
+
+#[test]
+fn the_current_theme_comes_from_the_platform() {
+    let headless = Headless::new(800, 600);
+    headless.add_file("/sys/themes/dark.theme", include_bytes!("../../../tree/sys/themes/dark.theme"));
+    assert!(headless.set_theme("dark"));
+    let theme = Theme::current(&headless);
+    assert_eq!((theme.name.as_str(), theme.color("accent")), ("dark", Some(Color::rgb(90, 138, 230))));
+
+    assert!(headless.set_theme("missing"));
+    assert_eq!(Theme::current(&headless).name, "light");
+}
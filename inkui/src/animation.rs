@@ -0,0 +1,154 @@
+use crate::timer::TimerId;
+use crate::types::{Color, Size};
+use crate::widget::{Widget, WidgetId};
+use crate::window::Window;
+use alloc::boxed::Box;
+use core::time::Duration;
+use std::time::Instant;
+
+const FRAME: Duration = Duration::from_millis(16);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => {
+                let u = 1.0 - t;
+                1.0 - u * u * u
+            }
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    let u = 2.0 - 2.0 * t;
+                    1.0 - u * u * u / 2.0
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Property {
+    X(usize),
+    Y(usize),
+    Width(usize),
+    Height(usize),
+    Background(Color),
+    TextColor(Color),
+    Opacity(f32),
+}
+
+impl Property {
+    fn current(self, widget: &Widget) -> Property {
+        let geometry = widget.geometry();
+        let offset = |size: Size| match size {
+            Size::Absolute(offset) => offset,
+            _ => 0,
+        };
+        match self {
+            Property::X(_) => Property::X(offset(geometry.user_x)),
+            Property::Y(_) => Property::Y(offset(geometry.user_y)),
+            Property::Width(_) => Property::Width(geometry.width),
+            Property::Height(_) => Property::Height(geometry.height),
+            Property::Background(to) => Property::Background(widget.background().unwrap_or(to)),
+            Property::TextColor(to) => Property::TextColor(widget.text_color().unwrap_or(to)),
+            Property::Opacity(_) => Property::Opacity(geometry.opacity),
+        }
+    }
+
+    fn towards(self, to: Property, progress: f32) -> Property {
+        let size = |from: usize, to: usize| (from as f32 + (to as f32 - from as f32) * progress + 0.5) as usize;
+        match (self, to) {
+            (Property::X(from), Property::X(to)) => Property::X(size(from, to)),
+            (Property::Y(from), Property::Y(to)) => Property::Y(size(from, to)),
+            (Property::Width(from), Property::Width(to)) => Property::Width(size(from, to)),
+            (Property::Height(from), Property::Height(to)) => Property::Height(size(from, to)),
+            (Property::Background(from), Property::Background(to)) => Property::Background(mix(from, to, progress)),
+            (Property::TextColor(from), Property::TextColor(to)) => Property::TextColor(mix(from, to, progress)),
+            (Property::Opacity(from), Property::Opacity(to)) => Property::Opacity(from + (to - from) * progress),
+            _ => to,
+        }
+    }
+
+    fn set(self, widget: &mut Widget) {
+        widget.mark_dirty();
+        let geometry = widget.geometry_mut();
+        match self {
+            Property::X(x) => geometry.user_x = Size::Absolute(x),
+            Property::Y(y) => geometry.user_y = Size::Absolute(y),
+            Property::Width(width) => geometry.user_width = Size::Absolute(width),
+            Property::Height(height) => geometry.user_height = Size::Absolute(height),
+            // The window's restyle before painting puts these on the widget.
+            Property::Background(color) => geometry.custom.background = Some(color),
+            Property::TextColor(color) => geometry.custom.text_color = Some(color),
+            Property::Opacity(opacity) => geometry.opacity = opacity.clamp(0.0, 1.0),
+        }
+    }
+}
+
+fn mix(from: Color, to: Color, progress: f32) -> Color {
+    let channel = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * progress + 0.5) as u8;
+    Color::rgba(channel(from.r, to.r), channel(from.g, to.g), channel(from.b, to.b), channel(from.a, to.a))
+}
+
+pub struct Animation {
+    target: Property,
+    duration: Duration,
+    easing: Easing,
+    on_done: Option<Box<dyn FnOnce(&mut Window)>>,
+}
+
+impl Animation {
+    pub fn new(target: Property, duration: Duration) -> Self {
+        Animation { target, duration, easing: Easing::EaseInOut, on_done: None }
+    }
+
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    pub fn on_done(mut self, handler: impl FnOnce(&mut Window) + 'static) -> Self {
+        self.on_done = Some(Box::new(handler));
+        self
+    }
+}
+
+impl Window {
+    pub fn animate(&mut self, id: WidgetId, animation: Animation) -> TimerId {
+        let Animation { target, duration, easing, mut on_done } = animation;
+        let start = Instant::now();
+        let mut from = None;
+
+        self.repeat(FRAME, FRAME, move |win| {
+            let Some(widget) = win.find_widget_by_id_mut(id) else {
+                return false;
+            };
+            let from = *from.get_or_insert_with(|| target.current(widget));
+            let t = if duration.is_zero() {
+                1.0
+            } else {
+                start.elapsed().as_secs_f32() / duration.as_secs_f32()
+            };
+            from.towards(target, easing.apply(t)).set(widget);
+            if t < 1.0 {
+                return true;
+            }
+            if let Some(handler) = on_done.take() {
+                handler(win);
+            }
+            false
+        })
+    }
+}
@@ -0,0 +1,223 @@
+use crate::graphics::primitives::{draw_background_style, draw_square, draw_text_formatted};
+use crate::math::floor_f32;
+use crate::types::{BackgroundStyle, Color, Rect, Size};
+use crate::widget::{Text, WidgetGeometry};
+use alloc::format;
+use alloc::string::String;
+use titanf::TrueTypeFont;
+
+pub const ACCENT: Color = Color { r: 70, g: 110, b: 200, a: 255 };
+const INDICATOR_MAX: usize = 16;
+const LABEL_GAP: usize = 6;
+pub const SPIN_BUTTON_WIDTH: usize = 18;
+pub const MENU_ROWS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Orientation {
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Range {
+    pub min: f32,
+    pub max: f32,
+    pub step: f32,
+}
+
+impl Range {
+    pub fn new(min: f32, max: f32, step: f32) -> Self {
+        Range { min: min.min(max), max: max.max(min), step: step.max(0.0) }
+    }
+
+    pub fn clamp(&self, value: f32) -> f32 {
+        let snapped = if self.step > 0.0 {
+            self.min + floor_f32((value - self.min) / self.step + 0.5) * self.step
+        } else {
+            value
+        };
+        snapped.max(self.min).min(self.max)
+    }
+
+    pub fn key_step(&self) -> f32 {
+        if self.step > 0.0 { self.step } else { (self.max - self.min) / 100.0 }
+    }
+
+    pub fn fraction(&self, value: f32) -> f32 {
+        if self.max > self.min { (value - self.min) / (self.max - self.min) } else { 0.0 }
+    }
+
+    pub fn at_fraction(&self, fraction: f32) -> f32 {
+        self.clamp(self.min + fraction.max(0.0).min(1.0) * (self.max - self.min))
+    }
+
+    pub fn format(&self, value: f32) -> String {
+        if floor_f32(self.step) == self.step {
+            format!("{:.0}", value)
+        } else {
+            format!("{:.2}", value)
+        }
+    }
+}
+
+fn border_size(geometry: &WidgetGeometry) -> usize {
+    match geometry.border_size {
+        Size::Absolute(s) => s,
+        _ => 0,
+    }
+}
+
+fn draw_box(buffer: &mut [u32], buffer_width: usize, clip: &Rect, geometry: &WidgetGeometry, x: usize, y: usize, width: usize,
+            height: usize, rounding: Size, background: &BackgroundStyle) {
+    draw_background_style(buffer, buffer_width, clip, x, y, width, height, rounding, background, border_size(geometry), geometry.border_color);
+}
+
+pub fn draw_focus_ring(buffer: &mut [u32], buffer_width: usize, clip: &Rect, geometry: &WidgetGeometry, color: Color) {
+    draw_square(buffer, buffer_width, clip, geometry.x, geometry.y, geometry.width, geometry.height, Size::Absolute(3),
+                Color::rgba(0, 0, 0, 0), 1, color);
+}
+
+fn draw_line_text(buffer: &mut [u32], buffer_width: usize, clip: &Rect, font: &mut Option<TrueTypeFont>, text: &Text, label: &str,
+                  x: usize, top: usize, height: usize, right: usize) {
+    let Some(font) = font else { return };
+    if label.is_empty() || right <= x {
+        return;
+    }
+    let baseline = top + height / 2 + text.size as usize / 3;
+    draw_text_formatted(buffer, buffer_width, clip, x, baseline, label, font, text.size, text.color, right - x, 0, height, top);
+}
+
+fn draw_arrow(buffer: &mut [u32], buffer_width: usize, clip: &Rect, cx: usize, cy: usize, up: bool, color: Color) {
+    for i in 0..4 {
+        let row = if up { cy.saturating_sub(2) + i } else { (cy + 1).saturating_sub(i) };
+        draw_square(buffer, buffer_width, clip, cx.saturating_sub(i), row, i * 2 + 1, 1, Size::Absolute(0), color, 0, color);
+    }
+}
+
+fn indicator(geometry: &WidgetGeometry, row_top: usize, row_height: usize) -> (usize, usize, usize) {
+    let side = row_height.min(INDICATOR_MAX).max(1);
+    (geometry.x + geometry.padding, row_top + row_height.saturating_sub(side) / 2, side)
+}
+
+pub fn draw_checkbox(buffer: &mut [u32], buffer_width: usize, clip: &Rect, geometry: &WidgetGeometry, background: &BackgroundStyle,
+                     text: &Text, checked: bool, accent: Color, font: &mut Option<TrueTypeFont>) {
+    let (x, y, side) = indicator(geometry, geometry.y, geometry.height);
+    draw_box(buffer, buffer_width, clip, geometry, x, y, side, side, geometry.border_radius, background);
+    if checked {
+        let inset = side / 4;
+        draw_square(buffer, buffer_width, clip, x + inset, y + inset, side - inset * 2, side - inset * 2, Size::Absolute(2), accent, 0, accent);
+    }
+    draw_line_text(buffer, buffer_width, clip, font, text, &text.text, x + side + LABEL_GAP, geometry.y, geometry.height,
+                   geometry.x + geometry.width);
+}
+
+pub fn radio_row_height(geometry: &WidgetGeometry, count: usize) -> usize {
+    (geometry.height / count.max(1)).max(1)
+}
+
+pub fn draw_radio_group(buffer: &mut [u32], buffer_width: usize, clip: &Rect, geometry: &WidgetGeometry, background: &BackgroundStyle,
+                        text: &Text, options: &[String], selected: Option<usize>, accent: Color, font: &mut Option<TrueTypeFont>) {
+    let row_height = radio_row_height(geometry, options.len());
+    for (i, option) in options.iter().enumerate() {
+        let top = geometry.y + i * row_height;
+        let (x, y, side) = indicator(geometry, top, row_height);
+        draw_box(buffer, buffer_width, clip, geometry, x, y, side, side, Size::Relative(50), background);
+        if selected == Some(i) {
+            let inset = side / 4;
+            draw_square(buffer, buffer_width, clip, x + inset, y + inset, side - inset * 2, side - inset * 2, Size::Relative(50), accent, 0, accent);
+        }
+        draw_line_text(buffer, buffer_width, clip, font, text, option, x + side + LABEL_GAP, top, row_height, geometry.x + geometry.width);
+    }
+}
+
+fn slider_track(geometry: &WidgetGeometry, orientation: Orientation) -> (usize, usize, usize) {
+    let (start, length, cross) = match orientation {
+        Orientation::Horizontal => (geometry.x, geometry.width, geometry.height),
+        Orientation::Vertical => (geometry.y, geometry.height, geometry.width),
+    };
+    let thumb = cross.min(INDICATOR_MAX).max(1);
+    (start + thumb / 2, length.saturating_sub(thumb).max(1), thumb)
+}
+
+pub fn slider_value_at(geometry: &WidgetGeometry, orientation: Orientation, range: &Range, x: usize, y: usize) -> f32 {
+    let (start, length, _) = slider_track(geometry, orientation);
+    let fraction = match orientation {
+        Orientation::Horizontal => (x as f32 - start as f32) / length as f32,
+        // Vertical sliders grow upwards.
+        Orientation::Vertical => 1.0 - (y as f32 - start as f32) / length as f32,
+    };
+    range.at_fraction(fraction)
+}
+
+pub fn draw_slider(buffer: &mut [u32], buffer_width: usize, clip: &Rect, geometry: &WidgetGeometry, background: &BackgroundStyle,
+                   orientation: Orientation, range: &Range, value: f32, accent: Color, knob: Color) {
+    let (start, length, thumb) = slider_track(geometry, orientation);
+    let thickness = (thumb / 3).max(2);
+    let filled = (range.fraction(value).max(0.0).min(1.0) * length as f32) as usize;
+    let rounding = Size::Absolute(thickness / 2);
+
+    match orientation {
+        Orientation::Horizontal => {
+            let track_y = geometry.y + geometry.height.saturating_sub(thickness) / 2;
+            draw_box(buffer, buffer_width, clip, geometry, start, track_y, length, thickness, rounding, background);
+            draw_square(buffer, buffer_width, clip, start, track_y, filled, thickness, rounding, accent, 0, accent);
+            let thumb_x = start + filled - thumb / 2;
+            let thumb_y = geometry.y + geometry.height.saturating_sub(thumb) / 2;
+            draw_square(buffer, buffer_width, clip, thumb_x, thumb_y, thumb, thumb, Size::Relative(50), knob, 1, accent);
+        }
+        Orientation::Vertical => {
+            let track_x = geometry.x + geometry.width.saturating_sub(thickness) / 2;
+            draw_box(buffer, buffer_width, clip, geometry, track_x, start, thickness, length, rounding, background);
+            draw_square(buffer, buffer_width, clip, track_x, start + length - filled, thickness, filled, rounding, accent, 0, accent);
+            let thumb_x = geometry.x + geometry.width.saturating_sub(thumb) / 2;
+            let thumb_y = start + length - filled - thumb / 2;
+            draw_square(buffer, buffer_width, clip, thumb_x, thumb_y, thumb, thumb, Size::Relative(50), knob, 1, accent);
+        }
+    }
+}
+
+pub fn draw_progress_bar(buffer: &mut [u32], buffer_width: usize, clip: &Rect, geometry: &WidgetGeometry, background: &BackgroundStyle,
+                         progress: Option<f32>, accent: Color) {
+    draw_box(buffer, buffer_width, clip, geometry, geometry.x, geometry.y, geometry.width, geometry.height, geometry.border_radius, background);
+
+    let inset = border_size(geometry);
+    let width = geometry.width.saturating_sub(inset * 2);
+    let height = geometry.height.saturating_sub(inset * 2);
+    let (offset, filled) = match progress {
+        Some(progress) => (0, (progress.max(0.0).min(1.0) * width as f32) as usize),
+        None => {
+            let span = width / 3;
+            let travel = (width - span).max(1);
+            let step = (crate::platform::clock_ns() / 8_000_000) as usize % (travel * 2);
+            (if step < travel { step } else { travel * 2 - step }, span)
+        }
+    };
+    draw_square(buffer, buffer_width, clip, geometry.x + inset + offset, geometry.y + inset, filled, height, geometry.border_radius, accent, 0, accent);
+}
+
+pub fn draw_dropdown(buffer: &mut [u32], buffer_width: usize, clip: &Rect, geometry: &WidgetGeometry, background: &BackgroundStyle,
+                     text: &Text, label: &str, font: &mut Option<TrueTypeFont>) {
+    draw_box(buffer, buffer_width, clip, geometry, geometry.x, geometry.y, geometry.width, geometry.height, geometry.border_radius, background);
+    let arrow_x = (geometry.x + geometry.width).saturating_sub(SPIN_BUTTON_WIDTH / 2 + geometry.padding);
+    draw_line_text(buffer, buffer_width, clip, font, text, label, geometry.x + geometry.padding + 5, geometry.y, geometry.height,
+                   arrow_x.saturating_sub(SPIN_BUTTON_WIDTH / 2));
+    draw_arrow(buffer, buffer_width, clip, arrow_x, geometry.y + geometry.height / 2, false, text.color);
+}
+
+pub fn spinner_button_at(geometry: &WidgetGeometry, x: usize, y: usize) -> Option<bool> {
+    let buttons_x = (geometry.x + geometry.width).saturating_sub(SPIN_BUTTON_WIDTH);
+    (x >= buttons_x).then(|| y < geometry.y + geometry.height / 2)
+}
+
+pub fn draw_spinner(buffer: &mut [u32], buffer_width: usize, clip: &Rect, geometry: &WidgetGeometry, background: &BackgroundStyle,
+                    text: &Text, label: &str, font: &mut Option<TrueTypeFont>, divider: Color) {
+    draw_box(buffer, buffer_width, clip, geometry, geometry.x, geometry.y, geometry.width, geometry.height, geometry.border_radius, background);
+    let buttons_x = (geometry.x + geometry.width).saturating_sub(SPIN_BUTTON_WIDTH);
+    draw_line_text(buffer, buffer_width, clip, font, text, label, geometry.x + geometry.padding + 5, geometry.y, geometry.height, buttons_x);
+
+    let divider = if geometry.border_color.a > 0 { geometry.border_color } else { divider };
+    draw_square(buffer, buffer_width, clip, buttons_x, geometry.y, 1, geometry.height, Size::Absolute(0), divider, 0, divider);
+    let cx = buttons_x + SPIN_BUTTON_WIDTH / 2;
+    draw_arrow(buffer, buffer_width, clip, cx, geometry.y + geometry.height / 4, true, text.color);
+    draw_arrow(buffer, buffer_width, clip, cx, geometry.y + geometry.height * 3 / 4, false, text.color);
+}
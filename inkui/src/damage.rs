@@ -0,0 +1,40 @@
+use crate::types::Rect;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Past this many separate areas they are merged into one around them all, since each
+/// costs a paint pass and an update call of its own.
+const MAX_RECTS: usize = 8;
+
+#[derive(Debug, Clone, Default)]
+pub struct Damage {
+    rects: Vec<Rect>,
+}
+
+impl Damage {
+    /// Adds `rect`, merging it with the areas it overlaps or borders.
+    pub fn add(&mut self, rect: Rect) {
+        if rect.is_empty() {
+            return;
+        }
+        let mut rect = rect;
+        // A merged area can reach ones the original didn't, so keep going until none touch.
+        while let Some(i) = self.rects.iter().position(|r| r.touches(&rect)) {
+            rect = rect.union(&self.rects.swap_remove(i));
+        }
+        self.rects.push(rect);
+
+        if self.rects.len() > MAX_RECTS {
+            let all = self.rects.iter().fold(self.rects[0], |acc, r| acc.union(r));
+            self.rects = vec![all];
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    pub fn take(&mut self) -> Vec<Rect> {
+        core::mem::take(&mut self.rects)
+    }
+}
@@ -0,0 +1,411 @@
+use crate::handler::State;
+use crate::types::Size;
+use crate::widget::{Widget, WidgetId};
+use crate::window::Window;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+const PADDING: usize = 12;
+const TITLE_HEIGHT: usize = 26;
+const ROW: usize = 24;
+const BUTTON_WIDTH: usize = 80;
+
+const FRAME: WidgetId = 1;
+const TITLE: WidgetId = 2;
+const INPUT: WidgetId = 3;
+const LIST: WidgetId = 4;
+const PATH: WidgetId = 5;
+const FILTER: WidgetId = 6;
+const NEW_FOLDER: WidgetId = 7;
+const MESSAGE: WidgetId = 10;
+const BUTTONS: WidgetId = 100;
+
+type Reply<R> = State<Option<Option<R>>>;
+
+pub(crate) struct Dialog {
+    pub(crate) window: Box<Window>,
+    answer: Box<dyn FnMut(bool) -> Option<Box<dyn FnOnce(&mut Window)>>>,
+}
+
+impl Dialog {
+    pub(crate) fn answer(&mut self, cancel: bool) -> Option<Box<dyn FnOnce(&mut Window)>> {
+        (self.answer)(cancel)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FileChooser {
+    title: String,
+    directory: String,
+    extensions: Vec<String>,
+    name: Option<String>,
+}
+
+impl FileChooser {
+    pub fn open() -> Self {
+        FileChooser { title: String::from("Open"), directory: String::from("/"), extensions: Vec::new(), name: None }
+    }
+
+    pub fn save(name: &str) -> Self {
+        FileChooser { title: String::from("Save"), name: Some(String::from(name)), ..FileChooser::open() }
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = String::from(title);
+        self
+    }
+
+    pub fn directory(mut self, path: &str) -> Self {
+        self.directory = String::from(path);
+        self
+    }
+
+    pub fn filter(mut self, extensions: &[&str]) -> Self {
+        self.extensions = extensions.iter().map(|e| String::from(e.trim_start_matches('.'))).collect();
+        self
+    }
+}
+
+struct Browser {
+    directory: String,
+    entries: Vec<String>,
+    extensions: Vec<String>,
+    all_files: bool,
+}
+
+impl Window {
+    pub fn message_box(&mut self, title: &str, message: &str, buttons: &[&str],
+                       handler: impl FnOnce(&mut Window, Option<usize>) + 'static) {
+        let buttons = if buttons.is_empty() { &["OK"][..] } else { buttons };
+        let lines: Vec<&str> = message.lines().collect();
+        let width = (buttons.len() * (BUTTON_WIDTH + PADDING) + PADDING).max(320);
+        let height = TITLE_HEIGHT + PADDING + lines.len().max(1) * ROW + PADDING + ROW + PADDING;
+
+        let reply: Reply<usize> = State::new(None);
+        let mut dialog = dialog_window(self, title, width, height);
+        for (i, line) in lines.iter().enumerate() {
+            dialog.children.push(line_label(MESSAGE + i, line, width, TITLE_HEIGHT + PADDING + i * ROW));
+        }
+        for (i, text) in buttons.iter().enumerate() {
+            let on_click = reply.clone();
+            dialog.children.push(button(BUTTONS + i, text, buttons.len() - 1 - i).on_click(move |_, _| on_click.set(Some(Some(i)))));
+        }
+        focus(&mut dialog, BUTTONS);
+        open(self, dialog, reply, handler);
+    }
+
+    pub fn prompt(&mut self, title: &str, message: &str, default: &str,
+                  handler: impl FnOnce(&mut Window, Option<String>) + 'static) {
+        let width = 360;
+        let height = TITLE_HEIGHT + PADDING + ROW + PADDING / 2 + ROW + PADDING + ROW + PADDING;
+
+        let reply: Reply<String> = State::new(None);
+        let mut dialog = dialog_window(self, title, width, height);
+        dialog.children.push(line_label(MESSAGE, message, width, TITLE_HEIGHT + PADDING));
+
+        let on_submit = reply.clone();
+        let mut input = Widget::text_input(INPUT, "")
+            .x(Size::Absolute(PADDING))
+            .y(Size::Absolute(TITLE_HEIGHT + PADDING + ROW + PADDING / 2))
+            .width(Size::Absolute(width - 2 * PADDING))
+            .height(Size::Absolute(ROW))
+            .on_submit(move |win, _| on_submit.set(Some(Some(text_of(win, INPUT)))));
+        input.append_text(default);
+        dialog.children.push(input);
+
+        let on_ok = reply.clone();
+        let on_cancel = reply.clone();
+        dialog.children.push(button(BUTTONS, "OK", 1).on_click(move |win, _| on_ok.set(Some(Some(text_of(win, INPUT))))));
+        dialog.children.push(button(BUTTONS + 1, "Cancel", 0).on_click(move |_, _| on_cancel.set(Some(None))));
+        focus(&mut dialog, INPUT);
+        open(self, dialog, reply, handler);
+    }
+
+    pub fn choose_file(&mut self, chooser: FileChooser, handler: impl FnOnce(&mut Window, Option<String>) + 'static) {
+        let width = 440;
+        let height = 380;
+        let saving = chooser.name.is_some();
+        let list_y = TITLE_HEIGHT + PADDING + ROW + PADDING / 2;
+        let bottom = if saving { 2 * (ROW + PADDING) } else { ROW + PADDING };
+
+        let reply: Reply<String> = State::new(None);
+        let browser = State::new(Browser {
+            directory: String::from(normalize(&chooser.directory)),
+            entries: Vec::new(),
+            extensions: chooser.extensions.clone(),
+            all_files: chooser.extensions.is_empty(),
+        });
+        let mut dialog = dialog_window(self, &chooser.title, width, height);
+
+        let filtered = !chooser.extensions.is_empty();
+        let path_width = if filtered { width - 3 * PADDING - 140 } else { width - 2 * PADDING };
+        dialog.children.push(line_label(PATH, "", path_width + 2 * PADDING, TITLE_HEIGHT + PADDING));
+        if filtered {
+            let patterns: Vec<String> = chooser.extensions.iter().map(|e| format!("*.{}", e)).collect();
+            let on_filter = browser.clone();
+            let mut filter = Widget::dropdown(FILTER, vec![patterns.join(", "), String::from("All files")])
+                .x(Size::FromRight(PADDING))
+                .y(Size::Absolute(TITLE_HEIGHT + PADDING))
+                .width(Size::Absolute(140))
+                .height(Size::Absolute(ROW))
+                .on_change(move |win, _, value| {
+                    on_filter.update(|b| b.all_files = value.as_selected() == Some(1));
+                    refresh(win, &on_filter);
+                });
+            filter.set_selected(Some(0));
+            dialog.children.push(filter);
+        }
+
+        let on_select = browser.clone();
+        let on_open = browser.clone();
+        let open_reply = reply.clone();
+        dialog.children.push(Widget::list(LIST, Vec::new())
+            .x(Size::Absolute(PADDING))
+            .y(Size::Absolute(list_y))
+            .width(Size::Absolute(width - 2 * PADDING))
+            .height(Size::Absolute(height - list_y - PADDING - bottom))
+            .set_border_size(Size::Absolute(1))
+            .set_border_color(self.theme().entry("border"))
+            .on_change(move |win, _, value| {
+                // Picking a file to save over fills in its name.
+                let entry = value.as_selected().and_then(|i| on_select.borrow().entries.get(i).cloned());
+                if let Some(name) = entry.filter(|e| !e.ends_with('/')) {
+                    if win.find_widget_by_id(INPUT).is_some() {
+                        set_text(win, INPUT, &name);
+                    }
+                }
+            })
+            .on_double_click(move |win, _| {
+                if !enter_folder(win, &on_open) {
+                    accept(win, &on_open, &open_reply, saving);
+                }
+            }));
+
+        if let Some(name) = &chooser.name {
+            let on_submit = browser.clone();
+            let submit_reply = reply.clone();
+            let mut input = Widget::text_input(INPUT, "")
+                .x(Size::Absolute(PADDING))
+                .y(Size::FromDown(PADDING + ROW + PADDING))
+                .width(Size::Absolute(width - 2 * PADDING))
+                .height(Size::Absolute(ROW))
+                .on_submit(move |win, _| accept(win, &on_submit, &submit_reply, true));
+            input.append_text(name);
+            dialog.children.push(input);
+        }
+
+        let on_new_folder = browser.clone();
+        dialog.children.push(Widget::button(NEW_FOLDER, "New folder")
+            .x(Size::Absolute(PADDING))
+            .y(Size::FromDown(PADDING))
+            .width(Size::Absolute(BUTTON_WIDTH + 20))
+            .height(Size::Absolute(ROW))
+            .on_click(move |win, _| new_folder(win, &on_new_folder)));
+
+        let on_accept = browser.clone();
+        let accept_reply = reply.clone();
+        let on_cancel = reply.clone();
+        let verb = if saving { "Save" } else { "Open" };
+        dialog.children.push(button(BUTTONS, verb, 1).on_click(move |win, _| accept(win, &on_accept, &accept_reply, saving)));
+        dialog.children.push(button(BUTTONS + 1, "Cancel", 0).on_click(move |_, _| on_cancel.set(Some(None))));
+
+        refresh(&mut dialog, &browser);
+        focus(&mut dialog, if saving { INPUT } else { LIST });
+        open(self, dialog, reply, handler);
+    }
+}
+
+fn dialog_window(parent: &Window, title: &str, width: usize, height: usize) -> Window {
+    let mut window = parent.popup(width, height);
+    window.title = String::from(title);
+    let (x, y) = parent.platform().window_position(parent.id).unwrap_or((0, 0));
+    window.x = (x + (parent.width as isize - width as isize) / 2).max(0);
+    window.y = (y + (parent.height as isize - height as isize) / 2).max(0);
+    window.dismiss_on_escape = true;
+
+    let border = window.theme().entry("border");
+    let header = window.theme().entry("header");
+    window.children.push(Widget::frame(FRAME)
+        .set_border_size(Size::Absolute(1))
+        .set_border_color(border));
+    window.children.push(Widget::label(TITLE, title)
+        .x(Size::Absolute(1))
+        .y(Size::Absolute(1))
+        .width(Size::Absolute(width - 2))
+        .height(Size::Absolute(TITLE_HEIGHT - 1))
+        .padding(Size::Absolute(6))
+        .background_color(header));
+    window
+}
+
+fn line_label(id: WidgetId, text: &str, width: usize, y: usize) -> Widget {
+    Widget::label(id, text)
+        .x(Size::Absolute(PADDING))
+        .y(Size::Absolute(y))
+        .width(Size::Absolute(width - 2 * PADDING))
+        .height(Size::Absolute(ROW))
+}
+
+fn button(id: WidgetId, text: &str, slot: usize) -> Widget {
+    Widget::button(id, text)
+        .x(Size::FromRight(PADDING + slot * (BUTTON_WIDTH + PADDING)))
+        .y(Size::FromDown(PADDING))
+        .width(Size::Absolute(BUTTON_WIDTH))
+        .height(Size::Absolute(ROW))
+}
+
+fn focus(window: &mut Window, id: WidgetId) {
+    window.focus = id;
+    if let Some(widget) = window.find_widget_by_id_mut(id) {
+        widget.set_focused(true);
+    }
+}
+
+fn open<R: 'static>(parent: &mut Window, mut window: Window, reply: Reply<R>,
+                    handler: impl FnOnce(&mut Window, Option<R>) + 'static) {
+    let on_close = reply.clone();
+    window.set_on_close(move |_| {
+        on_close.update(|r| { r.get_or_insert(None); });
+    });
+
+    let mut handler = Some(handler);
+    let answer = move |cancel: bool| -> Option<Box<dyn FnOnce(&mut Window)>> {
+        let result = match reply.update(|r| r.take()) {
+            Some(result) => result,
+            None if cancel => None,
+            None => return None,
+        };
+        let handler = handler.take()?;
+        Some(Box::new(move |win: &mut Window| handler(win, result)))
+    };
+    parent.open_dialog(Dialog { window: Box::new(window), answer: Box::new(answer) });
+}
+
+fn text_of(win: &Window, id: WidgetId) -> String {
+    win.find_widget_by_id(id).map(|w| w.get_text()).unwrap_or_default()
+}
+
+fn set_text(win: &mut Window, id: WidgetId, new_text: &str) {
+    if let Some(Widget::Label { text, .. } | Widget::TextInput { text, .. }) = win.find_widget_by_id_mut(id) {
+        text.text = String::from(new_text);
+    }
+    win.invalidate(id);
+}
+
+fn refresh(win: &mut Window, browser: &State<Browser>) {
+    let platform = win.platform().clone();
+    let entries = browser.update(|b| {
+        let mut folders = Vec::new();
+        let mut files = Vec::new();
+        for entry in platform.read_dir(&b.directory).unwrap_or_default() {
+            if entry.name == "." || entry.name == ".." {
+                continue;
+            }
+            if entry.is_dir {
+                folders.push(format!("{}/", entry.name));
+            } else if b.all_files || matches_filter(&entry.name, &b.extensions) {
+                files.push(entry.name);
+            }
+        }
+        folders.sort();
+        files.sort();
+
+        b.entries.clear();
+        if b.directory != "/" {
+            b.entries.push(String::from("../"));
+        }
+        b.entries.append(&mut folders);
+        b.entries.append(&mut files);
+        b.entries.clone()
+    });
+
+    let directory = browser.borrow().directory.clone();
+    set_text(win, PATH, &directory);
+    if let Some(list) = win.find_widget_by_id_mut(LIST) {
+        list.set_items(entries);
+        list.set_selected(None);
+    }
+    win.invalidate(LIST);
+}
+
+fn matches_filter(name: &str, extensions: &[String]) -> bool {
+    name.rsplit_once('.').is_some_and(|(_, ext)| extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)))
+}
+
+fn selected_entry(win: &Window, browser: &State<Browser>) -> Option<String> {
+    win.find_widget_by_id(LIST)
+        .and_then(|w| w.selected())
+        .and_then(|i| browser.borrow().entries.get(i).cloned())
+}
+
+fn enter_folder(win: &mut Window, browser: &State<Browser>) -> bool {
+    let Some(entry) = selected_entry(win, browser) else {
+        return false;
+    };
+    let Some(folder) = entry.strip_suffix('/') else {
+        return false;
+    };
+    browser.update(|b| {
+        b.directory = match folder {
+            ".." => String::from(parent_of(&b.directory)),
+            name => join(&b.directory, name),
+        };
+    });
+    refresh(win, browser);
+    true
+}
+
+// When saving, the typed name wins over whatever row is highlighted.
+fn accept(win: &mut Window, browser: &State<Browser>, reply: &Reply<String>, saving: bool) {
+    if !saving && enter_folder(win, browser) {
+        return;
+    }
+
+    let name = if saving { Some(text_of(win, INPUT)) } else { selected_entry(win, browser) };
+    match name.as_deref().map(str::trim) {
+        Some(name) if !name.is_empty() => {
+            let path = if name.starts_with('/') { String::from(name) } else { join(&browser.borrow().directory, name) };
+            reply.set(Some(Some(path)));
+        }
+        _ => {}
+    }
+}
+
+fn new_folder(win: &mut Window, browser: &State<Browser>) {
+    let browser = browser.clone();
+    win.prompt("New folder", "Folder name:", "", move |win, name| {
+        let Some(name) = name.filter(|n| !n.trim().is_empty()) else {
+            return;
+        };
+        let path = join(&browser.borrow().directory, name.trim());
+        if !win.platform().create_dir(&path) {
+            win.message_box("New folder", &format!("Couldn't create {}", path), &["OK"], |_, _| {});
+            return;
+        }
+        refresh(win, &browser);
+    });
+}
+
+fn normalize(path: &str) -> &str {
+    match path.trim_end_matches('/') {
+        "" => "/",
+        trimmed => trimmed,
+    }
+}
+
+fn join(directory: &str, name: &str) -> String {
+    match directory {
+        "/" => format!("/{}", name),
+        _ => format!("{}/{}", directory, name),
+    }
+}
+
+pub(crate) fn parent_of(path: &str) -> &str {
+    match path.rsplit_once('/') {
+        Some(("", _)) | None => "/",
+        Some((parent, _)) => parent,
+    }
+}
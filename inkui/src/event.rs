@@ -33,6 +33,67 @@ pub struct RedrawEvent {
     pub to_db: bool,
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(C)]
+pub struct CloseRequestedEvent {
+    pub wid: u32,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(C)]
+pub struct FocusEvent {
+    pub wid: u32,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(C)]
+pub struct PointerEvent {
+    pub wid: u32,
+    pub x: usize,
+    pub y: usize,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(C)]
+pub struct CloseEvent {
+    pub wid: u32,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(C)]
+pub struct ClipboardEvent {
+    pub wid: u32,
+    pub owner: u32,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(C)]
+pub struct ScreenEvent {
+    pub wid: u32,
+    pub width: usize,
+    pub height: usize,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(C)]
+pub struct ThemeEvent {
+    pub wid: u32,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(C)]
+pub struct WindowListEvent {
+    pub wid: u32,
+    pub subject: u32,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(C)]
+pub struct OverflowEvent {
+    pub wid: u32,
+    pub dropped: usize,
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 #[repr(C)]
 pub enum Event {
@@ -40,5 +101,47 @@ pub enum Event {
     Keyboard(KeyboardEvent),
     Resize(ResizeEvent),
     Redraw(RedrawEvent),
+    CloseRequested(CloseRequestedEvent),
+    FocusIn(FocusEvent),
+    FocusOut(FocusEvent),
+    PointerEnter(PointerEvent),
+    PointerLeave(PointerEvent),
+    Close(CloseEvent),
+    SelectionChanged(ClipboardEvent),
+    WindowCreated(WindowListEvent),
+    WindowDestroyed(WindowListEvent),
+    WindowFocused(WindowListEvent),
+    WindowChanged(WindowListEvent),
+    WindowSwitch(WindowListEvent),
+    ScreenResized(ScreenEvent),
+    WorkspaceChanged(WindowListEvent),
+    ThemeChanged(ThemeEvent),
+    Overflow(OverflowEvent),
     None,
 }
+
+impl Event {
+    pub fn get_window_id(&self) -> u32 {
+        match self {
+            Event::Mouse(event) => event.wid,
+            Event::Keyboard(event) => event.wid,
+            Event::Resize(event) => event.wid,
+            Event::Redraw(event) => event.wid,
+            Event::CloseRequested(event) => event.wid,
+            Event::FocusIn(event) | Event::FocusOut(event) => event.wid,
+            Event::PointerEnter(event) | Event::PointerLeave(event) => event.wid,
+            Event::Close(event) => event.wid,
+            Event::SelectionChanged(event) => event.wid,
+            Event::WindowCreated(event)
+            | Event::WindowDestroyed(event)
+            | Event::WindowFocused(event)
+            | Event::WindowChanged(event)
+            | Event::WindowSwitch(event)
+            | Event::WorkspaceChanged(event) => event.wid,
+            Event::ScreenResized(event) => event.wid,
+            Event::ThemeChanged(event) => event.wid,
+            Event::Overflow(event) => event.wid,
+            Event::None => 0,
+        }
+    }
+}
@@ -1,9 +1,9 @@
-use crate::types::Color;
+use crate::types::{Color, Rect};
 
 pub mod primitives;
 
-pub fn draw_pixel(buffer: &mut [u32], width: usize, x: usize, y: usize, color: Color) {
-    if width == 0 || color.a == 0 { return; }
+pub fn draw_pixel(buffer: &mut [u32], width: usize, clip: &Rect, x: usize, y: usize, color: Color) {
+    if width == 0 || color.a == 0 || !clip.contains(x, y) { return; }
 
     let idx = y * width + x;
     if idx >= buffer.len() { return; }
@@ -35,7 +35,8 @@ pub fn draw_pixel(buffer: &mut [u32], width: usize, x: usize, y: usize, color: C
     }
 }
 
-pub fn draw_u32(buffer: &mut [u32], width: usize, x: usize, y: usize, color: u32) {
+pub fn draw_u32(buffer: &mut [u32], width: usize, clip: &Rect, x: usize, y: usize, color: u32) {
+    if !clip.contains(x, y) { return; }
     let idx = y * width + x;
     if idx < buffer.len() {
         buffer[idx] = color;
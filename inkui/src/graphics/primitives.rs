@@ -1,10 +1,10 @@
 use crate::graphics::{draw_pixel, draw_u32};
 use crate::math::{ceil_f32, sqrt_f64};
-use crate::types::{Color, Size};
+use crate::types::{Color, Rect, Size};
 use alloc::vec::Vec;
 use titanf::TrueTypeFont;
 
-pub fn draw_line(buffer: &mut [u32], width0: usize, x0: usize, y0: usize, x1: usize, y1: usize, color: Color, width: usize) {
+pub fn draw_line(buffer: &mut [u32], width0: usize, clip: &Rect, x0: usize, y0: usize, x1: usize, y1: usize, color: Color, width: usize) {
     let dx = (x1 as isize - x0 as isize).abs();
     let dy = -(y1 as isize - y0 as isize).abs();
     let sx = if x0 < x1 { 1 } else { -1 };
@@ -22,7 +22,7 @@ pub fn draw_line(buffer: &mut [u32], width0: usize, x0: usize, y0: usize, x1: us
                 if nx >= 0 && nx < width0 as isize && ny >= 0 && ny < core::cmp::max(y0, y1) as isize {
                     let idx = (ny as usize) * width0 + (nx as usize);
                     if idx < buffer.len() {
-                        draw_pixel(buffer, width0, nx as usize, ny as usize, color)
+                        draw_pixel(buffer, width0, clip, nx as usize, ny as usize, color)
                     }
                 }
             }
@@ -47,6 +47,7 @@ pub fn draw_line(buffer: &mut [u32], width0: usize, x0: usize, y0: usize, x1: us
 pub fn draw_square(
     buffer: &mut [u32],
     buffer_width: usize,
+    clip: &Rect,
     x: usize,
     y: usize,
     width: usize,
@@ -59,12 +60,13 @@ pub fn draw_square(
     if color.a == 0 && (border_size == 0 || border_color.a == 0) {
         return;
     }
-    draw_square_alpha(buffer, buffer_width, x, y, width, height, rounding, color, border_size, border_color);
+    draw_square_alpha(buffer, buffer_width, clip, x, y, width, height, rounding, color, border_size, border_color);
 }
 
 pub fn draw_square_alpha(
     buffer: &mut [u32],
     buffer_width: usize,
+    clip: &Rect,
     x: usize,
     y: usize,
     width: usize,
@@ -85,8 +87,10 @@ pub fn draw_square_alpha(
     };
     let r = r_val.min(width as f32 / 2.0).min(height as f32 / 2.0);
 
-    let end_y = (y + height).min(buffer.len() / buffer_width);
-    let end_x = (x + width).min(buffer_width);
+    let end_y = (y + height).min(buffer.len() / buffer_width).min(clip.bottom());
+    let end_x = (x + width).min(buffer_width).min(clip.right());
+    let start_y = y.max(clip.y);
+    let start_x = x.max(clip.x);
 
     let is_bg_opaque = color.a == 255;
     let bg_u32 = color.to_u32();
@@ -100,11 +104,9 @@ pub fn draw_square_alpha(
 
 
     if r < 0.1 && border_size == 0 && is_bg_opaque {
-        for row in y..end_y {
-            let start = row * buffer_width + x;
-            let end = start + width;
-            if end <= buffer.len() {
-                buffer[start..end].fill(bg_u32);
+        for row in start_y..end_y {
+            if start_x < end_x {
+                buffer[row * buffer_width + start_x..row * buffer_width + end_x].fill(bg_u32);
             }
         }
         return;
@@ -117,7 +119,7 @@ pub fn draw_square_alpha(
 
     let border_sz_f = border_size as f32;
 
-    for row in y..end_y {
+    for row in start_y..end_y {
         let ly = (row - y) as f32 + 0.5;
         let is_top_row = row < inner_y_start;
         let is_bottom_row = row >= inner_y_end;
@@ -130,7 +132,7 @@ pub fn draw_square_alpha(
         let is_top_inner_edge = has_border && (row == y + border_size - 1);
         let is_bottom_inner_edge = has_border && (row == y + height - border_size);
 
-        for col in x..end_x {
+        for col in start_x..end_x {
             if (!check_corners) || (col >= inner_x_start && col < inner_x_end) {
                 let is_left_border = has_border && (col < x + border_size);
                 let is_right_border = has_border && (col >= x + width - border_size);
@@ -150,14 +152,14 @@ pub fn draw_square_alpha(
                     }
 
                     if is_border_opaque && final_color.a == 255 {
-                        draw_u32(buffer, buffer_width, col, row, border_u32);
+                        draw_u32(buffer, buffer_width, clip, col, row, border_u32);
                     } else {
-                        if is_bg_opaque { draw_u32(buffer, buffer_width, col, row, bg_u32); } else { draw_pixel(buffer, buffer_width, col, row, color); }
+                        if is_bg_opaque { draw_u32(buffer, buffer_width, clip, col, row, bg_u32); } else { draw_pixel(buffer, buffer_width, clip, col, row, color); }
 
-                        draw_pixel(buffer, buffer_width, col, row, final_color);
+                        draw_pixel(buffer, buffer_width, clip, col, row, final_color);
                     }
                 } else {
-                    if is_bg_opaque { draw_u32(buffer, buffer_width, col, row, bg_u32); } else { draw_pixel(buffer, buffer_width, col, row, color); }
+                    if is_bg_opaque { draw_u32(buffer, buffer_width, clip, col, row, bg_u32); } else { draw_pixel(buffer, buffer_width, clip, col, row, color); }
                 }
                 continue;
             }
@@ -197,7 +199,7 @@ pub fn draw_square_alpha(
                     let base_color = if has_border { border_color } else { color };
                     let mut final_color = base_color;
                     final_color.a = (base_color.a as f32 * alpha_factor) as u8;
-                    draw_pixel(buffer, buffer_width, col, row, final_color);
+                    draw_pixel(buffer, buffer_width, clip, col, row, final_color);
                 }
                 continue;
             }
@@ -215,16 +217,16 @@ pub fn draw_square_alpha(
                         let border_alpha_factor = (dist - inner_r).clamp(0.0, 1.0);
 
 
-                        if is_bg_opaque { draw_u32(buffer, buffer_width, col, row, bg_u32); } else { draw_pixel(buffer, buffer_width, col, row, color); }
+                        if is_bg_opaque { draw_u32(buffer, buffer_width, clip, col, row, bg_u32); } else { draw_pixel(buffer, buffer_width, clip, col, row, color); }
 
 
                         final_color.a = (border_color.a as f32 * border_alpha_factor) as u8;
-                        draw_pixel(buffer, buffer_width, col, row, final_color);
+                        draw_pixel(buffer, buffer_width, clip, col, row, final_color);
                     } else {
-                        if is_border_opaque { draw_u32(buffer, buffer_width, col, row, border_u32); } else { draw_pixel(buffer, buffer_width, col, row, border_color); }
+                        if is_border_opaque { draw_u32(buffer, buffer_width, clip, col, row, border_u32); } else { draw_pixel(buffer, buffer_width, clip, col, row, border_color); }
                     }
                 } else {
-                    if is_bg_opaque { draw_u32(buffer, buffer_width, col, row, bg_u32); } else { draw_pixel(buffer, buffer_width, col, row, color); }
+                    if is_bg_opaque { draw_u32(buffer, buffer_width, clip, col, row, bg_u32); } else { draw_pixel(buffer, buffer_width, clip, col, row, color); }
                 }
             } else {
                 if dist > r - 1.0 {
@@ -234,9 +236,9 @@ pub fn draw_square_alpha(
                     let _alpha_factor = (r + 1.0 - dist).clamp(0.0, 1.0);
 
 
-                    if is_bg_opaque { draw_u32(buffer, buffer_width, col, row, bg_u32); } else { draw_pixel(buffer, buffer_width, col, row, color); }
+                    if is_bg_opaque { draw_u32(buffer, buffer_width, clip, col, row, bg_u32); } else { draw_pixel(buffer, buffer_width, clip, col, row, color); }
                 } else {
-                    if is_bg_opaque { draw_u32(buffer, buffer_width, col, row, bg_u32); } else { draw_pixel(buffer, buffer_width, col, row, color); }
+                    if is_bg_opaque { draw_u32(buffer, buffer_width, clip, col, row, bg_u32); } else { draw_pixel(buffer, buffer_width, clip, col, row, color); }
                 }
             }
         }
@@ -423,6 +425,7 @@ fn parse_ansi_text(text: &str, default_color: Color, default_size: f32) -> (Vec<
 pub fn draw_text_formatted(
     buffer: &mut [u32],
     buffer_width: usize,
+    clip: &Rect,
     x: usize,
     y: usize,
     text: &str,
@@ -519,7 +522,7 @@ pub fn draw_text_formatted(
                         if dx >= buffer_width { break; }
                         if max_width > 0 && dx >= x + max_width { break; }
 
-                        draw_pixel(buffer, buffer_width, dx, udy, segment.bg_color);
+                        draw_pixel(buffer, buffer_width, clip, dx, udy, segment.bg_color);
                     }
                 }
             }
@@ -545,7 +548,7 @@ pub fn draw_text_formatted(
                     if bitmap_alpha > 0 {
                         let mut pixel_color = segment.color;
                         pixel_color.a = ((pixel_color.a as u16 * bitmap_alpha as u16) / 255) as u8;
-                        draw_pixel(buffer, buffer_width, dest_x, dest_y, pixel_color);
+                        draw_pixel(buffer, buffer_width, clip, dest_x, dest_y, pixel_color);
                     }
                 }
             }
@@ -562,6 +565,7 @@ pub fn draw_text_formatted(
 pub fn draw_text(
     buffer: &mut [u32],
     buffer_width: usize,
+    clip: &Rect,
     x: usize,
     y: usize,
     text: &str,
@@ -569,7 +573,23 @@ pub fn draw_text(
     size: f32,
     color: Color,
 ) {
-    draw_text_formatted(buffer, buffer_width, x, y, text, font, size, color, 0, 0, 9999, y);
+    draw_text_formatted(buffer, buffer_width, clip, x, y, text, font, size, color, 0, 0, 9999, y);
+}
+
+pub fn text_width(text: &str, font: &mut TrueTypeFont, default_size: f32) -> usize {
+    let (segments, clean_text) = parse_ansi_text(text, Color::rgb(0, 0, 0), default_size);
+    let mut widest = 0;
+    let mut line = 0;
+    for (i, c) in clean_text.chars().enumerate() {
+        if c == '\n' {
+            line = 0;
+            continue;
+        }
+        let size = segments.iter().find(|s| i >= s.start && i < s.end).map_or(default_size, |s| s.size);
+        line += font.get_char::<true>(c, size).0.advance_width;
+        widest = widest.max(line);
+    }
+    widest
 }
 
 fn lerp_u8(a: u8, b: u8, t: f32) -> u8 {
@@ -592,6 +612,7 @@ use crate::types::{BackgroundStyle, GradientDirection, LinearGradient};
 pub fn draw_square_gradient(
     buffer: &mut [u32],
     buffer_width: usize,
+    clip: &Rect,
     x: usize,
     y: usize,
     width: usize,
@@ -610,16 +631,18 @@ pub fn draw_square_gradient(
     };
     let r = r_val.min(width as f32 / 2.0).min(height as f32 / 2.0);
 
-    let end_y = (y + height).min(buffer.len() / buffer_width);
-    let end_x = (x + width).min(buffer_width);
+    let end_y = (y + height).min(buffer.len() / buffer_width).min(clip.bottom());
+    let end_x = (x + width).min(buffer_width).min(clip.right());
+    let start_y = y.max(clip.y);
+    let start_x = x.max(clip.x);
 
     let w_f = width as f32;
     let h_f = height as f32;
 
-    for row in y..end_y {
+    for row in start_y..end_y {
         let ly = (row - y) as f32 + 0.5;
 
-        for col in x..end_x {
+        for col in start_x..end_x {
             let lx = (col - x) as f32 + 0.5;
 
             let mut dist = 0.0;
@@ -683,7 +706,7 @@ pub fn draw_square_gradient(
             }
 
             if color.a > 0 {
-                draw_pixel(buffer, buffer_width, col, row, color);
+                draw_pixel(buffer, buffer_width, clip, col, row, color);
             }
         }
     }
@@ -693,6 +716,7 @@ pub fn draw_square_gradient(
 pub fn draw_background_style(
     buffer: &mut [u32],
     buffer_width: usize,
+    clip: &Rect,
     x: usize,
     y: usize,
     width: usize,
@@ -704,14 +728,14 @@ pub fn draw_background_style(
 ) {
     match style {
         BackgroundStyle::Solid(color) => {
-            draw_square(buffer, buffer_width, x, y, width, height, rounding, *color, border_size, border_color);
+            draw_square(buffer, buffer_width, clip, x, y, width, height, rounding, *color, border_size, border_color);
         }
         BackgroundStyle::Gradient(gradient) => {
-            draw_square_gradient(buffer, buffer_width, x, y, width, height, rounding, gradient);
+            draw_square_gradient(buffer, buffer_width, clip, x, y, width, height, rounding, gradient);
 
 
             if border_size > 0 && border_color.a > 0 {
-                draw_border_only(buffer, buffer_width, x, y, width, height, rounding, border_size, border_color);
+                draw_border_only(buffer, buffer_width, clip, x, y, width, height, rounding, border_size, border_color);
             }
         }
     }
@@ -720,6 +744,7 @@ pub fn draw_background_style(
 fn draw_border_only(
     buffer: &mut [u32],
     buffer_width: usize,
+    clip: &Rect,
     x: usize,
     y: usize,
     width: usize,
@@ -728,5 +753,5 @@ fn draw_border_only(
     border_size: usize,
     border_color: Color,
 ) {
-    draw_square(buffer, buffer_width, x, y, width, height, rounding, Color::rgba(0, 0, 0, 0), border_size, border_color);
+    draw_square(buffer, buffer_width, clip, x, y, width, height, rounding, Color::rgba(0, 0, 0, 0), border_size, border_color);
 }
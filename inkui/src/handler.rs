@@ -0,0 +1,118 @@
+use crate::widget::WidgetId;
+use crate::window::Window;
+use alloc::rc::Rc;
+use alloc::string::String;
+use core::cell::{Ref, RefCell};
+use core::fmt;
+
+pub struct Callback<A>(Rc<RefCell<dyn FnMut(&mut Window, WidgetId, A)>>);
+
+impl<A> Callback<A> {
+    pub fn new(handler: impl FnMut(&mut Window, WidgetId, A) + 'static) -> Self {
+        Callback(Rc::new(RefCell::new(handler)))
+    }
+
+    pub fn call(&self, win: &mut Window, id: WidgetId, arg: A) {
+        if let Ok(mut handler) = self.0.try_borrow_mut() {
+            handler(win, id, arg);
+        }
+    }
+}
+
+impl<A> Clone for Callback<A> {
+    fn clone(&self) -> Self {
+        Callback(self.0.clone())
+    }
+}
+
+impl<A> fmt::Debug for Callback<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Callback")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Text(String),
+    Selected(usize),
+    Bool(bool),
+    Number(f32),
+}
+
+impl Value {
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Value::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn as_selected(&self) -> Option<usize> {
+        match self {
+            Value::Selected(index) => Some(*index),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<f32> {
+        match self {
+            Value::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Handlers {
+    pub click: Option<Callback<()>>,
+    pub double_click: Option<Callback<()>>,
+    pub right_click: Option<Callback<()>>,
+    pub hover: Option<Callback<bool>>,
+    pub change: Option<Callback<Value>>,
+    pub submit: Option<Callback<()>>,
+}
+
+pub struct State<T>(Rc<RefCell<T>>);
+
+impl<T> State<T> {
+    pub fn new(value: T) -> Self {
+        State(Rc::new(RefCell::new(value)))
+    }
+
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.0.borrow()
+    }
+
+    pub fn set(&self, value: T) {
+        *self.0.borrow_mut() = value;
+    }
+
+    pub fn update<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut self.0.borrow_mut())
+    }
+}
+
+impl<T: Clone> State<T> {
+    pub fn get(&self) -> T {
+        self.0.borrow().clone()
+    }
+}
+
+impl<T> Clone for State<T> {
+    fn clone(&self) -> Self {
+        State(self.0.clone())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for State<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("State").field(&self.0.borrow()).finish()
+    }
+}
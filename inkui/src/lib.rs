@@ -1,17 +1,41 @@
 #![no_std]
 
 extern crate alloc;
+#[cfg(not(feature = "krakeos"))]
+extern crate std;
 
 pub mod types;
+pub mod animation;
+pub mod controls;
+pub mod damage;
+pub mod dialog;
 pub mod event;
 pub mod math;
 pub mod graphics;
+pub mod handler;
 pub mod layout;
+pub mod markup;
+pub mod platform;
+pub mod scroll;
+pub mod table;
+pub mod text_area;
+pub mod theme;
+pub mod timer;
 pub mod widget;
 pub mod window;
 
+pub use animation::{Animation, Easing, Property};
+pub use controls::{Orientation, Range};
+pub use dialog::FileChooser;
 pub use event::Event;
+pub use handler::{State, Value};
 pub use layout::{Display, FlexDirection};
-pub use types::{Align, BackgroundStyle, Color, GradientDirection, LinearGradient, Size};
+pub use markup::{Bindings, MarkupError};
+pub use platform::{CursorShape, Headless, Items, Platform};
+pub use table::{Column, SortOrder};
+pub use text_area::Editor;
+pub use theme::{Style, Theme, WidgetState};
+pub use timer::TimerId;
+pub use types::{Align, BackgroundStyle, Color, GradientDirection, LinearGradient, Rect, Size};
 pub use widget::Widget;
 pub use window::Window;
@@ -0,0 +1,584 @@
+use crate::controls::{Orientation, Range};
+use crate::handler::{Callback, Handlers, Value};
+use crate::layout::{Display, FlexDirection};
+use crate::platform::Platform;
+use crate::table::Column;
+use crate::types::{Align, Color, Size};
+use crate::widget::{Widget, WidgetId};
+use crate::window::Window;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkupError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for MarkupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 {
+            f.write_str(&self.message)
+        } else {
+            write!(f, "{}:{}: {}", self.line, self.column, self.message)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Position {
+    line: usize,
+    column: usize,
+}
+
+impl Position {
+    fn error(self, message: String) -> MarkupError {
+        MarkupError { line: self.line, column: self.column, message }
+    }
+
+    fn after(mut self, text: &str) -> Position {
+        for c in text.chars() {
+            if c == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
+        self
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct Bindings {
+    actions: BTreeMap<String, Callback<()>>,
+    hovers: BTreeMap<String, Callback<bool>>,
+    changes: BTreeMap<String, Callback<Value>>,
+    lenient: bool,
+}
+
+impl Bindings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn lenient(mut self) -> Self {
+        self.lenient = true;
+        self
+    }
+
+    pub fn action(mut self, name: &str, mut handler: impl FnMut(&mut Window, WidgetId) + 'static) -> Self {
+        self.actions.insert(String::from(name), Callback::new(move |win, id, ()| handler(win, id)));
+        self
+    }
+
+    pub fn hover(mut self, name: &str, handler: impl FnMut(&mut Window, WidgetId, bool) + 'static) -> Self {
+        self.hovers.insert(String::from(name), Callback::new(handler));
+        self
+    }
+
+    pub fn change(mut self, name: &str, handler: impl FnMut(&mut Window, WidgetId, Value) + 'static) -> Self {
+        self.changes.insert(String::from(name), Callback::new(handler));
+        self
+    }
+
+    fn find<A>(&self, map: &BTreeMap<String, Callback<A>>, attribute: &Attribute) -> Result<Option<Callback<A>>, MarkupError> {
+        match map.get(&attribute.value) {
+            Some(handler) => Ok(Some(handler.clone())),
+            None if self.lenient => Ok(None),
+            None => Err(attribute.at.error(format!("no handler named `{}`", attribute.value))),
+        }
+    }
+}
+
+pub fn parse(platform: &dyn Platform, source: &str, bindings: &Bindings) -> Result<Vec<Widget>, MarkupError> {
+    let mut parser = Parser { source, pos: 0, line: 1, column: 1 };
+    let mut widgets = Vec::new();
+    parser.skip_misc()?;
+    while parser.peek().is_some() {
+        let element = parser.element()?;
+        widgets.push(build(platform, &element, bindings)?);
+        parser.skip_misc()?;
+    }
+    Ok(widgets)
+}
+
+pub fn load(platform: &dyn Platform, path: &str, bindings: &Bindings) -> Result<Vec<Widget>, MarkupError> {
+    let source = platform.read_file(path)
+        .and_then(|data| String::from_utf8(data).ok())
+        .ok_or_else(|| MarkupError { line: 0, column: 0, message: format!("can't read {}", path) })?;
+    parse(platform, &source, bindings)
+}
+
+struct Attribute {
+    name: String,
+    value: String,
+    at: Position,
+}
+
+struct Element {
+    name: String,
+    attributes: Vec<Attribute>,
+    children: Vec<Element>,
+    text: String,
+    at: Position,
+}
+
+impl Element {
+    fn attribute(&self, name: &str) -> Option<&Attribute> {
+        self.attributes.iter().find(|a| a.name == name)
+    }
+
+    fn number<T: FromStr>(&self, name: &str, default: T) -> Result<T, MarkupError> {
+        self.attribute(name).map_or(Ok(default), number)
+    }
+
+    fn flag(&self, name: &str, default: bool) -> Result<bool, MarkupError> {
+        self.attribute(name).map_or(Ok(default), flag)
+    }
+
+    fn text(&self) -> &str {
+        self.attribute("text").map_or(self.text.trim(), |a| a.value.as_str())
+    }
+
+    fn options(&self) -> Vec<String> {
+        self.children.iter().map(|option| String::from(option.text.trim())).collect()
+    }
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    pos: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Parser<'_> {
+    fn here(&self) -> Position {
+        Position { line: self.line, column: self.column }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.source[self.pos..].chars().next()
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        self.source[self.pos..].starts_with(s)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn skip(&mut self, s: &str) {
+        for _ in s.chars() {
+            self.bump();
+        }
+    }
+
+    fn expect(&mut self, s: &str) -> Result<(), MarkupError> {
+        if !self.starts_with(s) {
+            return Err(self.here().error(format!("expected `{}`", s)));
+        }
+        self.skip(s);
+        Ok(())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    fn skip_past(&mut self, end: &str, at: Position, what: &str) -> Result<(), MarkupError> {
+        let Some(offset) = self.source[self.pos..].find(end) else {
+            return Err(at.error(format!("{} is never closed", what)));
+        };
+        let target = self.pos + offset + end.len();
+        while self.pos < target {
+            self.bump();
+        }
+        Ok(())
+    }
+
+    fn skip_misc(&mut self) -> Result<(), MarkupError> {
+        loop {
+            self.skip_whitespace();
+            let at = self.here();
+            if self.starts_with("<!--") {
+                self.skip_past("-->", at, "comment")?;
+            } else if self.starts_with("<?") {
+                self.skip_past("?>", at, "declaration")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<String, MarkupError> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+            self.bump();
+        }
+        if self.pos == start {
+            return Err(self.here().error(String::from("expected a name")));
+        }
+        Ok(String::from(&self.source[start..self.pos]))
+    }
+
+    fn attribute(&mut self) -> Result<Attribute, MarkupError> {
+        let at = self.here();
+        let name = self.name()?;
+        self.skip_whitespace();
+        self.expect("=")?;
+        self.skip_whitespace();
+        let quote = match self.peek() {
+            Some(q @ ('"' | '\'')) => q,
+            _ => return Err(self.here().error(format!("expected a quoted value for `{}`", name))),
+        };
+        self.bump();
+        let value_at = self.here();
+        let mut raw = String::new();
+        loop {
+            match self.bump() {
+                Some(c) if c == quote => break,
+                Some(c) => raw.push(c),
+                None => return Err(at.error(format!("value of `{}` is never closed", name))),
+            }
+        }
+        Ok(Attribute { name, value: unescape(&raw, value_at)?, at })
+    }
+
+    fn element(&mut self) -> Result<Element, MarkupError> {
+        let at = self.here();
+        self.expect("<")?;
+        let name = self.name()?;
+        let mut element = Element { name, attributes: Vec::new(), children: Vec::new(), text: String::new(), at };
+
+        loop {
+            self.skip_whitespace();
+            if self.starts_with("/>") {
+                self.skip("/>");
+                return Ok(element);
+            }
+            if self.starts_with(">") {
+                self.skip(">");
+                break;
+            }
+            let attribute = self.attribute()?;
+            if element.attribute(&attribute.name).is_some() {
+                return Err(attribute.at.error(format!("`{}` is set twice", attribute.name)));
+            }
+            element.attributes.push(attribute);
+        }
+
+        loop {
+            let here = self.here();
+            if self.starts_with("<!--") {
+                self.skip_past("-->", here, "comment")?;
+            } else if self.starts_with("</") {
+                self.skip("</");
+                let close = self.name()?;
+                if close != element.name {
+                    return Err(here.error(format!("expected `</{}>`, found `</{}>`", element.name, close)));
+                }
+                self.skip_whitespace();
+                self.expect(">")?;
+                return Ok(element);
+            } else if self.starts_with("<") {
+                element.children.push(self.element()?);
+            } else if self.peek().is_none() {
+                return Err(at.error(format!("`<{}>` is never closed", element.name)));
+            } else {
+                let start = self.pos;
+                while self.peek().is_some_and(|c| c != '<') {
+                    self.bump();
+                }
+                element.text.push_str(&unescape(&self.source[start..self.pos], here)?);
+            }
+        }
+    }
+}
+
+fn unescape(raw: &str, at: Position) -> Result<String, MarkupError> {
+    let mut out = String::new();
+    let mut rest = raw;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        let amp_at = at.after(&raw[..raw.len() - rest.len() + amp]);
+        let Some(semi) = rest[amp..].find(';') else {
+            return Err(amp_at.error(String::from("`&` must start an entity such as `&amp;`")));
+        };
+        let entity = &rest[amp + 1..amp + semi];
+        let c = match entity {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => match entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X")) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                None => entity.strip_prefix('#').and_then(|dec| dec.parse().ok()).and_then(char::from_u32),
+            },
+        };
+        match c {
+            Some(c) => out.push(c),
+            None => return Err(amp_at.error(format!("unknown entity `&{};`", entity))),
+        }
+        rest = &rest[amp + semi + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn number<T: FromStr>(attribute: &Attribute) -> Result<T, MarkupError> {
+    attribute.value.trim().parse()
+        .map_err(|_| attribute.at.error(format!("`{}` of `{}` isn't a number", attribute.value, attribute.name)))
+}
+
+fn flag(attribute: &Attribute) -> Result<bool, MarkupError> {
+    match attribute.value.trim() {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        other => Err(attribute.at.error(format!("`{}` should be `true` or `false`, not `{}`", attribute.name, other))),
+    }
+}
+
+fn size(attribute: &Attribute) -> Result<Size, MarkupError> {
+    let value = attribute.value.trim();
+    let bad = || attribute.at.error(format!("`{}` isn't a size", value));
+    let pixels = |v: &str| v.trim().trim_end_matches("px").parse::<usize>().map_err(|_| bad());
+
+    if value == "auto" {
+        return Ok(Size::Auto);
+    }
+    if let Some(percent) = value.strip_suffix('%') {
+        return percent.trim().parse().map(Size::Relative).map_err(|_| bad());
+    }
+    match value.split_once(' ') {
+        Some(("left", v)) => pixels(v).map(Size::FromLeft),
+        Some(("right", v)) => pixels(v).map(Size::FromRight),
+        Some(("top", v)) => pixels(v).map(Size::FromUp),
+        Some(("bottom", v)) => pixels(v).map(Size::FromDown),
+        Some(_) => Err(bad()),
+        None => pixels(value).map(Size::Absolute),
+    }
+}
+
+fn color(attribute: &Attribute) -> Result<Color, MarkupError> {
+    Color::parse(&attribute.value).ok_or_else(|| attribute.at.error(format!("`{}` isn't a color", attribute.value.trim())))
+}
+
+const COMMON: &[&str] = &[
+    "id", "x", "y", "width", "height", "margin", "padding", "background", "border-size", "border-color",
+    "border-radius", "text-color", "text-size", "text-align", "display", "direction", "flex-wrap", "rows", "cols",
+    "enabled", "on-click", "on-double-click", "on-right-click", "on-hover", "on-change", "on-submit",
+];
+
+fn widget_kind(name: &str) -> Option<(&'static [&'static str], Option<&'static str>)> {
+    let kind: (&[&str], Option<&str>) = match name {
+        "frame" | "scroll-view" => (&["content-width", "content-height"], None),
+        "button" | "label" => (&["text"], Some("")),
+        "text-input" => (&["placeholder"], Some("")),
+        "text-area" => (&["text", "wrap", "line-numbers"], Some("")),
+        "canvas" => (&[], Some("")),
+        "image" => (&["src"], Some("")),
+        "list" => (&["selected", "row-height"], Some("option")),
+        "table" => (&["row-height"], Some("column")),
+        "checkbox" => (&["text", "checked", "accent"], Some("")),
+        "radio-group" => (&["selected", "accent"], Some("option")),
+        "dropdown" => (&["selected"], Some("option")),
+        "slider" => (&["min", "max", "step", "value", "orientation", "accent"], Some("")),
+        "spinner" => (&["min", "max", "step", "value"], Some("")),
+        "progress-bar" => (&["progress", "accent"], Some("")),
+        _ => return None,
+    };
+    Some(kind)
+}
+
+fn build(platform: &dyn Platform, element: &Element, bindings: &Bindings) -> Result<Widget, MarkupError> {
+    let Some((own, holds)) = widget_kind(&element.name) else {
+        return Err(element.at.error(format!("unknown widget `<{}>`", element.name)));
+    };
+    for attribute in &element.attributes {
+        if !COMMON.contains(&attribute.name.as_str()) && !own.contains(&attribute.name.as_str()) {
+            return Err(attribute.at.error(format!("`<{}>` has no attribute `{}`", element.name, attribute.name)));
+        }
+    }
+    if let Some(holds) = holds {
+        if let Some(child) = element.children.iter().find(|c| c.name != holds) {
+            return Err(child.at.error(format!("`<{}>` can't hold `<{}>`", element.name, child.name)));
+        }
+    }
+
+    let id = element.number("id", 0)?;
+    let range = || -> Result<(Range, f32), MarkupError> {
+        let min = element.number("min", 0.0)?;
+        let max = element.number("max", 100.0)?;
+        let range = Range::new(min, max, element.number("step", 1.0)?);
+        Ok((range, element.number("value", min)?))
+    };
+
+    let mut widget = match element.name.as_str() {
+        "frame" => Widget::frame(id),
+        "scroll-view" => {
+            let width = element.attribute("content-width").map_or(Ok(Size::Auto), size)?;
+            let height = element.attribute("content-height").map_or(Ok(Size::Auto), size)?;
+            Widget::scroll_view(id).content_size(width, height)
+        }
+        "button" => Widget::button(id, element.text()),
+        "label" => Widget::label(id, element.text()),
+        "text-input" => Widget::text_input(id, element.attribute("placeholder").map_or("", |a| a.value.as_str())),
+        "text-area" => {
+            let mut area = Widget::text_area(id)
+                .wrap(element.flag("wrap", true)?)
+                .line_numbers(element.flag("line-numbers", false)?);
+            if let Some(editor) = area.editor_mut() {
+                editor.set_text(element.text());
+            }
+            area
+        }
+        "canvas" => Widget::canvas(id),
+        "image" => {
+            let Some(src) = element.attribute("src") else {
+                return Err(element.at.error(String::from("`<image>` needs a `src`")));
+            };
+            let data = platform.read_file(&src.value).ok_or_else(|| src.at.error(format!("can't read {}", src.value)))?;
+            Widget::image(id, &data)
+        }
+        "list" => Widget::list(id, element.options()),
+        "table" => {
+            let mut columns = Vec::new();
+            for column in &element.children {
+                let title = column.attribute("title").map_or(column.text.trim(), |a| a.value.as_str());
+                columns.push(Column::new(title, column.number("width", 100)?));
+            }
+            Widget::table(id, columns)
+        }
+        "checkbox" => {
+            let mut checkbox = Widget::checkbox(id, element.text());
+            checkbox.set_checked(element.flag("checked", false)?);
+            checkbox
+        }
+        "radio-group" => Widget::radio_group(id, element.options()),
+        "dropdown" => Widget::dropdown(id, element.options()),
+        "slider" => {
+            let (range, value) = range()?;
+            let orientation = match element.attribute("orientation") {
+                None => Orientation::Horizontal,
+                Some(a) if a.value == "horizontal" => Orientation::Horizontal,
+                Some(a) if a.value == "vertical" => Orientation::Vertical,
+                Some(a) => return Err(a.at.error(format!("`{}` isn't `horizontal` or `vertical`", a.value))),
+            };
+            Widget::slider(id, range, value).orientation(orientation)
+        }
+        "spinner" => {
+            let (range, value) = range()?;
+            Widget::spinner(id, range, value)
+        }
+        "progress-bar" => {
+            let mut bar = Widget::progress_bar(id);
+            match element.attribute("progress") {
+                Some(a) if a.value == "indeterminate" => bar.set_progress(None),
+                Some(a) => bar.set_progress(Some(number(a)?)),
+                None => {}
+            }
+            bar
+        }
+        _ => unreachable!(),
+    };
+
+    if let Some(selected) = element.attribute("selected") {
+        widget.set_selected(Some(number(selected)?));
+    }
+    if let Some(row_height) = element.attribute("row-height") {
+        widget = widget.row_height(number(row_height)?);
+    }
+    if let Some(accent) = element.attribute("accent") {
+        widget = widget.accent_color(color(accent)?);
+    }
+    for attribute in &element.attributes {
+        widget = apply(widget, attribute, bindings)?;
+    }
+    if let Some(display) = display(element)? {
+        widget = widget.set_display(display);
+    }
+
+    if holds.is_none() {
+        for child in &element.children {
+            widget = widget.add_child(build(platform, child, bindings)?);
+        }
+    }
+    Ok(widget)
+}
+
+fn apply(widget: Widget, attribute: &Attribute, bindings: &Bindings) -> Result<Widget, MarkupError> {
+    Ok(match attribute.name.as_str() {
+        "x" => widget.x(size(attribute)?),
+        "y" => widget.y(size(attribute)?),
+        "width" => widget.width(size(attribute)?),
+        "height" => widget.height(size(attribute)?),
+        "margin" => widget.margin(size(attribute)?),
+        "padding" => widget.padding(size(attribute)?),
+        "background" => widget.background_color(color(attribute)?),
+        "border-size" => widget.set_border_size(size(attribute)?),
+        "border-color" => widget.set_border_color(color(attribute)?),
+        "border-radius" => widget.set_border_radius(size(attribute)?),
+        "text-color" => widget.set_text_color(color(attribute)?),
+        "text-size" => widget.set_text_size(number(attribute)?),
+        "enabled" => widget.enabled(flag(attribute)?),
+        "text-align" => widget.set_text_align(match attribute.value.trim() {
+            "left" => Align::Left,
+            "center" => Align::Center,
+            "right" => Align::Right,
+            other => return Err(attribute.at.error(format!("`{}` isn't `left`, `center` or `right`", other))),
+        }),
+        "on-click" => bind(widget, bindings.find(&bindings.actions, attribute)?, |h| &mut h.click),
+        "on-double-click" => bind(widget, bindings.find(&bindings.actions, attribute)?, |h| &mut h.double_click),
+        "on-right-click" => bind(widget, bindings.find(&bindings.actions, attribute)?, |h| &mut h.right_click),
+        "on-submit" => bind(widget, bindings.find(&bindings.actions, attribute)?, |h| &mut h.submit),
+        "on-hover" => bind(widget, bindings.find(&bindings.hovers, attribute)?, |h| &mut h.hover),
+        "on-change" => bind(widget, bindings.find(&bindings.changes, attribute)?, |h| &mut h.change),
+        _ => widget,
+    })
+}
+
+fn bind<A>(mut widget: Widget, handler: Option<Callback<A>>, slot: fn(&mut Handlers) -> &mut Option<Callback<A>>) -> Widget {
+    if handler.is_some() {
+        *slot(widget.handlers_mut()) = handler;
+    }
+    widget
+}
+
+fn display(element: &Element) -> Result<Option<Display>, MarkupError> {
+    let Some(attribute) = element.attribute("display") else {
+        return Ok(None);
+    };
+    Ok(Some(match attribute.value.trim() {
+        "none" => Display::None,
+        "flex" => {
+            let direction = match element.attribute("direction") {
+                None => FlexDirection::Row,
+                Some(a) if a.value == "row" => FlexDirection::Row,
+                Some(a) if a.value == "column" => FlexDirection::Column,
+                Some(a) => return Err(a.at.error(format!("`{}` isn't `row` or `column`", a.value))),
+            };
+            Display::Flex { direction, wrap: element.flag("flex-wrap", false)? }
+        }
+        "grid" => Display::Grid { rows: element.number("rows", 1)?, cols: element.number("cols", 1)? },
+        other => return Err(attribute.at.error(format!("`{}` isn't `none`, `flex` or `grid`", other))),
+    }))
+}
@@ -0,0 +1,401 @@
+use crate::dialog::parent_of;
+use crate::event::Event;
+use crate::window::Window;
+use alloc::collections::BTreeMap;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+#[cfg(feature = "krakeos")]
+pub use std::graphics::{CursorShape, Items};
+
+#[cfg(not(feature = "krakeos"))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Items {
+    Wallpaper,
+    Bar,
+    Popup,
+    Window,
+    Null,
+}
+
+#[cfg(not(feature = "krakeos"))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CursorShape {
+    Arrow,
+    Text,
+    Hand,
+    ResizeHorizontal,
+    ResizeVertical,
+    ResizeDiagonal,
+    Busy,
+}
+
+pub const MIME_TEXT: &str = "text/plain;charset=utf-8";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEntry {
+    pub name: String,
+    pub is_dir: bool,
+}
+
+pub trait Platform {
+    fn alloc_buffer(&self, size: usize) -> *mut u32;
+
+    fn add_window(&self, window: &Window) -> usize;
+    fn update_window(&self, window: &Window);
+    fn update_window_area(&self, id: usize, x: usize, y: usize, width: usize, height: usize);
+    fn remove_window(&self, id: usize) -> bool;
+    fn activate_window(&self, id: usize) -> bool;
+    fn window_position(&self, id: usize) -> Option<(isize, isize)>;
+    fn screen_size(&self) -> (usize, usize);
+    fn watch_windows(&self, id: usize, enable: bool) -> bool;
+
+    fn poll_events(&self, id: usize) -> Vec<Event>;
+    fn wait_events(&self, id: usize, timeout_ns: Option<u64>) -> Vec<Event>;
+
+    fn set_cursor(&self, id: usize, shape: CursorShape) -> bool;
+    fn set_custom_cursor(&self, id: usize, width: usize, height: usize, hot_x: usize, hot_y: usize, pixels: &[u32]) -> bool;
+
+    fn set_clipboard(&self, id: usize, mime: &str, data: &[u8]) -> bool;
+    fn clipboard(&self, mime: &str) -> Option<Vec<u8>>;
+
+    fn theme(&self) -> String;
+    fn set_theme(&self, name: &str) -> bool;
+
+    fn read_file(&self, path: &str) -> Option<Vec<u8>>;
+    fn read_dir(&self, path: &str) -> Option<Vec<DirEntry>>;
+    fn create_dir(&self, path: &str) -> bool;
+
+    fn quit(&self);
+}
+
+pub fn native() -> Rc<dyn Platform> {
+    #[cfg(feature = "krakeos")]
+    {
+        Rc::new(KrakeOS)
+    }
+    #[cfg(not(feature = "krakeos"))]
+    {
+        Rc::new(Headless::new(1024, 768))
+    }
+}
+
+pub(crate) fn clock_ns() -> u64 {
+    #[cfg(feature = "krakeos")]
+    {
+        std::time::Instant::now().as_nanos()
+    }
+    #[cfg(not(feature = "krakeos"))]
+    {
+        std::time::UNIX_EPOCH.elapsed().map_or(0, |t| t.as_nanos() as u64)
+    }
+}
+
+#[cfg(feature = "krakeos")]
+pub struct KrakeOS;
+
+#[cfg(feature = "krakeos")]
+impl KrakeOS {
+    fn describe(window: &Window) -> std::graphics::Window {
+        std::graphics::Window {
+            id: window.id,
+            buffer: window.buffer.address as usize,
+            pid: window.pid,
+            x: window.x,
+            y: window.y,
+            z: 0,
+            width: window.width,
+            height: window.height,
+            can_move: window.can_move,
+            can_resize: window.can_resize,
+            transparent: window.transparent,
+            treat_as_transparent: window.treat_as_transparent,
+            min_width: window.min_width,
+            min_height: window.min_height,
+            event_handler: 1,
+            w_type: window.w_type,
+            decorated: window.decorated,
+            minimized: false,
+            workspace: 0,
+            title: std::graphics::title_bytes(&window.title),
+        }
+    }
+}
+
+#[cfg(feature = "krakeos")]
+impl Platform for KrakeOS {
+    fn alloc_buffer(&self, size: usize) -> *mut u32 {
+        std::memory::malloc(size) as *mut u32
+    }
+
+    fn add_window(&self, window: &Window) -> usize {
+        std::graphics::add_window(&KrakeOS::describe(window))
+    }
+
+    fn update_window(&self, window: &Window) {
+        std::graphics::update_window(&KrakeOS::describe(window));
+    }
+
+    fn update_window_area(&self, id: usize, x: usize, y: usize, width: usize, height: usize) {
+        std::graphics::update_window_area(id, x, y, width, height);
+    }
+
+    fn remove_window(&self, id: usize) -> bool {
+        std::graphics::remove_window(id)
+    }
+
+    fn activate_window(&self, id: usize) -> bool {
+        std::graphics::activate_window(id)
+    }
+
+    fn window_position(&self, id: usize) -> Option<(isize, isize)> {
+        std::graphics::list_windows().into_iter().find(|w| w.id == id).map(|w| (w.x, w.y))
+    }
+
+    fn screen_size(&self) -> (usize, usize) {
+        (std::graphics::get_screen_width(), std::graphics::get_screen_height())
+    }
+
+    fn watch_windows(&self, id: usize, enable: bool) -> bool {
+        std::graphics::watch_windows(id, enable)
+    }
+
+    fn poll_events(&self, id: usize) -> Vec<Event> {
+        let mut events: [Event; 64] = [Event::None; 64];
+        unsafe {
+            std::os::syscall(104, id as u64, events.as_mut_ptr() as u64, 64);
+        }
+        events.iter().take_while(|&&e| e != Event::None).copied().collect()
+    }
+
+    fn wait_events(&self, id: usize, timeout_ns: Option<u64>) -> Vec<Event> {
+        let mut events: [Event; 64] = [Event::None; 64];
+        let count = std::graphics::wait_events(id, events.as_mut_ptr() as usize, 64, timeout_ns).unwrap_or(0);
+        events[..count].to_vec()
+    }
+
+    fn set_cursor(&self, id: usize, shape: CursorShape) -> bool {
+        std::graphics::set_cursor(id, shape)
+    }
+
+    fn set_custom_cursor(&self, id: usize, width: usize, height: usize, hot_x: usize, hot_y: usize, pixels: &[u32]) -> bool {
+        std::graphics::set_custom_cursor(id, width, height, hot_x, hot_y, pixels)
+    }
+
+    fn set_clipboard(&self, id: usize, mime: &str, data: &[u8]) -> bool {
+        std::graphics::set_clipboard(id, mime, data)
+    }
+
+    fn clipboard(&self, mime: &str) -> Option<Vec<u8>> {
+        std::graphics::get_clipboard(mime)
+    }
+
+    fn theme(&self) -> String {
+        std::graphics::get_theme()
+    }
+
+    fn set_theme(&self, name: &str) -> bool {
+        std::graphics::set_theme(name)
+    }
+
+    fn read_file(&self, path: &str) -> Option<Vec<u8>> {
+        let mut data = Vec::new();
+        let mut file = std::fs::File::open(path).ok()?;
+        std::io::Read::read_to_end(&mut file, &mut data).ok()?;
+        Some(data)
+    }
+
+    fn read_dir(&self, path: &str) -> Option<Vec<DirEntry>> {
+        let entries = std::fs::read_dir(path).ok()?;
+        Some(entries.into_iter().map(|e| DirEntry {
+            is_dir: e.file_type == std::fs::FileType::Directory,
+            name: e.name,
+        }).collect())
+    }
+
+    fn create_dir(&self, path: &str) -> bool {
+        std::fs::create_dir(path).is_ok()
+    }
+
+    fn quit(&self) {
+        std::os::exit(0);
+    }
+}
+
+pub struct Headless {
+    screen: (usize, usize),
+    state: RefCell<HeadlessState>,
+}
+
+#[derive(Default)]
+struct HeadlessState {
+    windows: Vec<(usize, isize, isize)>,
+    last_id: usize,
+    focused: usize,
+    events: Vec<Event>,
+    clipboard: Option<(String, Vec<u8>)>,
+    theme: String,
+    quit: bool,
+    // Folders have no contents.
+    files: BTreeMap<String, Option<Vec<u8>>>,
+}
+
+impl Headless {
+    pub fn new(screen_width: usize, screen_height: usize) -> Self {
+        let state = HeadlessState { theme: String::from("light"), ..HeadlessState::default() };
+        Headless { screen: (screen_width, screen_height), state: RefCell::new(state) }
+    }
+
+    pub fn push_event(&self, event: Event) {
+        self.state.borrow_mut().events.push(event);
+    }
+
+    pub fn focused(&self) -> usize {
+        self.state.borrow().focused
+    }
+
+    pub fn has_quit(&self) -> bool {
+        self.state.borrow().quit
+    }
+
+    pub fn add_file(&self, path: &str, data: &[u8]) {
+        let mut state = self.state.borrow_mut();
+        let mut parent = parent_of(path);
+        while parent != "/" {
+            state.files.insert(String::from(parent), None);
+            parent = parent_of(parent);
+        }
+        state.files.insert(String::from(path), Some(data.to_vec()));
+    }
+
+    fn is_dir(&self, path: &str) -> bool {
+        path == "/" || self.state.borrow().files.get(path).is_some_and(Option::is_none)
+    }
+}
+
+impl Platform for Headless {
+    fn alloc_buffer(&self, size: usize) -> *mut u32 {
+        // Windows never give their buffers back, the same as on KrakeOS.
+        vec![0u32; size.div_ceil(4)].leak().as_mut_ptr()
+    }
+
+    fn add_window(&self, window: &Window) -> usize {
+        let mut state = self.state.borrow_mut();
+        state.last_id += 1;
+        let id = state.last_id;
+        state.windows.push((id, window.x, window.y));
+        id
+    }
+
+    fn update_window(&self, window: &Window) {
+        let mut state = self.state.borrow_mut();
+        if let Some(entry) = state.windows.iter_mut().find(|(id, _, _)| *id == window.id) {
+            *entry = (window.id, window.x, window.y);
+        }
+    }
+
+    fn update_window_area(&self, _id: usize, _x: usize, _y: usize, _width: usize, _height: usize) {}
+
+    fn remove_window(&self, id: usize) -> bool {
+        let mut state = self.state.borrow_mut();
+        let count = state.windows.len();
+        state.windows.retain(|(wid, _, _)| *wid != id);
+        if state.focused == id {
+            state.focused = 0;
+        }
+        state.windows.len() != count
+    }
+
+    fn activate_window(&self, id: usize) -> bool {
+        let mut state = self.state.borrow_mut();
+        let shown = state.windows.iter().any(|(wid, _, _)| *wid == id);
+        if shown {
+            state.focused = id;
+        }
+        shown
+    }
+
+    fn window_position(&self, id: usize) -> Option<(isize, isize)> {
+        self.state.borrow().windows.iter().find(|(wid, _, _)| *wid == id).map(|&(_, x, y)| (x, y))
+    }
+
+    fn screen_size(&self) -> (usize, usize) {
+        self.screen
+    }
+
+    fn watch_windows(&self, _id: usize, _enable: bool) -> bool {
+        false
+    }
+
+    fn poll_events(&self, id: usize) -> Vec<Event> {
+        let mut state = self.state.borrow_mut();
+        let (mine, others) = state.events.drain(..).partition(|e| e.get_window_id() == id as u32);
+        state.events = others;
+        mine
+    }
+
+    fn wait_events(&self, id: usize, _timeout_ns: Option<u64>) -> Vec<Event> {
+        // Nothing else can queue events while this waits, so there's no use sleeping.
+        self.poll_events(id)
+    }
+
+    fn set_cursor(&self, _id: usize, _shape: CursorShape) -> bool {
+        true
+    }
+
+    fn set_custom_cursor(&self, _id: usize, _width: usize, _height: usize, _hot_x: usize, _hot_y: usize, _pixels: &[u32]) -> bool {
+        true
+    }
+
+    fn set_clipboard(&self, _id: usize, mime: &str, data: &[u8]) -> bool {
+        self.state.borrow_mut().clipboard = Some((String::from(mime), data.to_vec()));
+        true
+    }
+
+    fn clipboard(&self, mime: &str) -> Option<Vec<u8>> {
+        let state = self.state.borrow();
+        let (offered, data) = state.clipboard.as_ref()?;
+        let wanted = mime.is_empty() || offered == mime || (mime == "text/plain" && offered.starts_with("text/plain"));
+        wanted.then(|| data.clone())
+    }
+
+    fn theme(&self) -> String {
+        self.state.borrow().theme.clone()
+    }
+
+    fn set_theme(&self, name: &str) -> bool {
+        self.state.borrow_mut().theme = String::from(name);
+        true
+    }
+
+    fn read_file(&self, path: &str) -> Option<Vec<u8>> {
+        self.state.borrow().files.get(path).cloned().flatten()
+    }
+
+    fn read_dir(&self, path: &str) -> Option<Vec<DirEntry>> {
+        if !self.is_dir(path) {
+            return None;
+        }
+        let state = self.state.borrow();
+        let entries = state.files.iter().filter(|(file, _)| parent_of(file) == path).map(|(file, data)| DirEntry {
+            name: String::from(file.rsplit_once('/').map_or(file.as_str(), |(_, name)| name)),
+            is_dir: data.is_none(),
+        });
+        Some(entries.collect())
+    }
+
+    fn create_dir(&self, path: &str) -> bool {
+        if path == "/" || !self.is_dir(parent_of(path)) || self.state.borrow().files.contains_key(path) {
+            return false;
+        }
+        self.state.borrow_mut().files.insert(String::from(path), None);
+        true
+    }
+
+    fn quit(&self) {
+        self.state.borrow_mut().quit = true;
+    }
+}
@@ -0,0 +1,173 @@
+use crate::graphics::primitives::draw_square;
+use crate::theme::Theme;
+use crate::types::{Rect, Size};
+
+pub const SCROLLBAR_SIZE: usize = 10;
+const MIN_THUMB: usize = 16;
+pub const SCROLL_STEP: usize = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Scroll {
+    pub x: usize,
+    pub y: usize,
+    pub content_width: usize,
+    pub content_height: usize,
+    pub view_x: usize,
+    pub view_y: usize,
+    pub view_width: usize,
+    pub view_height: usize,
+    drag: Option<(Axis, usize)>,
+}
+
+impl Scroll {
+    pub fn fit(&mut self, x: usize, y: usize, width: usize, height: usize, content_width: usize, content_height: usize) {
+        let mut vertical = content_height > height;
+        let horizontal = content_width > width.saturating_sub(if vertical { SCROLLBAR_SIZE } else { 0 });
+        if horizontal && !vertical {
+            vertical = content_height > height.saturating_sub(SCROLLBAR_SIZE);
+        }
+
+        self.view_x = x;
+        self.view_y = y;
+        self.view_width = width.saturating_sub(if vertical { SCROLLBAR_SIZE } else { 0 });
+        self.view_height = height.saturating_sub(if horizontal { SCROLLBAR_SIZE } else { 0 });
+        self.content_width = content_width;
+        self.content_height = content_height;
+        self.x = self.x.min(self.max_x());
+        self.y = self.y.min(self.max_y());
+    }
+
+    pub fn max_x(&self) -> usize {
+        self.content_width.saturating_sub(self.view_width)
+    }
+
+    pub fn max_y(&self) -> usize {
+        self.content_height.saturating_sub(self.view_height)
+    }
+
+    pub fn has_vertical(&self) -> bool {
+        self.content_height > self.view_height
+    }
+
+    pub fn has_horizontal(&self) -> bool {
+        self.content_width > self.view_width
+    }
+
+    pub fn scroll_by(&mut self, dx: isize, dy: isize) {
+        self.x = self.x.saturating_add_signed(dx).min(self.max_x());
+        self.y = self.y.saturating_add_signed(dy).min(self.max_y());
+    }
+
+    pub fn reveal_y(&mut self, top: usize, height: usize) {
+        if top < self.y {
+            self.y = top;
+        } else if top + height > self.y + self.view_height {
+            self.y = (top + height).saturating_sub(self.view_height).min(self.max_y());
+        }
+    }
+
+    pub fn in_view(&self, x: usize, y: usize) -> bool {
+        x >= self.view_x && x < self.view_x + self.view_width && y >= self.view_y && y < self.view_y + self.view_height
+    }
+
+    pub fn to_content(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        if self.in_view(x, y) {
+            Some((x - self.view_x + self.x, y - self.view_y + self.y))
+        } else {
+            None
+        }
+    }
+
+    fn track(&self, axis: Axis) -> (usize, usize, usize) {
+        match axis {
+            Axis::Vertical => (self.view_height, self.view_height, self.content_height),
+            Axis::Horizontal => (self.view_width, self.view_width, self.content_width),
+        }
+    }
+
+    fn thumb(&self, axis: Axis) -> (usize, usize) {
+        let (track, view, content) = self.track(axis);
+        let (pos, max) = match axis {
+            Axis::Vertical => (self.y, self.max_y()),
+            Axis::Horizontal => (self.x, self.max_x()),
+        };
+        let len = (track * view / content.max(1)).max(MIN_THUMB).min(track);
+        let start = if max == 0 { 0 } else { pos * (track - len) / max };
+        (start, len)
+    }
+
+    fn bar_at(&self, x: usize, y: usize) -> Option<(Axis, usize)> {
+        let right = self.view_x + self.view_width;
+        let bottom = self.view_y + self.view_height;
+        if self.has_vertical() && x >= right && x < right + SCROLLBAR_SIZE && y >= self.view_y && y < bottom {
+            return Some((Axis::Vertical, y - self.view_y));
+        }
+        if self.has_horizontal() && y >= bottom && y < bottom + SCROLLBAR_SIZE && x >= self.view_x && x < right {
+            return Some((Axis::Horizontal, x - self.view_x));
+        }
+        None
+    }
+
+    pub fn press(&mut self, x: usize, y: usize) -> bool {
+        let Some((axis, along)) = self.bar_at(x, y) else {
+            return false;
+        };
+        let (start, len) = self.thumb(axis);
+        let grab = if along >= start && along < start + len {
+            along - start
+        } else {
+            len / 2
+        };
+        self.drag = Some((axis, grab));
+        self.drag_to(x, y);
+        true
+    }
+
+    pub fn drag_to(&mut self, x: usize, y: usize) -> bool {
+        let Some((axis, grab)) = self.drag else {
+            return false;
+        };
+        let (track, _, _) = self.track(axis);
+        let (_, len) = self.thumb(axis);
+        let room = track.saturating_sub(len).max(1);
+        match axis {
+            Axis::Vertical => {
+                let along = y.saturating_sub(self.view_y).saturating_sub(grab).min(room);
+                self.y = along * self.max_y() / room;
+            }
+            Axis::Horizontal => {
+                let along = x.saturating_sub(self.view_x).saturating_sub(grab).min(room);
+                self.x = along * self.max_x() / room;
+            }
+        }
+        true
+    }
+
+    pub fn release(&mut self) {
+        self.drag = None;
+    }
+
+    pub fn draw(&self, buffer: &mut [u32], buffer_width: usize, clip: &Rect, theme: &Theme) {
+        let track = theme.entry("scrollbar-track");
+        let thumb = theme.entry(if self.drag.is_some() { "scrollbar-active" } else { "scrollbar" });
+
+        if self.has_vertical() {
+            let x = self.view_x + self.view_width;
+            let (start, len) = self.thumb(Axis::Vertical);
+            draw_square(buffer, buffer_width, clip, x, self.view_y, SCROLLBAR_SIZE, self.view_height, Size::Absolute(0), track, 0, track);
+            draw_square(buffer, buffer_width, clip, x + 2, self.view_y + start, SCROLLBAR_SIZE - 4, len, Size::Absolute(3), thumb, 0, thumb);
+        }
+        if self.has_horizontal() {
+            let y = self.view_y + self.view_height;
+            let (start, len) = self.thumb(Axis::Horizontal);
+            draw_square(buffer, buffer_width, clip, self.view_x, y, self.view_width, SCROLLBAR_SIZE, Size::Absolute(0), track, 0, track);
+            draw_square(buffer, buffer_width, clip, self.view_x + start, y + 2, len, SCROLLBAR_SIZE - 4, Size::Absolute(3), thumb, 0, thumb);
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use crate::graphics::primitives::{draw_square, draw_text_formatted};
+use crate::scroll::Scroll;
+use crate::theme::Theme;
+use crate::types::{Color, Rect, Size};
+use crate::widget::Text;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use titanf::TrueTypeFont;
+
+const CELL_PADDING: usize = 6;
+const RESIZE_GRIP: usize = 4;
+const MIN_COLUMN_WIDTH: usize = 24;
+
+
+#[derive(Debug, Clone)]
+pub struct Column {
+    pub title: String,
+    pub width: usize,
+}
+
+impl Column {
+    pub fn new(title: &str, width: usize) -> Self {
+        Column { title: String::from(title), width }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+pub fn sorted_rows(rows: &[Vec<String>], sort: Option<(usize, SortOrder)>) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..rows.len()).collect();
+    if let Some((col, dir)) = sort {
+        let cell = |row: usize| rows[row].get(col).map(String::as_str).unwrap_or("");
+        order.sort_by(|&a, &b| {
+            let ord = compare_cells(cell(a), cell(b));
+            if dir == SortOrder::Descending { ord.reverse() } else { ord }
+        });
+    }
+    order
+}
+
+fn compare_cells(a: &str, b: &str) -> Ordering {
+    match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+        (Ok(x), Ok(y)) => x.partial_cmp(&y).unwrap_or(Ordering::Equal),
+        _ => a.cmp(b),
+    }
+}
+
+pub enum HeaderHit {
+    Sort(usize),
+    Resize(usize, usize),
+}
+
+pub fn header_hit(columns: &[Column], scroll: &Scroll, x: usize) -> Option<HeaderHit> {
+    let x = x.checked_sub(scroll.view_x)? + scroll.x;
+    let mut right = 0;
+    for (i, column) in columns.iter().enumerate() {
+        right += column.width;
+        if x + RESIZE_GRIP >= right && x < right + RESIZE_GRIP {
+            return Some(HeaderHit::Resize(i, column.width));
+        }
+        if x < right {
+            return Some(HeaderHit::Sort(i));
+        }
+    }
+    None
+}
+
+pub fn resized_width(grabbed_width: usize, grab_x: usize, x: usize) -> usize {
+    (grabbed_width as isize + x as isize - grab_x as isize).max(MIN_COLUMN_WIDTH as isize) as usize
+}
+
+pub fn row_at(scroll: &Scroll, row_height: usize, count: usize, x: usize, y: usize) -> Option<usize> {
+    let (_, y) = scroll.to_content(x, y)?;
+    let row = y / row_height.max(1);
+    (row < count).then_some(row)
+}
+
+fn fit_text(font: &mut TrueTypeFont, text: &str, size: f32, max_width: usize) -> String {
+    let width = |font: &mut TrueTypeFont, c: char| font.get_char::<true>(c, size).0.advance_width;
+    let ellipsis = width(font, '…');
+
+    let mut used = 0;
+    let mut fitted = String::new();
+    let mut cut = None;
+    for c in text.chars() {
+        let w = width(font, c);
+        if cut.is_none() && used + w + ellipsis > max_width {
+            cut = Some(fitted.len());
+        }
+        if used + w > max_width {
+            fitted.truncate(cut.unwrap_or(0));
+            fitted.push('…');
+            return fitted;
+        }
+        used += w;
+        fitted.push(c);
+    }
+    fitted
+}
+
+pub fn visible_row(scroll: &Scroll, row_height: usize, row: usize) -> Option<(usize, usize)> {
+    let top = (row * row_height) as isize - scroll.y as isize;
+    let start = top.max(0) as usize;
+    let end = ((top + row_height as isize).max(0) as usize).min(scroll.view_height);
+    (end > start).then(|| (scroll.view_y + start, end - start))
+}
+
+fn draw_cell_text(buffer: &mut [u32], buffer_width: usize, clip: &Rect, scroll: &Scroll, font: &mut TrueTypeFont, text: &Text,
+                  label: &str, color: Color, x: usize, width: usize, row_top: usize, row_height: usize) {
+    let width = width.min((scroll.view_x + scroll.view_width).saturating_sub(x));
+    if width <= CELL_PADDING {
+        return;
+    }
+    let fitted = fit_text(font, label, text.size, width - CELL_PADDING);
+    let baseline = scroll.view_y + row_top + row_height / 2 + text.size as usize / 3;
+    draw_text_formatted(buffer, buffer_width, clip, x + CELL_PADDING / 2, baseline, &fitted, font, text.size, color,
+                        0, scroll.y, scroll.view_height, scroll.view_y);
+}
+
+pub fn draw_list(buffer: &mut [u32], buffer_width: usize, clip: &Rect, scroll: &Scroll, items: &[String], selected: Option<usize>,
+                 row_height: usize, text: &Text, font: &mut Option<TrueTypeFont>, theme: &Theme) {
+    let (selected_bg, selected_text) = (theme.entry("selection"), theme.entry("selection-text"));
+    let row_height = row_height.max(1);
+    let first = scroll.y / row_height;
+    let last = ((scroll.y + scroll.view_height) / row_height + 1).min(items.len());
+
+    for row in first..last {
+        let Some((top, height)) = visible_row(scroll, row_height, row) else { continue };
+        let is_selected = selected == Some(row);
+        if is_selected {
+            draw_square(buffer, buffer_width, clip, scroll.view_x, top, scroll.view_width, height, Size::Absolute(0), selected_bg, 0, selected_bg);
+        }
+        if let Some(font) = font {
+            let color = if is_selected { selected_text } else { text.color };
+            draw_cell_text(buffer, buffer_width, clip, scroll, font, text, &items[row], color, scroll.view_x, scroll.view_width,
+                           row * row_height, row_height);
+        }
+    }
+
+    scroll.draw(buffer, buffer_width, clip, theme);
+}
+
+pub fn draw_table(buffer: &mut [u32], buffer_width: usize, clip: &Rect, scroll: &Scroll, columns: &[Column], rows: &[Vec<String>],
+                  order: &[usize], selected: Option<usize>, sort: Option<(usize, SortOrder)>, row_height: usize,
+                  text: &Text, font: &mut Option<TrueTypeFont>, theme: &Theme) {
+    let (selected_bg, selected_text) = (theme.entry("selection"), theme.entry("selection-text"));
+    let (header, divider) = (theme.entry("header"), theme.entry("divider"));
+    let row_height = row_height.max(1);
+    let header_y = scroll.view_y.saturating_sub(row_height);
+    let full_width = scroll.view_width + if scroll.has_vertical() { crate::scroll::SCROLLBAR_SIZE } else { 0 };
+    draw_square(buffer, buffer_width, clip, scroll.view_x, header_y, full_width, row_height, Size::Absolute(0), header, 0, header);
+
+    // Columns scrolled partly off the left edge keep their background but lose their text,
+    // which can't be drawn from a negative position.
+    let column_x = |left: usize| left.checked_sub(scroll.x).map(|x| scroll.view_x + x).filter(|&x| x < scroll.view_x + scroll.view_width);
+
+    let mut left = 0;
+    for (i, column) in columns.iter().enumerate() {
+        if let Some(x) = column_x(left) {
+            if let Some(font) = font {
+                let mut title = column.title.clone();
+                match sort {
+                    Some((col, SortOrder::Ascending)) if col == i => title.push_str(" ^"),
+                    Some((col, SortOrder::Descending)) if col == i => title.push_str(" v"),
+                    _ => {}
+                }
+                let fitted = fit_text(font, &title, text.size, column.width.saturating_sub(CELL_PADDING));
+                draw_text_formatted(buffer, buffer_width, clip, x + CELL_PADDING / 2, header_y + row_height / 2 + text.size as usize / 3,
+                                    &fitted, font, text.size, text.color, 0, 0, row_height, header_y);
+            }
+        }
+        left += column.width;
+        if let Some(x) = column_x(left) {
+            draw_square(buffer, buffer_width, clip, x.saturating_sub(1), header_y, 1, row_height, Size::Absolute(0), divider, 0, divider);
+        }
+    }
+
+    let first = scroll.y / row_height;
+    let last = ((scroll.y + scroll.view_height) / row_height + 1).min(order.len());
+    for (display_row, &row) in order.iter().enumerate().take(last).skip(first) {
+        let Some((top, height)) = visible_row(scroll, row_height, display_row) else { continue };
+        let is_selected = selected == Some(row);
+        if is_selected {
+            draw_square(buffer, buffer_width, clip, scroll.view_x, top, scroll.view_width, height, Size::Absolute(0), selected_bg, 0, selected_bg);
+        }
+        let Some(font) = font else { continue };
+        let color = if is_selected { selected_text } else { text.color };
+
+        let mut left = 0;
+        for (i, column) in columns.iter().enumerate() {
+            if let (Some(x), Some(cell)) = (column_x(left), rows[row].get(i)) {
+                draw_cell_text(buffer, buffer_width, clip, scroll, font, text, cell, color, x, column.width, display_row * row_height, row_height);
+            }
+            left += column.width;
+        }
+    }
+
+    scroll.draw(buffer, buffer_width, clip, theme);
+}
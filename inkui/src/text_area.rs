@@ -0,0 +1,595 @@
+use crate::graphics::primitives::{draw_square, draw_text_formatted};
+use crate::scroll::{Scroll, SCROLLBAR_SIZE};
+use crate::table::visible_row;
+use crate::theme::Theme;
+use crate::types::{Color, Rect, Size};
+use crate::widget::Text;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use titanf::TrueTypeFont;
+
+const KEY_LEFT: u32 = 0x110001;
+const KEY_RIGHT: u32 = 0x110002;
+const KEY_UP: u32 = 0x110003;
+const KEY_DOWN: u32 = 0x110004;
+const KEY_HOME: u32 = 0x110009;
+const KEY_END: u32 = 0x11000A;
+const KEY_PAGE_UP: u32 = 0x11000B;
+const KEY_PAGE_DOWN: u32 = 0x11000C;
+const KEY_DELETE: u32 = 0x11000D;
+const KEY_BACKSPACE: u32 = 0x08;
+const CTRL_A: u32 = 0x01;
+const CTRL_Y: u32 = 0x19;
+const CTRL_Z: u32 = 0x1A;
+
+const TAB_WIDTH: usize = 4;
+const UNDO_LIMIT: usize = 200;
+const GUTTER_PADDING: usize = 8;
+const CARET_WIDTH: usize = 2;
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Edit {
+    Ignored,
+    Moved,
+    Changed,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Row {
+    start: usize,
+    end: usize,
+    line: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Snapshot {
+    text: String,
+    caret: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Editor {
+    text: String,
+    caret: usize,
+    anchor: Option<usize>,
+    goal_x: Option<usize>,
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+    typing: bool,
+    selecting: bool,
+    reveal: bool,
+    pub wrap: bool,
+    pub line_numbers: bool,
+    pub scroll: Scroll,
+    rows: Vec<Row>,
+    wrap_width: Option<usize>,
+    advances: BTreeMap<char, usize>,
+    size: f32,
+    line_height: usize,
+    gutter: usize,
+}
+
+impl Editor {
+    pub fn new() -> Self {
+        Editor { wrap: true, line_height: 1, ..Default::default() }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn set_text(&mut self, text: &str) {
+        self.text = String::from(text);
+        self.caret = 0;
+        self.anchor = None;
+        self.goal_x = None;
+        self.undo.clear();
+        self.redo.clear();
+        self.typing = false;
+        self.reveal = true;
+        self.rewrap();
+    }
+
+    pub fn caret(&self) -> usize {
+        self.caret
+    }
+
+    pub fn selection(&self) -> Option<(usize, usize)> {
+        let anchor = self.anchor?;
+        (anchor != self.caret).then(|| (anchor.min(self.caret), anchor.max(self.caret)))
+    }
+
+    pub fn selected_text(&self) -> Option<String> {
+        self.selection().map(|(start, end)| String::from(&self.text[start..end]))
+    }
+
+    pub fn select(&mut self, start: usize, end: usize) {
+        let end = self.boundary(end);
+        self.anchor = Some(self.boundary(start));
+        self.caret = end;
+        self.goal_x = None;
+        self.reveal = true;
+    }
+
+    pub fn select_all(&mut self) {
+        self.select(0, self.text.len());
+    }
+
+    pub fn insert(&mut self, text: &str) {
+        let mut chars = text.chars();
+        let typing = matches!((chars.next(), chars.next()), (Some(c), None) if !c.is_whitespace());
+        self.record(typing);
+        self.replace_selection(text);
+    }
+
+    pub fn delete_selection(&mut self) -> bool {
+        if self.selection().is_none() {
+            return false;
+        }
+        self.record(false);
+        self.replace_selection("");
+        true
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let Some(snapshot) = self.undo.pop() else {
+            return false;
+        };
+        let current = self.snapshot();
+        self.redo.push(current);
+        self.restore(snapshot);
+        true
+    }
+
+    pub fn redo(&mut self) -> bool {
+        let Some(snapshot) = self.redo.pop() else {
+            return false;
+        };
+        let current = self.snapshot();
+        self.undo.push(current);
+        self.restore(snapshot);
+        true
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot { text: self.text.clone(), caret: self.caret }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.text = snapshot.text;
+        self.caret = snapshot.caret.min(self.text.len());
+        self.anchor = None;
+        self.goal_x = None;
+        self.typing = false;
+        self.reveal = true;
+        self.rewrap();
+    }
+
+    fn record(&mut self, typing: bool) {
+        if !(typing && self.typing) {
+            let snapshot = self.snapshot();
+            self.undo.push(snapshot);
+            if self.undo.len() > UNDO_LIMIT {
+                self.undo.remove(0);
+            }
+        }
+        self.typing = typing;
+        self.redo.clear();
+    }
+
+    fn replace_selection(&mut self, text: &str) {
+        let (start, end) = self.selection().unwrap_or((self.caret, self.caret));
+        self.text.replace_range(start..end, text);
+        self.caret = start + text.len();
+        self.anchor = None;
+        self.goal_x = None;
+        self.reveal = true;
+        self.rewrap();
+    }
+
+    fn rewrap(&mut self) {
+        self.rows = self.wrap_rows(self.wrap_width).0;
+    }
+
+    fn boundary(&self, pos: usize) -> usize {
+        let mut pos = pos.min(self.text.len());
+        while !self.text.is_char_boundary(pos) {
+            pos -= 1;
+        }
+        pos
+    }
+
+    fn prev_char(&self, pos: usize) -> usize {
+        self.text[..pos].chars().next_back().map_or(0, |c| pos - c.len_utf8())
+    }
+
+    fn next_char(&self, pos: usize) -> usize {
+        self.text[pos..].chars().next().map_or(pos, |c| pos + c.len_utf8())
+    }
+
+    fn word_left(&self, pos: usize) -> usize {
+        let mut pos = pos;
+        while pos > 0 && self.char_before(pos).is_some_and(|c| !c.is_alphanumeric()) {
+            pos = self.prev_char(pos);
+        }
+        while pos > 0 && self.char_before(pos).is_some_and(|c| c.is_alphanumeric()) {
+            pos = self.prev_char(pos);
+        }
+        pos
+    }
+
+    fn word_right(&self, pos: usize) -> usize {
+        let mut pos = pos;
+        while self.text[pos..].chars().next().is_some_and(|c| !c.is_alphanumeric()) {
+            pos = self.next_char(pos);
+        }
+        while self.text[pos..].chars().next().is_some_and(|c| c.is_alphanumeric()) {
+            pos = self.next_char(pos);
+        }
+        pos
+    }
+
+    fn char_before(&self, pos: usize) -> Option<char> {
+        self.text[..pos].chars().next_back()
+    }
+
+    fn move_to(&mut self, pos: usize, extend: bool) {
+        if extend {
+            self.anchor.get_or_insert(self.caret);
+        } else {
+            self.anchor = None;
+        }
+        self.caret = pos;
+        self.typing = false;
+        self.reveal = true;
+    }
+
+    fn row_of(&self, pos: usize) -> usize {
+        self.rows.partition_point(|row| row.start <= pos).saturating_sub(1)
+    }
+
+    fn advance(&self, c: char) -> usize {
+        let c = if c.is_control() && c != '\t' { ' ' } else { c };
+        self.advances.get(&c).copied().unwrap_or(self.size as usize / 2)
+    }
+
+    fn width_of(&self, start: usize, end: usize) -> usize {
+        self.text[start..end].chars().map(|c| self.advance(c)).sum()
+    }
+
+    fn x_in_row(&self, row: &Row, pos: usize) -> usize {
+        self.width_of(row.start, pos.clamp(row.start, row.end))
+    }
+
+    fn pos_in_row(&self, row: &Row, x: usize) -> usize {
+        let mut used = 0;
+        for (i, c) in self.text[row.start..row.end].char_indices() {
+            let w = self.advance(c);
+            if used + w / 2 > x {
+                return row.start + i;
+            }
+            used += w;
+        }
+        self.row_end(row)
+    }
+
+    fn row_end(&self, row: &Row) -> usize {
+        let wrapped = self.rows.get(self.row_of(row.start) + 1).is_some_and(|next| next.line == row.line);
+        if wrapped { self.prev_char(row.end).max(row.start) } else { row.end }
+    }
+
+    fn vertical(&mut self, rows: isize) -> usize {
+        if self.rows.is_empty() {
+            return self.caret;
+        }
+        let row = self.row_of(self.caret);
+        let x = match self.goal_x {
+            Some(x) => x,
+            None => self.x_in_row(&self.rows[row], self.caret),
+        };
+        self.goal_x = Some(x);
+        let target = row.saturating_add_signed(rows).min(self.rows.len() - 1);
+        if rows < 0 && row == 0 {
+            return 0;
+        }
+        if rows > 0 && row == self.rows.len() - 1 {
+            return self.text.len();
+        }
+        let target = self.rows[target];
+        self.pos_in_row(&target, x)
+    }
+
+    fn page_rows(&self) -> isize {
+        (self.scroll.view_height / self.line_height.max(1)).max(1) as isize
+    }
+
+    pub fn key(&mut self, key: u32, shift: bool, ctrl: bool) -> Edit {
+        let keeps_goal = matches!(key, KEY_UP | KEY_DOWN | KEY_PAGE_UP | KEY_PAGE_DOWN);
+        if !keeps_goal {
+            self.goal_x = None;
+        }
+
+        let target = match key {
+            KEY_LEFT if !shift && self.selection().is_some() => self.selection().map(|(start, _)| start),
+            KEY_RIGHT if !shift && self.selection().is_some() => self.selection().map(|(_, end)| end),
+            KEY_LEFT if ctrl => Some(self.word_left(self.caret)),
+            KEY_RIGHT if ctrl => Some(self.word_right(self.caret)),
+            KEY_LEFT => Some(self.prev_char(self.caret)),
+            KEY_RIGHT => Some(self.next_char(self.caret)),
+            KEY_UP => Some(self.vertical(-1)),
+            KEY_DOWN => Some(self.vertical(1)),
+            KEY_PAGE_UP => Some(self.vertical(-self.page_rows())),
+            KEY_PAGE_DOWN => Some(self.vertical(self.page_rows())),
+            KEY_HOME if ctrl => Some(0),
+            KEY_END if ctrl => Some(self.text.len()),
+            KEY_HOME => self.rows.get(self.row_of(self.caret)).map(|row| row.start),
+            KEY_END => self.rows.get(self.row_of(self.caret)).map(|row| self.row_end(row)),
+            _ => None,
+        };
+        if let Some(pos) = target {
+            self.move_to(pos, shift);
+            return Edit::Moved;
+        }
+
+        match key {
+            CTRL_A => {
+                self.select_all();
+                Edit::Moved
+            }
+            CTRL_Z if shift => if self.redo() { Edit::Changed } else { Edit::Ignored },
+            CTRL_Z => if self.undo() { Edit::Changed } else { Edit::Ignored },
+            CTRL_Y => if self.redo() { Edit::Changed } else { Edit::Ignored },
+            KEY_BACKSPACE | KEY_DELETE => {
+                if self.delete_selection() {
+                    return Edit::Changed;
+                }
+                let (start, end) = if key == KEY_BACKSPACE {
+                    (self.prev_char(self.caret), self.caret)
+                } else {
+                    (self.caret, self.next_char(self.caret))
+                };
+                if start == end {
+                    return Edit::Ignored;
+                }
+                self.record(false);
+                self.anchor = Some(start);
+                self.caret = end;
+                self.replace_selection("");
+                Edit::Changed
+            }
+            13 | 10 => {
+                self.insert("\n");
+                Edit::Changed
+            }
+            _ => match char::from_u32(key) {
+                Some(c) if c == '\t' || !c.is_control() => {
+                    let mut buf = [0u8; 4];
+                    self.insert(c.encode_utf8(&mut buf));
+                    Edit::Changed
+                }
+                _ => Edit::Ignored,
+            },
+        }
+    }
+
+    fn pos_at(&self, x: usize, y: usize) -> usize {
+        if self.rows.is_empty() {
+            return 0;
+        }
+        let view = &self.scroll;
+        let x = x.clamp(view.view_x, (view.view_x + view.view_width).saturating_sub(1)) - view.view_x + view.x;
+        let y = y.clamp(view.view_y, (view.view_y + view.view_height).saturating_sub(1)) - view.view_y + view.y;
+        let row = (y / self.line_height.max(1)).min(self.rows.len() - 1);
+        self.pos_in_row(&self.rows[row], x)
+    }
+
+    pub fn press(&mut self, x: usize, y: usize, extend: bool) {
+        if self.scroll.press(x, y) {
+            return;
+        }
+        let pos = self.pos_at(x, y);
+        self.move_to(pos, extend);
+        self.goal_x = None;
+        self.selecting = true;
+    }
+
+    pub fn drag_to(&mut self, x: usize, y: usize) -> bool {
+        if self.scroll.drag_to(x, y) {
+            return true;
+        }
+        if !self.selecting {
+            return false;
+        }
+        let line = self.line_height as isize;
+        if y < self.scroll.view_y {
+            self.scroll.scroll_by(0, -line);
+        } else if y >= self.scroll.view_y + self.scroll.view_height {
+            self.scroll.scroll_by(0, line);
+        }
+        let pos = self.pos_at(x, y);
+        self.move_to(pos, true);
+        true
+    }
+
+    pub fn release(&mut self) {
+        self.selecting = false;
+        self.scroll.release();
+    }
+
+    fn wrap_rows(&self, wrap_width: Option<usize>) -> (Vec<Row>, usize) {
+        let mut rows = Vec::new();
+        let mut widest = 0;
+        let mut line_start = 0;
+        for (line, text) in self.text.split('\n').enumerate() {
+            let line_end = line_start + text.len();
+            let mut start = line_start;
+            let mut used = 0;
+            let mut last_break = None;
+            for (i, c) in text.char_indices() {
+                let pos = line_start + i;
+                let w = self.advance(c);
+                if let Some(limit) = wrap_width {
+                    if used + w > limit && pos > start {
+                        let cut = last_break.filter(|&b| b > start).unwrap_or(pos);
+                        rows.push(Row { start, end: cut, line });
+                        widest = widest.max(used);
+                        used = self.width_of(cut, pos);
+                        start = cut;
+                        last_break = None;
+                    }
+                }
+                used += w;
+                if c == ' ' || c == '\t' {
+                    last_break = Some(pos + c.len_utf8());
+                }
+            }
+            rows.push(Row { start, end: line_end, line });
+            widest = widest.max(used);
+            line_start = line_end + 1;
+        }
+        (rows, widest)
+    }
+
+    pub fn layout(&mut self, font: &mut TrueTypeFont, size: f32, x: usize, y: usize, width: usize, height: usize) {
+        if size != self.size {
+            self.advances.clear();
+            self.size = size;
+        }
+        let space = *self.advances.entry(' ').or_insert_with(|| font.get_char::<true>(' ', size).0.advance_width);
+        self.advances.insert('\t', space * TAB_WIDTH);
+        for c in self.text.chars().chain("0123456789".chars()) {
+            if !c.is_control() {
+                self.advances.entry(c).or_insert_with(|| font.get_char::<true>(c, size).0.advance_width);
+            }
+        }
+        self.line_height = ((size * 1.2) as usize).max(1);
+
+        self.gutter = if self.line_numbers {
+            let lines = self.text.split('\n').count();
+            format!("{}", lines).len() * self.advance('0') + GUTTER_PADDING * 2
+        } else {
+            0
+        };
+        let width = width.saturating_sub(self.gutter);
+
+        self.wrap_width = self.wrap.then_some(width);
+        let (mut rows, mut widest) = self.wrap_rows(self.wrap_width);
+        if self.wrap && rows.len() * self.line_height > height {
+            self.wrap_width = Some(width.saturating_sub(SCROLLBAR_SIZE));
+            (rows, widest) = self.wrap_rows(self.wrap_width);
+        }
+        let content_height = rows.len() * self.line_height;
+        // Room past the end of the longest row for the caret.
+        self.scroll.fit(x + self.gutter, y, width, height, widest + CARET_WIDTH, content_height);
+        self.rows = rows;
+
+        if self.reveal {
+            self.reveal = false;
+            self.reveal_caret();
+        }
+    }
+
+    fn reveal_caret(&mut self) {
+        let Some(row) = self.rows.get(self.row_of(self.caret)).copied() else {
+            return;
+        };
+        let top = self.row_of(self.caret) * self.line_height;
+        self.scroll.reveal_y(top, self.line_height);
+
+        let x = self.x_in_row(&row, self.caret);
+        if x < self.scroll.x {
+            self.scroll.x = x;
+        } else if x + CARET_WIDTH > self.scroll.x + self.scroll.view_width {
+            self.scroll.x = (x + CARET_WIDTH).saturating_sub(self.scroll.view_width).min(self.scroll.max_x());
+        }
+    }
+
+    fn visible_text(&self, row: &Row) -> Option<(usize, String)> {
+        let view_right = self.scroll.x + self.scroll.view_width;
+        let mut x = 0;
+        let mut start_x = None;
+        let mut shown = String::new();
+        for c in self.text[row.start..row.end].chars() {
+            let w = self.advance(c);
+            if x >= self.scroll.x {
+                if x + w > view_right {
+                    break;
+                }
+                start_x.get_or_insert(x);
+                match c {
+                    '\t' => (0..TAB_WIDTH).for_each(|_| shown.push(' ')),
+                    c if c.is_control() => shown.push(' '),
+                    c => shown.push(c),
+                }
+            }
+            x += w;
+        }
+        Some((self.scroll.view_x + start_x? - self.scroll.x, shown))
+    }
+
+    fn fill_span(&self, buffer: &mut [u32], buffer_width: usize, clip: &Rect, from: usize, to: usize, top: usize, height: usize, color: Color) {
+        let from = from.max(self.scroll.x);
+        let to = to.min(self.scroll.x + self.scroll.view_width);
+        if to > from {
+            let x = self.scroll.view_x + from - self.scroll.x;
+            draw_square(buffer, buffer_width, clip, x, top, to - from, height, Size::Absolute(0), color, 0, color);
+        }
+    }
+
+    pub fn draw(&self, buffer: &mut [u32], buffer_width: usize, clip: &Rect, text: &Text, font: &mut TrueTypeFont, focused: bool, theme: &Theme) {
+        let view = &self.scroll;
+        let line_height = self.line_height;
+        if self.gutter > 0 {
+            let x = view.view_x - self.gutter;
+            let gutter = theme.entry("gutter");
+            draw_square(buffer, buffer_width, clip, x, view.view_y, self.gutter, view.view_height, Size::Absolute(0), gutter, 0, gutter);
+        }
+
+        let selection = self.selection();
+        let first = view.y / line_height;
+        let last = ((view.y + view.view_height) / line_height + 1).min(self.rows.len());
+        for (i, row) in self.rows.iter().enumerate().take(last).skip(first) {
+            let Some((top, height)) = visible_row(view, line_height, i) else { continue };
+            let baseline = view.view_y + i * line_height + text.size as usize;
+
+            if let Some((start, end)) = selection {
+                let from = start.max(row.start);
+                let to = end.min(row.end);
+                // A selected line break shows as a space past the end of the line.
+                let newline = end > row.end && self.rows.get(i + 1).is_none_or(|next| next.line != row.line);
+                if from < to || (newline && start <= row.end) {
+                    let x0 = self.x_in_row(row, from);
+                    let x1 = self.x_in_row(row, to) + if newline { self.advance(' ') } else { 0 };
+                    self.fill_span(buffer, buffer_width, clip, x0, x1, top, height, theme.entry("text-selection"));
+                }
+            }
+
+            if let Some((x, shown)) = self.visible_text(row) {
+                draw_text_formatted(buffer, buffer_width, clip, x, baseline, &shown, font, text.size, text.color,
+                                    0, view.y, view.view_height, view.view_y);
+            }
+
+            let starts_line = i == 0 || self.rows[i - 1].line != row.line;
+            if self.gutter > 0 && starts_line {
+                let number = format!("{}", row.line + 1);
+                let width = number.len() * self.advance('0');
+                let x = view.view_x.saturating_sub(GUTTER_PADDING + width);
+                draw_text_formatted(buffer, buffer_width, clip, x, baseline, &number, font, text.size, theme.entry("gutter-text"),
+                                    0, view.y, view.view_height, view.view_y);
+            }
+        }
+
+        if focused {
+            let row = self.row_of(self.caret);
+            if let (Some(caret_row), Some((top, height))) = (self.rows.get(row), visible_row(view, line_height, row)) {
+                let x = self.x_in_row(caret_row, self.caret);
+                self.fill_span(buffer, buffer_width, clip, x, x + CARET_WIDTH, top, height, text.color);
+            }
+        }
+
+        view.draw(buffer, buffer_width, clip, theme);
+    }
+}
@@ -0,0 +1,227 @@
+use crate::platform::Platform;
+use crate::types::{Color, Size};
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+
+pub const THEME_DIR: &str = "/sys/themes";
+
+pub const CLASSES: &[&str] = &[
+    "frame", "button", "label", "text-input", "text-area", "canvas", "image", "scroll-view", "list", "table",
+    "checkbox", "radio-group", "slider", "progress-bar", "dropdown", "spinner",
+];
+
+const LIGHT: &str = include_str!("../../tree/sys/themes/light.theme");
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Style {
+    pub background: Option<Color>,
+    pub text_color: Option<Color>,
+    pub text_size: Option<f32>,
+    pub border_color: Option<Color>,
+    pub border_size: Option<Size>,
+    pub border_radius: Option<Size>,
+    pub accent: Option<Color>,
+}
+
+impl Style {
+    pub fn or(self, base: Style) -> Style {
+        Style {
+            background: self.background.or(base.background),
+            text_color: self.text_color.or(base.text_color),
+            text_size: self.text_size.or(base.text_size),
+            border_color: self.border_color.or(base.border_color),
+            border_size: self.border_size.or(base.border_size),
+            border_radius: self.border_radius.or(base.border_radius),
+            accent: self.accent.or(base.accent),
+        }
+    }
+
+    pub fn faded(self, opacity: f32) -> Style {
+        if opacity >= 1.0 {
+            return self;
+        }
+        let fade = |color: Option<Color>| color.map(|c| Color { a: (c.a as f32 * opacity.max(0.0)) as u8, ..c });
+        Style {
+            background: fade(self.background),
+            text_color: fade(self.text_color),
+            border_color: fade(self.border_color),
+            accent: fade(self.accent),
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WidgetState {
+    Normal,
+    Focused,
+    Hover,
+    Pressed,
+    Disabled,
+}
+
+impl WidgetState {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "normal" => Some(WidgetState::Normal),
+            "focused" => Some(WidgetState::Focused),
+            "hover" => Some(WidgetState::Hover),
+            "pressed" => Some(WidgetState::Pressed),
+            "disabled" => Some(WidgetState::Disabled),
+            _ => None,
+        }
+    }
+}
+
+enum Section {
+    None,
+    Palette,
+    Style(String, WidgetState),
+}
+
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub name: String,
+    palette: BTreeMap<String, Color>,
+    styles: BTreeMap<(String, WidgetState), BTreeMap<String, String>>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        let mut theme = Theme { name: String::from("light"), palette: BTreeMap::new(), styles: BTreeMap::new() };
+        // The built-in theme is checked by the host tests.
+        let _ = theme.read(LIGHT);
+        theme
+    }
+}
+
+impl Theme {
+    pub fn parse(name: &str, source: &str) -> Result<Theme, String> {
+        let mut theme = Theme::default();
+        theme.name = String::from(name);
+        theme.read(source)?;
+        Ok(theme)
+    }
+
+    pub fn load(platform: &dyn Platform, name: &str) -> Result<Theme, String> {
+        let path = format!("{}/{}.theme", THEME_DIR, name);
+        let source = platform.read_file(&path)
+            .and_then(|data| String::from_utf8(data).ok())
+            .ok_or_else(|| format!("can't read {}", path))?;
+        Theme::parse(name, &source).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn current(platform: &dyn Platform) -> Theme {
+        Theme::load(platform, &platform.theme()).unwrap_or_default()
+    }
+
+    pub fn select(platform: &dyn Platform, name: &str) -> bool {
+        platform.set_theme(name)
+    }
+
+    pub fn color(&self, name: &str) -> Option<Color> {
+        self.palette.get(name).copied()
+    }
+
+    pub(crate) fn entry(&self, name: &str) -> Color {
+        self.color(name).unwrap_or(Color::rgb(255, 0, 255))
+    }
+
+    pub fn style(&self, class: &str, state: WidgetState) -> Style {
+        let get = |class: &str, state: WidgetState| self.styles.get(&(String::from(class), state)).map_or(Style::default(), |p| self.resolve(p));
+        get(class, state)
+            .or(get("*", state))
+            .or(get(class, WidgetState::Normal))
+            .or(get("*", WidgetState::Normal))
+    }
+
+    pub fn varies(&self, class: &str, state: WidgetState) -> bool {
+        [class, "*"].iter().any(|&class| self.styles.contains_key(&(String::from(class), state)))
+    }
+
+    fn paint(&self, value: &str) -> Option<Color> {
+        Color::parse(value).or_else(|| self.color(value))
+    }
+
+    fn resolve(&self, properties: &BTreeMap<String, String>) -> Style {
+        let color = |key: &str| properties.get(key).and_then(|v| self.paint(v));
+        let size = |key: &str| properties.get(key).and_then(|v| parse_size(v));
+        Style {
+            background: color("background"),
+            text_color: color("text-color"),
+            text_size: properties.get("text-size").and_then(|v| v.parse().ok()),
+            border_color: color("border-color"),
+            border_size: size("border-size"),
+            border_radius: size("border-radius"),
+            accent: color("accent"),
+        }
+    }
+
+    fn read(&mut self, source: &str) -> Result<(), String> {
+        let mut section = Section::None;
+
+        for (number, line) in source.lines().enumerate() {
+            let line = line.trim();
+            let fail = |message: String| format!("line {}: {}", number + 1, message);
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let header = header.trim();
+                if header == "palette" {
+                    section = Section::Palette;
+                    continue;
+                }
+                let (class, state) = match header.split_once(':') {
+                    Some((class, state)) => match WidgetState::from_name(state.trim()) {
+                        Some(state) => (class.trim(), state),
+                        None => return Err(fail(format!("unknown state `{}`", state.trim()))),
+                    },
+                    None => (header, WidgetState::Normal),
+                };
+                if class != "*" && !CLASSES.contains(&class) {
+                    return Err(fail(format!("unknown widget class `{}`", class)));
+                }
+                section = Section::Style(String::from(class), state);
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(fail(String::from("expected `name = value`")));
+            };
+            let (key, value) = (key.trim(), value.trim());
+            let valid = match (&section, key) {
+                (Section::None, _) => return Err(fail(String::from("entry outside a section"))),
+                (Section::Palette, _) | (_, "background" | "text-color" | "border-color" | "accent") => self.paint(value).is_some(),
+                (_, "text-size") => value.parse::<f32>().is_ok(),
+                (_, "border-size" | "border-radius") => parse_size(value).is_some(),
+                _ => return Err(fail(format!("unknown property `{}`", key))),
+            };
+            if !valid {
+                return Err(fail(format!("`{}` isn't a valid {}", value, key)));
+            }
+
+            match &section {
+                Section::Palette => {
+                    if let Some(color) = self.paint(value) {
+                        self.palette.insert(String::from(key), color);
+                    }
+                }
+                Section::Style(class, state) => {
+                    self.styles.entry((class.clone(), *state)).or_default().insert(String::from(key), String::from(value));
+                }
+                Section::None => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_size(value: &str) -> Option<Size> {
+    match value.strip_suffix('%') {
+        Some(percent) => percent.trim().parse().ok().map(Size::Relative),
+        None => value.trim_end_matches("px").parse().ok().map(Size::Absolute),
+    }
+}
@@ -0,0 +1,76 @@
+use crate::window::Window;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::time::Duration;
+use std::time::Instant;
+
+pub type TimerId = usize;
+
+const MIN_PERIOD: Duration = Duration::from_millis(1);
+
+pub(crate) struct Timer {
+    pub(crate) id: TimerId,
+    pub(crate) due: Instant,
+    pub(crate) period: Option<Duration>,
+    pub(crate) handler: Box<dyn FnMut(&mut Window) -> bool>,
+}
+
+#[derive(Default)]
+pub(crate) struct Timers {
+    pending: Vec<Timer>,
+    last_id: TimerId,
+    running: Option<(TimerId, bool)>,
+}
+
+impl Timers {
+    pub(crate) fn add(&mut self, delay: Duration, period: Option<Duration>, handler: Box<dyn FnMut(&mut Window) -> bool>) -> TimerId {
+        self.last_id += 1;
+        let id = self.last_id;
+        let period = period.map(|p| p.max(MIN_PERIOD));
+        self.schedule(Timer { id, due: Instant::now() + delay, period, handler });
+        id
+    }
+
+    pub(crate) fn remove(&mut self, id: TimerId) -> bool {
+        if let Some((running, cleared)) = &mut self.running {
+            if *running == id {
+                *cleared = true;
+                return true;
+            }
+        }
+        let count = self.pending.len();
+        self.pending.retain(|t| t.id != id);
+        self.pending.len() != count
+    }
+
+    pub(crate) fn next_due(&self) -> Option<Instant> {
+        self.pending.first().map(|t| t.due)
+    }
+
+    pub(crate) fn take_due(&mut self, now: Instant) -> Option<Timer> {
+        if self.pending.first()?.due > now {
+            return None;
+        }
+        let timer = self.pending.remove(0);
+        self.running = Some((timer.id, false));
+        Some(timer)
+    }
+
+    /// Puts a timer back after its handler ran: an interval to run again a period on,
+    /// unless it finished or was cleared.
+    pub(crate) fn finish(&mut self, mut timer: Timer, keep: bool, now: Instant) {
+        let cleared = self.running.take().is_some_and(|(_, cleared)| cleared);
+        let Some(period) = timer.period.filter(|_| keep && !cleared) else {
+            return;
+        };
+        // A window that fell behind skips the runs it missed rather than catching up.
+        let next = timer.due + period;
+        timer.due = if next > now { next } else { now + period };
+        self.schedule(timer);
+    }
+
+    fn schedule(&mut self, timer: Timer) {
+        let at = self.pending.partition_point(|t| t.due <= timer.due);
+        self.pending.insert(at, timer);
+    }
+}
@@ -11,6 +11,60 @@ pub enum Size {
     Auto,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    pub const fn new(x: usize, y: usize, width: usize, height: usize) -> Rect {
+        Rect { x, y, width, height }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    pub fn right(&self) -> usize {
+        self.x + self.width
+    }
+
+    pub fn bottom(&self) -> usize {
+        self.y + self.height
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+    }
+
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.x < other.right() && other.x < self.right() && self.y < other.bottom() && other.y < self.bottom()
+    }
+
+    /// Whether the two overlap or share an edge, so their union covers nothing extra
+    /// along that edge.
+    pub fn touches(&self, other: &Rect) -> bool {
+        self.x <= other.right() && other.x <= self.right() && self.y <= other.bottom() && other.y <= self.bottom()
+    }
+
+    pub fn union(&self, other: &Rect) -> Rect {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        Rect::new(x, y, self.right().max(other.right()) - x, self.bottom().max(other.bottom()) - y)
+    }
+
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        (right > x && bottom > y).then(|| Rect::new(x, y, right - x, bottom - y))
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub enum Align {
     Center,
@@ -50,6 +104,30 @@ impl Color {
         ((self.a as u32) << 24) | ((self.r as u32) << 16) | ((self.g as u32) << 8) | (self.b as u32)
     }
 
+    pub fn parse(value: &str) -> Option<Color> {
+        let value = value.trim();
+        if value == "transparent" {
+            return Some(Color::rgba(0, 0, 0, 0));
+        }
+        if let Some(hex) = value.strip_prefix('#') {
+            let channel = |i: usize| hex.get(i..i + 2).and_then(|c| u8::from_str_radix(c, 16).ok());
+            return match hex.len() {
+                6 => Some(Color::rgb(channel(0)?, channel(2)?, channel(4)?)),
+                8 => Some(Color::rgba(channel(0)?, channel(2)?, channel(4)?, channel(6)?)),
+                _ => None,
+            };
+        }
+
+        let (args, alpha) = match value.strip_prefix("rgba(") {
+            Some(args) => (args, true),
+            None => (value.strip_prefix("rgb(")?, false),
+        };
+        let mut channels = args.strip_suffix(')')?.split(',').map(|c| c.trim().parse::<u8>().ok());
+        let (r, g, b) = (channels.next()??, channels.next()??, channels.next()??);
+        let a = if alpha { channels.next()?? } else { 255 };
+        channels.next().is_none().then_some(Color::rgba(r, g, b, a))
+    }
+
     pub fn from_u32(color: u32) -> Color {
         Color::rgba(
             ((color >> 16) & 0xFF) as u8,
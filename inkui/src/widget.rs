@@ -1,13 +1,33 @@
+use crate::controls::{Orientation, Range, ACCENT};
+use crate::handler::{Callback, Handlers, Value};
+use crate::text_area::{Edit, Editor};
+use crate::theme::{Style, Theme};
 use crate::layout::{Display, FlexDirection};
 use crate::math::ceil_f32;
-use crate::types::{Align, Color, Size};
+use crate::scroll::{Scroll, SCROLLBAR_SIZE, SCROLL_STEP};
+use crate::table::{Column, HeaderHit, SortOrder};
+use crate::types::{Align, Color, Rect, Size};
 use crate::window::Window;
 use alloc::string::String;
 use alloc::vec::Vec;
 use asvgard::load_image;
 
 pub type WidgetId = usize;
-pub type EventHandler = fn(&mut Window, WidgetId);
+
+const CONTENT_LIMIT: usize = 1 << 15;
+const KEY_LEFT: u32 = 0x110001;
+const KEY_RIGHT: u32 = 0x110002;
+const KEY_UP: u32 = 0x110003;
+const KEY_DOWN: u32 = 0x110004;
+const KEY_ENTER: u32 = 13;
+const KEY_SPACE: u32 = 32;
+const CONTROL_BORDER: Color = Color { r: 150, g: 150, b: 150, a: 255 };
+
+pub enum Press {
+    Ignored,
+    Captured,
+    Changed(Value),
+}
 
 #[derive(Debug, Clone)]
 pub struct Text {
@@ -60,6 +80,11 @@ pub struct WidgetGeometry {
 
     pub scroll_offset_y: usize,
     pub content_height: usize,
+
+    pub dirty: bool,
+    pub custom: Style,
+    pub disabled: bool,
+    pub opacity: f32,
 }
 
 impl WidgetGeometry {
@@ -90,8 +115,16 @@ impl WidgetGeometry {
             border_size: Size::Auto,
             scroll_offset_y: 0,
             content_height: 0,
+            dirty: true,
+            custom: Style::default(),
+            disabled: false,
+            opacity: 1.0,
         }
     }
+
+    pub fn rect(&self) -> Rect {
+        Rect::new(self.x, self.y, self.width, self.height)
+    }
 }
 
 use crate::types::BackgroundStyle;
@@ -103,13 +136,14 @@ pub enum Widget {
         children: Vec<Widget>,
         display: Display,
         background: BackgroundStyle,
+        handlers: Handlers,
     },
 
     Button {
         geometry: WidgetGeometry,
         text: Text,
         background: BackgroundStyle,
-        on_click: Option<EventHandler>,
+        handlers: Handlers,
         focused: bool,
     },
 
@@ -117,6 +151,7 @@ pub enum Widget {
         geometry: WidgetGeometry,
         text: Text,
         background: BackgroundStyle,
+        handlers: Handlers,
         writable: bool,
     },
 
@@ -124,7 +159,16 @@ pub enum Widget {
         geometry: WidgetGeometry,
         text: Text,
         background: BackgroundStyle,
-        on_submit: Option<EventHandler>,
+        handlers: Handlers,
+        focused: bool,
+    },
+
+    TextArea {
+        geometry: WidgetGeometry,
+        text: Text,
+        background: BackgroundStyle,
+        handlers: Handlers,
+        editor: Editor,
         focused: bool,
     },
 
@@ -132,6 +176,7 @@ pub enum Widget {
         geometry: WidgetGeometry,
         framebuffer: Vec<u32>,
         background: BackgroundStyle,
+        handlers: Handlers,
     },
 
     Image {
@@ -139,6 +184,106 @@ pub enum Widget {
         source_data: Vec<u8>,
         rasterized_buffer: Vec<u32>,
         last_raster_size: (usize, usize),
+        handlers: Handlers,
+    },
+
+    ScrollView {
+        geometry: WidgetGeometry,
+        children: Vec<Widget>,
+        background: BackgroundStyle,
+        handlers: Handlers,
+        content_size: (Size, Size),
+        scroll: Scroll,
+        content: Vec<u32>,
+    },
+
+    List {
+        geometry: WidgetGeometry,
+        items: Vec<String>,
+        text: Text,
+        background: BackgroundStyle,
+        handlers: Handlers,
+        selected: Option<usize>,
+        row_height: usize,
+        scroll: Scroll,
+        focused: bool,
+    },
+
+    Table {
+        geometry: WidgetGeometry,
+        columns: Vec<Column>,
+        rows: Vec<Vec<String>>,
+        order: Vec<usize>,
+        sort: Option<(usize, SortOrder)>,
+        text: Text,
+        background: BackgroundStyle,
+        handlers: Handlers,
+        selected: Option<usize>,
+        row_height: usize,
+        scroll: Scroll,
+        resizing: Option<(usize, usize, usize)>,
+        focused: bool,
+    },
+
+    Checkbox {
+        geometry: WidgetGeometry,
+        text: Text,
+        background: BackgroundStyle,
+        handlers: Handlers,
+        checked: bool,
+        accent: Color,
+        focused: bool,
+    },
+
+    RadioGroup {
+        geometry: WidgetGeometry,
+        options: Vec<String>,
+        text: Text,
+        background: BackgroundStyle,
+        handlers: Handlers,
+        selected: Option<usize>,
+        accent: Color,
+        focused: bool,
+    },
+
+    Slider {
+        geometry: WidgetGeometry,
+        background: BackgroundStyle,
+        handlers: Handlers,
+        range: Range,
+        value: f32,
+        orientation: Orientation,
+        accent: Color,
+        dragging: bool,
+        focused: bool,
+    },
+
+    ProgressBar {
+        geometry: WidgetGeometry,
+        background: BackgroundStyle,
+        handlers: Handlers,
+        progress: Option<f32>,
+        accent: Color,
+    },
+
+    Dropdown {
+        geometry: WidgetGeometry,
+        options: Vec<String>,
+        text: Text,
+        background: BackgroundStyle,
+        handlers: Handlers,
+        selected: Option<usize>,
+        focused: bool,
+    },
+
+    Spinner {
+        geometry: WidgetGeometry,
+        text: Text,
+        background: BackgroundStyle,
+        handlers: Handlers,
+        range: Range,
+        value: f32,
+        focused: bool,
     },
 }
 impl Widget {
@@ -148,6 +293,7 @@ impl Widget {
             children: Vec::new(),
             display: Display::None,
             background: BackgroundStyle::solid(Color::rgb(255, 255, 255)),
+            handlers: Handlers::default(),
         }
     }
 
@@ -156,7 +302,7 @@ impl Widget {
             geometry: WidgetGeometry::new(id),
             text: Text::new(text),
             background: BackgroundStyle::solid(Color::rgb(200, 200, 200)),
-            on_click: None,
+            handlers: Handlers::default(),
             focused: false,
         }
     }
@@ -166,6 +312,7 @@ impl Widget {
             geometry: WidgetGeometry::new(id),
             text: Text::new(text),
             background: BackgroundStyle::solid(Color::rgb(255, 255, 255)),
+            handlers: Handlers::default(),
             writable: false,
         }
     }
@@ -175,7 +322,18 @@ impl Widget {
             geometry: WidgetGeometry::new(id),
             text: Text::new(placeholder),
             background: BackgroundStyle::solid(Color::rgb(240, 240, 240)),
-            on_submit: None,
+            handlers: Handlers::default(),
+            focused: false,
+        }
+    }
+
+    pub fn text_area(id: WidgetId) -> Self {
+        Widget::TextArea {
+            geometry: control_geometry(id),
+            text: Text::new(""),
+            background: BackgroundStyle::solid(Color::rgb(255, 255, 255)),
+            handlers: Handlers::default(),
+            editor: Editor::new(),
             focused: false,
         }
     }
@@ -185,6 +343,7 @@ impl Widget {
             geometry: WidgetGeometry::new(id),
             framebuffer: Vec::new(),
             background: BackgroundStyle::solid(Color::rgb(100, 100, 100)),
+            handlers: Handlers::default(),
         }
     }
 
@@ -194,16 +353,295 @@ impl Widget {
             source_data: data.to_vec(),
             rasterized_buffer: Vec::new(),
             last_raster_size: (0, 0),
+            handlers: Handlers::default(),
+        }
+    }
+
+    pub fn scroll_view(id: WidgetId) -> Self {
+        Widget::ScrollView {
+            geometry: WidgetGeometry::new(id),
+            children: Vec::new(),
+            background: BackgroundStyle::solid(Color::rgb(255, 255, 255)),
+            handlers: Handlers::default(),
+            content_size: (Size::Auto, Size::Auto),
+            scroll: Scroll::default(),
+            content: Vec::new(),
+        }
+    }
+
+    pub fn list(id: WidgetId, items: Vec<String>) -> Self {
+        Widget::List {
+            geometry: WidgetGeometry::new(id),
+            items,
+            text: Text::new(""),
+            background: BackgroundStyle::solid(Color::rgb(255, 255, 255)),
+            handlers: Handlers::default(),
+            selected: None,
+            row_height: 22,
+            scroll: Scroll::default(),
+            focused: false,
+        }
+    }
+
+    pub fn table(id: WidgetId, columns: Vec<Column>) -> Self {
+        Widget::Table {
+            geometry: WidgetGeometry::new(id),
+            columns,
+            rows: Vec::new(),
+            order: Vec::new(),
+            sort: None,
+            text: Text::new(""),
+            background: BackgroundStyle::solid(Color::rgb(255, 255, 255)),
+            handlers: Handlers::default(),
+            selected: None,
+            row_height: 22,
+            scroll: Scroll::default(),
+            resizing: None,
+            focused: false,
+        }
+    }
+
+    pub fn checkbox(id: WidgetId, label: &str) -> Self {
+        Widget::Checkbox {
+            geometry: control_geometry(id),
+            text: Text::new(label),
+            background: BackgroundStyle::solid(Color::rgb(255, 255, 255)),
+            handlers: Handlers::default(),
+            checked: false,
+            accent: ACCENT,
+            focused: false,
+        }
+    }
+
+    pub fn radio_group(id: WidgetId, options: Vec<String>) -> Self {
+        Widget::RadioGroup {
+            geometry: control_geometry(id),
+            options,
+            text: Text::new(""),
+            background: BackgroundStyle::solid(Color::rgb(255, 255, 255)),
+            handlers: Handlers::default(),
+            selected: None,
+            accent: ACCENT,
+            focused: false,
+        }
+    }
+
+    pub fn slider(id: WidgetId, range: Range, value: f32) -> Self {
+        Widget::Slider {
+            geometry: control_geometry(id),
+            background: BackgroundStyle::solid(Color::rgb(220, 220, 220)),
+            handlers: Handlers::default(),
+            range,
+            value: range.clamp(value),
+            orientation: Orientation::Horizontal,
+            accent: ACCENT,
+            dragging: false,
+            focused: false,
+        }
+    }
+
+    pub fn progress_bar(id: WidgetId) -> Self {
+        Widget::ProgressBar {
+            geometry: control_geometry(id),
+            background: BackgroundStyle::solid(Color::rgb(230, 230, 230)),
+            handlers: Handlers::default(),
+            progress: Some(0.0),
+            accent: ACCENT,
+        }
+    }
+
+    pub fn dropdown(id: WidgetId, options: Vec<String>) -> Self {
+        Widget::Dropdown {
+            geometry: control_geometry(id),
+            options,
+            text: Text::new(""),
+            background: BackgroundStyle::solid(Color::rgb(255, 255, 255)),
+            handlers: Handlers::default(),
+            selected: None,
+            focused: false,
+        }
+    }
+
+    pub fn spinner(id: WidgetId, range: Range, value: f32) -> Self {
+        Widget::Spinner {
+            geometry: control_geometry(id),
+            text: Text::new(""),
+            background: BackgroundStyle::solid(Color::rgb(255, 255, 255)),
+            handlers: Handlers::default(),
+            range,
+            value: range.clamp(value),
+            focused: false,
+        }
+    }
+
+    pub fn accent_color(mut self, color: Color) -> Self {
+        self.geometry_mut().custom.accent = Some(color);
+        match &mut self {
+            Widget::Checkbox { accent, .. } |
+            Widget::RadioGroup { accent, .. } |
+            Widget::Slider { accent, .. } |
+            Widget::ProgressBar { accent, .. } => *accent = color,
+            _ => {}
+        }
+        self
+    }
+
+    pub fn orientation(mut self, new_orientation: Orientation) -> Self {
+        if let Widget::Slider { orientation, .. } = &mut self {
+            *orientation = new_orientation;
+        }
+        self
+    }
+
+    pub fn wrap(mut self, enabled: bool) -> Self {
+        if let Widget::TextArea { editor, .. } = &mut self {
+            editor.wrap = enabled;
+        }
+        self
+    }
+
+    pub fn line_numbers(mut self, enabled: bool) -> Self {
+        if let Widget::TextArea { editor, .. } = &mut self {
+            editor.line_numbers = enabled;
+        }
+        self
+    }
+
+    pub fn editor(&self) -> Option<&Editor> {
+        match self {
+            Widget::TextArea { editor, .. } => Some(editor),
+            _ => None,
+        }
+    }
+
+    pub fn editor_mut(&mut self) -> Option<&mut Editor> {
+        match self {
+            Widget::TextArea { editor, .. } => Some(editor),
+            _ => None,
+        }
+    }
+
+    pub fn set_checked(&mut self, is_checked: bool) {
+        self.mark_dirty();
+        if let Widget::Checkbox { checked, .. } = self {
+            *checked = is_checked;
+        }
+    }
+
+    pub fn set_value(&mut self, new_value: f32) {
+        self.mark_dirty();
+        match self {
+            Widget::Slider { range, value, .. } | Widget::Spinner { range, value, .. } => *value = range.clamp(new_value),
+            _ => {}
+        }
+    }
+
+    pub fn set_progress(&mut self, new_progress: Option<f32>) {
+        self.mark_dirty();
+        if let Widget::ProgressBar { progress, .. } = self {
+            *progress = new_progress.map(|p| p.max(0.0).min(1.0));
+        }
+    }
+
+    pub fn content_size(mut self, width: Size, height: Size) -> Self {
+        if let Widget::ScrollView { content_size, .. } = &mut self {
+            *content_size = (width, height);
+        }
+        self
+    }
+
+    pub fn row_height(mut self, height: usize) -> Self {
+        match &mut self {
+            Widget::List { row_height, .. } | Widget::Table { row_height, .. } => *row_height = height.max(1),
+            _ => {}
+        }
+        self
+    }
+
+    pub fn set_items(&mut self, new_items: Vec<String>) {
+        self.mark_dirty();
+        if let Widget::List { items, selected, .. } = self {
+            *items = new_items;
+            if selected.is_some_and(|s| s >= items.len()) {
+                *selected = None;
+            }
+        }
+    }
+
+    pub fn set_rows(&mut self, new_rows: Vec<Vec<String>>) {
+        self.mark_dirty();
+        if let Widget::Table { rows, order, sort, selected, .. } = self {
+            *rows = new_rows;
+            *order = crate::table::sorted_rows(rows, *sort);
+            if selected.is_some_and(|s| s >= rows.len()) {
+                *selected = None;
+            }
+        }
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        match self {
+            Widget::List { selected, .. } |
+            Widget::Table { selected, .. } |
+            Widget::RadioGroup { selected, .. } |
+            Widget::Dropdown { selected, .. } => *selected,
+            _ => None,
+        }
+    }
+
+    pub fn set_selected(&mut self, index: Option<usize>) {
+        self.mark_dirty();
+        match self {
+            Widget::List { selected, items, .. } => *selected = index.filter(|&i| i < items.len()),
+            Widget::Table { selected, rows, .. } => *selected = index.filter(|&i| i < rows.len()),
+            Widget::RadioGroup { selected, options, .. } |
+            Widget::Dropdown { selected, options, .. } => *selected = index.filter(|&i| i < options.len()),
+            _ => {}
+        }
+    }
+
+    pub fn background(&self) -> Option<Color> {
+        match self {
+            Widget::Frame { background, .. } |
+            Widget::Button { background, .. } |
+            Widget::Label { background, .. } |
+            Widget::TextInput { background, .. } |
+            Widget::TextArea { background, .. } |
+            Widget::Canvas { background, .. } |
+            Widget::ScrollView { background, .. } |
+            Widget::List { background, .. } |
+            Widget::Table { background, .. } |
+            Widget::Checkbox { background, .. } |
+            Widget::RadioGroup { background, .. } |
+            Widget::Slider { background, .. } |
+            Widget::ProgressBar { background, .. } |
+            Widget::Dropdown { background, .. } |
+            Widget::Spinner { background, .. } => match background {
+                BackgroundStyle::Solid(color) => Some(*color),
+                BackgroundStyle::Gradient(_) => None,
+            },
+            Widget::Image { .. } => None,
         }
     }
 
     pub fn background_color(mut self, color: Color) -> Self {
+        self.geometry_mut().custom.background = Some(color);
         match &mut self {
             Widget::Frame { background, .. } |
             Widget::Button { background, .. } |
             Widget::Label { background, .. } |
             Widget::TextInput { background, .. } |
-            Widget::Canvas { background, .. } => {
+            Widget::TextArea { background, .. } |
+            Widget::Canvas { background, .. } |
+            Widget::ScrollView { background, .. } |
+            Widget::List { background, .. } |
+            Widget::Table { background, .. } |
+            Widget::Checkbox { background, .. } |
+            Widget::RadioGroup { background, .. } |
+            Widget::Slider { background, .. } |
+            Widget::ProgressBar { background, .. } |
+            Widget::Dropdown { background, .. } |
+            Widget::Spinner { background, .. } => {
                 *background = BackgroundStyle::solid(color);
             }
             Widget::Image { .. } => {}
@@ -212,12 +650,23 @@ impl Widget {
     }
 
     pub fn background_gradient(mut self, gradient: LinearGradient) -> Self {
+        self.geometry_mut().custom.background = None;
         match &mut self {
             Widget::Frame { background, .. } |
             Widget::Button { background, .. } |
             Widget::Label { background, .. } |
             Widget::TextInput { background, .. } |
-            Widget::Canvas { background, .. } => {
+            Widget::TextArea { background, .. } |
+            Widget::Canvas { background, .. } |
+            Widget::ScrollView { background, .. } |
+            Widget::List { background, .. } |
+            Widget::Table { background, .. } |
+            Widget::Checkbox { background, .. } |
+            Widget::RadioGroup { background, .. } |
+            Widget::Slider { background, .. } |
+            Widget::ProgressBar { background, .. } |
+            Widget::Dropdown { background, .. } |
+            Widget::Spinner { background, .. } => {
                 *background = BackgroundStyle::gradient(gradient);
             }
             Widget::Image { .. } => {}
@@ -256,46 +705,73 @@ impl Widget {
     }
 
     pub fn set_border_radius(mut self, radius: Size) -> Self {
+        self.geometry_mut().custom.border_radius = Some(radius);
         self.geometry_mut().border_radius = radius;
         self
     }
 
     pub fn set_border_size(mut self, size: Size) -> Self {
+        self.geometry_mut().custom.border_size = Some(size);
         self.geometry_mut().border_size = size;
         self
     }
 
     pub fn set_border_color(mut self, color: Color) -> Self {
+        self.geometry_mut().custom.border_color = Some(color);
         self.geometry_mut().border_color = color;
         self
     }
 
-    pub fn on_click(mut self, handler: fn(&mut Window, WidgetId)) -> Self {
-        if let Widget::Button { on_click, .. } = &mut self {
-            *on_click = Some(handler);
-        }
+    pub fn on_click(mut self, mut handler: impl FnMut(&mut Window, WidgetId) + 'static) -> Self {
+        self.handlers_mut().click = Some(Callback::new(move |win, id, ()| handler(win, id)));
         self
     }
 
-    pub fn on_submit(mut self, handler: fn(&mut Window, WidgetId)) -> Self {
-        if let Widget::TextInput { on_submit, .. } = &mut self {
-            *on_submit = Some(handler);
-        }
+    pub fn on_double_click(mut self, mut handler: impl FnMut(&mut Window, WidgetId) + 'static) -> Self {
+        self.handlers_mut().double_click = Some(Callback::new(move |win, id, ()| handler(win, id)));
+        self
+    }
+
+    pub fn on_right_click(mut self, mut handler: impl FnMut(&mut Window, WidgetId) + 'static) -> Self {
+        self.handlers_mut().right_click = Some(Callback::new(move |win, id, ()| handler(win, id)));
+        self
+    }
+
+    pub fn on_hover(mut self, handler: impl FnMut(&mut Window, WidgetId, bool) + 'static) -> Self {
+        self.handlers_mut().hover = Some(Callback::new(handler));
+        self
+    }
+
+    pub fn on_change(mut self, handler: impl FnMut(&mut Window, WidgetId, Value) + 'static) -> Self {
+        self.handlers_mut().change = Some(Callback::new(handler));
+        self
+    }
+
+    pub fn on_submit(mut self, mut handler: impl FnMut(&mut Window, WidgetId) + 'static) -> Self {
+        self.handlers_mut().submit = Some(Callback::new(move |win, id, ()| handler(win, id)));
         self
     }
 
     pub fn add_child(mut self, child: Widget) -> Self {
-        if let Widget::Frame { children, .. } = &mut self {
+        if let Some(children) = self.get_children_mut() {
             children.push(child);
         }
         self
     }
 
     pub fn set_text_color(mut self, color: Color) -> Self {
+        self.geometry_mut().custom.text_color = Some(color);
         match &mut self {
             Widget::Button { text, .. } |
             Widget::Label { text, .. } |
-            Widget::TextInput { text, .. } => {
+            Widget::TextInput { text, .. } |
+            Widget::TextArea { text, .. } |
+            Widget::List { text, .. } |
+            Widget::Table { text, .. } |
+            Widget::Checkbox { text, .. } |
+            Widget::RadioGroup { text, .. } |
+            Widget::Dropdown { text, .. } |
+            Widget::Spinner { text, .. } => {
                 text.color = color;
             }
             _ => {}
@@ -303,11 +779,34 @@ impl Widget {
         self
     }
 
+    pub fn text_color(&self) -> Option<Color> {
+        match self {
+            Widget::Button { text, .. } |
+            Widget::Label { text, .. } |
+            Widget::TextInput { text, .. } |
+            Widget::TextArea { text, .. } |
+            Widget::List { text, .. } |
+            Widget::Table { text, .. } |
+            Widget::Checkbox { text, .. } |
+            Widget::RadioGroup { text, .. } |
+            Widget::Dropdown { text, .. } |
+            Widget::Spinner { text, .. } => Some(text.color),
+            _ => None,
+        }
+    }
+
     pub fn set_text_align(mut self, align: Align) -> Self {
         match &mut self {
             Widget::Button { text, .. } |
             Widget::Label { text, .. } |
-            Widget::TextInput { text, .. } => {
+            Widget::TextInput { text, .. } |
+            Widget::TextArea { text, .. } |
+            Widget::List { text, .. } |
+            Widget::Table { text, .. } |
+            Widget::Checkbox { text, .. } |
+            Widget::RadioGroup { text, .. } |
+            Widget::Dropdown { text, .. } |
+            Widget::Spinner { text, .. } => {
                 text.align = align;
             }
             _ => {}
@@ -316,10 +815,18 @@ impl Widget {
     }
 
     pub fn set_text_size(mut self, size: f32) -> Self {
+        self.geometry_mut().custom.text_size = Some(size);
         match &mut self {
             Widget::Button { text, .. } |
             Widget::Label { text, .. } |
-            Widget::TextInput { text, .. } => {
+            Widget::TextInput { text, .. } |
+            Widget::TextArea { text, .. } |
+            Widget::List { text, .. } |
+            Widget::Table { text, .. } |
+            Widget::Checkbox { text, .. } |
+            Widget::RadioGroup { text, .. } |
+            Widget::Dropdown { text, .. } |
+            Widget::Spinner { text, .. } => {
                 text.size = size;
             }
             _ => {}
@@ -334,6 +841,19 @@ impl Widget {
     }
 
     pub fn handle_scroll(&mut self, delta: i8) {
+        self.mark_dirty();
+        match self {
+            Widget::ScrollView { scroll, .. } | Widget::List { scroll, .. } | Widget::Table { scroll, .. } => {
+                scroll.scroll_by(0, delta as isize * SCROLL_STEP as isize);
+                return;
+            }
+            Widget::TextArea { editor, .. } => {
+                editor.scroll.scroll_by(0, delta as isize * SCROLL_STEP as isize);
+                return;
+            }
+            _ => {}
+        }
+
         let geo = self.geometry_mut();
         let scroll_step = 60;
 
@@ -352,9 +872,18 @@ impl Widget {
     }
 
     pub fn set_focused(&mut self, is_focused: bool) {
+        self.mark_dirty();
         match self {
             Widget::TextInput { focused, .. } => *focused = is_focused,
+            Widget::TextArea { focused, .. } => *focused = is_focused,
             Widget::Button { focused, .. } => *focused = is_focused,
+            Widget::List { focused, .. } => *focused = is_focused,
+            Widget::Table { focused, .. } => *focused = is_focused,
+            Widget::Checkbox { focused, .. } |
+            Widget::RadioGroup { focused, .. } |
+            Widget::Slider { focused, .. } |
+            Widget::Dropdown { focused, .. } |
+            Widget::Spinner { focused, .. } => *focused = is_focused,
             _ => {}
         }
     }
@@ -362,19 +891,235 @@ impl Widget {
     pub fn get_text(&self) -> String {
         match self {
             Widget::TextInput { text, .. } => text.text.clone(),
+            Widget::TextArea { editor, .. } => String::from(editor.text()),
             _ => String::new(),
         }
     }
 
+    fn is_one_dimensional(&self) -> bool {
+        matches!(self, Widget::RadioGroup { .. } | Widget::Slider { .. })
+    }
+
+    pub fn value(&self) -> Option<Value> {
+        match self {
+            Widget::TextInput { text, .. } => Some(Value::Text(text.text.clone())),
+            Widget::TextArea { editor, .. } => Some(Value::Text(String::from(editor.text()))),
+            Widget::List { selected, .. } |
+            Widget::Table { selected, .. } |
+            Widget::RadioGroup { selected, .. } |
+            Widget::Dropdown { selected, .. } => selected.map(Value::Selected),
+            Widget::Checkbox { checked, .. } => Some(Value::Bool(*checked)),
+            Widget::Slider { value, .. } | Widget::Spinner { value, .. } => Some(Value::Number(*value)),
+            Widget::ProgressBar { progress, .. } => progress.map(Value::Number),
+            _ => None,
+        }
+    }
+
+    pub fn is_scrollable(&self) -> bool {
+        matches!(self, Widget::ScrollView { .. } | Widget::List { .. } | Widget::Table { .. } | Widget::TextArea { .. })
+    }
+
+    pub fn content_point(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        match self {
+            Widget::ScrollView { scroll, .. } => scroll.to_content(x, y),
+            _ => Some((x, y)),
+        }
+    }
+
+    pub fn mouse_down(&mut self, x: usize, y: usize, shift: bool) -> Press {
+        match self {
+            Widget::TextArea { editor, .. } => {
+                editor.press(x, y, shift);
+                Press::Captured
+            }
+            Widget::ScrollView { scroll, .. } => {
+                if scroll.press(x, y) { Press::Captured } else { Press::Ignored }
+            }
+            Widget::List { scroll, items, selected, row_height, .. } => {
+                if scroll.press(x, y) {
+                    return Press::Captured;
+                }
+                match crate::table::row_at(scroll, *row_height, items.len(), x, y) {
+                    Some(row) if *selected != Some(row) => {
+                        *selected = Some(row);
+                        Press::Changed(Value::Selected(row))
+                    }
+                    Some(_) => Press::Captured,
+                    None => Press::Ignored,
+                }
+            }
+            Widget::Table { scroll, columns, rows, order, sort, selected, row_height, resizing, .. } => {
+                if scroll.press(x, y) {
+                    return Press::Captured;
+                }
+                if y < scroll.view_y && y + *row_height >= scroll.view_y {
+                    match crate::table::header_hit(columns, scroll, x) {
+                        Some(HeaderHit::Resize(col, width)) => *resizing = Some((col, x, width)),
+                        Some(HeaderHit::Sort(col)) => {
+                            *sort = match *sort {
+                                Some((c, SortOrder::Ascending)) if c == col => Some((col, SortOrder::Descending)),
+                                _ => Some((col, SortOrder::Ascending)),
+                            };
+                            *order = crate::table::sorted_rows(rows, *sort);
+                        }
+                        None => return Press::Ignored,
+                    }
+                    return Press::Captured;
+                }
+                match crate::table::row_at(scroll, *row_height, order.len(), x, y).map(|r| order[r]) {
+                    Some(row) if *selected != Some(row) => {
+                        *selected = Some(row);
+                        Press::Changed(Value::Selected(row))
+                    }
+                    Some(_) => Press::Captured,
+                    None => Press::Ignored,
+                }
+            }
+            Widget::Checkbox { checked, .. } => {
+                *checked = !*checked;
+                Press::Changed(Value::Bool(*checked))
+            }
+            Widget::RadioGroup { geometry, options, selected, .. } => {
+                let row = y.saturating_sub(geometry.y) / crate::controls::radio_row_height(geometry, options.len());
+                if row < options.len() && *selected != Some(row) {
+                    *selected = Some(row);
+                    Press::Changed(Value::Selected(row))
+                } else {
+                    Press::Captured
+                }
+            }
+            Widget::Slider { dragging, .. } => {
+                *dragging = true;
+                self.mouse_drag(x, y)
+            }
+            // The window opens the menu.
+            Widget::Dropdown { .. } => Press::Captured,
+            Widget::Spinner { geometry, range, value, .. } => match crate::controls::spinner_button_at(geometry, x, y) {
+                Some(up) => step_value(range, value, if up { 1.0 } else { -1.0 }),
+                None => Press::Captured,
+            },
+            _ => Press::Ignored,
+        }
+    }
+
+    pub fn mouse_drag(&mut self, x: usize, y: usize) -> Press {
+        match self {
+            Widget::Table { resizing: Some((col, grab_x, width)), columns, .. } => {
+                if let Some(column) = columns.get_mut(*col) {
+                    column.width = crate::table::resized_width(*width, *grab_x, x);
+                }
+                Press::Captured
+            }
+            Widget::ScrollView { scroll, .. } | Widget::List { scroll, .. } | Widget::Table { scroll, .. } => {
+                if scroll.drag_to(x, y) { Press::Captured } else { Press::Ignored }
+            }
+            Widget::TextArea { editor, .. } => {
+                if editor.drag_to(x, y) { Press::Captured } else { Press::Ignored }
+            }
+            Widget::Slider { geometry, range, value, orientation, dragging: true, .. } => {
+                let new_value = crate::controls::slider_value_at(geometry, *orientation, range, x, y);
+                if new_value != *value {
+                    *value = new_value;
+                    Press::Changed(Value::Number(new_value))
+                } else {
+                    Press::Captured
+                }
+            }
+            _ => Press::Ignored,
+        }
+    }
+
+    pub fn mouse_up(&mut self) {
+        match self {
+            Widget::Table { scroll, resizing, .. } => {
+                *resizing = None;
+                scroll.release();
+            }
+            Widget::ScrollView { scroll, .. } | Widget::List { scroll, .. } => scroll.release(),
+            Widget::Slider { dragging, .. } => *dragging = false,
+            Widget::TextArea { editor, .. } => editor.release(),
+            _ => {}
+        }
+    }
+
+    pub fn handle_control_key(&mut self, key: u32) -> Option<Value> {
+        if let Widget::Checkbox { checked, .. } = self {
+            if key == KEY_SPACE || key == KEY_ENTER {
+                *checked = !*checked;
+                return Some(Value::Bool(*checked));
+            }
+            return None;
+        }
+
+        let step: isize = match key {
+            KEY_UP => -1,
+            KEY_DOWN => 1,
+            KEY_LEFT if self.is_one_dimensional() => -1,
+            KEY_RIGHT if self.is_one_dimensional() => 1,
+            _ => return None,
+        };
+        match self {
+            Widget::RadioGroup { options, selected, .. } | Widget::Dropdown { options, selected, .. } => {
+                let row = step_row(selected.unwrap_or(0), selected.is_some(), step, options.len())?;
+                if *selected == Some(row) {
+                    return None;
+                }
+                *selected = Some(row);
+                Some(Value::Selected(row))
+            }
+            // Up raises a value, so it runs against the list direction.
+            Widget::Slider { range, value, orientation, .. } => {
+                let up = match (key, orientation) {
+                    (KEY_LEFT | KEY_RIGHT, Orientation::Vertical) => return None,
+                    (KEY_UP | KEY_RIGHT, _) => 1.0,
+                    _ => -1.0,
+                };
+                match step_value(range, value, up) {
+                    Press::Changed(value) => Some(value),
+                    _ => None,
+                }
+            }
+            Widget::Spinner { range, value, .. } => match step_value(range, value, -step as f32) {
+                Press::Changed(value) => Some(value),
+                _ => None,
+            },
+            Widget::List { items, selected, row_height, scroll, .. } => {
+                let row = step_row(selected.unwrap_or(0), selected.is_some(), step, items.len())?;
+                *selected = Some(row);
+                scroll.reveal_y(row * *row_height, *row_height);
+                Some(Value::Selected(row))
+            }
+            Widget::Table { order, selected, row_height, scroll, .. } => {
+                let current = selected.and_then(|s| order.iter().position(|&r| r == s));
+                let display_row = step_row(current.unwrap_or(0), current.is_some(), step, order.len())?;
+                *selected = Some(order[display_row]);
+                scroll.reveal_y(display_row * *row_height, *row_height);
+                Some(Value::Selected(order[display_row]))
+            }
+            _ => None,
+        }
+    }
+
     pub fn append_text(&mut self, new_text: &str) {
+        self.mark_dirty();
         match self {
             Widget::TextInput { text, .. } => text.text.push_str(new_text),
+            Widget::TextArea { editor, .. } => editor.insert(new_text),
             Widget::Label { text, .. } => text.text.push_str(new_text),
             _ => {}
         }
     }
 
+    pub fn handle_edit_key(&mut self, key: u32, shift: bool, ctrl: bool) -> Option<Edit> {
+        self.mark_dirty();
+        match self {
+            Widget::TextArea { editor, .. } => Some(editor.key(key, shift, ctrl)),
+            _ => None,
+        }
+    }
+
     pub fn handle_key(&mut self, key: char) {
+        self.mark_dirty();
         if let Widget::TextInput { text, .. } = self {
             if key == '\x08' {
                 if text.text.len() > text.min_len {
@@ -394,8 +1139,140 @@ impl Widget {
             Widget::Button { geometry, .. } |
             Widget::Label { geometry, .. } |
             Widget::TextInput { geometry, .. } |
+            Widget::TextArea { geometry, .. } |
             Widget::Canvas { geometry, .. } |
-            Widget::Image { geometry, .. } => geometry,
+            Widget::Image { geometry, .. } |
+            Widget::ScrollView { geometry, .. } |
+            Widget::List { geometry, .. } |
+            Widget::Table { geometry, .. } |
+            Widget::Checkbox { geometry, .. } |
+            Widget::RadioGroup { geometry, .. } |
+            Widget::Slider { geometry, .. } |
+            Widget::ProgressBar { geometry, .. } |
+            Widget::Dropdown { geometry, .. } |
+            Widget::Spinner { geometry, .. } => geometry,
+        }
+    }
+
+    pub fn mark_dirty(&mut self) {
+        self.geometry_mut().dirty = true;
+    }
+
+    pub fn mark_tree_dirty(&mut self) {
+        self.mark_dirty();
+        if let Some(children) = self.get_children_mut() {
+            for child in children {
+                child.mark_tree_dirty();
+            }
+        }
+    }
+
+    pub fn class(&self) -> &'static str {
+        match self {
+            Widget::Frame { .. } => "frame",
+            Widget::Button { .. } => "button",
+            Widget::Label { .. } => "label",
+            Widget::TextInput { .. } => "text-input",
+            Widget::TextArea { .. } => "text-area",
+            Widget::Canvas { .. } => "canvas",
+            Widget::Image { .. } => "image",
+            Widget::ScrollView { .. } => "scroll-view",
+            Widget::List { .. } => "list",
+            Widget::Table { .. } => "table",
+            Widget::Checkbox { .. } => "checkbox",
+            Widget::RadioGroup { .. } => "radio-group",
+            Widget::Slider { .. } => "slider",
+            Widget::ProgressBar { .. } => "progress-bar",
+            Widget::Dropdown { .. } => "dropdown",
+            Widget::Spinner { .. } => "spinner",
+        }
+    }
+
+    pub fn apply_style(&mut self, style: &Style) {
+        let geometry = self.geometry_mut();
+        let style = geometry.custom.or(*style).faded(geometry.opacity);
+        if let Some(color) = style.border_color {
+            geometry.border_color = color;
+        }
+        if let Some(size) = style.border_size {
+            geometry.border_size = size;
+        }
+        if let Some(radius) = style.border_radius {
+            geometry.border_radius = radius;
+        }
+
+        match self {
+            Widget::Frame { background, .. } |
+            Widget::Canvas { background, .. } |
+            Widget::ScrollView { background, .. } => {
+                if let (BackgroundStyle::Solid(old), Some(color)) = (background, style.background) {
+                    *old = color;
+                }
+            }
+            Widget::Slider { background, accent, .. } |
+            Widget::ProgressBar { background, accent, .. } => {
+                if let (BackgroundStyle::Solid(old), Some(color)) = (background, style.background) {
+                    *old = color;
+                }
+                *accent = style.accent.unwrap_or(*accent);
+            }
+            Widget::Button { background, text, .. } |
+            Widget::Label { background, text, .. } |
+            Widget::TextInput { background, text, .. } |
+            Widget::TextArea { background, text, .. } |
+            Widget::List { background, text, .. } |
+            Widget::Table { background, text, .. } |
+            Widget::Dropdown { background, text, .. } |
+            Widget::Spinner { background, text, .. } => {
+                if let (BackgroundStyle::Solid(old), Some(color)) = (background, style.background) {
+                    *old = color;
+                }
+                text.color = style.text_color.unwrap_or(text.color);
+                text.size = style.text_size.unwrap_or(text.size);
+            }
+            Widget::Checkbox { background, text, accent, .. } |
+            Widget::RadioGroup { background, text, accent, .. } => {
+                if let (BackgroundStyle::Solid(old), Some(color)) = (background, style.background) {
+                    *old = color;
+                }
+                text.color = style.text_color.unwrap_or(text.color);
+                text.size = style.text_size.unwrap_or(text.size);
+                *accent = style.accent.unwrap_or(*accent);
+            }
+            Widget::Image { .. } => {}
+        }
+    }
+
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.set_enabled(enabled);
+        self
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.mark_tree_dirty();
+        self.geometry_mut().disabled = !enabled;
+        if !enabled {
+            self.set_focused(false);
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.geometry().disabled
+    }
+
+    pub fn is_focused(&self) -> bool {
+        match self {
+            Widget::Button { focused, .. } |
+            Widget::TextInput { focused, .. } |
+            Widget::TextArea { focused, .. } |
+            Widget::List { focused, .. } |
+            Widget::Table { focused, .. } |
+            Widget::Checkbox { focused, .. } |
+            Widget::RadioGroup { focused, .. } |
+            Widget::Slider { focused, .. } |
+            Widget::Dropdown { focused, .. } |
+            Widget::Spinner { focused, .. } => *focused,
+            _ => false,
         }
     }
 
@@ -405,8 +1282,18 @@ impl Widget {
             Widget::Button { geometry, .. } |
             Widget::Label { geometry, .. } |
             Widget::TextInput { geometry, .. } |
+            Widget::TextArea { geometry, .. } |
             Widget::Canvas { geometry, .. } |
-            Widget::Image { geometry, .. } => geometry,
+            Widget::Image { geometry, .. } |
+            Widget::ScrollView { geometry, .. } |
+            Widget::List { geometry, .. } |
+            Widget::Table { geometry, .. } |
+            Widget::Checkbox { geometry, .. } |
+            Widget::RadioGroup { geometry, .. } |
+            Widget::Slider { geometry, .. } |
+            Widget::ProgressBar { geometry, .. } |
+            Widget::Dropdown { geometry, .. } |
+            Widget::Spinner { geometry, .. } => geometry,
         }
     }
 
@@ -472,22 +1359,57 @@ impl Widget {
 
     pub fn get_children(&self) -> Option<&Vec<Widget>> {
         match self {
-            Widget::Frame { children, .. } => Some(children),
+            Widget::Frame { children, .. } | Widget::ScrollView { children, .. } => Some(children),
             _ => None,
         }
     }
 
     pub fn get_children_mut(&mut self) -> Option<&mut Vec<Widget>> {
         match self {
-            Widget::Frame { children, .. } => Some(children),
+            Widget::Frame { children, .. } | Widget::ScrollView { children, .. } => Some(children),
             _ => None,
         }
     }
 
-    pub fn get_event_handler(&self) -> Option<EventHandler> {
+    pub fn handlers(&self) -> &Handlers {
         match self {
-            Widget::Button { on_click, .. } => *on_click,
-            _ => None,
+            Widget::Frame { handlers, .. } |
+            Widget::Button { handlers, .. } |
+            Widget::Label { handlers, .. } |
+            Widget::TextInput { handlers, .. } |
+            Widget::TextArea { handlers, .. } |
+            Widget::Canvas { handlers, .. } |
+            Widget::Image { handlers, .. } |
+            Widget::ScrollView { handlers, .. } |
+            Widget::List { handlers, .. } |
+            Widget::Table { handlers, .. } |
+            Widget::Checkbox { handlers, .. } |
+            Widget::RadioGroup { handlers, .. } |
+            Widget::Slider { handlers, .. } |
+            Widget::ProgressBar { handlers, .. } |
+            Widget::Dropdown { handlers, .. } |
+            Widget::Spinner { handlers, .. } => handlers,
+        }
+    }
+
+    pub fn handlers_mut(&mut self) -> &mut Handlers {
+        match self {
+            Widget::Frame { handlers, .. } |
+            Widget::Button { handlers, .. } |
+            Widget::Label { handlers, .. } |
+            Widget::TextInput { handlers, .. } |
+            Widget::TextArea { handlers, .. } |
+            Widget::Canvas { handlers, .. } |
+            Widget::Image { handlers, .. } |
+            Widget::ScrollView { handlers, .. } |
+            Widget::List { handlers, .. } |
+            Widget::Table { handlers, .. } |
+            Widget::Checkbox { handlers, .. } |
+            Widget::RadioGroup { handlers, .. } |
+            Widget::Slider { handlers, .. } |
+            Widget::ProgressBar { handlers, .. } |
+            Widget::Dropdown { handlers, .. } |
+            Widget::Spinner { handlers, .. } => handlers,
         }
     }
 
@@ -674,7 +1596,62 @@ impl Widget {
                     }
                 }
             }
-            Widget::Image { geometry, source_data, rasterized_buffer, last_raster_size } => {
+            Widget::ScrollView { children, content_size, scroll, .. } => {
+                let x = widget_x + widget_padding;
+                let y = widget_y + widget_padding;
+                let width = widget_width.saturating_sub(widget_padding * 2);
+                let height = widget_height.saturating_sub(widget_padding * 2);
+
+                // Lay out for the full width first, and again beside a vertical scrollbar if
+                // the content turns out taller than the view.
+                for vertical_bar in [false, true] {
+                    let available = width.saturating_sub(if vertical_bar { SCROLLBAR_SIZE } else { 0 });
+                    let content_width = match content_size.0 {
+                        Size::Absolute(size) => size,
+                        Size::Relative(size) => available * size / 100,
+                        _ => available,
+                    };
+                    let bound = match content_size.1 {
+                        Size::Absolute(size) => size,
+                        Size::Relative(size) => height * size / 100,
+                        _ => CONTENT_LIMIT,
+                    };
+
+                    for child in children.iter_mut() {
+                        child.update_layout(0, 0, content_width, bound, 0, _widget_margin, &Display::None);
+                    }
+
+                    let content_height = match content_size.1 {
+                        Size::Absolute(_) | Size::Relative(_) => bound,
+                        _ => children.iter().map(content_bottom).max().unwrap_or(0),
+                    };
+                    scroll.fit(x, y, width, height, content_width, content_height);
+                    if vertical_bar || !scroll.has_vertical() {
+                        break;
+                    }
+                }
+            }
+            Widget::List { items, row_height, scroll, .. } => {
+                scroll.fit(
+                    widget_x + widget_padding,
+                    widget_y + widget_padding,
+                    widget_width.saturating_sub(widget_padding * 2),
+                    widget_height.saturating_sub(widget_padding * 2),
+                    0,
+                    items.len() * *row_height,
+                );
+            }
+            Widget::Table { columns, rows, row_height, scroll, .. } => {
+                scroll.fit(
+                    widget_x + widget_padding,
+                    widget_y + widget_padding + *row_height,
+                    widget_width.saturating_sub(widget_padding * 2),
+                    widget_height.saturating_sub(widget_padding * 2 + *row_height),
+                    columns.iter().map(|c| c.width).sum(),
+                    rows.len() * *row_height,
+                );
+            }
+            Widget::Image { geometry, source_data, rasterized_buffer, last_raster_size, .. } => {
                 if geometry.width > 0 && geometry.height > 0 {
                     if geometry.width != last_raster_size.0 || geometry.height != last_raster_size.1 {
                         std::println!("Image Layout: geometry {}x{}, last {}x{}", geometry.width, geometry.height, last_raster_size.0, last_raster_size.1);
@@ -707,7 +1684,7 @@ use crate::LinearGradient;
 use titanf::TrueTypeFont;
 
 impl Widget {
-    pub fn draw(&mut self, framebuffer: &mut [u32], buffer_width: usize, font: &mut Option<TrueTypeFont>) {
+    pub fn draw(&mut self, framebuffer: &mut [u32], buffer_width: usize, clip: &Rect, font: &mut Option<TrueTypeFont>, theme: &Theme) {
         if buffer_width == 0 { return; }
 
         match self {
@@ -723,16 +1700,15 @@ impl Widget {
                             std::println!("Image Draw: Clipped at bottom y={}", dest_y);
                             break;
                         }
+                        if dest_y < clip.y || dest_y >= clip.bottom() {
+                            continue;
+                        }
 
-                        let dest_start = dest_y * buffer_width + geometry.x;
-                        let src_start = row * img_w;
-
-                        let copy_width = img_w.min(buffer_width.saturating_sub(geometry.x));
-
-                        if dest_start + copy_width <= framebuffer.len() && src_start + copy_width <= rasterized_buffer.len() {
-                            for i in 0..copy_width {
-                                let pixel = rasterized_buffer[src_start + i];
-                                framebuffer[dest_start + i] = pixel | 0xFF000000;
+                        let start_x = geometry.x.max(clip.x);
+                        let end_x = (geometry.x + img_w).min(clip.right()).min(buffer_width);
+                        for x in start_x..end_x {
+                            if let Some(&pixel) = rasterized_buffer.get(row * img_w + x - geometry.x) {
+                                framebuffer[dest_y * buffer_width + x] = pixel | 0xFF000000;
                             }
                         }
                     }
@@ -742,6 +1718,7 @@ impl Widget {
                 crate::graphics::primitives::draw_background_style(
                     framebuffer,
                     buffer_width,
+                    clip,
                     geometry.x,
                     geometry.y,
                     geometry.width,
@@ -755,26 +1732,17 @@ impl Widget {
                     geometry.border_color,
                 );
             }
-            Widget::Button { geometry, background, text, focused, .. } => {
-                let mut display_bg = *background;
-                if *focused {
-                    if let BackgroundStyle::Solid(mut c) = display_bg {
-                        c.r = c.r.saturating_add(40);
-                        c.g = c.g.saturating_add(40);
-                        c.b = c.b.saturating_add(40);
-                        display_bg = BackgroundStyle::Solid(c);
-                    }
-                }
-
+            Widget::Button { geometry, background, text, .. } => {
                 crate::graphics::primitives::draw_background_style(
                     framebuffer,
                     buffer_width,
+                    clip,
                     geometry.x,
                     geometry.y,
                     geometry.width,
                     geometry.height,
                     geometry.border_radius,
-                    &display_bg,
+                    background,
                     match geometry.border_size {
                         Size::Absolute(s) => s,
                         _ => 0
@@ -785,11 +1753,13 @@ impl Widget {
                 if let Some(font) = font {
                     if !text.text.is_empty() {
                         let text_y = geometry.y + (geometry.height / 2) + (text.size as usize / 3);
+                        let text_x = aligned_x(font, text, geometry.x + geometry.padding + 5, geometry.width.saturating_sub(geometry.padding * 2 + 10));
 
                         geometry.content_height = crate::graphics::primitives::draw_text_formatted(
                             framebuffer,
                             buffer_width,
-                            geometry.x + geometry.padding + 5,
+                            clip,
+                            text_x,
                             text_y,
                             &text.text,
                             font,
@@ -813,6 +1783,7 @@ impl Widget {
                     crate::graphics::primitives::draw_background_style(
                         framebuffer,
                         buffer_width,
+                        clip,
                         geometry.x,
                         geometry.y,
                         geometry.width,
@@ -830,11 +1801,13 @@ impl Widget {
                 if let Some(font) = font {
                     if !text.text.is_empty() {
                         let text_y = geometry.y + geometry.padding + text.size as usize;
+                        let text_x = aligned_x(font, text, geometry.x + geometry.padding, geometry.width.saturating_sub(geometry.padding * 2));
 
                         geometry.content_height = crate::graphics::primitives::draw_text_formatted(
                             framebuffer,
                             buffer_width,
-                            geometry.x + geometry.padding,
+                            clip,
+                            text_x,
                             text_y,
                             &text.text,
                             font,
@@ -852,6 +1825,7 @@ impl Widget {
                 crate::graphics::primitives::draw_background_style(
                     framebuffer,
                     buffer_width,
+                    clip,
                     geometry.x,
                     geometry.y,
                     geometry.width,
@@ -879,6 +1853,7 @@ impl Widget {
                     geometry.content_height = crate::graphics::primitives::draw_text_formatted(
                         framebuffer,
                         buffer_width,
+                        clip,
                         geometry.x + geometry.padding,
                         text_y,
                         &display_text,
@@ -892,10 +1867,39 @@ impl Widget {
                     );
                 }
             }
+            Widget::TextArea { geometry, text, background, editor, focused, .. } => {
+                crate::graphics::primitives::draw_background_style(
+                    framebuffer,
+                    buffer_width,
+                    clip,
+                    geometry.x,
+                    geometry.y,
+                    geometry.width,
+                    geometry.height,
+                    geometry.border_radius,
+                    background,
+                    match geometry.border_size {
+                        Size::Absolute(s) => s,
+                        _ => 0
+                    },
+                    geometry.border_color,
+                );
+
+                if let Some(font) = font {
+                    let inset = geometry.padding + match geometry.border_size {
+                        Size::Absolute(s) => s,
+                        _ => 0
+                    };
+                    editor.layout(font, text.size, geometry.x + inset, geometry.y + inset,
+                                  geometry.width.saturating_sub(inset * 2), geometry.height.saturating_sub(inset * 2));
+                    editor.draw(framebuffer, buffer_width, clip, text, font, *focused, theme);
+                }
+            }
             Widget::Canvas { geometry, framebuffer: widget_buffer, background, .. } => {
                 crate::graphics::primitives::draw_background_style(
                     framebuffer,
                     buffer_width,
+                    clip,
                     geometry.x,
                     geometry.y,
                     geometry.width,
@@ -909,12 +1913,12 @@ impl Widget {
                     geometry.border_color,
                 );
 
-                if !widget_buffer.is_empty() {
-                    for row in 0..geometry.height {
-                        let dest_start = (geometry.y + row) * buffer_width + geometry.x;
-                        let dest_end = dest_start + geometry.width;
-                        let src_start = row * geometry.width;
-                        let src_end = src_start + geometry.width;
+                if let Some(shown) = geometry.rect().intersection(clip) {
+                    for y in shown.y..shown.bottom() {
+                        let dest_start = y * buffer_width + shown.x;
+                        let dest_end = dest_start + shown.width;
+                        let src_start = (y - geometry.y) * geometry.width + shown.x - geometry.x;
+                        let src_end = src_start + shown.width;
 
                         if dest_end <= framebuffer.len() && src_end <= widget_buffer.len() {
                             framebuffer[dest_start..dest_end].copy_from_slice(&widget_buffer[src_start..src_end]);
@@ -922,6 +1926,133 @@ impl Widget {
                     }
                 }
             }
+            Widget::ScrollView { geometry, children, background, scroll, content, .. } => {
+                crate::graphics::primitives::draw_background_style(
+                    framebuffer,
+                    buffer_width,
+                    clip,
+                    geometry.x,
+                    geometry.y,
+                    geometry.width,
+                    geometry.height,
+                    geometry.border_radius,
+                    background,
+                    match geometry.border_size {
+                        Size::Absolute(s) => s,
+                        _ => 0
+                    },
+                    geometry.border_color,
+                );
+
+                let content_width = scroll.content_width.max(scroll.view_width);
+                let content_height = scroll.content_height.max(scroll.view_height);
+                content.resize(content_width * content_height, 0);
+
+                let visible_width = scroll.view_width.min(content_width - scroll.x);
+                let visible_height = scroll.view_height.min(content_height.saturating_sub(scroll.y));
+                if let Some(shown) = Rect::new(scroll.view_x, scroll.view_y, visible_width, visible_height).intersection(clip) {
+                    // The shown area in the content's own coordinates.
+                    let content_clip = Rect::new(shown.x - scroll.view_x + scroll.x, shown.y - scroll.view_y + scroll.y,
+                                                 shown.width, shown.height);
+                    for row in content_clip.y..content_clip.bottom() {
+                        content[row * content_width + content_clip.x..row * content_width + content_clip.right()].fill(0);
+                    }
+                    for child in children.iter_mut() {
+                        crate::window::paint_recursive(content, content_width, child, &content_clip, font, theme);
+                    }
+
+                    for row in 0..shown.height {
+                        let src_start = (content_clip.y + row) * content_width + content_clip.x;
+                        let dest_start = (shown.y + row) * buffer_width + shown.x;
+                        if dest_start + shown.width > framebuffer.len() {
+                            break;
+                        }
+                        for i in 0..shown.width {
+                            let pixel = content[src_start + i];
+                            match pixel >> 24 {
+                                0 => {}
+                                255 => framebuffer[dest_start + i] = pixel,
+                                _ => crate::graphics::draw_pixel(framebuffer, buffer_width, clip, shown.x + i, shown.y + row, Color::from_u32(pixel)),
+                            }
+                        }
+                    }
+                }
+
+                scroll.draw(framebuffer, buffer_width, clip, theme);
+            }
+            Widget::List { geometry, items, text, background, selected, row_height, scroll, .. } => {
+                crate::graphics::primitives::draw_background_style(
+                    framebuffer,
+                    buffer_width,
+                    clip,
+                    geometry.x,
+                    geometry.y,
+                    geometry.width,
+                    geometry.height,
+                    geometry.border_radius,
+                    background,
+                    match geometry.border_size {
+                        Size::Absolute(s) => s,
+                        _ => 0
+                    },
+                    geometry.border_color,
+                );
+                crate::table::draw_list(framebuffer, buffer_width, clip, scroll, items, *selected, *row_height, text, font, theme);
+            }
+            Widget::Table { geometry, columns, rows, order, sort, text, background, selected, row_height, scroll, .. } => {
+                crate::graphics::primitives::draw_background_style(
+                    framebuffer,
+                    buffer_width,
+                    clip,
+                    geometry.x,
+                    geometry.y,
+                    geometry.width,
+                    geometry.height,
+                    geometry.border_radius,
+                    background,
+                    match geometry.border_size {
+                        Size::Absolute(s) => s,
+                        _ => 0
+                    },
+                    geometry.border_color,
+                );
+                crate::table::draw_table(framebuffer, buffer_width, clip, scroll, columns, rows, order, *selected, *sort, *row_height, text, font, theme);
+            }
+            Widget::Checkbox { geometry, text, background, checked, accent, focused, .. } => {
+                crate::controls::draw_checkbox(framebuffer, buffer_width, clip, geometry, background, text, *checked, *accent, font);
+                if *focused {
+                    crate::controls::draw_focus_ring(framebuffer, buffer_width, clip, geometry, *accent);
+                }
+            }
+            Widget::RadioGroup { geometry, options, text, background, selected, accent, focused, .. } => {
+                crate::controls::draw_radio_group(framebuffer, buffer_width, clip, geometry, background, text, options, *selected, *accent, font);
+                if *focused {
+                    crate::controls::draw_focus_ring(framebuffer, buffer_width, clip, geometry, *accent);
+                }
+            }
+            Widget::Slider { geometry, background, range, value, orientation, accent, focused, .. } => {
+                crate::controls::draw_slider(framebuffer, buffer_width, clip, geometry, background, *orientation, range, *value, *accent, theme.entry("knob"));
+                if *focused {
+                    crate::controls::draw_focus_ring(framebuffer, buffer_width, clip, geometry, *accent);
+                }
+            }
+            Widget::ProgressBar { geometry, background, progress, accent, .. } => {
+                crate::controls::draw_progress_bar(framebuffer, buffer_width, clip, geometry, background, *progress, *accent);
+            }
+            Widget::Dropdown { geometry, options, text, background, selected, focused, .. } => {
+                let label = selected.and_then(|i| options.get(i)).map(String::as_str).unwrap_or("");
+                crate::controls::draw_dropdown(framebuffer, buffer_width, clip, geometry, background, text, label, font);
+                if *focused {
+                    crate::controls::draw_focus_ring(framebuffer, buffer_width, clip, geometry, theme.entry("accent"));
+                }
+            }
+            Widget::Spinner { geometry, text, background, range, value, focused, .. } => {
+                crate::controls::draw_spinner(framebuffer, buffer_width, clip, geometry, background, text, &range.format(*value), font,
+                                              theme.entry("divider"));
+                if *focused {
+                    crate::controls::draw_focus_ring(framebuffer, buffer_width, clip, geometry, theme.entry("accent"));
+                }
+            }
         }
     }
 
@@ -929,9 +2060,9 @@ impl Widget {
         let geometry = self.geometry();
         if x >= geometry.x && x < geometry.x + geometry.width &&
             y >= geometry.y && y < geometry.y + geometry.height {
-            if let Some(children) = self.get_children() {
+            if let (Some(children), Some((cx, cy))) = (self.get_children(), self.content_point(x, y)) {
                 for child in children.iter().rev() {
-                    if let Some(child_id) = child.find_widget_at(x, y) {
+                    if let Some(child_id) = child.find_widget_at(cx, cy) {
                         return Some(child_id);
                     }
                 }
@@ -970,3 +2101,48 @@ impl Widget {
         None
     }
 }
+
+fn content_bottom(widget: &Widget) -> usize {
+    let geometry = widget.geometry();
+    match (widget, widget.get_children()) {
+        (Widget::Frame { .. }, Some(children)) if matches!(geometry.user_height, Size::Auto) && !children.is_empty() => {
+            children.iter().map(content_bottom).max().unwrap_or(0) + geometry.padding + geometry.margin
+        }
+        _ => geometry.y + geometry.height + geometry.margin,
+    }
+}
+
+fn step_row(current: usize, has_current: bool, step: isize, count: usize) -> Option<usize> {
+    if count == 0 {
+        return None;
+    }
+    if !has_current {
+        return Some(0);
+    }
+    Some(current.saturating_add_signed(step).min(count - 1))
+}
+
+fn aligned_x(font: &mut TrueTypeFont, text: &Text, left: usize, width: usize) -> usize {
+    let free = width.saturating_sub(crate::graphics::primitives::text_width(&text.text, font, text.size));
+    match text.align {
+        Align::Left => left,
+        Align::Center => left + free / 2,
+        Align::Right => left + free,
+    }
+}
+
+fn control_geometry(id: WidgetId) -> WidgetGeometry {
+    let mut geometry = WidgetGeometry::new(id);
+    geometry.border_size = Size::Absolute(1);
+    geometry.border_color = CONTROL_BORDER;
+    geometry
+}
+
+fn step_value(range: &Range, value: &mut f32, direction: f32) -> Press {
+    let new_value = range.clamp(*value + direction * range.key_step());
+    if new_value == *value {
+        return Press::Captured;
+    }
+    *value = new_value;
+    Press::Changed(Value::Number(new_value))
+}
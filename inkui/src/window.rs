@@ -1,11 +1,22 @@
 use crate::event::Event;
+use crate::controls::MENU_ROWS;
+use crate::damage::Damage;
+use crate::dialog::Dialog;
+use crate::handler::{Callback, Handlers, State, Value};
 use crate::layout::Display;
-use crate::widget::{Widget, WidgetId};
+use crate::platform::{self, CursorShape, Items, Platform, MIME_TEXT};
+use crate::text_area::Edit;
+use crate::theme::{Theme, WidgetState};
+use crate::timer::{TimerId, Timers};
+use crate::types::{Rect, Size};
+use crate::widget::{Press, Widget, WidgetId};
+use alloc::boxed::Box;
+use alloc::rc::Rc;
 use alloc::string::String;
 use alloc::vec::Vec;
 use core::slice;
-use std::graphics::{self, Items};
-use std::os::syscall;
+use core::time::Duration;
+use std::time::Instant;
 
 pub struct FrameBuffer {
     pub address: *mut u32,
@@ -13,14 +24,14 @@ pub struct FrameBuffer {
 }
 
 impl FrameBuffer {
-    pub fn new(size: usize) -> Self {
-        let address = std::memory::malloc(size) as *mut u32;
+    pub fn new(platform: &dyn Platform, size: usize) -> Self {
+        let address = platform.alloc_buffer(size);
         Self { address, size }
     }
 
-    pub fn resize(&mut self, size: usize) {
+    pub fn resize(&mut self, platform: &dyn Platform, size: usize) {
         if self.size < size {
-            let new_addr = std::memory::malloc(size) as *mut u32;
+            let new_addr = platform.alloc_buffer(size);
             self.address = new_addr;
             self.size = size;
         }
@@ -51,19 +62,63 @@ pub struct Window {
     pub min_height: usize,
 
     pub w_type: Items,
+    pub decorated: bool,
     pub focus: WidgetId,
 
     pub font: Option<TrueTypeFont>,
+    pub on_close: Option<Box<dyn FnMut(&mut Window)>>,
+    pub on_event: Option<Box<dyn FnMut(&mut Window, &Event)>>,
+    pub owns_selection: bool,
+    cursor_pinned: bool,
+    cursor: Option<CursorShape>,
+    hovered: WidgetId,
+    pointed: WidgetId,
+    held: WidgetId,
+    buttons: [bool; 3],
+    last_click: Option<(WidgetId, Instant)>,
+    grab: Option<(WidgetId, isize, isize)>,
+    shift: bool,
+    ctrl: bool,
+    font_data: Option<&'static [u8]>,
+    menu: Option<Menu>,
+    dialog: Option<Dialog>,
+    pub(crate) dismiss_on_escape: bool,
+    timers: Timers,
+    damage: Damage,
+    unsent: Damage,
+    painted: Vec<(WidgetId, Rect)>,
+    theme: Theme,
+    platform: Rc<dyn Platform>,
 }
 
+struct Menu {
+    owner: WidgetId,
+    window: Box<Window>,
+    picked: State<Option<usize>>,
+}
+
+const CTRL_C: u32 = 0x03;
+const CTRL_V: u32 = 0x16;
+const CTRL_X: u32 = 0x18;
+const KEY_CTRL: u32 = 0x110005;
+const KEY_SHIFT: u32 = 0x110007;
+const ESCAPE: u32 = 0x1B;
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
+const POPUP_POLL_NS: u64 = 20_000_000;
+const MENU_LIST: WidgetId = 1;
+
 impl Window {
     pub fn new(title: &str, width: usize, height: usize) -> Self {
+        Window::with_platform(title, width, height, platform::native())
+    }
+
+    pub fn with_platform(title: &str, width: usize, height: usize, platform: Rc<dyn Platform>) -> Self {
         let size = width * height * 4 + 4;
 
         Window {
             id: 0,
             title: String::from(title),
-            buffer: FrameBuffer::new(size),
+            buffer: FrameBuffer::new(&*platform, size),
             pid: 0,
             x: 0,
             y: 0,
@@ -78,14 +133,47 @@ impl Window {
             min_width: 0,
             min_height: 0,
             w_type: Items::Window,
+            decorated: true,
             focus: 0,
             font: None,
+            on_close: None,
+            on_event: None,
+            owns_selection: false,
+            cursor_pinned: false,
+            cursor: Some(CursorShape::Arrow),
+            hovered: 0,
+            pointed: 0,
+            held: 0,
+            buttons: [false; 3],
+            last_click: None,
+            grab: None,
+            shift: false,
+            ctrl: false,
+            font_data: None,
+            menu: None,
+            dialog: None,
+            dismiss_on_escape: false,
+            timers: Timers::default(),
+            damage: Damage::default(),
+            unsent: Damage::default(),
+            painted: Vec::new(),
+            theme: Theme::current(&*platform),
+            platform,
         }
     }
 
+    pub fn platform(&self) -> &Rc<dyn Platform> {
+        &self.platform
+    }
+
+    pub fn pixels(&self) -> &[u32] {
+        unsafe { slice::from_raw_parts(self.buffer.address, self.width * self.height) }
+    }
+
     pub fn load_font(&mut self, data: &'static [u8]) {
         if let Ok(font) = TrueTypeFont::load_font(data) {
             self.font = Some(font);
+            self.font_data = Some(data);
         }
     }
 
@@ -97,30 +185,347 @@ impl Window {
         self.treat_as_transparent = treat;
     }
 
-    pub fn show(&mut self) {
-        let std_window = graphics::Window {
-            id: self.id,
-            buffer: self.buffer.address as usize,
-            pid: self.pid,
-            x: self.x,
-            y: self.y,
-            z: 0,
-            width: self.width,
-            height: self.height,
-            can_move: self.can_move,
-            can_resize: self.can_resize,
-            transparent: self.transparent,
-            treat_as_transparent: self.treat_as_transparent,
-            min_width: self.min_width,
-            min_height: self.min_height,
-            event_handler: 1,
-            w_type: self.w_type,
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        for child in &mut self.children {
+            child.mark_tree_dirty();
+        }
+    }
+
+    pub fn set_title(&mut self, title: &str) {
+        self.title = String::from(title);
+        if self.id != 0 {
+            self.update();
+        }
+    }
+
+    pub fn set_on_close(&mut self, handler: impl FnMut(&mut Window) + 'static) {
+        self.on_close = Some(Box::new(handler));
+    }
+
+    pub fn set_on_event(&mut self, handler: impl FnMut(&mut Window, &Event) + 'static) {
+        self.on_event = Some(Box::new(handler));
+    }
+
+    pub fn close(&mut self) {
+        match self.on_close.take() {
+            Some(mut handler) => {
+                handler(self);
+                self.on_close.get_or_insert(handler);
+            }
+            None => self.platform.quit(),
+        }
+    }
+
+    fn emit(&mut self, event: &Event) {
+        if let Some(mut handler) = self.on_event.take() {
+            handler(self, event);
+            self.on_event.get_or_insert(handler);
+        }
+    }
+
+    fn fire<A>(&mut self, id: WidgetId, pick: fn(&Handlers) -> &Option<Callback<A>>, arg: A) -> bool {
+        let handler = self.find_widget_by_id(id).and_then(|w| pick(w.handlers()).clone());
+        match handler {
+            Some(handler) => {
+                handler.call(self, id, arg);
+                self.invalidate(id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn fire_change(&mut self, id: WidgetId) -> bool {
+        match self.find_widget_by_id(id).and_then(|w| w.value()) {
+            Some(value) => self.fire(id, |h| &h.change, value),
+            None => false,
+        }
+    }
+
+    fn press(&mut self, path: &[(WidgetId, usize, usize)], x: usize, y: usize) -> bool {
+        let shift = self.shift;
+        for &(id, local_x, local_y) in path.iter().rev() {
+            let press = match self.find_widget_by_id_mut(id) {
+                Some(widget) => widget.mouse_down(local_x, local_y, shift),
+                None => continue,
+            };
+            if matches!(press, Press::Ignored) {
+                continue;
+            }
+            self.invalidate(id);
+
+            self.grab = Some((id, local_x as isize - x as isize, local_y as isize - y as isize));
+            if let Press::Changed(value) = press {
+                self.fire(id, |h| &h.change, value);
+            }
+            if let Some(Widget::Dropdown { .. }) = self.find_widget_by_id(id) {
+                self.toggle_menu(id);
+            }
+            return true;
+        }
+        false
+    }
+
+    fn drag(&mut self, x: usize, y: usize) -> bool {
+        let Some((id, dx, dy)) = self.grab else {
+            return false;
+        };
+        let press = match self.find_widget_by_id_mut(id) {
+            Some(widget) => widget.mouse_drag(x.saturating_add_signed(dx), y.saturating_add_signed(dy)),
+            None => return false,
+        };
+        if !matches!(press, Press::Ignored) {
+            self.invalidate(id);
+        }
+        match press {
+            Press::Ignored => false,
+            Press::Captured => true,
+            Press::Changed(value) => {
+                self.fire(id, |h| &h.change, value);
+                true
+            }
+        }
+    }
+
+    fn release(&mut self) {
+        if let Some((id, _, _)) = self.grab.take() {
+            if let Some(widget) = self.find_widget_by_id_mut(id) {
+                widget.mouse_up();
+                widget.mark_dirty();
+            }
+        }
+    }
+
+    fn set_hovered(&mut self, id: WidgetId) -> bool {
+        let old = self.hovered;
+        if old == id {
+            return false;
+        }
+        self.hovered = id;
+        let left = old != 0 && self.fire(old, |h| &h.hover, false);
+        let entered = id != 0 && self.fire(id, |h| &h.hover, true);
+        left || entered
+    }
+
+    fn move_state(&mut self, old: WidgetId, new: WidgetId, state: WidgetState) -> bool {
+        if old == new {
+            return false;
+        }
+        let mut changed = false;
+        for id in [old, new] {
+            if self.find_widget_by_id(id).is_some_and(|w| self.theme.varies(w.class(), state)) {
+                self.invalidate(id);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    fn click(&mut self, id: WidgetId) -> bool {
+        let now = Instant::now();
+        let double = matches!(self.last_click, Some((last, at)) if last == id && now.duration_since(at) < DOUBLE_CLICK_TIME);
+        self.last_click = if double { None } else { Some((id, now)) };
+
+        let clicked = self.fire(id, |h| &h.click, ());
+        let double_clicked = double && self.fire(id, |h| &h.double_click, ());
+        clicked || double_clicked
+    }
+
+    fn toggle_menu(&mut self, id: WidgetId) {
+        if self.close_menu() != Some(id) {
+            self.open_menu(id);
+        }
+    }
+
+    fn open_menu(&mut self, id: WidgetId) {
+        let Some(Widget::Dropdown { geometry, options, text, .. }) = self.find_widget_by_id(id) else {
+            return;
+        };
+        let Some((window_x, window_y)) = self.platform.window_position(self.id) else {
+            return;
+        };
+        let row_height = geometry.height.max(1);
+        let width = geometry.width.max(1);
+        let height = row_height * options.len().clamp(1, MENU_ROWS) + 2;
+        let below = window_y + (geometry.y + geometry.height) as isize;
+
+        let picked = State::new(None);
+        let on_pick = picked.clone();
+        let list = Widget::list(MENU_LIST, options.clone())
+            .row_height(row_height)
+            .width(Size::Absolute(width))
+            .height(Size::Absolute(height))
+            .set_border_size(Size::Absolute(1))
+            .set_border_color(self.theme.entry("border"))
+            .set_text_size(text.size)
+            .set_text_color(text.color)
+            .on_change(move |_, _, value| on_pick.set(value.as_selected()));
+
+        let x = window_x + geometry.x as isize;
+        let y = if below + height as isize > self.platform.screen_size().1 as isize {
+            window_y + geometry.y as isize - height as isize
+        } else {
+            below
         };
+        let mut popup = self.popup(width, height);
+        popup.x = x;
+        popup.y = y;
+        popup.children.push(list);
+        popup.show();
 
+        self.menu = Some(Menu { owner: id, window: Box::new(popup), picked });
+    }
+
+    pub(crate) fn popup(&self, width: usize, height: usize) -> Window {
+        let mut popup = Window::with_platform("", width, height, self.platform.clone());
+        popup.w_type = Items::Popup;
+        popup.decorated = false;
+        popup.can_move = false;
+        popup.can_resize = false;
+        popup.transparent = false;
+        popup.treat_as_transparent = false;
+        popup.theme = self.theme.clone();
+        if let Some(data) = self.font_data {
+            popup.load_font(data);
+        }
+        popup
+    }
+
+    pub(crate) fn open_dialog(&mut self, mut dialog: Dialog) {
+        self.close_menu();
+        self.finish_dialog(true);
+        dialog.window.show();
+        self.platform.activate_window(dialog.window.id);
+        self.dialog = Some(dialog);
+    }
+
+    fn pump_dialog(&mut self) -> bool {
+        let Some(dialog) = self.dialog.as_mut() else {
+            return false;
+        };
+        let events = dialog.window.poll_events();
+        dialog.window.dispatch(&events);
+        self.finish_dialog(false)
+    }
+
+    fn finish_dialog(&mut self, cancel: bool) -> bool {
+        let Some(reply) = self.dialog.as_mut().and_then(|d| d.answer(cancel)) else {
+            return false;
+        };
+        if let Some(dialog) = self.dialog.take() {
+            self.platform.remove_window(dialog.window.id);
+        }
+        self.platform.activate_window(self.id);
+        reply(self);
+        true
+    }
+
+    fn close_menu(&mut self) -> Option<WidgetId> {
+        let menu = self.menu.take()?;
+        self.platform.remove_window(menu.window.id);
+        Some(menu.owner)
+    }
+
+    fn pump_menu(&mut self, focus_lost: bool) -> bool {
+        let Some(menu) = self.menu.as_mut() else {
+            return false;
+        };
+        let events = menu.window.poll_events();
+        menu.window.dispatch(&events);
+
+        let focused = events.iter().any(|e| matches!(e, Event::FocusIn(_)));
+        let unfocused = events.iter().any(|e| matches!(e, Event::FocusOut(_)));
+        let owner = menu.owner;
+        let picked = menu.picked.get();
+
+        if let Some(index) = picked {
+            self.close_menu();
+            self.platform.activate_window(self.id);
+            let changed = self.find_widget_by_id(owner).and_then(|w| w.selected()) != Some(index);
+            if let Some(widget) = self.find_widget_by_id_mut(owner) {
+                widget.set_selected(Some(index));
+            }
+            if changed {
+                self.fire(owner, |h| &h.change, Value::Selected(index));
+            }
+            return true;
+        }
+        if unfocused || (focus_lost && !focused) {
+            self.close_menu();
+        }
+        false
+    }
+
+    fn sync_menu(&mut self) {
+        let Some(owner) = self.menu.as_ref().map(|m| m.owner) else {
+            return;
+        };
+        let selected = self.find_widget_by_id(owner).and_then(|w| w.selected());
+        if let Some(menu) = self.menu.as_mut() {
+            if let Some(list) = menu.window.find_widget_by_id_mut(MENU_LIST) {
+                list.set_selected(selected);
+            }
+            menu.window.draw();
+            menu.window.update();
+        }
+    }
+
+    pub fn set_clipboard(&mut self, mime: &str, data: &[u8]) -> bool {
+        let ok = self.id != 0 && self.platform.set_clipboard(self.id, mime, data);
+        if ok {
+            self.owns_selection = true;
+        }
+        ok
+    }
+
+    pub fn get_clipboard(&self, mime: &str) -> Option<Vec<u8>> {
+        self.platform.clipboard(mime)
+    }
+
+    pub fn copy_text(&mut self, text: &str) -> bool {
+        self.set_clipboard(MIME_TEXT, text.as_bytes())
+    }
+
+    pub fn paste_text(&self) -> Option<String> {
+        self.get_clipboard("text/plain").and_then(|data| String::from_utf8(data).ok())
+    }
+
+    pub fn set_cursor(&mut self, shape: Option<CursorShape>) {
+        self.cursor_pinned = shape.is_some();
+        if let Some(shape) = shape {
+            self.show_cursor(shape);
+        }
+    }
+
+    pub fn set_custom_cursor(&mut self, width: usize, height: usize, hot_x: usize, hot_y: usize, pixels: &[u32]) -> bool {
+        if !self.platform.set_custom_cursor(self.id, width, height, hot_x, hot_y, pixels) {
+            return false;
+        }
+        self.cursor_pinned = true;
+        self.cursor = None;
+        true
+    }
+
+    fn show_cursor(&mut self, shape: CursorShape) {
+        if self.cursor != Some(shape) && self.platform.set_cursor(self.id, shape) {
+            self.cursor = Some(shape);
+        }
+    }
+
+    pub fn watch_windows(&mut self, enable: bool) -> bool {
+        self.id != 0 && self.platform.watch_windows(self.id, enable)
+    }
+
+    pub fn show(&mut self) {
         if self.id == 0 {
-            self.id = graphics::add_window(&std_window);
+            self.id = self.platform.add_window(self);
         } else {
-            graphics::update_window(&std_window);
+            self.platform.update_window(self);
         }
 
         self.draw();
@@ -128,57 +533,94 @@ impl Window {
     }
 
     pub fn draw(&mut self) {
-        if self.buffer.address.is_null() {
-            return;
+        self.invalidate_area(0, 0, self.width, self.height);
+        self.repaint();
+    }
+
+    pub fn draw_widget(&mut self, id: WidgetId) {
+        self.invalidate(id);
+        self.repaint();
+    }
+
+    pub fn invalidate(&mut self, id: WidgetId) {
+        if let Some(widget) = self.find_widget_by_id_mut(id) {
+            widget.mark_dirty();
         }
+    }
 
-        let buffer_len = self.buffer.size / 4;
-        let buffer = unsafe {
-            slice::from_raw_parts_mut(
-                self.buffer.address,
-                buffer_len,
-            )
-        };
+    pub fn invalidate_area(&mut self, x: usize, y: usize, width: usize, height: usize) {
+        self.damage.add(Rect::new(x, y, width, height));
+    }
+
+    pub fn present(&mut self) {
+        self.repaint();
+        for area in self.unsent.take() {
+            self.update_area(area.x, area.y, area.width, area.height);
+        }
+    }
 
-        buffer.fill(0);
+    pub fn update(&mut self) {
+        self.unsent.take();
+        self.platform.update_window(self);
+    }
+
+    fn repaint(&mut self) {
+        if self.buffer.address.is_null() {
+            return;
+        }
 
         for child in &mut self.children {
+            restyle(child, &self.theme, self.pointed, self.held, false);
             child.update_layout(0, 0, self.width, self.height, 0, 0, &Display::None);
         }
 
+        let mut painted = Vec::new();
         for child in &mut self.children {
-            paint_recursive(buffer, self.width, child, &mut self.font);
+            collect_painted(child, None, &mut painted);
         }
-    }
 
-    pub fn draw_widget(&mut self, _id: WidgetId) {
-        self.draw();
+        let window = Rect::new(0, 0, self.width, self.height);
+        let same_tree = painted.len() == self.painted.len() &&
+            painted.iter().zip(&self.painted).all(|((id, _, _), (old_id, _))| id == old_id);
+        if same_tree {
+            for ((_, rect, dirty), (_, old)) in painted.iter().zip(&self.painted) {
+                if *dirty || rect != old {
+                    self.damage.add(*rect);
+                    self.damage.add(*old);
+                }
+            }
+        } else {
+            self.damage.add(window);
+        }
+        self.painted = painted.into_iter().map(|(id, rect, _)| (id, rect)).collect();
+
+        for area in self.damage.take() {
+            if let Some(area) = area.intersection(&window) {
+                self.paint_area(area);
+                self.unsent.add(area);
+            }
+        }
     }
 
-    pub fn update(&mut self) {
-        let std_window = graphics::Window {
-            id: self.id,
-            buffer: self.buffer.address as usize,
-            pid: self.pid,
-            x: self.x,
-            y: self.y,
-            z: 0,
-            width: self.width,
-            height: self.height,
-            can_move: self.can_move,
-            can_resize: self.can_resize,
-            transparent: self.transparent,
-            treat_as_transparent: self.treat_as_transparent,
-            min_width: self.min_width,
-            min_height: self.min_height,
-            event_handler: 1,
-            w_type: self.w_type,
+    fn paint_area(&mut self, area: Rect) {
+        let buffer = unsafe {
+            slice::from_raw_parts_mut(
+                self.buffer.address,
+                self.buffer.size / 4,
+            )
         };
-        graphics::update_window(&std_window);
+        let width = self.width;
+
+        for row in area.y..area.bottom() {
+            buffer[row * width + area.x..row * width + area.right()].fill(0);
+        }
+        for child in &mut self.children {
+            paint_recursive(buffer, width, child, &area, &mut self.font, &self.theme);
+        }
     }
 
     pub fn update_area(&mut self, x: usize, y: usize, w: usize, h: usize) {
-        graphics::update_window_area(self.id, x, y, w, h);
+        self.platform.update_window_area(self.id, x, y, w, h);
     }
 
     pub fn resize(&mut self, width: usize, height: usize, can_move: bool) {
@@ -188,26 +630,41 @@ impl Window {
         self.can_move = can_move;
 
         let new_size = width * height * 4 + 4;
-        self.buffer.resize(new_size);
+        self.buffer.resize(&*self.platform, new_size);
+
+        self.draw();
+        self.update();
+    }
+
+    pub fn set_bounds(&mut self, x: isize, y: isize, width: usize, height: usize) {
+        self.x = x;
+        self.y = y;
+        self.width = width;
+        self.height = height;
+        self.buffer.resize(&*self.platform, width * height * 4 + 4);
 
         self.draw();
         self.update();
     }
 
     pub fn poll_events(&mut self) -> Vec<Event> {
-        let mut events: [Event; 64] = [Event::None; 64];
-        unsafe {
-            syscall(104, self.id as u64, events.as_mut_ptr() as u64, 64);
-        }
+        self.platform.poll_events(self.id)
+    }
 
-        let mut vec = Vec::new();
-        for e in events {
-            if e == Event::None {
-                break;
+    pub fn wait_events(&mut self, timeout_ns: Option<u64>) -> Vec<Event> {
+        let timeout_ns = if self.menu.is_some() || self.dialog.is_some() {
+            Some(timeout_ns.map_or(POPUP_POLL_NS, |t| t.min(POPUP_POLL_NS)))
+        } else {
+            timeout_ns
+        };
+        let timeout_ns = match self.timers.next_due() {
+            Some(due) => {
+                let until_due = due.saturating_duration_since(Instant::now()).as_nanos() as u64;
+                Some(timeout_ns.map_or(until_due, |t| t.min(until_due)))
             }
-            vec.push(e);
-        }
-        vec
+            None => timeout_ns,
+        };
+        self.platform.wait_events(self.id, timeout_ns)
     }
 
     pub fn focus_next(&mut self) {
@@ -231,25 +688,60 @@ impl Window {
                 if let Some(w) = self.find_widget_by_id_mut(old_focus) {
                     w.set_focused(false);
                 }
-                self.draw_widget(old_focus);
             }
 
             self.focus = next_id;
             if let Some(w) = self.find_widget_by_id_mut(self.focus) {
                 w.set_focused(true);
             }
-            self.draw_widget(self.focus);
-            self.update();
+            self.present();
         }
     }
 
     pub fn event_loop(&mut self) {
-        let mut events: [Event; 64] = [Event::None; 64];
-        let mut any_redraw = false;
+        let events = self.wait_events(None);
+        self.dispatch(&events);
+    }
+
+    pub fn set_timeout(&mut self, delay: Duration, handler: impl FnOnce(&mut Window) + 'static) -> TimerId {
+        let mut handler = Some(handler);
+        self.timers.add(delay, None, Box::new(move |win| {
+            if let Some(handler) = handler.take() {
+                handler(win);
+            }
+            false
+        }))
+    }
 
-        unsafe {
-            syscall(104, self.id as u64, events.as_mut_ptr() as u64, 64);
+    pub fn set_interval(&mut self, period: Duration, mut handler: impl FnMut(&mut Window) + 'static) -> TimerId {
+        self.repeat(period, period, move |win| {
+            handler(win);
+            true
+        })
+    }
+
+    pub(crate) fn repeat(&mut self, delay: Duration, period: Duration, handler: impl FnMut(&mut Window) -> bool + 'static) -> TimerId {
+        self.timers.add(delay, Some(period), Box::new(handler))
+    }
+
+    pub fn clear_timer(&mut self, id: TimerId) -> bool {
+        self.timers.remove(id)
+    }
+
+    fn run_timers(&mut self) -> bool {
+        let now = Instant::now();
+        let mut ran = false;
+        while let Some(mut timer) = self.timers.take_due(now) {
+            let keep = (timer.handler)(self);
+            self.timers.finish(timer, keep, now);
+            ran = true;
         }
+        ran
+    }
+
+    pub fn dispatch(&mut self, events: &[Event]) {
+        let mut any_redraw = false;
+        let mut focus_lost = false;
 
         for event in events.iter() {
             match event {
@@ -258,15 +750,63 @@ impl Window {
                         self.resize(e.width, e.height, self.can_move);
                     }
                 }
+                Event::CloseRequested(_) | Event::Close(_) => {
+                    self.close();
+                }
+                Event::SelectionChanged(e) => {
+                    self.owns_selection = e.owner as usize == self.id;
+                }
+                Event::ThemeChanged(_) => {
+                    self.set_theme(Theme::current(&*self.platform));
+                    any_redraw = true;
+                    self.emit(event);
+                }
+                Event::Overflow(_) => {
+                    // The modifier releases may have been among the dropped events.
+                    self.shift = false;
+                    self.ctrl = false;
+                    self.emit(event);
+                }
+                Event::FocusOut(_) => {
+                    // The modifiers may be released in another window.
+                    self.shift = false;
+                    self.ctrl = false;
+                    focus_lost = true;
+                    self.emit(event);
+                }
                 Event::Mouse(e) => {
-                    let target_id = if let Some(widget) = self.find_interactive_widget_at(e.x, e.y) {
-                        Some(widget.get_id())
-                    } else {
-                        None
+                    // A dialog keeps the input until it is answered.
+                    if let Some(dialog) = &self.dialog {
+                        if e.buttons.iter().zip(self.buttons).any(|(&now, before)| now && !before) {
+                            self.platform.activate_window(dialog.window.id);
+                        }
+                        self.buttons = e.buttons;
+                        continue;
+                    }
+
+                    let (target_id, shape) = match self.find_interactive_widget_at(e.x, e.y) {
+                        Some(widget @ (Widget::TextInput { .. } | Widget::TextArea { .. })) => (Some(widget.get_id()), CursorShape::Text),
+                        Some(widget @ (Widget::Button { .. } |
+                                       Widget::Checkbox { .. } |
+                                       Widget::RadioGroup { .. } |
+                                       Widget::Slider { .. } |
+                                       Widget::Dropdown { .. } |
+                                       Widget::Spinner { .. })) => (Some(widget.get_id()), CursorShape::Hand),
+                        Some(widget) => (Some(widget.get_id()), CursorShape::Arrow),
+                        None => (None, CursorShape::Arrow),
                     };
+                    if !self.cursor_pinned {
+                        self.show_cursor(shape);
+                    }
+
+                    let path = self.widgets_at(e.x, e.y);
 
                     if e.scroll != 0 {
-                        let scroll_target = if self.focus != 0 {
+                        let container = path.iter().rev().map(|&(id, _, _)| id)
+                            .find(|&id| self.find_widget_by_id(id).is_some_and(|w| w.is_scrollable()));
+                        let scroll_target = if container.is_some() {
+                            container
+                        } else if self.focus != 0 {
                             Some(self.focus)
                         } else {
                             target_id
@@ -296,52 +836,158 @@ impl Window {
                         }
                     }
 
-                    if e.buttons[0] {
-                        if let Some(id) = target_id {
-                            let mut handler_opt = None;
-                            if let Some(w) = self.find_widget_by_id(id) {
-                                handler_opt = w.get_event_handler();
-                            }
+                    let hovered = self.handled_at(&path, |h| h.hover.is_some()).unwrap_or(0);
+                    if self.set_hovered(hovered) {
+                        any_redraw = true;
+                    }
+
+                    let pressed = [0, 1, 2].map(|i| e.buttons[i] && !self.buttons[i]);
+                    let released = self.buttons[0] && !e.buttons[0];
+                    self.buttons = e.buttons;
+
+                    let pointed = target_id.unwrap_or(0);
+                    if self.move_state(self.pointed, pointed, WidgetState::Hover) {
+                        any_redraw = true;
+                    }
+                    self.pointed = pointed;
+                    let held = if pressed[0] { pointed } else if released { 0 } else { self.held };
+                    if self.move_state(self.held, held, WidgetState::Pressed) {
+                        any_redraw = true;
+                    }
+                    self.held = held;
+
+                    // Pressing the dropdown itself toggles its menu in `press` instead.
+                    let owner = self.menu.as_ref().map(|m| m.owner);
+                    if pressed.iter().any(|&p| p) && owner.is_some_and(|id| !path.iter().any(|&(p, _, _)| p == id)) {
+                        self.close_menu();
+                    }
 
-                            if let Some(handler) = handler_opt {
-                                handler(self, id);
+                    if pressed[0] && self.press(&path, e.x, e.y) {
+                        any_redraw = true;
+                    } else if e.buttons[0] && self.drag(e.x, e.y) {
+                        any_redraw = true;
+                    }
+                    if released && self.grab.is_some() {
+                        self.release();
+                        any_redraw = true;
+                    }
+
+                    if pressed[0] {
+                        if let Some(id) = self.handled_at(&path, |h| h.click.is_some() || h.double_click.is_some()) {
+                            if self.click(id) {
+                                any_redraw = true;
+                            }
+                        }
+                    }
+                    if pressed[1] {
+                        if let Some(id) = self.handled_at(&path, |h| h.right_click.is_some()) {
+                            if self.fire(id, |h| &h.right_click, ()) {
                                 any_redraw = true;
                             }
                         }
                     }
                 }
                 Event::Keyboard(e) => {
+                    if self.dialog.is_some() {
+                        continue;
+                    }
+
                     let char_opt = if e.key < 0x110000 {
                         core::char::from_u32(e.key)
                     } else {
                         None
                     };
 
-                    if e.key == 9 {
+                    match e.key {
+                        KEY_SHIFT => self.shift = e.pressed,
+                        KEY_CTRL => self.ctrl = e.pressed,
+                        _ => {}
+                    }
+
+                    // A widget disabled while focused gives the focus up.
+                    if self.find_widget_by_id(self.focus).is_some_and(|w| !w.is_enabled()) {
+                        self.focus = 0;
+                    }
+
+                    // Tab is typed into a text area; Ctrl+Tab leaves it.
+                    let in_text_area = matches!(self.find_widget_by_id(self.focus), Some(Widget::TextArea { .. }));
+                    if e.key == 9 && !(in_text_area && !self.ctrl) {
                         self.focus_next();
                         continue;
                     }
 
+                    if e.pressed && e.key == ESCAPE && self.close_menu().is_some() {
+                        continue;
+                    }
+                    if e.pressed && e.key == ESCAPE && self.dismiss_on_escape {
+                        self.close();
+                        continue;
+                    }
+
+                    if e.pressed && self.focus != 0 {
+                        let moved = self.find_widget_by_id_mut(self.focus).and_then(|w| w.handle_control_key(e.key));
+                        if let Some(value) = moved {
+                            self.invalidate(self.focus);
+                            self.fire(self.focus, |h| &h.change, value);
+                            self.sync_menu();
+                            any_redraw = true;
+                            continue;
+                        }
+                    }
+
                     if self.focus != 0 {
-                        let mut click_handler: Option<fn(&mut Window, WidgetId)> = None;
+                        let mut handler: Option<fn(&Handlers) -> &Option<Callback<()>>> = None;
+                        let mut changed = false;
+                        let mut copied = None;
+                        let mut paste = false;
+                        let mut menu = false;
+                        let (shift, ctrl) = (self.shift, self.ctrl);
 
                         if let Some(widget) = self.find_widget_by_id_mut(self.focus) {
                             match widget {
-                                Widget::Button { on_click, .. } => {
+                                Widget::Button { .. } => {
+                                    if e.pressed && (e.key == 13 || e.key == 32) {
+                                        handler = Some(|h| &h.click);
+                                    }
+                                }
+                                Widget::Dropdown { .. } => {
                                     if e.pressed && (e.key == 13 || e.key == 32) {
-                                        click_handler = *on_click;
+                                        menu = true;
                                     }
                                 }
-                                Widget::TextInput { on_submit, .. } => {
+                                Widget::TextInput { .. } => {
                                     if e.pressed && (e.key == 13 || e.key == 10) {
-                                        click_handler = *on_submit;
+                                        handler = Some(|h| &h.submit);
+                                    } else if e.pressed && e.key == CTRL_C {
+                                        copied = Some(widget.get_text());
+                                    } else if e.pressed && e.key == CTRL_V {
+                                        paste = true;
                                     } else if e.pressed {
                                         if let Some(c) = char_opt {
                                             for _ in 0..e.repeat {
                                                 widget.handle_key(c);
                                             }
+                                            changed = true;
+                                            any_redraw = true;
+                                        }
+                                    }
+                                }
+                                Widget::TextArea { .. } => {
+                                    if e.pressed && (e.key == CTRL_C || e.key == CTRL_X) {
+                                        copied = widget.editor().and_then(|editor| editor.selected_text());
+                                        if e.key == CTRL_X && widget.editor_mut().is_some_and(|editor| editor.delete_selection()) {
+                                            changed = true;
                                             any_redraw = true;
                                         }
+                                    } else if e.pressed && e.key == CTRL_V {
+                                        paste = true;
+                                    } else if e.pressed {
+                                        for _ in 0..e.repeat {
+                                            if widget.handle_edit_key(e.key, shift, ctrl) == Some(Edit::Changed) {
+                                                changed = true;
+                                            }
+                                        }
+                                        any_redraw = true;
                                     }
                                 }
                                 _ => {
@@ -357,20 +1003,78 @@ impl Window {
                             }
                         }
 
-                        if let Some(handler) = click_handler {
-                            handler(self, self.focus);
-                            any_redraw = true;
+                        if let Some(text) = copied {
+                            self.copy_text(&text);
+                        }
+
+                        if paste {
+                            if let Some(text) = self.paste_text() {
+                                let text: String = text.chars().filter(|c| !c.is_control() || *c == '\n' || *c == '\t').collect();
+                                if let Some(w) = self.find_widget_by_id_mut(self.focus) {
+                                    w.append_text(&text);
+                                    changed = true;
+                                    any_redraw = true;
+                                }
+                            }
+                        }
+
+                        if changed {
+                            self.fire_change(self.focus);
+                        }
+
+                        if menu {
+                            self.toggle_menu(self.focus);
+                        }
+
+                        if let Some(pick) = handler {
+                            if self.fire(self.focus, pick, ()) {
+                                any_redraw = true;
+                            }
                         }
                     }
                 }
-                _ => {}
+                Event::PointerLeave(_) => {
+                    if self.set_hovered(0) {
+                        any_redraw = true;
+                    }
+                    if self.move_state(self.pointed, 0, WidgetState::Hover) {
+                        any_redraw = true;
+                    }
+                    self.pointed = 0;
+                    self.emit(event);
+                }
+                Event::None => {}
+                other => self.emit(other),
             }
         }
 
+        if self.pump_menu(focus_lost) {
+            any_redraw = true;
+        }
+        if self.pump_dialog() {
+            any_redraw = true;
+        }
+        if self.run_timers() {
+            any_redraw = true;
+        }
+
         if any_redraw {
-            self.draw();
-            self.update();
+            self.present();
+        }
+    }
+
+    pub fn widgets_at(&self, x: usize, y: usize) -> Vec<(WidgetId, usize, usize)> {
+        let mut path = Vec::new();
+        for child in self.children.iter().rev() {
+            if collect_widgets_at(child, x, y, &mut path) {
+                break;
+            }
         }
+        path
+    }
+
+    fn handled_at(&self, path: &[(WidgetId, usize, usize)], wanted: fn(&Handlers) -> bool) -> Option<WidgetId> {
+        path.iter().rev().map(|&(id, _, _)| id).find(|&id| self.find_widget_by_id(id).is_some_and(|w| wanted(w.handlers())))
     }
 
     pub fn find_interactive_widget_at(&self, x: usize, y: usize) -> Option<&Widget> {
@@ -402,8 +1106,21 @@ impl Window {
 }
 
 fn collect_focusable_widgets(widget: &Widget, ids: &mut Vec<WidgetId>) {
+    if !widget.is_enabled() {
+        return;
+    }
+
     match widget {
-        Widget::Button { .. } | Widget::TextInput { .. } => {
+        Widget::Button { .. } |
+        Widget::TextInput { .. } |
+        Widget::TextArea { .. } |
+        Widget::List { .. } |
+        Widget::Table { .. } |
+        Widget::Checkbox { .. } |
+        Widget::RadioGroup { .. } |
+        Widget::Slider { .. } |
+        Widget::Dropdown { .. } |
+        Widget::Spinner { .. } => {
             ids.push(widget.get_id());
         }
         _ => {}
@@ -416,15 +1133,83 @@ fn collect_focusable_widgets(widget: &Widget, ids: &mut Vec<WidgetId>) {
     }
 }
 
-fn find_interactive_widget_recursive(widget: &Widget, x: usize, y: usize) -> Option<&Widget> {
+fn collect_widgets_at(widget: &Widget, x: usize, y: usize, path: &mut Vec<(WidgetId, usize, usize)>) -> bool {
     let geometry = widget.geometry();
 
     if x < geometry.x || x >= geometry.x + geometry.width ||
         y < geometry.y || y >= geometry.y + geometry.height {
+        return false;
+    }
+    // Disabled widgets still cover what is below them.
+    if !widget.is_enabled() {
+        return true;
+    }
+
+    path.push((geometry.id, x, y));
+    if let (Some(children), Some((cx, cy))) = (widget.get_children(), widget.content_point(x, y)) {
+        for child in children.iter().rev() {
+            if collect_widgets_at(child, cx, cy, path) {
+                break;
+            }
+        }
+    }
+    true
+}
+
+fn restyle(widget: &mut Widget, theme: &Theme, pointed: WidgetId, held: WidgetId, disabled: bool) {
+    let disabled = disabled || !widget.is_enabled();
+    if widget.geometry().dirty {
+        let id = widget.get_id();
+        let state = if disabled {
+            WidgetState::Disabled
+        } else if id == held {
+            WidgetState::Pressed
+        } else if id == pointed {
+            WidgetState::Hover
+        } else if widget.is_focused() {
+            WidgetState::Focused
+        } else {
+            WidgetState::Normal
+        };
+        widget.apply_style(&theme.style(widget.class(), state));
+    }
+
+    if let Some(children) = widget.get_children_mut() {
+        for child in children {
+            restyle(child, theme, pointed, held, disabled);
+        }
+    }
+}
+
+fn collect_painted(widget: &mut Widget, view: Option<Rect>, out: &mut Vec<(WidgetId, Rect, bool)>) {
+    let geometry = widget.geometry_mut();
+    let rect = view.unwrap_or(geometry.rect());
+    out.push((geometry.id, rect, core::mem::take(&mut geometry.dirty)));
+
+    let view = match widget {
+        Widget::ScrollView { .. } => Some(rect),
+        _ => view,
+    };
+    if let Some(children) = widget.get_children_mut() {
+        for child in children {
+            collect_painted(child, view, out);
+        }
+    }
+}
+
+fn find_interactive_widget_recursive(widget: &Widget, x: usize, y: usize) -> Option<&Widget> {
+    let geometry = widget.geometry();
+
+    if x < geometry.x || x >= geometry.x + geometry.width ||
+        y < geometry.y || y >= geometry.y + geometry.height || !widget.is_enabled() {
         return None;
     }
 
     if let Some(children) = widget.get_children() {
+        // The scrollbars of a scroll view are its own.
+        let Some((x, y)) = widget.content_point(x, y) else {
+            return Some(widget);
+        };
         for child in children.iter().rev() {
             if let Some(found) = find_interactive_widget_recursive(child, x, y) {
                 return Some(found);
@@ -441,13 +1226,23 @@ pub fn paint_recursive(
     buffer: &mut [u32],
     width0: usize,
     widget: &mut Widget,
+    clip: &Rect,
     font: &mut Option<TrueTypeFont>,
+    theme: &Theme,
 ) {
-    widget.draw(buffer, width0, font);
+    if widget.geometry().rect().intersects(clip) {
+        widget.draw(buffer, width0, clip, font, theme);
+    }
+
+    // A scroll view paints its children itself, clipped.
+    if let Widget::ScrollView { .. } = widget {
+        return;
+    }
 
+    // Children may reach outside their parent, so they are checked even when it isn't.
     if let Some(children) = widget.get_children_mut() {
         for child in children {
-            paint_recursive(buffer, width0, child, font);
+            paint_recursive(buffer, width0, child, clip, font, theme);
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,241 @@
+extern crate alloc;
+
+/// The kernel's window manager, built for the host so the compositor can be driven against
+/// a headless display. The modules below stand in for the kernel services it touches.
+pub mod window_manager;
+
+/// The kernel mutex masks interrupts, which user space can't do; a plain spin lock is
+/// enough here.
+pub mod sync {
+    use core::cell::UnsafeCell;
+    use core::ops::{Deref, DerefMut};
+    use core::sync::atomic::{AtomicBool, Ordering};
+
+    pub struct Mutex<T> {
+        lock: AtomicBool,
+        data: UnsafeCell<T>,
+    }
+
+    unsafe impl<T: Send> Sync for Mutex<T> {}
+    unsafe impl<T: Send> Send for Mutex<T> {}
+
+    pub struct MutexGuard<'a, T> {
+        lock: &'a AtomicBool,
+        data: &'a mut T,
+    }
+
+    impl<T> Mutex<T> {
+        pub const fn new(data: T) -> Self {
+            Self { lock: AtomicBool::new(false), data: UnsafeCell::new(data) }
+        }
+
+        pub fn lock(&self) -> MutexGuard<'_, T> {
+            while self.lock.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed).is_err() {
+                core::hint::spin_loop();
+            }
+            MutexGuard { lock: &self.lock, data: unsafe { &mut *self.data.get() } }
+        }
+
+        pub fn int_lock(&self) -> MutexGuard<'_, T> {
+            self.lock()
+        }
+
+        pub fn try_int_lock(&self) -> Option<MutexGuard<'_, T>> {
+            if self.lock.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed).is_err() {
+                return None;
+            }
+            Some(MutexGuard { lock: &self.lock, data: unsafe { &mut *self.data.get() } })
+        }
+    }
+
+    impl<T> Deref for MutexGuard<'_, T> {
+        type Target = T;
+
+        fn deref(&self) -> &T {
+            self.data
+        }
+    }
+
+    impl<T> DerefMut for MutexGuard<'_, T> {
+        fn deref_mut(&mut self) -> &mut T {
+            self.data
+        }
+    }
+
+    impl<T> Drop for MutexGuard<'_, T> {
+        fn drop(&mut self) {
+            self.lock.store(false, Ordering::Release);
+        }
+    }
+}
+
+pub mod boot {
+    #[derive(Clone, Copy)]
+    pub struct ModeInfo {
+        pub width: u16,
+        pub height: u16,
+        pub pitch: u16,
+        pub framebuffer: u32,
+    }
+
+    #[derive(Clone, Copy)]
+    pub struct BootInfo {
+        pub mode: ModeInfo,
+    }
+
+    pub static mut BOOT_INFO: BootInfo = BootInfo { mode: ModeInfo { width: 0, height: 0, pitch: 0, framebuffer: 0 } };
+}
+
+pub mod memory {
+    pub mod address {
+        pub struct PhysAddr(u64);
+
+        impl PhysAddr {
+            pub fn new(addr: u64) -> Self {
+                PhysAddr(addr)
+            }
+
+            pub fn as_u64(&self) -> u64 {
+                self.0
+            }
+        }
+    }
+
+    pub mod paging {
+        pub const HHDM_OFFSET: u64 = 0;
+
+        pub fn phys_to_virt(phys: super::address::PhysAddr) -> super::address::PhysAddr {
+            phys
+        }
+    }
+
+    pub mod pmm {
+        pub fn allocate_frames(_count: usize, _pid: u64) -> Option<u64> {
+            None
+        }
+
+        pub fn free_frame(_addr: u64) {}
+    }
+}
+
+pub mod drivers {
+    pub mod periferics {
+        pub mod keyboard {
+            pub fn is_super_active() -> bool {
+                false
+            }
+        }
+
+        pub mod mouse {
+            pub static mut MOUSE_PACKET: [u8; 4] = [0; 4];
+
+            pub const CURSOR_BUFFER: [u32; 4 * 4] = [0xFFFFFFFF; 16];
+            pub const CURSOR_WIDTH: usize = 4;
+            pub const CURSOR_HEIGHT: usize = 4;
+        }
+    }
+
+    pub mod video {
+        pub mod virtio {
+            use alloc::vec::Vec;
+
+            pub mod queue {
+                pub static mut VIRT_QUEUES: [Option<()>; 2] = [None, None];
+            }
+
+            pub mod cursor {
+                pub const HW_CURSOR_SIZE: u32 = 64;
+
+                pub fn setup_cursor(_phys_ptr: u64) {}
+
+                pub fn update_cursor(_x: u32, _y: u32, _hot_x: u32, _hot_y: u32) {}
+
+                pub fn move_cursor(_x: u32, _y: u32) {}
+            }
+
+            pub mod structs {
+                #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+                pub struct VirtioGpuRect {
+                    pub x: u32,
+                    pub y: u32,
+                    pub width: u32,
+                    pub height: u32,
+                }
+            }
+
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            pub struct ScanoutInfo {
+                pub id: u32,
+                pub width: u32,
+                pub height: u32,
+            }
+
+            pub fn init() {}
+
+            pub fn display_modes() -> Vec<ScanoutInfo> {
+                Vec::new()
+            }
+
+            pub fn take_display_event() -> bool {
+                false
+            }
+
+            pub fn create_resource(_id: u32, _width: u32, _height: u32, _phys: u64) {}
+
+            pub fn destroy_resource(_id: u32) {}
+
+            pub fn set_layout(_layout: Vec<(u32, structs::VirtioGpuRect)>) {}
+
+            pub fn disable_scanouts() {}
+
+            pub fn get_display_info() -> Option<(u32, u32)> {
+                None
+            }
+
+            pub fn start_gpu(_width: u32, _height: u32, _phys_buf1: u64, _phys_buf2: u64) {}
+
+            pub fn transfer_and_flush(_resource_id: u32, _width: u32, _height: u32) {}
+
+            pub fn flush(_x: u32, _y: u32, _width: u32, _height: u32, _screen_width: u32, _resource_id: u32) {}
+
+            pub fn set_scanout(_resource_id: u32, _width: u32, _height: u32) {}
+        }
+    }
+}
+
+pub mod interrupts {
+    pub mod task {
+        use crate::sync::Mutex;
+        use alloc::vec::Vec;
+
+        /// Records the processes the compositor asked to kill instead of killing them.
+        pub struct TaskManager {
+            pub killed: Vec<u64>,
+        }
+
+        impl TaskManager {
+            pub fn kill_process(&mut self, pid: u64) {
+                self.killed.push(pid);
+            }
+        }
+
+        pub static TASK_MANAGER: Mutex<TaskManager> = Mutex::new(TaskManager { killed: Vec::new() });
+
+        pub fn wake_thread(_idx: usize) {}
+    }
+}
+
+/// Kernel logging goes to the serial port; the host build just drops it.
+#[macro_export]
+macro_rules! debugln {
+    ($($arg:tt)*) => {{
+        let _ = format_args!($($arg)*);
+    }};
+}
+
+#[macro_export]
+macro_rules! println {
+    ($($arg:tt)*) => {{
+        let _ = format_args!($($arg)*);
+    }};
+}
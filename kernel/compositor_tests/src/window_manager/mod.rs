@@ -0,0 +1,20 @@
+#[path = "../../../src/window_manager/composer.rs"]
+pub mod composer;
+#[path = "../../../src/window_manager/cursor.rs"]
+pub mod cursor;
+#[path = "../../../src/window_manager/decorations.rs"]
+pub mod decorations;
+#[path = "../../../src/window_manager/display.rs"]
+pub mod display;
+#[path = "../../../src/window_manager/events.rs"]
+pub mod events;
+#[path = "../../../src/window_manager/font.rs"]
+pub mod font;
+#[path = "../../../src/window_manager/input.rs"]
+pub mod input;
+#[path = "../../../src/window_manager/tiling.rs"]
+pub mod tiling;
+#[path = "../../../src/window_manager/window.rs"]
+pub mod window;
+#[path = "../../../src/window_manager/workspace.rs"]
+pub mod workspace;
@@ -0,0 +1,240 @@
+use compositor_tests::window_manager::composer::COMPOSER;
+use compositor_tests::window_manager::display::{self, ScanoutMode, DISPLAY_SERVER};
+use compositor_tests::window_manager::input::CLICKED_WINDOW_ID;
+use compositor_tests::window_manager::window::{Items, Window, NULL_WINDOW, TITLE_LEN};
+use compositor_tests::window_manager::workspace::WORKSPACES;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+
+const WIDTH: usize = 96;
+const HEIGHT: usize = 64;
+
+const RED: u32 = 0xFFD04040;
+const BLUE: u32 = 0xFF4060D0;
+const GREEN: u32 = 0xFF30A050;
+const YELLOW: u32 = 0xFFE0C030;
+
+/// The compositor lives in globals, so tests take turns.
+static SERIAL: Mutex<()> = Mutex::new(());
+
+fn setup() -> MutexGuard<'static, ()> {
+    let guard = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+    unsafe {
+        (*(&raw mut DISPLAY_SERVER)).init_headless(WIDTH as u64, HEIGHT as u64);
+        let composer = &mut *(&raw mut COMPOSER);
+        for w in composer.windows.clone() {
+            if w.w_type != Items::Null {
+                composer.remove_window(w.id);
+            }
+        }
+        CLICKED_WINDOW_ID = 0;
+        (*(&raw mut WORKSPACES)).current = 0;
+    }
+    guard
+}
+
+fn composer() -> &'static mut compositor_tests::window_manager::composer::Composer {
+    unsafe { &mut *(&raw mut COMPOSER) }
+}
+
+/// Builds a window whose client buffer is filled by `pixel(x, y)`. The buffer is leaked,
+/// the same way an app's buffer outlives the compositor's view of it.
+fn window(w_type: Items, width: usize, height: usize, pixel: impl Fn(usize, usize) -> u32) -> Window {
+    let mut pixels = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            pixels.push(pixel(x, y));
+        }
+    }
+    let buffer = Box::leak(pixels.into_boxed_slice()).as_ptr() as usize;
+
+    Window {
+        buffer,
+        width,
+        height,
+        w_type,
+        transparent: false,
+        treat_as_transparent: false,
+        ..NULL_WINDOW
+    }
+}
+
+fn solid(width: usize, height: usize, color: u32) -> Window {
+    window(Items::Window, width, height, |_, _| color)
+}
+
+/// Moves a window the way a drag does and recomposes the whole screen.
+fn place(id: usize, x: isize, y: isize) {
+    let w = composer().find_window_id(id).expect("window is gone");
+    w.x = x;
+    w.y = y;
+    composer().update_window_area_rect(0, 0, WIDTH as u32, HEIGHT as u32);
+}
+
+fn back_buffer() -> Vec<u32> {
+    let ds = unsafe { &*(&raw const DISPLAY_SERVER) };
+    let stride = ds.pitch as usize / 4;
+    let db = unsafe { std::slice::from_raw_parts(ds.double_buffer as *const u32, stride * HEIGHT) };
+    (0..HEIGHT).flat_map(|y| db[y * stride..y * stride + WIDTH].iter().copied()).collect()
+}
+
+fn pixel(x: usize, y: usize) -> u32 {
+    back_buffer()[y * WIDTH + x] & 0x00FF_FFFF
+}
+
+fn assert_golden(name: &str) {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    golden::assert_golden(&dir, name, WIDTH, HEIGHT, &back_buffer());
+}
+
+#[test]
+fn later_windows_are_drawn_on_top() {
+    let _serial = setup();
+    let a = composer().add_window(solid(40, 30, RED));
+    let b = composer().add_window(solid(40, 30, BLUE));
+    place(a, 8, 8);
+    place(b, 28, 20);
+
+    assert_eq!(pixel(20, 15), RED & 0x00FF_FFFF);
+    assert_eq!(pixel(40, 30), BLUE & 0x00FF_FFFF);
+    assert_golden("z_order");
+}
+
+#[test]
+fn focus_raises_window() {
+    let _serial = setup();
+    let a = composer().add_window(solid(40, 30, RED));
+    let b = composer().add_window(solid(40, 30, BLUE));
+    place(a, 8, 8);
+    place(b, 28, 20);
+    composer().set_focus(a);
+
+    assert_eq!(pixel(40, 30), RED & 0x00FF_FFFF);
+    assert_golden("focus_raise");
+}
+
+#[test]
+fn windows_are_clipped_to_the_screen() {
+    let _serial = setup();
+    let gradient = |x: usize, y: usize| 0xFF00_0000 | ((x as u32 * 6) << 16) | ((y as u32 * 8) << 8) | 0x80;
+    let a = composer().add_window(window(Items::Window, 40, 30, gradient));
+    let b = composer().add_window(window(Items::Window, 40, 30, gradient));
+    place(a, -13, -7);
+    place(b, 70, 45);
+
+    assert_eq!(pixel(0, 0), gradient(13, 7) & 0x00FF_FFFF);
+    assert_golden("screen_clip");
+}
+
+#[test]
+fn transparent_popup_blends_over_windows() {
+    let _serial = setup();
+    let below = composer().add_window(solid(80, 48, GREEN));
+    place(below, 8, 8);
+
+    // Alpha ramps from clear to opaque across the popup, so the SIMD blend and the
+    // scalar edges both see partial alpha.
+    let mut popup = window(Items::Popup, 51, 20, |x, _| ((x as u32 * 255 / 50) << 24) | 0x00FF_FFFF);
+    popup.treat_as_transparent = true;
+    popup.x = 20;
+    popup.y = 20;
+    composer().add_window(popup);
+
+    assert_eq!(pixel(20, 25), GREEN & 0x00FF_FFFF);
+    assert_eq!(pixel(70, 25), 0x00FF_FFFF);
+    assert_golden("transparency");
+}
+
+#[test]
+fn dirty_rect_only_redraws_inside_clip() {
+    let _serial = setup();
+    let id = composer().add_window(solid(60, 40, RED));
+    place(id, 10, 10);
+
+    let w = *composer().find_window_id(id).unwrap();
+    let pixels = unsafe { std::slice::from_raw_parts_mut(w.buffer as *mut u32, w.width * w.height) };
+    pixels.fill(YELLOW);
+    composer().update_window_area_rect(25, 20, 20, 12);
+
+    assert_eq!(pixel(30, 25), YELLOW & 0x00FF_FFFF);
+    assert_eq!(pixel(15, 15), RED & 0x00FF_FFFF);
+    assert_golden("dirty_rect");
+}
+
+#[test]
+fn removing_window_uncovers_the_one_below() {
+    let _serial = setup();
+    let a = composer().add_window(solid(40, 30, RED));
+    let b = composer().add_window(solid(40, 30, BLUE));
+    place(a, 8, 8);
+    place(b, 28, 20);
+    composer().remove_window(b);
+
+    assert_eq!(pixel(40, 30), RED & 0x00FF_FFFF);
+    assert_eq!(pixel(60, 45), 0);
+    assert_golden("remove");
+}
+
+#[test]
+fn decorated_window_gets_title_bar() {
+    let _serial = setup();
+    let mut w = solid(80, 30, BLUE);
+    w.decorated = true;
+    let mut title = [0u8; TITLE_LEN];
+    title[..2].copy_from_slice(b"Hi");
+    w.title = title;
+
+    let id = composer().add_window(w);
+    composer().set_focus(id);
+    place(id, 8, 28);
+
+    assert_golden("title_bar");
+}
+
+#[test]
+fn mode_change_pulls_windows_back_on_screen() {
+    let _serial = setup();
+    let id = composer().add_window(solid(40, 30, RED));
+    place(id, 50, 30);
+
+    display::change_mode(64, 48, ScanoutMode::Primary).unwrap();
+
+    let w = *composer().find_window_id(id).unwrap();
+    assert_eq!((w.x, w.y), (24, 18));
+
+    let ds = unsafe { &*(&raw const DISPLAY_SERVER) };
+    assert_eq!((ds.width, ds.height, ds.pitch), (64, 48, 64 * 4));
+    let db = unsafe { std::slice::from_raw_parts(ds.double_buffer as *const u32, 64 * 48) };
+    assert_eq!(db[30 * 64 + 40], RED);
+    assert_eq!(db[5 * 64 + 5] & 0x00FF_FFFF, 0);
+}
+
+#[test]
+fn workspaces_hide_other_windows_and_remember_focus() {
+    let _serial = setup();
+    let a = composer().add_window(solid(40, 30, RED));
+    place(a, 8, 8);
+    composer().set_focus(a);
+
+    composer().switch_workspace(1);
+    assert_eq!(pixel(20, 15), 0);
+    assert_eq!(unsafe { CLICKED_WINDOW_ID }, 0);
+
+    let b = composer().add_window(solid(40, 30, BLUE));
+    place(b, 28, 20);
+    composer().set_focus(b);
+    assert_eq!(pixel(40, 30), BLUE & 0x00FF_FFFF);
+
+    composer().switch_workspace(0);
+    assert_eq!(unsafe { CLICKED_WINDOW_ID }, a);
+    assert_eq!(pixel(20, 15), RED & 0x00FF_FFFF);
+    assert_eq!(pixel(60, 45), 0);
+
+    assert!(composer().move_to_workspace(a, 1));
+    assert_eq!(unsafe { CLICKED_WINDOW_ID }, 0);
+    assert_eq!(pixel(20, 15), 0);
+
+    composer().switch_workspace(1);
+    assert_eq!(unsafe { CLICKED_WINDOW_ID }, a);
+    assert_eq!(pixel(40, 30), RED & 0x00FF_FFFF);
+}
@@ -0,0 +1,175 @@
+use crate::debugln;
+use crate::drivers::audio::ac97::{AC97, CHANNELS, SAMPLE_RATE};
+use crate::sync::Mutex;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+const MAX_STREAMS: usize = 16;
+const BUFFER_MS: u32 = 250;
+
+pub struct Stream {
+    rate: u32,
+    channels: usize,
+    volume: u32,
+    samples: VecDeque<i16>,
+    capacity: usize,
+    phase: u32,
+    pending_byte: Option<u8>,
+}
+
+pub struct Mixer {
+    streams: Vec<Option<Stream>>,
+    pub master_volume: u32,
+    scratch: Vec<i32>,
+}
+
+pub static MIXER: Mutex<Mixer> = Mutex::new(Mixer::new());
+
+pub fn init() {
+    if crate::drivers::audio::ac97::init() {
+        debugln!("Audio: Mixer ready ({} Hz, {} channels).", SAMPLE_RATE, CHANNELS);
+    } else {
+        debugln!("Audio: No output device, streams will be discarded.");
+    }
+}
+
+pub fn poll() {
+    let mut mixer = MIXER.int_lock();
+    unsafe {
+        if let Some(dev) = (*(&raw mut AC97)).as_mut() {
+            dev.refill(|period| mixer.mix(period));
+        }
+    }
+}
+
+pub fn available() -> bool {
+    unsafe { (*(&raw const AC97)).is_some() }
+}
+
+impl Stream {
+    fn frames(&self) -> usize {
+        self.samples.len() / self.channels
+    }
+
+    fn frame(&self, index: usize) -> (i32, i32) {
+        let base = index * self.channels;
+        let left = self.samples[base] as i32;
+        let right = if self.channels == 2 { self.samples[base + 1] as i32 } else { left };
+        (left, right)
+    }
+}
+
+impl Mixer {
+    pub const fn new() -> Self {
+        Mixer { streams: Vec::new(), master_volume: 256, scratch: Vec::new() }
+    }
+
+    pub fn open(&mut self, rate: u32, channels: usize) -> Option<usize> {
+        if !(8000..=SAMPLE_RATE).contains(&rate) || !(1..=2).contains(&channels) {
+            return None;
+        }
+
+        let capacity = (rate * BUFFER_MS / 1000) as usize * channels;
+        let stream = Stream {
+            rate,
+            channels,
+            volume: 256,
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+            phase: 0,
+            pending_byte: None,
+        };
+
+        if let Some(i) = self.streams.iter().position(|s| s.is_none()) {
+            self.streams[i] = Some(stream);
+            return Some(i);
+        }
+        if self.streams.len() < MAX_STREAMS {
+            self.streams.push(Some(stream));
+            return Some(self.streams.len() - 1);
+        }
+        None
+    }
+
+    pub fn close(&mut self, id: usize) {
+        if let Some(slot) = self.streams.get_mut(id) {
+            *slot = None;
+        }
+    }
+
+    pub fn write(&mut self, id: usize, data: &[u8]) -> Option<usize> {
+        let stream = self.streams.get_mut(id)?.as_mut()?;
+        let mut accepted = 0;
+
+        for &byte in data {
+            match stream.pending_byte.take() {
+                Some(low) => stream.samples.push_back(i16::from_le_bytes([low, byte])),
+                None => {
+                    if stream.samples.len() >= stream.capacity {
+                        break;
+                    }
+                    stream.pending_byte = Some(byte);
+                }
+            }
+            accepted += 1;
+        }
+
+        if !available() {
+            stream.samples.clear();
+        }
+        Some(accepted)
+    }
+
+    pub fn has_space(&self, id: usize) -> bool {
+        match self.streams.get(id) {
+            Some(Some(stream)) => stream.samples.len() < stream.capacity,
+            _ => false,
+        }
+    }
+
+    pub fn set_volume(&mut self, id: usize, percent: u32) -> bool {
+        match self.streams.get_mut(id) {
+            Some(Some(stream)) => {
+                stream.volume = percent.min(100) * 256 / 100;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn mix(&mut self, out: &mut [i16]) {
+        let frames = out.len() / CHANNELS;
+        self.scratch.clear();
+        self.scratch.resize(out.len(), 0);
+        let acc = &mut self.scratch;
+
+        for stream in self.streams.iter_mut().flatten() {
+            let step = ((stream.rate as u64) << 16) / SAMPLE_RATE as u64;
+            let available = stream.frames();
+
+            for i in 0..frames {
+                let index = (stream.phase >> 16) as usize;
+                if index >= available {
+                    break;
+                }
+                let (left, right) = stream.frame(index);
+                acc[i * 2] += (left * stream.volume as i32) >> 8;
+                acc[i * 2 + 1] += (right * stream.volume as i32) >> 8;
+                stream.phase += step as u32;
+            }
+
+            let consumed = ((stream.phase >> 16) as usize).min(available);
+            stream.samples.drain(..consumed * stream.channels);
+            stream.phase = if consumed == available { 0 } else { stream.phase - ((consumed as u32) << 16) };
+        }
+
+        for (sample, mixed) in out.iter_mut().zip(acc.iter()) {
+            *sample = ((*mixed * self.master_volume as i32) >> 8).clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+        }
+    }
+}
+
+pub fn release(id: usize) {
+    MIXER.int_lock().close(id);
+}
+
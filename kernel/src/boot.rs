@@ -3,7 +3,7 @@ pub static mut BOOT_INFO: BootInfo = unsafe { core::mem::zeroed() };
 #[derive(Debug, Clone, Copy)]
 pub struct BootInfo {
     pub mmap: MemoryMap,
-    rsdp: Rsdp,
+    pub rsdp: Rsdp,
     pub tss: u16,
     vbe: VbeInfoBlock,
     pub mode: VbeModeInfoBlock,
@@ -29,11 +29,11 @@ pub struct MemoryMap {
 #[repr(C, packed)]
 #[derive(Debug, Clone, Copy)]
 pub struct Rsdp {
-    signature: [u8; 8],
+    pub signature: [u8; 8],
     checksum: u8,
     oem_id: [u8; 6],
     revision: u8,
-    rsdt_address: u32,
+    pub rsdt_address: u32,
 }
 
 #[repr(C, packed)]
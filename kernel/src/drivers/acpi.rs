@@ -0,0 +1,37 @@
+use crate::boot::BOOT_INFO;
+use crate::memory::vmm;
+
+const SDT_HEADER_SIZE: usize = 36;
+
+fn map_phys(phys: u64, size: usize) -> u64 {
+    let offset = phys & 0xFFF;
+    vmm::map_mmio(phys - offset, size + offset as usize) + offset
+}
+
+pub fn find_table(signature: &[u8; 4]) -> Option<*const u8> {
+    let rsdp = unsafe { (*(&raw const BOOT_INFO)).rsdp };
+    if rsdp.signature != *b"RSD PTR " || rsdp.rsdt_address == 0 {
+        return None;
+    }
+
+    unsafe {
+        let rsdt = map_phys(rsdp.rsdt_address as u64, 4096) as *const u8;
+        if core::slice::from_raw_parts(rsdt, 4) != b"RSDT" {
+            return None;
+        }
+
+        let length = (rsdt.add(4) as *const u32).read_unaligned() as usize;
+        let entries = length.saturating_sub(SDT_HEADER_SIZE) / 4;
+        let rsdt = map_phys(rsdp.rsdt_address as u64, length) as *const u8;
+
+        for i in 0..entries {
+            let table_phys = (rsdt.add(SDT_HEADER_SIZE + i * 4) as *const u32).read_unaligned() as u64;
+            let table = map_phys(table_phys, SDT_HEADER_SIZE) as *const u8;
+            if core::slice::from_raw_parts(table, 4) == signature {
+                let length = (table.add(4) as *const u32).read_unaligned() as usize;
+                return Some(map_phys(table_phys, length) as *const u8);
+            }
+        }
+    }
+    None
+}
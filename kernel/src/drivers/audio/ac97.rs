@@ -0,0 +1,161 @@
+use crate::debugln;
+use crate::drivers::port::{inb, inw, outb, outl, outw};
+use crate::memory::paging::HHDM_OFFSET;
+use crate::memory::pmm;
+
+const NAM_RESET: u16 = 0x00;
+const NAM_MASTER_VOLUME: u16 = 0x02;
+const NAM_PCM_OUT_VOLUME: u16 = 0x18;
+const NAM_EXT_AUDIO_ID: u16 = 0x28;
+const NAM_EXT_AUDIO_CTRL: u16 = 0x2A;
+const NAM_FRONT_DAC_RATE: u16 = 0x2C;
+
+const NABM_PO_BDBAR: u16 = 0x10;
+const NABM_PO_CIV: u16 = 0x14;
+const NABM_PO_LVI: u16 = 0x15;
+const NABM_PO_SR: u16 = 0x16;
+const NABM_PO_CR: u16 = 0x1B;
+const NABM_GLOB_CNT: u16 = 0x2C;
+
+const GLOB_CNT_COLD_RESET: u32 = 1 << 1;
+const CR_RUN: u8 = 1 << 0;
+const CR_RESET: u8 = 1 << 1;
+const SR_DCH: u16 = 1 << 0;
+const SR_CLEAR: u16 = 0x1C;
+const EXT_VRA: u16 = 1 << 0;
+
+pub const SAMPLE_RATE: u32 = 48000;
+pub const CHANNELS: usize = 2;
+pub const PERIOD_FRAMES: usize = 480;
+
+const BDL_ENTRIES: usize = 32;
+const PERIOD_SAMPLES: usize = PERIOD_FRAMES * CHANNELS;
+const PERIOD_BYTES: usize = PERIOD_SAMPLES * 2;
+const PERIODS_AHEAD: usize = 4;
+
+#[repr(C)]
+struct BdlEntry {
+    addr: u32,
+    samples: u16,
+    flags: u16,
+}
+
+pub struct Ac97 {
+    nabm: u16,
+    buffers_phys: u64,
+    next_fill: usize,
+}
+
+pub static mut AC97: Option<Ac97> = None;
+
+pub fn init() -> bool {
+    let device = match crate::drivers::pci::find_device(0x8086, 0x2415) {
+        Some(d) => d,
+        None => {
+            debugln!("AC97: Device not found.");
+            return false;
+        }
+    };
+    debugln!("AC97: Found device at Bus {}, Device {}, Func {}", device.bus, device.device, device.function);
+
+    if !device.enable_bus_mastering() {
+        debugln!("AC97: Failed to enable bus mastering.");
+    }
+
+    let (nam, nabm) = match (device.get_bar(0), device.get_bar(1)) {
+        (Some(nam), Some(nabm)) => (nam as u16, nabm as u16),
+        _ => {
+            debugln!("AC97: Missing I/O BARs.");
+            return false;
+        }
+    };
+
+    unsafe {
+        let bdl_phys = match pmm::allocate_frame(0) {
+            Some(p) => p,
+            None => return false,
+        };
+        let buffer_frames = (BDL_ENTRIES * PERIOD_BYTES).div_ceil(4096);
+        let buffers_phys = match pmm::allocate_frames(buffer_frames, 0) {
+            Some(p) => p,
+            None => return false,
+        };
+        if buffers_phys + (buffer_frames * 4096) as u64 > u32::MAX as u64 || bdl_phys > u32::MAX as u64 {
+            debugln!("AC97: DMA buffers above 4 GiB, giving up.");
+            return false;
+        }
+        core::ptr::write_bytes((buffers_phys + HHDM_OFFSET) as *mut u8, 0, buffer_frames * 4096);
+
+        let bdl = (bdl_phys + HHDM_OFFSET) as *mut BdlEntry;
+        for i in 0..BDL_ENTRIES {
+            bdl.add(i).write_volatile(BdlEntry {
+                addr: (buffers_phys + (i * PERIOD_BYTES) as u64) as u32,
+                samples: PERIOD_SAMPLES as u16,
+                flags: 0,
+            });
+        }
+
+        outl(nabm + NABM_GLOB_CNT, GLOB_CNT_COLD_RESET);
+        outw(nam + NAM_RESET, 0);
+        for _ in 0..10000 { core::hint::spin_loop(); }
+
+        outw(nam + NAM_MASTER_VOLUME, 0x0000);
+        outw(nam + NAM_PCM_OUT_VOLUME, 0x0808);
+
+        if inw(nam + NAM_EXT_AUDIO_ID) & EXT_VRA != 0 {
+            outw(nam + NAM_EXT_AUDIO_CTRL, inw(nam + NAM_EXT_AUDIO_CTRL) | EXT_VRA);
+            outw(nam + NAM_FRONT_DAC_RATE, SAMPLE_RATE as u16);
+        }
+
+        outb(nabm + NABM_PO_CR, CR_RESET);
+        let mut timeout = 100000;
+        while inb(nabm + NABM_PO_CR) & CR_RESET != 0 && timeout > 0 {
+            timeout -= 1;
+            core::hint::spin_loop();
+        }
+
+        outl(nabm + NABM_PO_BDBAR, bdl_phys as u32);
+        outb(nabm + NABM_PO_LVI, (PERIODS_AHEAD - 1) as u8);
+        outb(nabm + NABM_PO_CR, CR_RUN);
+
+        *(&raw mut AC97) = Some(Ac97 { nabm, buffers_phys, next_fill: PERIODS_AHEAD });
+    }
+
+    debugln!("AC97: Output running at {} Hz.", SAMPLE_RATE);
+    true
+}
+
+impl Ac97 {
+    pub fn refill(&mut self, mut mix: impl FnMut(&mut [i16])) {
+        let civ = inb(self.nabm + NABM_PO_CIV) as usize % BDL_ENTRIES;
+        let halted = inw(self.nabm + NABM_PO_SR) & SR_DCH != 0;
+
+        let mut queued = (self.next_fill + BDL_ENTRIES - civ - 1) % BDL_ENTRIES;
+        if halted {
+            self.next_fill = (civ + 1) % BDL_ENTRIES;
+            queued = 0;
+        }
+
+        let mut last = None;
+        while queued < PERIODS_AHEAD {
+            let period = unsafe {
+                core::slice::from_raw_parts_mut(
+                    (self.buffers_phys + HHDM_OFFSET + (self.next_fill * PERIOD_BYTES) as u64) as *mut i16,
+                    PERIOD_SAMPLES,
+                )
+            };
+            mix(period);
+            last = Some(self.next_fill);
+            self.next_fill = (self.next_fill + 1) % BDL_ENTRIES;
+            queued += 1;
+        }
+
+        if let Some(lvi) = last {
+            outb(self.nabm + NABM_PO_LVI, lvi as u8);
+            if halted {
+                outw(self.nabm + NABM_PO_SR, SR_CLEAR);
+                outb(self.nabm + NABM_PO_CR, CR_RUN);
+            }
+        }
+    }
+}
@@ -0,0 +1 @@
+pub mod ac97;
\ No newline at end of file
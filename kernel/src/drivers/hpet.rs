@@ -0,0 +1,57 @@
+use crate::debugln;
+use crate::memory::mmio::{read_64, write_64};
+use crate::memory::vmm;
+
+const REG_CAPABILITIES: usize = 0x000;
+const REG_CONFIG: usize = 0x010;
+const REG_COUNTER: usize = 0x0F0;
+
+const CAP_COUNTER_64: u64 = 1 << 13;
+const CONFIG_ENABLE: u64 = 1 << 0;
+
+pub struct Hpet {
+    base: *mut u8,
+    pub period_fs: u64,
+    pub wide: bool,
+}
+
+pub static mut HPET: Option<Hpet> = None;
+
+pub fn init() -> bool {
+    let table = match crate::drivers::acpi::find_table(b"HPET") {
+        Some(t) => t,
+        None => {
+            debugln!("HPET: Not present.");
+            return false;
+        }
+    };
+
+    unsafe {
+        let phys = (table.add(44) as *const u64).read_unaligned();
+        let base = vmm::map_mmio(phys & !0xFFF, 4096) as *mut u8;
+
+        let caps = read_64(base.add(REG_CAPABILITIES));
+        let period_fs = caps >> 32;
+        if period_fs == 0 || period_fs > 100_000_000 {
+            debugln!("HPET: Invalid period {} fs.", period_fs);
+            return false;
+        }
+
+        write_64(base.add(REG_CONFIG), read_64(base.add(REG_CONFIG)) | CONFIG_ENABLE);
+
+        let hpet = Hpet { base, period_fs, wide: caps & CAP_COUNTER_64 != 0 };
+        debugln!("HPET: {} MHz counter at {:#x}{}", 1_000_000_000 / period_fs, phys, if hpet.wide { "" } else { " (32-bit)" });
+        *(&raw mut HPET) = Some(hpet);
+    }
+    true
+}
+
+impl Hpet {
+    pub fn counter(&self) -> u64 {
+        unsafe { read_64(self.base.add(REG_COUNTER)) }
+    }
+
+    pub fn ticks_to_ns(&self, ticks: u64) -> u64 {
+        (ticks as u128 * self.period_fs as u128 / 1_000_000) as u64
+    }
+}
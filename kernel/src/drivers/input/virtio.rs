@@ -0,0 +1,443 @@
+use crate::debugln;
+use crate::drivers::pci::PciDevice;
+use crate::drivers::video::virtio::consts::*;
+use crate::drivers::video::virtio::structs::{VirtqAvail, VirtqDesc, VirtqUsed};
+use crate::memory::mmio::{read_16, read_32, read_8, write_16, write_32, write_64, write_8};
+use crate::memory::paging::HHDM_OFFSET;
+use crate::memory::{pmm, vmm};
+use crate::window_manager::display::DISPLAY_SERVER;
+use crate::window_manager::input::MOUSE;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ptr::{read_volatile, write_volatile};
+
+const QUEUE_SIZE: u16 = 64;
+const EVENT_SIZE: usize = 8;
+const EVENT_QUEUE: u16 = 0;
+
+const CFG_SELECT: usize = 0;
+const CFG_SUBSEL: usize = 1;
+const CFG_SIZE: usize = 2;
+const CFG_DATA: usize = 8;
+
+const CFG_ID_NAME: u8 = 0x01;
+const CFG_ABS_INFO: u8 = 0x12;
+
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const EV_REL: u16 = 0x02;
+const EV_ABS: u16 = 0x03;
+
+const REL_X: u16 = 0x00;
+const REL_Y: u16 = 0x01;
+const REL_WHEEL: u16 = 0x08;
+
+const ABS_X: u16 = 0x00;
+const ABS_Y: u16 = 0x01;
+
+const BTN_LEFT: u16 = 0x110;
+const BTN_RIGHT: u16 = 0x111;
+const BTN_MIDDLE: u16 = 0x112;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct InputEvent {
+    ty: u16,
+    code: u16,
+    value: u32,
+}
+
+struct EventQueue {
+    desc_phys: u64,
+    avail_phys: u64,
+    used_phys: u64,
+    notify_addr: u64,
+    last_used_idx: u16,
+    buffers_phys: u64,
+}
+
+struct InputDevice {
+    queue: EventQueue,
+    abs_max: [u32; 2],
+    abs: [u32; 2],
+    abs_moved: bool,
+    rel: [i32; 2],
+    wheel: i32,
+    buttons: u8,
+    pointer_dirty: bool,
+}
+
+pub struct VirtioInput {
+    devices: Vec<InputDevice>,
+}
+
+pub static mut VIRTIO_INPUT: Option<VirtioInput> = None;
+
+pub fn init() -> bool {
+    let found = crate::drivers::pci::find_devices(0x1AF4, 0x1052);
+    if found.is_empty() {
+        debugln!("VirtIO Input: No devices found.");
+        return false;
+    }
+
+    let mut next_bar_addr = 0xF3000000;
+    let mut devices = Vec::new();
+
+    for pci in found {
+        debugln!("VirtIO Input: Found device at Bus {}, Device {}, Func {}", pci.bus, pci.device, pci.function);
+        if let Some(dev) = unsafe { init_device(&pci, &mut next_bar_addr) } {
+            devices.push(dev);
+        }
+    }
+
+    if devices.is_empty() {
+        return false;
+    }
+
+    unsafe {
+        *(&raw mut VIRTIO_INPUT) = Some(VirtioInput { devices });
+    }
+    true
+}
+
+pub fn poll() {
+    unsafe {
+        if let Some(input) = (*(&raw mut VIRTIO_INPUT)).as_mut() {
+            for dev in input.devices.iter_mut() {
+                dev.drain();
+            }
+        }
+    }
+}
+
+unsafe fn init_device(virtio: &PciDevice, next_bar_addr: &mut u32) -> Option<InputDevice> {
+    if !virtio.enable_bus_mastering() {
+        debugln!("VirtIO Input: Failed to enable bus mastering.");
+    }
+
+    let mut common_cfg_ptr: *mut u8 = core::ptr::null_mut();
+    let mut device_cfg_ptr: *mut u8 = core::ptr::null_mut();
+    let mut notify_base: u64 = 0;
+    let mut notify_multiplier: u32 = 0;
+
+    for cap in virtio.list_capabilities() {
+        if cap.id != 0x09 { continue; }
+
+        let cfg_type = virtio.read_u8(cap.offset as u32 + 3);
+        let bar = virtio.read_u8(cap.offset as u32 + 4);
+        let offset = virtio.read_u32(cap.offset as u32 + 8);
+
+        let mut bar_base_opt = virtio.get_bar(bar);
+        if bar_base_opt.is_none() || bar_base_opt.unwrap() < 0x100000 {
+            let raw_bar = virtio.read_bar_raw(bar);
+            if (raw_bar & 0xFFFFFFF0) < 0x100000 {
+                debugln!("VirtIO Input: BAR {} is unmapped ({:#x}). Remapping to {:#x}", bar, raw_bar, *next_bar_addr);
+                virtio.write_bar(bar, *next_bar_addr);
+                *next_bar_addr += 0x100000;
+                bar_base_opt = virtio.get_bar(bar);
+            }
+        }
+
+        let bar_base = match bar_base_opt {
+            Some(b) => b as u64,
+            None => continue,
+        };
+        let addr = bar_base + offset as u64;
+
+        match cfg_type {
+            VIRTIO_CAP_COMMON => common_cfg_ptr = vmm::map_mmio(addr, 4096) as *mut u8,
+            VIRTIO_CAP_NOTIFY => {
+                notify_base = vmm::map_mmio(addr, 4096);
+                notify_multiplier = virtio.read_capability_data(cap.offset, 16);
+            }
+            VIRTIO_CAP_DEVICE => device_cfg_ptr = vmm::map_mmio(addr, 4096) as *mut u8,
+            _ => {}
+        }
+    }
+
+    if common_cfg_ptr.is_null() || device_cfg_ptr.is_null() || notify_base == 0 {
+        debugln!("VirtIO Input: Missing Common/Notify/Device capability.");
+        return None;
+    }
+
+    unsafe {
+        write_8(common_cfg_ptr.add(OFF_DEVICE_STATUS), 0);
+        let mut status = STATUS_ACKNOWLEDGE;
+        write_8(common_cfg_ptr.add(OFF_DEVICE_STATUS), status);
+        status |= STATUS_DRIVER;
+        write_8(common_cfg_ptr.add(OFF_DEVICE_STATUS), status);
+
+        write_32(common_cfg_ptr.add(OFF_DEVICE_FEATURE_SELECT), 1);
+        if (read_32(common_cfg_ptr.add(OFF_DEVICE_FEATURE)) & 1) == 0 {
+            debugln!("VirtIO Input: Device does not offer VIRTIO_F_VERSION_1.");
+            write_8(common_cfg_ptr.add(OFF_DEVICE_STATUS), STATUS_FAILED);
+            return None;
+        }
+
+        write_32(common_cfg_ptr.add(OFF_DRIVER_FEATURE_SELECT), 0);
+        write_32(common_cfg_ptr.add(OFF_DRIVER_FEATURE), 0);
+        write_32(common_cfg_ptr.add(OFF_DRIVER_FEATURE_SELECT), 1);
+        write_32(common_cfg_ptr.add(OFF_DRIVER_FEATURE), 1);
+
+        status |= STATUS_FEATURES_OK;
+        write_8(common_cfg_ptr.add(OFF_DEVICE_STATUS), status);
+        if (read_8(common_cfg_ptr.add(OFF_DEVICE_STATUS)) & STATUS_FEATURES_OK) == 0 {
+            debugln!("VirtIO Input: Feature negotiation failed.");
+            return None;
+        }
+
+        let queue = setup_queue(common_cfg_ptr, notify_base, notify_multiplier)?;
+
+        let name = read_name(device_cfg_ptr);
+        let abs_max = [read_abs_max(device_cfg_ptr, ABS_X as u8), read_abs_max(device_cfg_ptr, ABS_Y as u8)];
+
+        let mut dev = InputDevice {
+            queue,
+            abs_max,
+            abs: [0; 2],
+            abs_moved: false,
+            rel: [0; 2],
+            wheel: 0,
+            buttons: 0,
+            pointer_dirty: false,
+        };
+        dev.fill();
+
+        status |= STATUS_DRIVER_OK;
+        write_8(common_cfg_ptr.add(OFF_DEVICE_STATUS), status);
+
+        if abs_max[0] != 0 && abs_max[1] != 0 {
+            debugln!("VirtIO Input: '{}' ready, absolute axes {}x{}", name, abs_max[0], abs_max[1]);
+        } else {
+            debugln!("VirtIO Input: '{}' ready", name);
+        }
+        Some(dev)
+    }
+}
+
+unsafe fn select_config(cfg: *mut u8, select: u8, subsel: u8) -> usize {
+    unsafe {
+        write_8(cfg.add(CFG_SELECT), select);
+        write_8(cfg.add(CFG_SUBSEL), subsel);
+        read_8(cfg.add(CFG_SIZE)) as usize
+    }
+}
+
+unsafe fn read_name(cfg: *mut u8) -> String {
+    unsafe {
+        let size = select_config(cfg, CFG_ID_NAME, 0).min(128);
+        let bytes: Vec<u8> = (0..size).map(|i| read_8(cfg.add(CFG_DATA + i))).collect();
+        String::from_utf8_lossy(&bytes).into()
+    }
+}
+
+unsafe fn read_abs_max(cfg: *mut u8, axis: u8) -> u32 {
+    unsafe {
+        if select_config(cfg, CFG_ABS_INFO, axis) < 8 {
+            return 0;
+        }
+        read_32(cfg.add(CFG_DATA + 4))
+    }
+}
+
+unsafe fn setup_queue(common_cfg: *mut u8, notify_base: u64, notify_multiplier: u32) -> Option<EventQueue> {
+    unsafe {
+        write_16(common_cfg.add(OFF_QUEUE_SELECT), EVENT_QUEUE);
+
+        let max_size = read_16(common_cfg.add(OFF_QUEUE_SIZE));
+        if max_size < QUEUE_SIZE {
+            debugln!("VirtIO Input: Event queue too small ({}).", max_size);
+            return None;
+        }
+        write_16(common_cfg.add(OFF_QUEUE_SIZE), QUEUE_SIZE);
+
+        let frame = pmm::allocate_frame(0)?;
+        core::ptr::write_bytes((frame + HHDM_OFFSET) as *mut u8, 0, 4096);
+        let buffers_phys = pmm::allocate_frame(0)?;
+        core::ptr::write_bytes((buffers_phys + HHDM_OFFSET) as *mut u8, 0, 4096);
+
+        let desc_addr = frame;
+        let avail_addr = desc_addr + 1024;
+        let used_addr = desc_addr + 2048;
+
+        write_64(common_cfg.add(OFF_QUEUE_DESC), desc_addr);
+        write_64(common_cfg.add(OFF_QUEUE_DRIVER), avail_addr);
+        write_64(common_cfg.add(OFF_QUEUE_DEVICE), used_addr);
+
+        let notify_off = read_16(common_cfg.add(OFF_QUEUE_NOTIFY_OFF));
+        let notify_addr = notify_base + (notify_off as u64 * notify_multiplier as u64);
+
+        write_16(common_cfg.add(OFF_QUEUE_ENABLE), 1);
+
+        Some(EventQueue {
+            desc_phys: desc_addr,
+            avail_phys: avail_addr,
+            used_phys: used_addr,
+            notify_addr,
+            last_used_idx: 0,
+            buffers_phys,
+        })
+    }
+}
+
+impl InputDevice {
+    fn fill(&mut self) {
+        unsafe {
+            let desc = (self.queue.desc_phys + HHDM_OFFSET) as *mut VirtqDesc;
+            let avail = (self.queue.avail_phys + HHDM_OFFSET) as *mut VirtqAvail;
+
+            for i in 0..QUEUE_SIZE as usize {
+                *desc.add(i) = VirtqDesc {
+                    addr: self.queue.buffers_phys + (i * EVENT_SIZE) as u64,
+                    len: EVENT_SIZE as u32,
+                    flags: 2,
+                    next: 0,
+                };
+                (*avail).ring[i] = i as u16;
+            }
+
+            core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst);
+            (*avail).idx = QUEUE_SIZE;
+            write_volatile(self.queue.notify_addr as *mut u16, EVENT_QUEUE);
+        }
+    }
+
+    fn drain(&mut self) {
+        let mut reposted = false;
+        unsafe {
+            let used = (self.queue.used_phys + HHDM_OFFSET) as *const VirtqUsed;
+            let avail = (self.queue.avail_phys + HHDM_OFFSET) as *mut VirtqAvail;
+
+            loop {
+                let used_idx = read_volatile(core::ptr::addr_of!((*used).idx));
+                if used_idx == self.queue.last_used_idx { break; }
+
+                let slot = (self.queue.last_used_idx % QUEUE_SIZE) as usize;
+                let id = read_volatile(core::ptr::addr_of!((*used).ring[slot])).id as usize;
+                self.queue.last_used_idx = self.queue.last_used_idx.wrapping_add(1);
+
+                if id >= QUEUE_SIZE as usize { continue; }
+
+                let buf = (self.queue.buffers_phys + HHDM_OFFSET + (id * EVENT_SIZE) as u64) as *const InputEvent;
+                let event = read_volatile(buf);
+
+                let idx = (*avail).idx;
+                (*avail).ring[(idx % QUEUE_SIZE) as usize] = id as u16;
+                core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst);
+                (*avail).idx = idx.wrapping_add(1);
+                reposted = true;
+
+                self.handle(event);
+            }
+
+            if reposted {
+                write_volatile(self.queue.notify_addr as *mut u16, EVENT_QUEUE);
+            }
+        }
+    }
+
+    fn handle(&mut self, event: InputEvent) {
+        match event.ty {
+            EV_SYN => self.flush_pointer(),
+            EV_REL => {
+                let value = event.value as i32;
+                match event.code {
+                    REL_X => self.rel[0] += value,
+                    REL_Y => self.rel[1] += value,
+                    REL_WHEEL => self.wheel += value,
+                    _ => return,
+                }
+                self.pointer_dirty = true;
+            }
+            EV_ABS => {
+                let axis = match event.code {
+                    ABS_X => 0,
+                    ABS_Y => 1,
+                    _ => return,
+                };
+                self.abs[axis] = event.value;
+                self.abs_moved = true;
+                self.pointer_dirty = true;
+            }
+            EV_KEY => {
+                let bit = match event.code {
+                    BTN_LEFT => 0b001,
+                    BTN_RIGHT => 0b010,
+                    BTN_MIDDLE => 0b100,
+                    code => {
+                        feed_key(code, event.value != 0);
+                        return;
+                    }
+                };
+                if event.value != 0 {
+                    self.buttons |= bit;
+                } else {
+                    self.buttons &= !bit;
+                }
+                self.pointer_dirty = true;
+            }
+            _ => {}
+        }
+    }
+
+    fn flush_pointer(&mut self) {
+        if !self.pointer_dirty { return; }
+        self.pointer_dirty = false;
+
+        let wheel = self.wheel.clamp(i8::MIN as i32, i8::MAX as i32) as i8;
+        self.wheel = 0;
+
+        let mouse = unsafe { &mut *(&raw mut MOUSE) };
+
+        if self.abs_moved && self.abs_max[0] != 0 && self.abs_max[1] != 0 {
+            self.abs_moved = false;
+            let (width, height) = unsafe {
+                let display = &*(&raw const DISPLAY_SERVER);
+                (display.width as u64, display.height as u64)
+            };
+            let x = self.abs[0].min(self.abs_max[0]) as u64 * width.saturating_sub(1) / self.abs_max[0] as u64;
+            let y = self.abs[1].min(self.abs_max[1]) as u64 * height.saturating_sub(1) / self.abs_max[1] as u64;
+            mouse.move_to(x as u16, y as u16, self.buttons, wheel);
+            return;
+        }
+
+        let dx = self.rel[0].clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+        let dy = self.rel[1].clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+        self.rel = [0; 2];
+        mouse.update(dx, -dy, self.buttons, wheel);
+    }
+}
+
+fn feed_key(code: u16, pressed: bool) {
+    use crate::drivers::periferics::keyboard::handle_scancode;
+
+    let (extended, scancode) = match code {
+        1..=88 => (false, code as u8),
+        96 => (true, 0x1C),
+        97 => (true, 0x1D),
+        98 => (true, 0x35),
+        99 => (true, 0x37),
+        100 => (true, 0x38),
+        102 => (true, 0x47),
+        103 => (true, 0x48),
+        104 => (true, 0x49),
+        105 => (true, 0x4B),
+        106 => (true, 0x4D),
+        107 => (true, 0x4F),
+        108 => (true, 0x50),
+        109 => (true, 0x51),
+        110 => (true, 0x52),
+        111 => (true, 0x53),
+        125 => (true, 0x5B),
+        126 => (true, 0x5C),
+        _ => return,
+    };
+
+    if extended {
+        handle_scancode(0xE0);
+    }
+    let scancode = if pressed { scancode } else { scancode | 0x80 };
+    if let Some((key, pressed)) = handle_scancode(scancode) {
+        crate::interrupts::exceptions::dispatch_key(key, pressed);
+    }
+}
@@ -0,0 +1,80 @@
+use crate::debugln;
+use crate::memory::mmio::{read_32, write_32};
+use crate::memory::vmm;
+
+const APIC_BASE_MSR: u32 = 0x1B;
+
+const REG_EOI: usize = 0x0B0;
+const REG_SVR: usize = 0x0F0;
+const REG_LVT_TIMER: usize = 0x320;
+const REG_LVT_LINT0: usize = 0x350;
+const REG_LVT_LINT1: usize = 0x360;
+const REG_TIMER_INITIAL: usize = 0x380;
+const REG_TIMER_CURRENT: usize = 0x390;
+const REG_TIMER_DIVIDE: usize = 0x3E0;
+
+const SVR_ENABLE: u32 = 1 << 8;
+const LVT_MASKED: u32 = 1 << 16;
+const DELIVERY_NMI: u32 = 0b100 << 8;
+const DELIVERY_EXTINT: u32 = 0b111 << 8;
+const DIVIDE_BY_16: u32 = 0b0011;
+
+pub const TIMER_VECTOR: u8 = 0x30;
+pub const SPURIOUS_VECTOR: u8 = 0xFF;
+
+const CALIBRATION_MS: u64 = 10;
+
+pub struct Lapic {
+    base: *mut u8,
+    ticks_per_ms: u64,
+}
+
+pub static mut LAPIC: Option<Lapic> = None;
+
+pub fn init() -> bool {
+    let has_apic = core::arch::x86_64::__cpuid(1).edx & (1 << 9) != 0;
+    if !has_apic {
+        debugln!("LAPIC: Not supported.");
+        return false;
+    }
+
+    unsafe {
+        let phys = crate::rdmsr(APIC_BASE_MSR) & 0xFFFF_F000;
+        let base = vmm::map_mmio(phys, 4096) as *mut u8;
+
+        // The PIC still delivers everything else, so keep LINT0 in virtual wire mode.
+        write_32(base.add(REG_LVT_LINT0), DELIVERY_EXTINT);
+        write_32(base.add(REG_LVT_LINT1), DELIVERY_NMI);
+        write_32(base.add(REG_SVR), SVR_ENABLE | SPURIOUS_VECTOR as u32);
+
+        write_32(base.add(REG_TIMER_DIVIDE), DIVIDE_BY_16);
+        write_32(base.add(REG_LVT_TIMER), LVT_MASKED | TIMER_VECTOR as u32);
+
+        write_32(base.add(REG_TIMER_INITIAL), u32::MAX);
+        crate::time::wait_reference(CALIBRATION_MS);
+        let elapsed = u32::MAX - read_32(base.add(REG_TIMER_CURRENT));
+        write_32(base.add(REG_TIMER_INITIAL), 0);
+
+        let ticks_per_ms = elapsed as u64 / CALIBRATION_MS;
+        if ticks_per_ms == 0 {
+            debugln!("LAPIC: Timer calibration failed.");
+            return false;
+        }
+
+        write_32(base.add(REG_LVT_TIMER), TIMER_VECTOR as u32);
+        debugln!("LAPIC: Timer at {} kHz (one-shot).", ticks_per_ms);
+        *(&raw mut LAPIC) = Some(Lapic { base, ticks_per_ms });
+    }
+    true
+}
+
+impl Lapic {
+    pub fn arm_oneshot(&self, ns: u64) {
+        let ticks = (ns as u128 * self.ticks_per_ms as u128 / 1_000_000).clamp(1, u32::MAX as u128);
+        unsafe { write_32(self.base.add(REG_TIMER_INITIAL), ticks as u32) };
+    }
+
+    pub fn eoi(&self) {
+        unsafe { write_32(self.base.add(REG_EOI), 0) };
+    }
+}
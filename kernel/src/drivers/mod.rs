@@ -2,4 +2,10 @@ pub mod video;
 pub mod periferics;
 pub mod port;
 pub mod pci;
-pub mod rtc;
\ No newline at end of file
+pub mod rtc;
+pub mod net;
+pub mod audio;
+pub mod input;
+pub mod acpi;
+pub mod hpet;
+pub mod lapic;
\ No newline at end of file
@@ -0,0 +1 @@
+pub mod virtio;
\ No newline at end of file
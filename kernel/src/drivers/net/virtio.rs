@@ -0,0 +1,343 @@
+use crate::debugln;
+use crate::drivers::video::virtio::consts::*;
+use crate::drivers::video::virtio::structs::{VirtqAvail, VirtqDesc, VirtqUsed};
+use crate::memory::mmio::{read_16, read_32, read_8, write_16, write_32, write_64, write_8};
+use crate::memory::paging::HHDM_OFFSET;
+use crate::memory::{pmm, vmm};
+use alloc::vec::Vec;
+use core::ptr::{read_volatile, write_volatile};
+
+const VIRTIO_NET_F_MAC: u32 = 1 << 5;
+
+const NET_HDR_SIZE: usize = 12;
+const BUF_SIZE: usize = 2048;
+const RX_BUFFERS: usize = 64;
+const TX_BUFFERS: usize = 32;
+const QUEUE_SIZE: u16 = 128;
+
+const RX_QUEUE: u16 = 0;
+const TX_QUEUE: u16 = 1;
+
+struct NetQueue {
+    desc_phys: u64,
+    avail_phys: u64,
+    used_phys: u64,
+    notify_addr: u64,
+    last_used_idx: u16,
+    buffers_phys: u64,
+}
+
+pub struct VirtioNet {
+    rx: NetQueue,
+    tx: NetQueue,
+    tx_next: usize,
+    tx_in_flight: usize,
+    isr_addr: u64,
+    pub mac: [u8; 6],
+    pub irq: u8,
+}
+
+pub static mut VIRTIO_NET: Option<VirtioNet> = None;
+
+pub fn init() -> bool {
+    let mut device = crate::drivers::pci::find_device(0x1AF4, 0x1041);
+    if device.is_none() {
+        device = crate::drivers::pci::find_device(0x1AF4, 0x1000);
+    }
+
+    let virtio = match device {
+        Some(d) => d,
+        None => {
+            debugln!("VirtIO Net: Device not found.");
+            return false;
+        }
+    };
+    debugln!("VirtIO Net: Found device at Bus {}, Device {}, Func {}", virtio.bus, virtio.device, virtio.function);
+
+    if !virtio.enable_bus_mastering() {
+        debugln!("VirtIO Net: Failed to enable bus mastering.");
+    }
+
+    let caps = virtio.list_capabilities();
+
+    let mut common_cfg_ptr: *mut u8 = core::ptr::null_mut();
+    let mut device_cfg_ptr: *mut u8 = core::ptr::null_mut();
+    let mut isr_addr: u64 = 0;
+    let mut notify_base: u64 = 0;
+    let mut notify_multiplier: u32 = 0;
+
+    let mut next_bar_addr = 0xF2000000;
+
+    for cap in caps {
+        if cap.id != 0x09 { continue; }
+
+        let cfg_type = virtio.read_u8(cap.offset as u32 + 3);
+        let bar = virtio.read_u8(cap.offset as u32 + 4);
+        let offset = virtio.read_u32(cap.offset as u32 + 8);
+
+        let mut bar_base_opt = virtio.get_bar(bar);
+        if bar_base_opt.is_none() || bar_base_opt.unwrap() < 0x100000 {
+            let raw_bar = virtio.read_bar_raw(bar);
+            if (raw_bar & 0xFFFFFFF0) < 0x100000 {
+                debugln!("VirtIO Net: BAR {} is unmapped ({:#x}). Remapping to {:#x}", bar, raw_bar, next_bar_addr);
+                virtio.write_bar(bar, next_bar_addr);
+                next_bar_addr += 0x100000;
+                bar_base_opt = virtio.get_bar(bar);
+            }
+        }
+
+        let bar_base = match bar_base_opt {
+            Some(b) => b as u64,
+            None => continue,
+        };
+        let addr = bar_base + offset as u64;
+
+        match cfg_type {
+            VIRTIO_CAP_COMMON => common_cfg_ptr = vmm::map_mmio(addr, 4096) as *mut u8,
+            VIRTIO_CAP_NOTIFY => {
+                notify_base = vmm::map_mmio(addr, 4096);
+                notify_multiplier = virtio.read_capability_data(cap.offset, 16);
+            }
+            VIRTIO_CAP_ISR => isr_addr = vmm::map_mmio(addr, 4096),
+            VIRTIO_CAP_DEVICE => device_cfg_ptr = vmm::map_mmio(addr, 4096) as *mut u8,
+            _ => {}
+        }
+    }
+
+    if common_cfg_ptr.is_null() || notify_base == 0 {
+        debugln!("VirtIO Net: Missing Common/Notify capability.");
+        return false;
+    }
+
+    unsafe {
+        write_8(common_cfg_ptr.add(OFF_DEVICE_STATUS), 0);
+        let mut status = STATUS_ACKNOWLEDGE;
+        write_8(common_cfg_ptr.add(OFF_DEVICE_STATUS), status);
+        status |= STATUS_DRIVER;
+        write_8(common_cfg_ptr.add(OFF_DEVICE_STATUS), status);
+
+        write_32(common_cfg_ptr.add(OFF_DEVICE_FEATURE_SELECT), 0);
+        let features_low = read_32(common_cfg_ptr.add(OFF_DEVICE_FEATURE));
+        write_32(common_cfg_ptr.add(OFF_DEVICE_FEATURE_SELECT), 1);
+        let features_high = read_32(common_cfg_ptr.add(OFF_DEVICE_FEATURE));
+
+        if (features_high & 1) == 0 {
+            debugln!("VirtIO Net: Device does not offer VIRTIO_F_VERSION_1.");
+            write_8(common_cfg_ptr.add(OFF_DEVICE_STATUS), STATUS_FAILED);
+            return false;
+        }
+
+        let driver_features_low = features_low & VIRTIO_NET_F_MAC;
+
+        write_32(common_cfg_ptr.add(OFF_DRIVER_FEATURE_SELECT), 0);
+        write_32(common_cfg_ptr.add(OFF_DRIVER_FEATURE), driver_features_low);
+        write_32(common_cfg_ptr.add(OFF_DRIVER_FEATURE_SELECT), 1);
+        write_32(common_cfg_ptr.add(OFF_DRIVER_FEATURE), 1);
+
+        status |= STATUS_FEATURES_OK;
+        write_8(common_cfg_ptr.add(OFF_DEVICE_STATUS), status);
+        if (read_8(common_cfg_ptr.add(OFF_DEVICE_STATUS)) & STATUS_FEATURES_OK) == 0 {
+            debugln!("VirtIO Net: Feature negotiation failed.");
+            return false;
+        }
+
+        let rx = match setup_queue(common_cfg_ptr, RX_QUEUE, notify_base, notify_multiplier, RX_BUFFERS) {
+            Some(q) => q,
+            None => return false,
+        };
+        let tx = match setup_queue(common_cfg_ptr, TX_QUEUE, notify_base, notify_multiplier, TX_BUFFERS) {
+            Some(q) => q,
+            None => return false,
+        };
+
+        let mut mac = [0u8; 6];
+        if (driver_features_low & VIRTIO_NET_F_MAC) != 0 && !device_cfg_ptr.is_null() {
+            for i in 0..6 {
+                mac[i] = read_8(device_cfg_ptr.add(i));
+            }
+        } else {
+            mac = [0x52, 0x54, 0x00, 0x12, 0x34, 0x56];
+        }
+
+        let mut net = VirtioNet {
+            rx,
+            tx,
+            tx_next: 0,
+            tx_in_flight: 0,
+            isr_addr,
+            mac,
+            irq: virtio.get_irq_line(),
+        };
+        net.fill_rx();
+
+        status |= STATUS_DRIVER_OK;
+        write_8(common_cfg_ptr.add(OFF_DEVICE_STATUS), status);
+
+        debugln!("VirtIO Net: Initialized. MAC {:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}, IRQ {}",
+            mac[0], mac[1], mac[2], mac[3], mac[4], mac[5], net.irq);
+
+        VIRTIO_NET = Some(net);
+    }
+    true
+}
+
+unsafe fn setup_queue(common_cfg: *mut u8, index: u16, notify_base: u64, notify_multiplier: u32, buffers: usize) -> Option<NetQueue> {
+    unsafe {
+        write_16(common_cfg.add(OFF_QUEUE_SELECT), index);
+
+        let max_size = read_16(common_cfg.add(OFF_QUEUE_SIZE));
+        if max_size < QUEUE_SIZE {
+            debugln!("VirtIO Net: Queue {} too small ({}).", index, max_size);
+            return None;
+        }
+        write_16(common_cfg.add(OFF_QUEUE_SIZE), QUEUE_SIZE);
+
+        let frame = pmm::allocate_frame(0)?;
+        core::ptr::write_bytes((frame + HHDM_OFFSET) as *mut u8, 0, 4096);
+
+        let buffer_frames = (buffers * BUF_SIZE + 4095) / 4096;
+        let buffers_phys = pmm::allocate_frames(buffer_frames, 0)?;
+        core::ptr::write_bytes((buffers_phys + HHDM_OFFSET) as *mut u8, 0, buffer_frames * 4096);
+
+        let desc_addr = frame;
+        let avail_addr = desc_addr + 2048;
+        let used_addr = (avail_addr + 262 + 3) & !3;
+
+        write_64(common_cfg.add(OFF_QUEUE_DESC), desc_addr);
+        write_64(common_cfg.add(OFF_QUEUE_DRIVER), avail_addr);
+        write_64(common_cfg.add(OFF_QUEUE_DEVICE), used_addr);
+
+        let notify_off = read_16(common_cfg.add(OFF_QUEUE_NOTIFY_OFF));
+        let notify_addr = notify_base + (notify_off as u64 * notify_multiplier as u64);
+
+        write_16(common_cfg.add(OFF_QUEUE_ENABLE), 1);
+
+        debugln!("VirtIO Net: Queue {} setup at phys {:#x}, {} buffers at {:#x}", index, frame, buffers, buffers_phys);
+
+        Some(NetQueue {
+            desc_phys: desc_addr,
+            avail_phys: avail_addr,
+            used_phys: used_addr,
+            notify_addr,
+            last_used_idx: 0,
+            buffers_phys,
+        })
+    }
+}
+
+impl VirtioNet {
+    fn fill_rx(&mut self) {
+        unsafe {
+            let desc = (self.rx.desc_phys + HHDM_OFFSET) as *mut VirtqDesc;
+            let avail = (self.rx.avail_phys + HHDM_OFFSET) as *mut VirtqAvail;
+
+            for i in 0..RX_BUFFERS {
+                *desc.add(i) = VirtqDesc {
+                    addr: self.rx.buffers_phys + (i * BUF_SIZE) as u64,
+                    len: BUF_SIZE as u32,
+                    flags: 2,
+                    next: 0,
+                };
+                (*avail).ring[i] = i as u16;
+            }
+
+            core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst);
+            (*avail).idx = RX_BUFFERS as u16;
+            write_volatile(self.rx.notify_addr as *mut u16, RX_QUEUE);
+        }
+    }
+
+    pub fn ack_interrupt(&self) -> u8 {
+        if self.isr_addr == 0 { return 0; }
+        unsafe { read_8(self.isr_addr as *mut u8) }
+    }
+
+    pub fn receive(&mut self) -> Vec<Vec<u8>> {
+        let mut frames = Vec::new();
+        unsafe {
+            let used = (self.rx.used_phys + HHDM_OFFSET) as *const VirtqUsed;
+            let avail = (self.rx.avail_phys + HHDM_OFFSET) as *mut VirtqAvail;
+            let mut reposted = false;
+
+            loop {
+                let used_idx = read_volatile(core::ptr::addr_of!((*used).idx));
+                if used_idx == self.rx.last_used_idx { break; }
+
+                let slot = (self.rx.last_used_idx % QUEUE_SIZE) as usize;
+                let elem = read_volatile(core::ptr::addr_of!((*used).ring[slot]));
+                let id = elem.id as usize;
+                let len = (elem.len as usize).min(BUF_SIZE);
+
+                if id < RX_BUFFERS {
+                    if len > NET_HDR_SIZE {
+                        let buf = (self.rx.buffers_phys + HHDM_OFFSET + (id * BUF_SIZE) as u64) as *const u8;
+                        let data = core::slice::from_raw_parts(buf.add(NET_HDR_SIZE), len - NET_HDR_SIZE);
+                        frames.push(data.to_vec());
+                    }
+
+                    let idx = (*avail).idx;
+                    (*avail).ring[(idx % QUEUE_SIZE) as usize] = id as u16;
+                    core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst);
+                    (*avail).idx = idx.wrapping_add(1);
+                    reposted = true;
+                }
+
+                self.rx.last_used_idx = self.rx.last_used_idx.wrapping_add(1);
+            }
+
+            if reposted {
+                write_volatile(self.rx.notify_addr as *mut u16, RX_QUEUE);
+            }
+        }
+        frames
+    }
+
+    fn reclaim_tx(&mut self) {
+        unsafe {
+            let used = (self.tx.used_phys + HHDM_OFFSET) as *const VirtqUsed;
+            let used_idx = read_volatile(core::ptr::addr_of!((*used).idx));
+            let completed = used_idx.wrapping_sub(self.tx.last_used_idx) as usize;
+            self.tx.last_used_idx = used_idx;
+            self.tx_in_flight = self.tx_in_flight.saturating_sub(completed);
+        }
+    }
+
+    pub fn transmit(&mut self, frame: &[u8]) -> bool {
+        if frame.len() > BUF_SIZE - NET_HDR_SIZE { return false; }
+
+        self.reclaim_tx();
+        if self.tx_in_flight >= TX_BUFFERS {
+            debugln!("VirtIO Net: TX ring full, dropping frame.");
+            return false;
+        }
+
+        let slot = self.tx_next;
+        self.tx_next = (self.tx_next + 1) % TX_BUFFERS;
+
+        unsafe {
+            let buf_phys = self.tx.buffers_phys + (slot * BUF_SIZE) as u64;
+            let buf = (buf_phys + HHDM_OFFSET) as *mut u8;
+            core::ptr::write_bytes(buf, 0, NET_HDR_SIZE);
+            core::ptr::copy_nonoverlapping(frame.as_ptr(), buf.add(NET_HDR_SIZE), frame.len());
+
+            let desc = (self.tx.desc_phys + HHDM_OFFSET) as *mut VirtqDesc;
+            *desc.add(slot) = VirtqDesc {
+                addr: buf_phys,
+                len: (NET_HDR_SIZE + frame.len()) as u32,
+                flags: 0,
+                next: 0,
+            };
+
+            let avail = (self.tx.avail_phys + HHDM_OFFSET) as *mut VirtqAvail;
+            let idx = (*avail).idx;
+            (*avail).ring[(idx % QUEUE_SIZE) as usize] = slot as u16;
+            core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst);
+            (*avail).idx = idx.wrapping_add(1);
+
+            write_volatile(self.tx.notify_addr as *mut u16, TX_QUEUE);
+        }
+
+        self.tx_in_flight += 1;
+        true
+    }
+}
@@ -98,6 +98,31 @@ pub fn find_device(v_id: u32, d_id: u32) -> Option<PciDevice> {
     None
 }
 
+pub fn find_devices(v_id: u32, d_id: u32) -> Vec<PciDevice> {
+    let mut found = Vec::new();
+    for bus in 0..=255 {
+        for device in 0..32 {
+            for function in 0..8 {
+                let vendor_id = pci_read(bus, device, function, 0) & 0xFFFF;
+
+                if vendor_id == v_id && (pci_read(bus, device, function, 2) >> 16) == d_id {
+                    let class_subclass = pci_read(bus, device, function, 8);
+                    found.push(PciDevice {
+                        class: (class_subclass >> 24) & 0xFF,
+                        subclass: (class_subclass >> 16) & 0xFF,
+                        vendor_id,
+                        device_id: d_id,
+                        bus,
+                        device,
+                        function,
+                    });
+                }
+            }
+        }
+    }
+    found
+}
+
 pub fn find_device_by_class(class_id: u32, subclass_id: u32) -> Option<PciDevice> {
     for bus in 0..=255 {
         for device in 0..32 {
@@ -155,6 +180,10 @@ impl PciDevice {
         (value & 0xFF) as u8
     }
 
+    pub fn get_irq_line(&self) -> u8 {
+        Self::get_pci_irq(self.bus, self.device, self.function)
+    }
+
     pub fn get_bar(&self, bar_index: u8) -> Option<u32> {
         if bar_index > 5 {
             return None;
@@ -15,6 +15,12 @@ pub const KEY_ENTER: u32 = 0x0D;
 pub const KEY_CTRL: u32 = 0x110005;
 pub const KEY_ALT: u32 = 0x110006;
 pub const KEY_SHIFT: u32 = 0x110007;
+pub const KEY_PRINT_SCREEN: u32 = 0x110008;
+pub const KEY_HOME: u32 = 0x110009;
+pub const KEY_END: u32 = 0x11000A;
+pub const KEY_PAGE_UP: u32 = 0x11000B;
+pub const KEY_PAGE_DOWN: u32 = 0x11000C;
+pub const KEY_DELETE: u32 = 0x11000D;
 
 
 #[allow(dead_code)]
@@ -84,6 +90,25 @@ pub fn is_super_active() -> bool {
     unsafe { SUPER_ACTIVE }
 }
 
+pub fn is_alt_active() -> bool {
+    unsafe { ALT_ACTIVE }
+}
+
+pub fn is_shift_active() -> bool {
+    unsafe { SHIFT_ACTIVE }
+}
+
+pub fn is_ctrl_active() -> bool {
+    unsafe { CTRL_ACTIVE }
+}
+
+pub fn number_row_digit(key: u32) -> Option<u32> {
+    let c = char::from_u32(key)?;
+    (0x02..=0x0B)
+        .find(|&i| SCANCODE_MAP_LOWERCASE[i] == c || SCANCODE_MAP_UPPERCASE[i] == c)
+        .and_then(|i| SCANCODE_MAP_LOWERCASE[i].to_digit(10))
+}
+
 const SCANCODE_MAP_ALT: [char; 128] = [
     '\0', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '{', '[', ']', '}', '\0', '\0', '\0', '\0',
     '@', '\0', '€', '\0', '\0', '\0', '\0', '\0', '\0', '\0', '[', ']', '\0', '\0', '\0', '\0',
@@ -183,6 +208,12 @@ pub fn handle_scancode(scancode: u8) -> Option<(u32, bool)> {
                 Some((KEY_ALT, pressed))
             }
 
+            // PrintScreen arrives wrapped in a fake shift press and release.
+            0x2A | 0x36 if is_e0 => None,
+            0x37 if is_e0 => Some((KEY_PRINT_SCREEN, pressed)),
+            // Alt+PrintScreen is sent as SysRq.
+            0x54 => Some((KEY_PRINT_SCREEN, pressed)),
+
             0x2A | 0x36 => {
                 SHIFT_ACTIVE = pressed;
                 Some((KEY_SHIFT, pressed))
@@ -203,6 +234,11 @@ pub fn handle_scancode(scancode: u8) -> Option<(u32, bool)> {
             0x4D if is_e0 => Some((KEY_RIGHT, pressed)),
             0x48 if is_e0 => Some((KEY_UP, pressed)),
             0x50 if is_e0 => Some((KEY_DOWN, pressed)),
+            0x47 if is_e0 => Some((KEY_HOME, pressed)),
+            0x4F if is_e0 => Some((KEY_END, pressed)),
+            0x49 if is_e0 => Some((KEY_PAGE_UP, pressed)),
+            0x51 if is_e0 => Some((KEY_PAGE_DOWN, pressed)),
+            0x53 if is_e0 => Some((KEY_DELETE, pressed)),
 
             0x56 => {
                 if SHIFT_ACTIVE {
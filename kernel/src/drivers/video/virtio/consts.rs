@@ -38,6 +38,14 @@ pub const VIRTIO_GPU_F_EDID: u32 = 1;
 pub const VIRTIO_GPU_FLAG_FENCE: u32 = 1 << 0;
 
 
+pub const OFF_GPU_EVENTS_READ: usize = 0x00;
+pub const OFF_GPU_EVENTS_CLEAR: usize = 0x04;
+pub const OFF_GPU_NUM_SCANOUTS: usize = 0x08;
+
+pub const VIRTIO_GPU_EVENT_DISPLAY: u32 = 1 << 0;
+pub const VIRTIO_GPU_MAX_SCANOUTS: usize = 16;
+
+
 pub const VIRTIO_GPU_FORMAT_B8G8R8A8_UNORM: u32 = 1;
 pub const VIRTIO_GPU_FORMAT_A8R8G8B8_UNORM: u32 = 2;
 
@@ -1,164 +1,71 @@
-use crate::debugln;
-
 use super::consts::*;
 use super::queue::send_command_queue;
 use super::structs::*;
+use super::{GPU_CMD_PHYS, GPU_CMD_VIRT};
 
-pub fn setup_cursor(phys_ptr: u64, width: u32, height: u32, x: u32, y: u32) {
-    let cursor_id = 3;
-
-
-    let req_create = VirtioGpuResourceCreate2d {
-        hdr: VirtioGpuCtrlHeader {
-            type_: VIRTIO_GPU_CMD_RESOURCE_CREATE_2D,
-            flags: 0,
-            fence_id: 0,
-            ctx_id: 0,
-            ring_idx: 0,
-            padding: [0; 3],
-        },
-        resource_id: cursor_id,
-        format: VIRTIO_GPU_FORMAT_B8G8R8A8_UNORM,
-        width,
-        height,
-    };
-    let resp_create: VirtioGpuCtrlHeader = unsafe { core::mem::zeroed() };
-
-    send_command_queue(
-        0,
-        &[crate::memory::paging::virt_to_phys(&req_create as *const _ as u64)],
-        &[core::mem::size_of_val(&req_create) as u32],
-        &[crate::memory::paging::virt_to_phys(&resp_create as *const _ as u64)],
-        &[core::mem::size_of_val(&resp_create) as u32],
-        true,
-    );
+pub const CURSOR_RESOURCE_ID: u32 = 3;
+pub const HW_CURSOR_SIZE: u32 = 64;
 
+const CURSOR_REQ_OFFSET: u64 = 2048;
+const CURSOR_RESP_OFFSET: u64 = 3072;
 
-    #[repr(C)]
-    struct AttachRequest {
-        hdr: VirtioGpuResourceAttachBacking,
-        entry: VirtioGpuMemEntry,
-    }
+pub fn setup_cursor(phys_ptr: u64) {
+    super::create_resource(CURSOR_RESOURCE_ID, HW_CURSOR_SIZE, HW_CURSOR_SIZE, phys_ptr);
+}
 
-    let req_attach = AttachRequest {
-        hdr: VirtioGpuResourceAttachBacking {
+pub fn update_cursor(x: u32, y: u32, hot_x: u32, hot_y: u32) {
+    unsafe {
+        let req_transfer_ptr = (GPU_CMD_VIRT + CURSOR_REQ_OFFSET) as *mut VirtioGpuTransferToHost2d;
+        core::ptr::write(req_transfer_ptr, VirtioGpuTransferToHost2d {
             hdr: VirtioGpuCtrlHeader {
-                type_: VIRTIO_GPU_CMD_RESOURCE_ATTACH_BACKING,
+                type_: VIRTIO_GPU_CMD_TRANSFER_TO_HOST_2D,
                 flags: 0,
                 fence_id: 0,
                 ctx_id: 0,
                 ring_idx: 0,
                 padding: [0; 3],
             },
-            resource_id: cursor_id,
-            nr_entries: 1,
-        },
-        entry: VirtioGpuMemEntry {
-            addr: phys_ptr,
-            length: width * height * 4,
-            padding: 0,
-        },
-    };
-    let resp_attach: VirtioGpuCtrlHeader = unsafe { core::mem::zeroed() };
-
-    send_command_queue(
-        0,
-        &[&req_attach as *const _ as u64],
-        &[core::mem::size_of_val(&req_attach) as u32],
-        &[&resp_attach as *const _ as u64],
-        &[core::mem::size_of_val(&resp_attach) as u32],
-        true,
-    );
-
-
-    let req_transfer = VirtioGpuTransferToHost2d {
-        hdr: VirtioGpuCtrlHeader {
-            type_: VIRTIO_GPU_CMD_TRANSFER_TO_HOST_2D,
-            flags: 0,
-            fence_id: 0,
-            ctx_id: 0,
-            ring_idx: 0,
-            padding: [0; 3],
-        },
-        r: VirtioGpuRect { x: 0, y: 0, width, height },
-        offset: 0,
-        resource_id: cursor_id,
-        padding: 0,
-    };
-    let resp_transfer: VirtioGpuCtrlHeader = unsafe { core::mem::zeroed() };
-
-    send_command_queue(
-        0,
-        &[&req_transfer as *const _ as u64],
-        &[core::mem::size_of_val(&req_transfer) as u32],
-        &[&resp_transfer as *const _ as u64],
-        &[core::mem::size_of_val(&resp_transfer) as u32],
-        true,
-    );
-
-
-    let req_update = VirtioGpuUpdateCursor {
-        hdr: VirtioGpuCtrlHeader {
-            type_: VIRTIO_GPU_CMD_UPDATE_CURSOR,
-            flags: 0,
-            fence_id: 0,
-            ctx_id: 0,
-            ring_idx: 0,
-            padding: [0; 3],
-        },
-        pos: VirtioGpuCursorPos {
-            scanout_id: 0,
-            x,
-            y,
+            r: VirtioGpuRect { x: 0, y: 0, width: HW_CURSOR_SIZE, height: HW_CURSOR_SIZE },
+            offset: 0,
+            resource_id: CURSOR_RESOURCE_ID,
             padding: 0,
-        },
-        resource_id: cursor_id,
-        hot_x: 0,
-        hot_y: 0,
-        padding: 0,
-    };
+        });
 
+        send_command_queue(0, &[GPU_CMD_PHYS + CURSOR_REQ_OFFSET], &[core::mem::size_of::<VirtioGpuTransferToHost2d>() as u32],
+                           &[GPU_CMD_PHYS + CURSOR_RESP_OFFSET], &[24], true);
+    }
 
-    send_command_queue(
-        1,
-        &[&req_update as *const _ as u64],
-        &[core::mem::size_of_val(&req_update) as u32],
-        &[],
-        &[],
-        true,
-    );
-    debugln!("VirtIO GPU: Hardware Cursor Setup & Update Sent (Queue 1).");
+    send_cursor_command(VIRTIO_GPU_CMD_UPDATE_CURSOR, x, y, CURSOR_RESOURCE_ID, hot_x, hot_y);
 }
 
 pub fn move_cursor(x: u32, y: u32) {
-    let req_move = VirtioGpuUpdateCursor {
-        hdr: VirtioGpuCtrlHeader {
-            type_: VIRTIO_GPU_CMD_MOVE_CURSOR,
-            flags: 0,
-            fence_id: 0,
-            ctx_id: 0,
-            ring_idx: 0,
-            padding: [0; 3],
-        },
-        pos: VirtioGpuCursorPos {
-            scanout_id: 0,
-            x,
-            y,
-            padding: 0,
-        },
-        resource_id: 0,
-        hot_x: 0,
-        hot_y: 0,
-        padding: 0,
-    };
+    send_cursor_command(VIRTIO_GPU_CMD_MOVE_CURSOR, x, y, 0, 0, 0);
+}
 
+fn send_cursor_command(type_: u32, x: u32, y: u32, resource_id: u32, hot_x: u32, hot_y: u32) {
+    unsafe {
+        let req_ptr = (GPU_CMD_VIRT + CURSOR_REQ_OFFSET) as *mut VirtioGpuUpdateCursor;
+        core::ptr::write(req_ptr, VirtioGpuUpdateCursor {
+            hdr: VirtioGpuCtrlHeader {
+                type_,
+                flags: 0,
+                fence_id: 0,
+                ctx_id: 0,
+                ring_idx: 0,
+                padding: [0; 3],
+            },
+            pos: VirtioGpuCursorPos {
+                scanout_id: 0,
+                x,
+                y,
+                padding: 0,
+            },
+            resource_id,
+            hot_x,
+            hot_y,
+            padding: 0,
+        });
 
-    send_command_queue(
-        1,
-        &[&req_move as *const _ as u64],
-        &[core::mem::size_of_val(&req_move) as u32],
-        &[],
-        &[],
-        true,
-    );
+        send_command_queue(1, &[GPU_CMD_PHYS + CURSOR_REQ_OFFSET], &[core::mem::size_of::<VirtioGpuUpdateCursor>() as u32], &[], &[], true);
+    }
 }
@@ -15,6 +15,16 @@ use crate::memory::vmm;
 use alloc::vec::Vec;
 
 pub static mut COMMON_CFG_ADDR: u64 = 0;
+static mut DEVICE_CFG_ADDR: u64 = 0;
+
+static mut SCANOUT_LAYOUT: Vec<(u32, VirtioGpuRect)> = Vec::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScanoutInfo {
+    pub id: u32,
+    pub width: u32,
+    pub height: u32,
+}
 
 
 static mut GPU_CMD_VIRT: u64 = 0;
@@ -108,6 +118,21 @@ pub fn init() {
                 notify_multiplier = virtio.read_capability_data(cap.offset as u8, 16);
                 if notify_multiplier == 0 { notify_multiplier = 4; }
             }
+        } else if cap.cfg_type == VIRTIO_CAP_DEVICE {
+            let mut bar_base_opt = virtio.get_bar(cap.bar);
+            if bar_base_opt.is_none() || bar_base_opt == Some(0) {
+                let raw_bar = virtio.read_bar_raw(cap.bar);
+                if (raw_bar & 0xFFFFFFF0) == 0 {
+                    virtio.write_bar(cap.bar, next_bar_addr);
+                    next_bar_addr += 0x100000;
+                    bar_base_opt = virtio.get_bar(cap.bar);
+                }
+            }
+
+            if let Some(bar_base) = bar_base_opt {
+                let addr = (bar_base as u64) + (cap.offset as u64);
+                unsafe { DEVICE_CFG_ADDR = vmm::map_mmio(addr, 4096); }
+            }
         }
     }
 
@@ -182,7 +207,7 @@ fn check_features(common_cfg: *mut u8) {
     }
 }
 
-pub fn get_display_info() -> Option<(u32, u32)> {
+fn query_display_info() -> Option<VirtioGpuRespDisplayInfo> {
     unsafe {
         let req_ptr = GPU_CMD_VIRT as *mut VirtioGpuCtrlHeader;
         let resp_ptr = (GPU_CMD_VIRT + 1024) as *mut VirtioGpuRespDisplayInfo;
@@ -206,37 +231,100 @@ pub fn get_display_info() -> Option<(u32, u32)> {
             true,
         );
 
-        let resp = &*resp_ptr;
-        if resp.hdr.type_ == VIRTIO_GPU_RESP_OK_DISPLAY_INFO {
-            let pmode = resp.pmodes[0];
+        let resp = *resp_ptr;
+        if resp.hdr.type_ == VIRTIO_GPU_RESP_OK_DISPLAY_INFO { Some(resp) } else { None }
+    }
+}
+
+pub fn get_display_info() -> Option<(u32, u32)> {
+    let resp = query_display_info()?;
+
+    let pmode = resp.pmodes[0];
+    if pmode.r.width > 0 && pmode.r.height > 0 {
+        return Some((pmode.r.width, pmode.r.height));
+    }
+
+    for i in 1..VIRTIO_GPU_MAX_SCANOUTS {
+        let pmode = resp.pmodes[i];
+        if pmode.enabled != 0 {
             if pmode.r.width > 0 && pmode.r.height > 0 {
                 return Some((pmode.r.width, pmode.r.height));
             }
-
-            for i in 1..16 {
-                let pmode = resp.pmodes[i];
-                if pmode.enabled != 0 {
-                    if pmode.r.width > 0 && pmode.r.height > 0 {
-                        return Some((pmode.r.width, pmode.r.height));
-                    }
-                }
-            }
         }
     }
     None
 }
 
-pub fn start_gpu(width: u32, height: u32, phys_buf1: u64, phys_buf2: u64) {
+pub fn display_modes() -> Vec<ScanoutInfo> {
+    let mut modes = Vec::new();
+    let Some(resp) = query_display_info() else { return modes };
+
+    let count = unsafe {
+        if DEVICE_CFG_ADDR != 0 {
+            (read_32((DEVICE_CFG_ADDR as *mut u8).add(OFF_GPU_NUM_SCANOUTS)) as usize).min(VIRTIO_GPU_MAX_SCANOUTS)
+        } else {
+            VIRTIO_GPU_MAX_SCANOUTS
+        }
+    };
+
+    for i in 0..count {
+        let pmode = resp.pmodes[i];
+        if pmode.enabled != 0 && pmode.r.width > 0 && pmode.r.height > 0 {
+            modes.push(ScanoutInfo { id: i as u32, width: pmode.r.width, height: pmode.r.height });
+        }
+    }
+    modes
+}
+
+pub fn take_display_event() -> bool {
     unsafe {
-        get_display_info();
+        if DEVICE_CFG_ADDR == 0 {
+            return false;
+        }
+        let cfg = DEVICE_CFG_ADDR as *mut u8;
+        if (read_32(cfg.add(OFF_GPU_EVENTS_READ)) & VIRTIO_GPU_EVENT_DISPLAY) == 0 {
+            return false;
+        }
+        write_32(cfg.add(OFF_GPU_EVENTS_CLEAR), VIRTIO_GPU_EVENT_DISPLAY);
+        true
+    }
+}
+
+pub fn start_gpu(width: u32, height: u32, phys_buf1: u64, phys_buf2: u64) {
+    get_display_info();
 
+    create_resource(1, width, height, phys_buf1);
+    create_resource(2, width, height, phys_buf2);
+
+    scanout_command(0, 1, VirtioGpuRect { x: 0, y: 0, width, height }, true);
+}
+
+pub fn create_resource(id: u32, width: u32, height: u32, phys: u64) {
+    unsafe {
         let req_create_ptr = GPU_CMD_VIRT as *mut VirtioGpuResourceCreate2d;
-        let resp_ptr = (GPU_CMD_VIRT + 1024) as *mut VirtioGpuCtrlHeader;
+        core::ptr::write(req_create_ptr, VirtioGpuResourceCreate2d {
+            hdr: VirtioGpuCtrlHeader {
+                type_: VIRTIO_GPU_CMD_RESOURCE_CREATE_2D,
+                flags: 0,
+                fence_id: 0,
+                ctx_id: 0,
+                ring_idx: 0,
+                padding: [0; 3],
+            },
+            resource_id: id,
+            format: 1,
+            width,
+            height,
+        });
 
-        let mut create_resource = |id: u32, phys: u64| {
-            core::ptr::write(req_create_ptr, VirtioGpuResourceCreate2d {
+        send_command_queue(0, &[GPU_CMD_PHYS], &[core::mem::size_of::<VirtioGpuResourceCreate2d>() as u32],
+                           &[GPU_CMD_PHYS + 1024], &[24], true);
+
+        let req_attach_ptr = GPU_CMD_VIRT as *mut AttachRequest;
+        core::ptr::write(req_attach_ptr, AttachRequest {
+            hdr: VirtioGpuResourceAttachBacking {
                 hdr: VirtioGpuCtrlHeader {
-                    type_: VIRTIO_GPU_CMD_RESOURCE_CREATE_2D,
+                    type_: VIRTIO_GPU_CMD_RESOURCE_ATTACH_BACKING,
                     flags: 0,
                     fence_id: 0,
                     ctx_id: 0,
@@ -244,54 +332,34 @@ pub fn start_gpu(width: u32, height: u32, phys_buf1: u64, phys_buf2: u64) {
                     padding: [0; 3],
                 },
                 resource_id: id,
-                format: 1,
-                width,
-                height,
-            });
-
-            send_command_queue(0, &[GPU_CMD_PHYS], &[core::mem::size_of::<VirtioGpuResourceCreate2d>() as u32],
-                               &[GPU_CMD_PHYS + 1024], &[24], true);
-
-            let req_attach_ptr = GPU_CMD_VIRT as *mut AttachRequest;
-            core::ptr::write(req_attach_ptr, AttachRequest {
-                hdr: VirtioGpuResourceAttachBacking {
-                    hdr: VirtioGpuCtrlHeader {
-                        type_: VIRTIO_GPU_CMD_RESOURCE_ATTACH_BACKING,
-                        flags: 0,
-                        fence_id: 0,
-                        ctx_id: 0,
-                        ring_idx: 0,
-                        padding: [0; 3],
-                    },
-                    resource_id: id,
-                    nr_entries: 1,
-                },
-                entry: VirtioGpuMemEntry { addr: phys, length: width * height * 4, padding: 0 },
-            });
-
-            send_command_queue(0, &[GPU_CMD_PHYS], &[core::mem::size_of::<AttachRequest>() as u32],
-                               &[GPU_CMD_PHYS + 1024], &[24], true);
-        };
+                nr_entries: 1,
+            },
+            entry: VirtioGpuMemEntry { addr: phys, length: width * height * 4, padding: 0 },
+        });
 
-        create_resource(1, phys_buf1);
-        create_resource(2, phys_buf2);
+        send_command_queue(0, &[GPU_CMD_PHYS], &[core::mem::size_of::<AttachRequest>() as u32],
+                           &[GPU_CMD_PHYS + 1024], &[24], true);
+    }
+}
 
-        let req_scanout_ptr = GPU_CMD_VIRT as *mut VirtioGpuSetScanout;
-        core::ptr::write(req_scanout_ptr, VirtioGpuSetScanout {
-            hdr: VirtioGpuCtrlHeader {
-                type_: VIRTIO_GPU_CMD_SET_SCANOUT,
-                flags: 0,
-                fence_id: 0,
-                ctx_id: 0,
-                ring_idx: 0,
-                padding: [0; 3],
-            },
-            r: VirtioGpuRect { x: 0, y: 0, width, height },
-            scanout_id: 0,
-            resource_id: 1,
+pub fn destroy_resource(id: u32) {
+    unsafe {
+        let req_detach_ptr = GPU_CMD_VIRT as *mut VirtioGpuResourceDetachBacking;
+        core::ptr::write(req_detach_ptr, VirtioGpuResourceDetachBacking {
+            hdr: VirtioGpuCtrlHeader { type_: VIRTIO_GPU_CMD_RESOURCE_DETACH_BACKING, flags: 0, fence_id: 0, ctx_id: 0, ring_idx: 0, padding: [0; 3] },
+            resource_id: id,
+            padding: 0,
         });
+        send_command_queue(0, &[GPU_CMD_PHYS], &[core::mem::size_of::<VirtioGpuResourceDetachBacking>() as u32],
+                           &[GPU_CMD_PHYS + 1024], &[24], true);
 
-        send_command_queue(0, &[GPU_CMD_PHYS], &[core::mem::size_of::<VirtioGpuSetScanout>() as u32],
+        let req_unref_ptr = GPU_CMD_VIRT as *mut VirtioGpuResourceUnref;
+        core::ptr::write(req_unref_ptr, VirtioGpuResourceUnref {
+            hdr: VirtioGpuCtrlHeader { type_: VIRTIO_GPU_CMD_RESOURCE_UNREF, flags: 0, fence_id: 0, ctx_id: 0, ring_idx: 0, padding: [0; 3] },
+            resource_id: id,
+            padding: 0,
+        });
+        send_command_queue(0, &[GPU_CMD_PHYS], &[core::mem::size_of::<VirtioGpuResourceUnref>() as u32],
                            &[GPU_CMD_PHYS + 1024], &[24], true);
     }
 }
@@ -353,14 +421,49 @@ pub fn flush(x: u32, y: u32, width: u32, height: u32, screen_width: u32, resourc
     }
 }
 pub fn set_scanout(resource_id: u32, width: u32, height: u32) {
+    let layout = unsafe { &*(&raw const SCANOUT_LAYOUT) };
+    if layout.is_empty() {
+        scanout_command(0, resource_id, VirtioGpuRect { x: 0, y: 0, width, height }, false);
+        return;
+    }
+
+    // The command buffer is shared, so only the last request may be left in flight.
+    for (i, &(scanout_id, rect)) in layout.iter().enumerate() {
+        scanout_command(scanout_id, resource_id, rect, i + 1 < layout.len());
+    }
+}
+
+pub fn set_layout(layout: Vec<(u32, VirtioGpuRect)>) {
+    let old = unsafe { &mut *(&raw mut SCANOUT_LAYOUT) };
+    let off = VirtioGpuRect { x: 0, y: 0, width: 0, height: 0 };
+    for &(scanout_id, _) in old.iter() {
+        if !layout.iter().any(|&(id, _)| id == scanout_id) {
+            scanout_command(scanout_id, 0, off, true);
+        }
+    }
+    *old = layout;
+}
+
+pub fn disable_scanouts() {
+    let layout = unsafe { &*(&raw const SCANOUT_LAYOUT) };
+    let off = VirtioGpuRect { x: 0, y: 0, width: 0, height: 0 };
+    if layout.is_empty() {
+        scanout_command(0, 0, off, true);
+    }
+    for &(scanout_id, _) in layout.iter() {
+        scanout_command(scanout_id, 0, off, true);
+    }
+}
+
+fn scanout_command(scanout_id: u32, resource_id: u32, r: VirtioGpuRect, wait: bool) {
     unsafe {
         let req_scanout_ptr = GPU_CMD_VIRT as *mut VirtioGpuSetScanout;
         core::ptr::write(req_scanout_ptr, VirtioGpuSetScanout {
             hdr: VirtioGpuCtrlHeader { type_: VIRTIO_GPU_CMD_SET_SCANOUT, flags: 0, fence_id: 0, ctx_id: 0, ring_idx: 0, padding: [0; 3] },
-            r: VirtioGpuRect { x: 0, y: 0, width, height },
-            scanout_id: 0,
+            r,
+            scanout_id,
             resource_id,
         });
-        send_command_queue(0, &[GPU_CMD_PHYS], &[core::mem::size_of::<VirtioGpuSetScanout>() as u32], &[GPU_CMD_PHYS + 1024], &[24], false);
+        send_command_queue(0, &[GPU_CMD_PHYS], &[core::mem::size_of::<VirtioGpuSetScanout>() as u32], &[GPU_CMD_PHYS + 1024], &[24], wait);
     }
-}
\ No newline at end of file
+}
@@ -1,3 +1,5 @@
+use super::consts::VIRTIO_GPU_MAX_SCANOUTS;
+
 #[derive(Debug, Copy, Clone)]
 pub struct VirtioPciCap {
     pub cfg_type: u8,
@@ -18,7 +20,7 @@ pub struct VirtioGpuCtrlHeader {
 }
 
 #[repr(C)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct VirtioGpuRect {
     pub x: u32,
     pub y: u32,
@@ -80,11 +82,27 @@ pub struct VirtioGpuResourceFlush {
     pub padding: u32,
 }
 
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct VirtioGpuResourceUnref {
+    pub hdr: VirtioGpuCtrlHeader,
+    pub resource_id: u32,
+    pub padding: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct VirtioGpuResourceDetachBacking {
+    pub hdr: VirtioGpuCtrlHeader,
+    pub resource_id: u32,
+    pub padding: u32,
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct VirtioGpuRespDisplayInfo {
     pub hdr: VirtioGpuCtrlHeader,
-    pub pmodes: [VirtioGpuDisplayOne; 16],
+    pub pmodes: [VirtioGpuDisplayOne; VIRTIO_GPU_MAX_SCANOUTS],
 }
 
 #[repr(C)]
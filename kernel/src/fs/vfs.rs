@@ -11,6 +11,9 @@ pub static mut GLOBAL_FILE_REFCOUNT: [u16; 256] = [0; 256];
 pub enum FileHandle {
     File { node: Box<dyn VfsNode>, offset: u64 },
     Pipe { pipe: crate::fs::pipe::Pipe },
+    Socket { id: usize },
+    UnixSocket { id: usize },
+    Audio { stream: usize },
 }
 
 pub fn init() {}
@@ -55,8 +58,12 @@ pub fn close_file(fd: usize) {
             if GLOBAL_FILE_REFCOUNT[fd] > 0 {
                 GLOBAL_FILE_REFCOUNT[fd] -= 1;
                 if GLOBAL_FILE_REFCOUNT[fd] == 0 {
-                    if let Some(FileHandle::Pipe { pipe }) = &OPEN_FILES[fd] {
-                        pipe.close();
+                    match &OPEN_FILES[fd] {
+                        Some(FileHandle::Pipe { pipe }) => pipe.close(),
+                        Some(FileHandle::Socket { id }) => crate::net::socket::release(*id),
+                        Some(FileHandle::UnixSocket { id }) => crate::net::unix::release(*id),
+                        Some(FileHandle::Audio { stream }) => crate::audio::release(*stream),
+                        _ => {}
                     }
                     OPEN_FILES[fd] = None;
                 }
@@ -163,6 +163,8 @@ pub extern "x86-interrupt" fn generic_handler(_info: &mut StackFrame) {
     serial_println("EXCEPTION: GENERIC");
 }
 
+pub extern "x86-interrupt" fn spurious_handler(_info: &mut StackFrame) {}
+
 pub extern "x86-interrupt" fn device_not_available(info: &mut StackFrame) {
     serial_println("EXCEPTION: DEVICE NOT AVAILABLE (#NM)");
     if (info.code_segment & 3) == 3 {
@@ -194,6 +196,16 @@ pub extern "x86-interrupt" fn simd_error(info: &mut StackFrame) {
 #[allow(dead_code)]
 pub const NET_INT: u8 = 43;
 
+pub extern "x86-interrupt" fn net_handler(_info: &mut StackFrame) {
+    let irq = crate::net::handle_irq();
+
+    if irq < 16 {
+        unsafe {
+            (*(&raw const crate::interrupts::pic::PICS)).end_interrupt(32 + irq);
+        }
+    }
+}
+
 pub const TIMER_INT: u8 = 32;
 
 pub const KEYBOARD_INT: u8 = 33;
@@ -202,89 +214,164 @@ pub extern "x86-interrupt" fn keyboard_handler(_info: &mut StackFrame) {
     let scancode: u8 = inb(0x60);
 
     if let Some((key, pressed)) = crate::drivers::periferics::keyboard::handle_scancode(scancode) {
-        if crate::drivers::periferics::keyboard::is_super_active() {
-            if pressed {
-                crate::debugln!("Global Shortcut: Super + {}", key);
+        dispatch_key(key, pressed);
+    }
+
+    unsafe {
+        (*(&raw const crate::interrupts::pic::PICS)).end_interrupt(KEYBOARD_INT);
+    }
+}
+
+pub fn dispatch_key(key: u32, pressed: bool) {
+    if crate::drivers::periferics::keyboard::is_super_active() {
+        if pressed {
+            crate::debugln!("Global Shortcut: Super + {}", key);
+
+            if key == 'p' as u32 {
+                crate::memory::pmm::print_allocations();
+            }
 
-                if key == 'p' as u32 {
-                    crate::memory::pmm::print_allocations();
+            if key == 't' as u32 {
+                crate::debugln!("Spawning terminal...");
+                match crate::interrupts::syscalls::spawn_process("@0xE0/sys/bin/term.elf", None, None) {
+                    Ok(pid) => crate::debugln!("Terminal spawned with PID: {}", pid),
+                    Err(e) => crate::debugln!("Failed to spawn terminal: {}", e),
                 }
+            }
+
+            if key == 'm' as u32 || key == 'M' as u32 {
+                unsafe { (*(&raw mut crate::window_manager::composer::COMPOSER)).restore_minimized() };
+            }
 
-                if key == 't' as u32 {
-                    crate::debugln!("Spawning terminal...");
-                    match crate::interrupts::syscalls::spawn_process("@0xE0/sys/bin/term.elf", None, None) {
-                        Ok(pid) => crate::debugln!("Terminal spawned with PID: {}", pid),
-                        Err(e) => crate::debugln!("Failed to spawn terminal: {}", e),
+            unsafe {
+                use crate::drivers::periferics::keyboard::{is_shift_active, number_row_digit};
+
+                let composer = &mut *(&raw mut crate::window_manager::composer::COMPOSER);
+                if let Some(n @ 1..=9) = number_row_digit(key) {
+                    if is_shift_active() {
+                        composer.move_to_workspace(crate::window_manager::input::CLICKED_WINDOW_ID, n as usize - 1);
+                    } else {
+                        composer.switch_workspace(n as usize - 1);
                     }
                 }
 
-                if key == 'x' as u32 || key == 'X' as u32 {
-                    unsafe {
-                        let active_window_id = crate::window_manager::input::CLICKED_WINDOW_ID;
-                        crate::debugln!("Global Shortcut: Win + X detected. Active window: {}", active_window_id);
-                        if active_window_id != 0 {
-                            let composer = &*(&raw const crate::window_manager::composer::COMPOSER);
-                            let mut pid_to_kill = None;
-
-                            for w in &composer.windows {
-                                if w.id == active_window_id {
-                                    pid_to_kill = Some(w.pid);
-                                    break;
-                                }
-                            }
+                match char::from_u32(key) {
+                    Some(' ') => composer.cycle_layout(),
+                    Some('f') | Some('F') => composer.toggle_floating(crate::window_manager::input::CLICKED_WINDOW_ID),
+                    Some('j') => composer.focus_neighbour(true),
+                    Some('k') => composer.focus_neighbour(false),
+                    Some('J') => composer.swap_focused(true),
+                    Some('K') => composer.swap_focused(false),
+                    Some('\r') | Some('\n') => composer.promote_focused(),
+                    Some('h') | Some('H') => composer.adjust_master_ratio(-5),
+                    Some('l') | Some('L') => composer.adjust_master_ratio(5),
+                    Some('[') => composer.adjust_gap(-2),
+                    Some(']') => composer.adjust_gap(2),
+                    _ => {}
+                }
+            }
 
-                            if let Some(pid) = pid_to_kill {
-                                crate::debugln!("Global Shortcut: Killing Process {} associated with Window {}", pid, active_window_id);
-                                crate::interrupts::task::TASK_MANAGER.int_lock().kill_process(pid);
-                            } else {
-                                crate::debugln!("Global Shortcut: No PID found for Window {}", active_window_id);
+            if key == 'x' as u32 || key == 'X' as u32 {
+                unsafe {
+                    let active_window_id = crate::window_manager::input::CLICKED_WINDOW_ID;
+                    crate::debugln!("Global Shortcut: Win + X detected. Active window: {}", active_window_id);
+                    if active_window_id != 0 {
+                        let composer = &*(&raw const crate::window_manager::composer::COMPOSER);
+                        let mut pid_to_kill = None;
+
+                        for w in &composer.windows {
+                            if w.id == active_window_id {
+                                pid_to_kill = Some(w.pid);
+                                break;
                             }
                         }
+
+                        if let Some(pid) = pid_to_kill {
+                            crate::debugln!("Global Shortcut: Killing Process {} associated with Window {}", pid, active_window_id);
+                            crate::interrupts::task::TASK_MANAGER.int_lock().kill_process(pid);
+                        } else {
+                            crate::debugln!("Global Shortcut: No PID found for Window {}", active_window_id);
+                        }
                     }
                 }
             }
-        } else {
+        }
+    } else {
+        use crate::drivers::periferics::keyboard::{is_alt_active, is_shift_active, KEY_ALT, KEY_PRINT_SCREEN};
+
+        if key == KEY_PRINT_SCREEN {
             if pressed {
-                KEYBOARD_BUFFER.lock().push_back(key);
-            } else {}
+                spawn_screenshot();
+            }
+            return;
+        }
 
+        unsafe {
+            let composer = &mut *(&raw mut crate::window_manager::composer::COMPOSER);
+            if key == '\t' as u32 && is_alt_active() {
+                if pressed {
+                    composer.switch_step(!is_shift_active());
+                }
+                return;
+            }
+            if key == KEY_ALT && !pressed {
+                composer.switch_finish();
+            }
+        }
 
-            unsafe {
-                let active_window_id = crate::window_manager::input::CLICKED_WINDOW_ID;
-                let repeat = 1;
-                if active_window_id != 0 {
-                    let composer = &*(&raw const crate::window_manager::composer::COMPOSER);
-                    let mut found = false;
-                    for w in &composer.windows {
-                        if w.id == active_window_id {
-                            if w.event_handler != 0 {
-                                found = true;
-                            }
-                            break;
+        if pressed {
+            KEYBOARD_BUFFER.lock().push_back(key);
+        } else {}
+
+
+        unsafe {
+            let active_window_id = crate::window_manager::input::CLICKED_WINDOW_ID;
+            let repeat = 1;
+            if active_window_id != 0 {
+                let composer = &*(&raw const crate::window_manager::composer::COMPOSER);
+                let mut found = false;
+                for w in &composer.windows {
+                    if w.id == active_window_id {
+                        if w.event_handler != 0 {
+                            found = true;
                         }
+                        break;
                     }
+                }
 
-                    if found {
-                        use crate::window_manager::events::{Event, KeyboardEvent, GLOBAL_EVENT_QUEUE};
+                if found {
+                    use crate::window_manager::events::{Event, KeyboardEvent, EVENT_QUEUES};
 
-                        for _ in 0..repeat {
-                            let event = Event::Keyboard(KeyboardEvent {
-                                wid: active_window_id as u32,
-                                key,
-                                pressed,
-                                repeat: 1,
-                            });
+                    for _ in 0..repeat {
+                        let event = Event::Keyboard(KeyboardEvent {
+                            wid: active_window_id as u32,
+                            key,
+                            pressed,
+                            repeat: 1,
+                        });
 
-                            GLOBAL_EVENT_QUEUE.int_lock().add_event(event);
-                        }
+                        EVENT_QUEUES.int_lock().add_event(event);
                     }
                 }
             }
         }
     }
+}
 
-    unsafe {
-        (*(&raw const crate::interrupts::pic::PICS)).end_interrupt(KEYBOARD_INT);
+fn spawn_screenshot() {
+    use crate::drivers::periferics::keyboard::{is_alt_active, is_ctrl_active};
+
+    let focused = alloc::format!("{}", unsafe { crate::window_manager::input::CLICKED_WINDOW_ID });
+    let args: &[&str] = if is_ctrl_active() {
+        &["--record"]
+    } else if is_alt_active() {
+        &["--window", focused.as_str()]
+    } else {
+        &[]
+    };
+
+    if let Err(e) = crate::interrupts::syscalls::spawn_process("@0xE0/sys/bin/screenshot.elf", Some(args), None) {
+        crate::debugln!("Failed to spawn screenshot: {}", e);
     }
 }
 
@@ -105,6 +105,8 @@ impl Idt {
         self.add_ring_3(exceptions::YIELD_INT as usize, task::yield_handler as u64);
         self.add(exceptions::KEYBOARD_INT as usize, exceptions::keyboard_handler as u64);
         self.add(exceptions::MOUSE_INT as usize, exceptions::mouse_handler as u64);
+        self.add(crate::drivers::lapic::TIMER_VECTOR as usize, task::deadline_handler as u64);
+        self.add(crate::drivers::lapic::SPURIOUS_VECTOR as usize, exceptions::spurious_handler as u64);
     }
 }
 
@@ -0,0 +1,60 @@
+use crate::audio::MIXER;
+use crate::fs::vfs::FileHandle;
+use crate::interrupts::task::CPUState;
+use core::arch::asm;
+
+fn stream_id(local_fd: u64) -> Option<usize> {
+    let tm = crate::interrupts::task::TASK_MANAGER.int_lock();
+    let current = tm.current_task;
+    if current < 0 || local_fd >= 16 {
+        return None;
+    }
+    let thread = tm.tasks[current as usize].as_ref()?;
+    let proc = thread.process.as_ref().expect("Thread has no process");
+    let global_fd = proc.fd_table.lock()[local_fd as usize];
+    if global_fd == -1 {
+        return None;
+    }
+    match crate::fs::vfs::get_file(global_fd as usize) {
+        Some(FileHandle::Audio { stream }) => Some(*stream),
+        _ => None,
+    }
+}
+
+pub fn write_blocking(stream: usize, buf: &[u8]) -> u64 {
+    loop {
+        let accepted = MIXER.int_lock().write(stream, buf);
+        match accepted {
+            Some(0) if !buf.is_empty() => unsafe { asm!("int 0x81") },
+            Some(n) => return n as u64,
+            None => return u64::MAX,
+        }
+    }
+}
+
+pub fn handle_audio_open(context: &mut CPUState) {
+    let rate = context.rdi as u32;
+    let channels = context.rsi as usize;
+
+    let stream = MIXER.int_lock().open(rate, channels);
+    context.rax = match stream {
+        Some(stream) => super::fs::install_handle(FileHandle::Audio { stream }),
+        None => u64::MAX,
+    };
+}
+
+pub fn handle_audio_volume(context: &mut CPUState) {
+    let fd = context.rdi;
+    let percent = context.rsi as u32;
+
+    if fd as i64 == -1 {
+        MIXER.int_lock().master_volume = percent.min(100) * 256 / 100;
+        context.rax = 0;
+        return;
+    }
+
+    context.rax = match stream_id(fd) {
+        Some(stream) if MIXER.int_lock().set_volume(stream, percent) => 0,
+        _ => u64::MAX,
+    };
+}
@@ -0,0 +1,96 @@
+use crate::interrupts::task::CPUState;
+use crate::window_manager::clipboard::{self, CLIPBOARD, MAX_MIME_LEN};
+
+fn user_str<'a>(ptr: u64, len: u64) -> Option<&'a str> {
+    if len == 0 {
+        return Some("");
+    }
+    if ptr == 0 || len as usize > MAX_MIME_LEN {
+        return None;
+    }
+    let bytes = unsafe { core::slice::from_raw_parts(ptr as *const u8, len as usize) };
+    core::str::from_utf8(bytes).ok()
+}
+
+fn copy_out(data: &[u8], buf: u64, buf_len: u64) -> u64 {
+    if buf != 0 {
+        let n = data.len().min(buf_len as usize);
+        unsafe { core::ptr::copy_nonoverlapping(data.as_ptr(), buf as *mut u8, n) };
+    }
+    data.len() as u64
+}
+
+/// rdi window, rsi/rdx MIME type, r10/r8 data. An empty MIME type clears the selection
+/// if the window still owns it.
+pub fn handle_clipboard_set(context: &mut CPUState) {
+    let wid = context.rdi as usize;
+    let mime = user_str(context.rsi, context.rdx);
+    let data_ptr = context.r10;
+    let data_len = context.r8 as usize;
+
+    if !super::window::owns_window(wid) {
+        context.rax = u64::MAX;
+        return;
+    }
+
+    let mime = match mime {
+        Some(mime) => mime,
+        None => {
+            context.rax = u64::MAX;
+            return;
+        }
+    };
+
+    if mime.is_empty() {
+        let cleared = CLIPBOARD.lock().clear(wid);
+        if cleared {
+            clipboard::notify(0);
+        }
+        context.rax = if cleared { 0 } else { u64::MAX };
+        return;
+    }
+
+    let data = if data_len == 0 || data_ptr == 0 {
+        &[][..]
+    } else {
+        unsafe { core::slice::from_raw_parts(data_ptr as *const u8, data_len) }
+    };
+
+    let result = CLIPBOARD.lock().set(wid, mime, data);
+    context.rax = match result {
+        Ok(()) => {
+            clipboard::notify(wid);
+            0
+        }
+        Err(e) => {
+            crate::debugln!("Clipboard: {}", e);
+            u64::MAX
+        }
+    };
+}
+
+/// rdi/rsi requested MIME type (empty for any), rdx/r10 destination buffer.
+pub fn handle_clipboard_get(context: &mut CPUState) {
+    let mime = user_str(context.rdi, context.rsi);
+    let buf = context.rdx;
+    let buf_len = context.r10;
+
+    let clipboard = CLIPBOARD.lock();
+    context.rax = match mime.and_then(|mime| clipboard.get(mime)) {
+        Some(data) => copy_out(data, buf, buf_len),
+        None => u64::MAX,
+    };
+}
+
+/// rdi/rsi destination for the MIME type on offer. Returns its length, or `u64::MAX`
+/// when the clipboard is empty.
+pub fn handle_clipboard_type(context: &mut CPUState) {
+    let buf = context.rdi;
+    let buf_len = context.rsi;
+
+    let clipboard = CLIPBOARD.lock();
+    context.rax = match clipboard.mime() {
+        Some(mime) => copy_out(mime.as_bytes(), buf, buf_len),
+        None => u64::MAX,
+    };
+}
@@ -0,0 +1,108 @@
+use crate::interrupts::task::{CPUState, TaskState, TASK_MANAGER};
+use core::arch::asm;
+
+const NS_PER_SEC: u64 = 1_000_000_000;
+const TIMER_ABSTIME: u64 = 1;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Timespec {
+    tv_sec: i64,
+    tv_nsec: i64,
+}
+
+impl Timespec {
+    fn from_ns(ns: u64) -> Self {
+        Timespec { tv_sec: (ns / NS_PER_SEC) as i64, tv_nsec: (ns % NS_PER_SEC) as i64 }
+    }
+
+    fn to_ns(self) -> Option<u64> {
+        if self.tv_sec < 0 || !(0..NS_PER_SEC as i64).contains(&self.tv_nsec) {
+            return None;
+        }
+        Some((self.tv_sec as u64).saturating_mul(NS_PER_SEC).saturating_add(self.tv_nsec as u64))
+    }
+}
+
+pub(super) fn sleep_until(deadline: u64) {
+    while crate::time::monotonic_ns() < deadline {
+        if !park(deadline) {
+            return;
+        }
+    }
+}
+
+pub(super) fn park(deadline: u64) -> bool {
+    {
+        let mut tm = TASK_MANAGER.int_lock();
+        let current = tm.current_task;
+        if current < 0 {
+            return false;
+        }
+        if let Some(task) = tm.tasks[current as usize].as_mut() {
+            task.wake_ns = deadline;
+            task.state = TaskState::Sleeping;
+        }
+    }
+    unsafe { asm!("int 0x81") };
+    true
+}
+
+pub fn handle_clock_gettime(context: &mut CPUState) {
+    let clock = context.rdi;
+    let ts_ptr = context.rsi as *mut Timespec;
+
+    context.rax = match crate::time::now(clock) {
+        Some(ns) if !ts_ptr.is_null() => {
+            unsafe { ts_ptr.write_unaligned(Timespec::from_ns(ns)) };
+            0
+        }
+        _ => u64::MAX,
+    };
+}
+
+pub fn handle_clock_getres(context: &mut CPUState) {
+    let clock = context.rdi;
+    let ts_ptr = context.rsi as *mut Timespec;
+
+    if crate::time::now(clock).is_none() {
+        context.rax = u64::MAX;
+        return;
+    }
+    if !ts_ptr.is_null() {
+        unsafe { ts_ptr.write_unaligned(Timespec::from_ns(crate::time::resolution_ns())) };
+    }
+    context.rax = 0;
+}
+
+pub fn handle_clock_nanosleep(context: &mut CPUState) {
+    let clock = context.rdi;
+    let flags = context.rsi;
+    let req_ptr = context.rdx as *const Timespec;
+
+    if req_ptr.is_null() {
+        context.rax = u64::MAX;
+        return;
+    }
+    let requested = match unsafe { req_ptr.read_unaligned() }.to_ns() {
+        Some(ns) => ns,
+        None => {
+            context.rax = u64::MAX;
+            return;
+        }
+    };
+
+    let deadline = if flags & TIMER_ABSTIME != 0 {
+        crate::time::to_monotonic(clock, requested)
+    } else {
+        crate::time::now(clock).map(|_| crate::time::monotonic_ns().saturating_add(requested))
+    };
+
+    context.rax = match deadline {
+        Some(deadline) => {
+            sleep_until(deadline);
+            0
+        }
+        None => u64::MAX,
+    };
+}
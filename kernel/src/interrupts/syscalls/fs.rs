@@ -138,6 +138,22 @@ pub fn handle_poll(context: &mut CPUState) {
                                             if (pfd.events & POLLIN) != 0 { pfd.revents |= POLLIN; }
                                             if (pfd.events & POLLOUT) != 0 { pfd.revents |= POLLOUT; }
                                         }
+                                        FileHandle::Socket { id } => {
+                                            let (readable, writable) = match crate::net::NET.int_lock().as_ref() {
+                                                Some(stack) => stack.socket_readiness(*id),
+                                                None => (false, false),
+                                            };
+                                            if (pfd.events & POLLIN) != 0 && readable { pfd.revents |= POLLIN; }
+                                            if (pfd.events & POLLOUT) != 0 && writable { pfd.revents |= POLLOUT; }
+                                        }
+                                        FileHandle::UnixSocket { id } => {
+                                            let (readable, writable) = crate::net::unix::UNIX.int_lock().readiness(*id);
+                                            if (pfd.events & POLLIN) != 0 && readable { pfd.revents |= POLLIN; }
+                                            if (pfd.events & POLLOUT) != 0 && writable { pfd.revents |= POLLOUT; }
+                                        }
+                                        FileHandle::Audio { stream } => {
+                                            if (pfd.events & POLLOUT) != 0 && crate::audio::MIXER.int_lock().has_space(*stream) { pfd.revents |= POLLOUT; }
+                                        }
                                     }
                                 } else {
                                     pfd.revents = POLLERR;
@@ -256,7 +272,7 @@ pub fn handle_create(context: &mut CPUState, syscall_num: u64) {
     }
 }
 
-fn get_current_cwd() -> String {
+pub(super) fn get_current_cwd() -> String {
     let tm = crate::interrupts::task::TASK_MANAGER.int_lock();
     if tm.current_task >= 0 {
         if let Some(thread) = tm.tasks[tm.current_task as usize].as_ref() {
@@ -269,7 +285,7 @@ fn get_current_cwd() -> String {
     String::from("@0xE0/")
 }
 
-fn assign_local_fd(global_fd: usize) -> u64 {
+pub(super) fn assign_local_fd(global_fd: usize) -> u64 {
     let mut tm = crate::interrupts::task::TASK_MANAGER.int_lock();
     let current = tm.current_task;
     if current >= 0 {
@@ -287,6 +303,30 @@ fn assign_local_fd(global_fd: usize) -> u64 {
     u64::MAX
 }
 
+pub(super) fn install_handle(handle: crate::fs::vfs::FileHandle) -> u64 {
+    use crate::fs::vfs::{FileHandle, GLOBAL_FILE_REFCOUNT, OPEN_FILES};
+    unsafe {
+        for i in 3..256 {
+            if OPEN_FILES[i].is_none() {
+                OPEN_FILES[i] = Some(handle);
+                GLOBAL_FILE_REFCOUNT[i] = 1;
+                let local = assign_local_fd(i);
+                if local == u64::MAX {
+                    crate::fs::vfs::close_file(i);
+                }
+                return local;
+            }
+        }
+    }
+    match handle {
+        FileHandle::Socket { id } => crate::net::socket::release(id),
+        FileHandle::UnixSocket { id } => crate::net::unix::release(id),
+        FileHandle::Audio { stream } => crate::audio::release(stream),
+        _ => {}
+    }
+    u64::MAX
+}
+
 pub fn handle_remove(context: &mut CPUState) {
     let ptr = context.rdi as *const u8;
     let len = context.rsi as usize;
@@ -378,6 +418,9 @@ pub fn handle_read_file(context: &mut CPUState) {
                     }
                 }
                 FileHandle::Pipe { pipe } => { context.rax = pipe.read(buf) as u64; }
+                FileHandle::Socket { id } => { context.rax = super::net::recv_blocking(*id, buf, None); }
+                FileHandle::UnixSocket { id } => { context.rax = super::net::unix_read(*id, buf); }
+                FileHandle::Audio { .. } => { context.rax = u64::MAX; }
             }
         } else { context.rax = u64::MAX; }
         return;
@@ -418,6 +461,9 @@ pub fn handle_write_file(context: &mut CPUState) {
                     }
                 }
                 FileHandle::Pipe { pipe } => { context.rax = pipe.write(buf) as u64; }
+                FileHandle::Socket { id } => { context.rax = super::net::send_blocking(*id, buf, None); }
+                FileHandle::UnixSocket { id } => { context.rax = super::net::unix_write(*id, buf); }
+                FileHandle::Audio { stream } => { context.rax = super::audio::write_blocking(*stream, buf); }
             }
         } else { context.rax = u64::MAX; }
         return;
@@ -24,7 +24,5 @@ pub fn handle_date(context: &mut CPUState) {
 }
 
 pub fn handle_ticks(context: &mut CPUState) {
-    unsafe {
-        context.rax = crate::interrupts::task::SYSTEM_TICKS;
-    }
+    context.rax = crate::time::monotonic_ns() / 1_000_000;
 }
@@ -9,6 +9,10 @@ pub mod process;
 pub mod memory;
 pub mod window;
 pub mod misc;
+pub mod net;
+pub mod audio;
+pub mod clock;
+pub mod clipboard;
 
 pub const SYS_READ: u64 = 0;
 pub const SYS_WRITE: u64 = 1;
@@ -25,6 +29,18 @@ pub const SYS_IOCTL: u64 = 16;
 pub const SYS_PIPE: u64 = 22;
 pub const SYS_NANOSLEEP: u64 = 35;
 pub const SYS_GETPID: u64 = 39;
+pub const SYS_SOCKET: u64 = 41;
+pub const SYS_CONNECT: u64 = 42;
+pub const SYS_ACCEPT: u64 = 43;
+pub const SYS_SENDTO: u64 = 44;
+pub const SYS_RECVFROM: u64 = 45;
+pub const SYS_SENDMSG: u64 = 46;
+pub const SYS_RECVMSG: u64 = 47;
+pub const SYS_BIND: u64 = 49;
+pub const SYS_LISTEN: u64 = 50;
+pub const SYS_GETSOCKNAME: u64 = 51;
+pub const SYS_GETPEERNAME: u64 = 52;
+pub const SYS_SOCKETPAIR: u64 = 53;
 pub const SYS_EXECVE: u64 = 59;
 pub const SYS_EXIT: u64 = 60;
 pub const SYS_WAIT4: u64 = 61;
@@ -58,6 +74,25 @@ pub const SYS_THREAD_EXIT: u64 = 113;
 
 pub const SYS_SPAWN_EXT: u64 = 114;
 pub const SYS_GET_DATE: u64 = 115;
+pub const SYS_AUDIO_OPEN: u64 = 116;
+pub const SYS_AUDIO_VOLUME: u64 = 117;
+pub const SYS_SET_TILING: u64 = 118;
+pub const SYS_WAIT_EVENTS: u64 = 119;
+pub const SYS_CLIPBOARD_SET: u64 = 120;
+pub const SYS_CLIPBOARD_GET: u64 = 121;
+pub const SYS_CLIPBOARD_TYPE: u64 = 122;
+pub const SYS_LIST_WINDOWS: u64 = 123;
+pub const SYS_WATCH_WINDOWS: u64 = 124;
+pub const SYS_WINDOW_ACTION: u64 = 125;
+pub const SYS_CAPTURE: u64 = 126;
+pub const SYS_SET_DISPLAY_MODE: u64 = 127;
+pub const SYS_WORKSPACE: u64 = 128;
+pub const SYS_SET_CURSOR: u64 = 129;
+pub const SYS_SET_THEME: u64 = 130;
+pub const SYS_GET_THEME: u64 = 131;
+pub const SYS_CLOCK_GETTIME: u64 = 228;
+pub const SYS_CLOCK_GETRES: u64 = 229;
+pub const SYS_CLOCK_NANOSLEEP: u64 = 230;
 pub const SYS_DEBUG_PRINT: u64 = 999;
 pub const SYS_MOUNT: u64 = 165;
 
@@ -136,6 +171,18 @@ pub extern "C" fn syscall_dispatcher(context: &mut CPUState) {
         SYS_IOCTL => fs::handle_ioctl(context),
         SYS_PIPE => fs::handle_pipe(context),
         SYS_NANOSLEEP => process::handle_sleep(context),
+        SYS_SOCKET => net::handle_socket(context),
+        SYS_CONNECT => net::handle_connect(context),
+        SYS_ACCEPT => net::handle_accept(context),
+        SYS_SENDTO => net::handle_send(context),
+        SYS_RECVFROM => net::handle_recv(context),
+        SYS_SENDMSG => net::handle_sendmsg(context),
+        SYS_RECVMSG => net::handle_recvmsg(context),
+        SYS_BIND => net::handle_bind(context),
+        SYS_LISTEN => net::handle_listen(context),
+        SYS_GETSOCKNAME => net::handle_getsockname(context),
+        SYS_GETPEERNAME => net::handle_getpeername(context),
+        SYS_SOCKETPAIR => net::handle_socketpair(context),
         SYS_EXECVE => process::handle_spawn(context),
         SYS_EXIT => process::handle_exit(context),
         SYS_WAIT4 => process::handle_wait_pid(context),
@@ -149,15 +196,33 @@ pub extern "C" fn syscall_dispatcher(context: &mut CPUState) {
         SYS_UNLINK => fs::handle_remove(context),
 
         SYS_ADD_WINDOW => window::handle_add_window(context),
+        SYS_REMOVE_WINDOW => window::handle_remove_window(context),
         SYS_UPDATE_WINDOW => window::handle_update_window(context),
         SYS_UPDATE_WINDOW_AREA => window::handle_update_window_area(context),
         SYS_GET_EVENTS => window::handle_get_events(context),
+        SYS_WAIT_EVENTS => window::handle_wait_events(context),
         SYS_GET_SCREEN_WIDTH => window::handle_get_width(context),
         SYS_GET_SCREEN_HEIGHT => window::handle_get_height(context),
         SYS_GET_MOUSE => window::handle_get_mouse(context),
+        SYS_SET_TILING => window::handle_set_tiling(context),
+        SYS_LIST_WINDOWS => window::handle_list_windows(context),
+        SYS_WATCH_WINDOWS => window::handle_watch_windows(context),
+        SYS_WINDOW_ACTION => window::handle_window_action(context),
+        SYS_CAPTURE => window::handle_capture(context),
+        SYS_SET_DISPLAY_MODE => window::handle_set_display_mode(context),
+        SYS_WORKSPACE => window::handle_workspace(context),
+        SYS_SET_CURSOR => window::handle_set_cursor(context),
+        SYS_SET_THEME => window::handle_set_theme(context),
+        SYS_GET_THEME => window::handle_get_theme(context),
+        SYS_CLIPBOARD_SET => clipboard::handle_clipboard_set(context),
+        SYS_CLIPBOARD_GET => clipboard::handle_clipboard_get(context),
+        SYS_CLIPBOARD_TYPE => clipboard::handle_clipboard_type(context),
         SYS_GET_TIME => misc::handle_time(context),
         SYS_GET_DATE => misc::handle_date(context),
         SYS_GET_TICKS => misc::handle_ticks(context),
+        SYS_CLOCK_GETTIME => clock::handle_clock_gettime(context),
+        SYS_CLOCK_GETRES => clock::handle_clock_getres(context),
+        SYS_CLOCK_NANOSLEEP => clock::handle_clock_nanosleep(context),
         SYS_GET_PROCESS_LIST => process::handle_get_process_list(context),
         SYS_GET_PROCESS_MEM => memory::handle_get_process_mem(context),
         SYS_FTRUNCATE => fs::handle_ftruncate(context),
@@ -165,6 +230,9 @@ pub extern "C" fn syscall_dispatcher(context: &mut CPUState) {
         SYS_SPAWN_THREAD => process::handle_spawn_thread(context),
         SYS_THREAD_EXIT => process::handle_thread_exit(context),
 
+        SYS_AUDIO_OPEN => audio::handle_audio_open(context),
+        SYS_AUDIO_VOLUME => audio::handle_audio_volume(context),
+
         SYS_DEBUG_PRINT => misc::handle_debug_print(context),
         SYS_MOUNT => {
             context.rax = 0;
@@ -0,0 +1,599 @@
+use crate::fs::vfs::FileHandle;
+use crate::interrupts::task::CPUState;
+use crate::net::socket::{SockAddr, SocketError, SocketType, AF_INET, SOCK_DGRAM, SOCK_STREAM};
+use crate::net::unix::{UnixReceived, UnixTable, AF_UNIX, MAX_FDS_PER_MESSAGE, UNIX};
+use crate::net::{NetStack, NET};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::arch::asm;
+
+const SOL_SOCKET: i32 = 1;
+const SCM_RIGHTS: i32 = 1;
+const MSG_CTRUNC: i32 = 0x08;
+const MSG_TRUNC: i32 = 0x20;
+
+const UNIX_PATH_MAX: usize = 108;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct SockAddrIn {
+    family: u16,
+    port: u16,
+    addr: [u8; 4],
+    zero: [u8; 8],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct SockAddrUn {
+    family: u16,
+    path: [u8; UNIX_PATH_MAX],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct IoVec {
+    base: u64,
+    len: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct MsgHdr {
+    name: u64,
+    namelen: u32,
+    iov: u64,
+    iovlen: u64,
+    control: u64,
+    controllen: u64,
+    flags: i32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct CmsgHdr {
+    len: u64,
+    level: i32,
+    ty: i32,
+}
+
+const CMSG_HDR_LEN: usize = core::mem::size_of::<CmsgHdr>();
+
+enum SocketRef {
+    Inet(usize),
+    Unix(usize),
+}
+
+fn read_sockaddr(ptr: u64, len: u64) -> Option<SockAddr> {
+    if ptr == 0 || (len as usize) < core::mem::size_of::<SockAddrIn>() {
+        return None;
+    }
+    let raw = unsafe { core::ptr::read_unaligned(ptr as *const SockAddrIn) };
+    if raw.family as u64 != AF_INET {
+        return None;
+    }
+    Some(SockAddr { ip: raw.addr, port: u16::from_be(raw.port) })
+}
+
+fn write_sockaddr(ptr: u64, len_ptr: u64, addr: SockAddr) {
+    if ptr == 0 {
+        return;
+    }
+    let raw = SockAddrIn { family: AF_INET as u16, port: addr.port.to_be(), addr: addr.ip, zero: [0; 8] };
+    unsafe {
+        if len_ptr != 0 {
+            let len = len_ptr as *mut u32;
+            if (*len as usize) < core::mem::size_of::<SockAddrIn>() {
+                return;
+            }
+            *len = core::mem::size_of::<SockAddrIn>() as u32;
+        }
+        core::ptr::write_unaligned(ptr as *mut SockAddrIn, raw);
+    }
+}
+
+fn read_unix_path(ptr: u64, len: u64) -> Option<String> {
+    if ptr == 0 || (len as usize) <= 2 {
+        return None;
+    }
+    let family = unsafe { core::ptr::read_unaligned(ptr as *const u16) };
+    if family as u64 != AF_UNIX {
+        return None;
+    }
+
+    let max = (len as usize - 2).min(UNIX_PATH_MAX);
+    let bytes = unsafe { core::slice::from_raw_parts((ptr + 2) as *const u8, max) };
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(max);
+    if end == 0 {
+        return None;
+    }
+
+    let path = String::from_utf8_lossy(&bytes[..end]).into_owned();
+    Some(super::fs::resolve_path(&super::fs::get_current_cwd(), &path))
+}
+
+fn write_unix_path(ptr: u64, len_ptr: u64, path: Option<&str>) {
+    if ptr == 0 || len_ptr == 0 {
+        return;
+    }
+    let mut raw = SockAddrUn { family: AF_UNIX as u16, path: [0; UNIX_PATH_MAX] };
+    let bytes = path.unwrap_or("").as_bytes();
+    let n = bytes.len().min(UNIX_PATH_MAX - 1);
+    raw.path[..n].copy_from_slice(&bytes[..n]);
+
+    unsafe {
+        let len = len_ptr as *mut u32;
+        let size = (2 + n + 1).min(*len as usize);
+        core::ptr::copy_nonoverlapping(&raw as *const SockAddrUn as *const u8, ptr as *mut u8, size);
+        *len = (2 + n + 1) as u32;
+    }
+}
+
+fn global_fd(local_fd: u64) -> Option<usize> {
+    let tm = crate::interrupts::task::TASK_MANAGER.int_lock();
+    let current = tm.current_task;
+    if current < 0 || local_fd >= 16 {
+        return None;
+    }
+    let thread = tm.tasks[current as usize].as_ref()?;
+    let proc = thread.process.as_ref().expect("Thread has no process");
+    let global_fd = proc.fd_table.lock()[local_fd as usize];
+    if global_fd == -1 { None } else { Some(global_fd as usize) }
+}
+
+fn lookup(local_fd: u64) -> Option<SocketRef> {
+    match crate::fs::vfs::get_file(global_fd(local_fd)?) {
+        Some(FileHandle::Socket { id }) => Some(SocketRef::Inet(*id)),
+        Some(FileHandle::UnixSocket { id }) => Some(SocketRef::Unix(*id)),
+        _ => None,
+    }
+}
+
+fn with_stack<T>(f: impl FnOnce(&mut NetStack) -> Result<T, SocketError>) -> Result<T, SocketError> {
+    let mut guard = NET.int_lock();
+    match guard.as_mut() {
+        Some(stack) => f(stack),
+        None => Err(SocketError::InvalidArgument),
+    }
+}
+
+fn block_on<T>(mut f: impl FnMut(&mut NetStack) -> Result<T, SocketError>) -> Result<T, SocketError> {
+    loop {
+        crate::net::poll();
+        match with_stack(&mut f) {
+            Err(SocketError::WouldBlock) => unsafe { asm!("int 0x81") },
+            other => return other,
+        }
+    }
+}
+
+fn block_on_unix<T>(mut f: impl FnMut(&mut UnixTable) -> Result<T, SocketError>) -> Result<T, SocketError> {
+    loop {
+        let res = f(&mut UNIX.int_lock());
+        match res {
+            Err(SocketError::WouldBlock) => unsafe { asm!("int 0x81") },
+            other => return other,
+        }
+    }
+}
+
+fn close_local(local_fd: u64) {
+    let tm = crate::interrupts::task::TASK_MANAGER.int_lock();
+    if let Some(thread) = tm.current_task_idx().and_then(|i| tm.tasks[i].as_ref()) {
+        let proc = thread.process.as_ref().expect("Thread has no process");
+        let mut fd_table = proc.fd_table.lock();
+        let global = fd_table[local_fd as usize];
+        if global != -1 {
+            fd_table[local_fd as usize] = -1;
+            crate::fs::vfs::close_file(global as usize);
+        }
+    }
+}
+
+fn close_all(fds: Vec<usize>) {
+    for fd in fds {
+        crate::fs::vfs::close_file(fd);
+    }
+}
+
+pub fn recv_blocking(id: usize, buf: &mut [u8], from: Option<(u64, u64)>) -> u64 {
+    match block_on(|stack| stack.socket_recv(id, buf)) {
+        Ok((n, addr)) => {
+            if let (Some((ptr, len_ptr)), Some(addr)) = (from, addr) {
+                write_sockaddr(ptr, len_ptr, addr);
+            }
+            n as u64
+        }
+        Err(_) => u64::MAX,
+    }
+}
+
+pub fn send_blocking(id: usize, buf: &[u8], dest: Option<SockAddr>) -> u64 {
+    match block_on(|stack| stack.socket_send(id, buf, dest)) {
+        Ok(n) => n as u64,
+        Err(_) => u64::MAX,
+    }
+}
+
+fn unix_recv_blocking(id: usize, buf: &mut [u8]) -> Result<UnixReceived, SocketError> {
+    block_on_unix(|table| table.recv(id, buf))
+}
+
+fn unix_send_blocking(id: usize, buf: &[u8], mut fds: Vec<usize>, dest: Option<&str>) -> u64 {
+    let res = block_on_unix(|table| table.send(id, buf, &mut fds, dest));
+    close_all(fds);
+    match res {
+        Ok(n) => n as u64,
+        Err(_) => u64::MAX,
+    }
+}
+
+pub fn unix_read(id: usize, buf: &mut [u8]) -> u64 {
+    match unix_recv_blocking(id, buf) {
+        Ok(received) => {
+            close_all(received.fds);
+            received.len as u64
+        }
+        Err(_) => u64::MAX,
+    }
+}
+
+pub fn unix_write(id: usize, buf: &[u8]) -> u64 {
+    unix_send_blocking(id, buf, Vec::new(), None)
+}
+
+fn parse_type(raw: u64) -> Option<SocketType> {
+    match raw & 0xF {
+        SOCK_STREAM => Some(SocketType::Stream),
+        SOCK_DGRAM => Some(SocketType::Datagram),
+        _ => None,
+    }
+}
+
+pub fn handle_socket(context: &mut CPUState) {
+    let domain = context.rdi;
+    let sock_type = match parse_type(context.rsi) {
+        Some(t) => t,
+        None => { context.rax = u64::MAX; return; }
+    };
+
+    context.rax = match domain {
+        AF_INET => match with_stack(|stack| stack.socket_create(sock_type)) {
+            Ok(id) => super::fs::install_handle(FileHandle::Socket { id }),
+            Err(_) => u64::MAX,
+        },
+        AF_UNIX => match UNIX.int_lock().create(sock_type) {
+            Ok(id) => super::fs::install_handle(FileHandle::UnixSocket { id }),
+            Err(_) => u64::MAX,
+        },
+        _ => u64::MAX,
+    };
+}
+
+pub fn handle_socketpair(context: &mut CPUState) {
+    let fds_ptr = context.r10 as *mut i32;
+    let sock_type = parse_type(context.rsi);
+    if context.rdi != AF_UNIX || sock_type.is_none() || fds_ptr.is_null() {
+        context.rax = u64::MAX;
+        return;
+    }
+
+    let pair = UNIX.int_lock().pair(sock_type.unwrap());
+    let (a, b) = match pair {
+        Ok(p) => p,
+        Err(_) => { context.rax = u64::MAX; return; }
+    };
+
+    let la = super::fs::install_handle(FileHandle::UnixSocket { id: a });
+    let lb = super::fs::install_handle(FileHandle::UnixSocket { id: b });
+    if la == u64::MAX || lb == u64::MAX {
+        for local in [la, lb] {
+            if local != u64::MAX {
+                close_local(local);
+            }
+        }
+        context.rax = u64::MAX;
+        return;
+    }
+
+    unsafe {
+        *fds_ptr.add(0) = la as i32;
+        *fds_ptr.add(1) = lb as i32;
+    }
+    context.rax = 0;
+}
+
+pub fn handle_bind(context: &mut CPUState) {
+    let res = match lookup(context.rdi) {
+        Some(SocketRef::Inet(id)) => match read_sockaddr(context.rsi, context.rdx) {
+            Some(addr) => with_stack(|stack| stack.socket_bind(id, addr)),
+            None => Err(SocketError::InvalidArgument),
+        },
+        Some(SocketRef::Unix(id)) => match read_unix_path(context.rsi, context.rdx) {
+            Some(path) => UNIX.int_lock().bind(id, path),
+            None => Err(SocketError::InvalidArgument),
+        },
+        None => Err(SocketError::InvalidArgument),
+    };
+    context.rax = if res.is_ok() { 0 } else { u64::MAX };
+}
+
+pub fn handle_listen(context: &mut CPUState) {
+    let backlog = context.rsi as usize;
+    let res = match lookup(context.rdi) {
+        Some(SocketRef::Inet(id)) => with_stack(|stack| stack.socket_listen(id, backlog)),
+        Some(SocketRef::Unix(id)) => UNIX.int_lock().listen(id, backlog),
+        None => Err(SocketError::InvalidArgument),
+    };
+    context.rax = if res.is_ok() { 0 } else { u64::MAX };
+}
+
+pub fn handle_accept(context: &mut CPUState) {
+    let addr_ptr = context.rsi;
+    let len_ptr = context.rdx;
+
+    context.rax = match lookup(context.rdi) {
+        Some(SocketRef::Inet(id)) => match block_on(|stack| stack.socket_accept(id)) {
+            Ok((child, peer)) => {
+                write_sockaddr(addr_ptr, len_ptr, peer);
+                super::fs::install_handle(FileHandle::Socket { id: child })
+            }
+            Err(_) => u64::MAX,
+        },
+        Some(SocketRef::Unix(id)) => match block_on_unix(|table| table.accept(id)) {
+            Ok(child) => {
+                write_unix_path(addr_ptr, len_ptr, None);
+                super::fs::install_handle(FileHandle::UnixSocket { id: child })
+            }
+            Err(_) => u64::MAX,
+        },
+        None => u64::MAX,
+    };
+}
+
+pub fn handle_connect(context: &mut CPUState) {
+    let res = match lookup(context.rdi) {
+        Some(SocketRef::Inet(id)) => match read_sockaddr(context.rsi, context.rdx) {
+            Some(addr) => with_stack(|stack| stack.socket_connect(id, addr))
+                .and_then(|_| block_on(|stack| stack.socket_connect_status(id))),
+            None => Err(SocketError::InvalidArgument),
+        },
+        Some(SocketRef::Unix(id)) => match read_unix_path(context.rsi, context.rdx) {
+            Some(path) => block_on_unix(|table| table.connect(id, &path)),
+            None => Err(SocketError::InvalidArgument),
+        },
+        None => Err(SocketError::InvalidArgument),
+    };
+    context.rax = if res.is_ok() { 0 } else { u64::MAX };
+}
+
+pub fn handle_send(context: &mut CPUState) {
+    let buf = unsafe { core::slice::from_raw_parts(context.rsi as *const u8, context.rdx as usize) };
+    let (addr_ptr, addr_len) = (context.r8, context.r9);
+
+    context.rax = match lookup(context.rdi) {
+        Some(SocketRef::Inet(id)) if addr_ptr != 0 => match read_sockaddr(addr_ptr, addr_len) {
+            Some(addr) => send_blocking(id, buf, Some(addr)),
+            None => u64::MAX,
+        },
+        Some(SocketRef::Inet(id)) => send_blocking(id, buf, None),
+        Some(SocketRef::Unix(id)) if addr_ptr != 0 => match read_unix_path(addr_ptr, addr_len) {
+            Some(path) => unix_send_blocking(id, buf, Vec::new(), Some(&path)),
+            None => u64::MAX,
+        },
+        Some(SocketRef::Unix(id)) => unix_write(id, buf),
+        None => u64::MAX,
+    };
+}
+
+pub fn handle_recv(context: &mut CPUState) {
+    let buf = unsafe { core::slice::from_raw_parts_mut(context.rsi as *mut u8, context.rdx as usize) };
+    let (addr_ptr, len_ptr) = (context.r8, context.r9);
+
+    context.rax = match lookup(context.rdi) {
+        Some(SocketRef::Inet(id)) => recv_blocking(id, buf, if addr_ptr != 0 { Some((addr_ptr, len_ptr)) } else { None }),
+        Some(SocketRef::Unix(id)) => match unix_recv_blocking(id, buf) {
+            Ok(received) => {
+                write_unix_path(addr_ptr, len_ptr, received.from.as_deref());
+                close_all(received.fds);
+                received.len as u64
+            }
+            Err(_) => u64::MAX,
+        },
+        None => u64::MAX,
+    };
+}
+
+fn read_control_fds(hdr: &MsgHdr) -> Option<Vec<usize>> {
+    let mut fds = Vec::new();
+    if hdr.control == 0 {
+        return Some(fds);
+    }
+
+    let mut offset = 0usize;
+    while offset + CMSG_HDR_LEN <= hdr.controllen as usize {
+        let cmsg = unsafe { core::ptr::read_unaligned((hdr.control as usize + offset) as *const CmsgHdr) };
+        let len = cmsg.len as usize;
+        if len < CMSG_HDR_LEN || offset + len > hdr.controllen as usize {
+            break;
+        }
+
+        if cmsg.level == SOL_SOCKET && cmsg.ty == SCM_RIGHTS {
+            let data = (hdr.control as usize + offset + CMSG_HDR_LEN) as *const i32;
+            for i in 0..(len - CMSG_HDR_LEN) / 4 {
+                let local = unsafe { core::ptr::read_unaligned(data.add(i)) };
+                let global = match global_fd(local as u64) {
+                    Some(g) if fds.len() < MAX_FDS_PER_MESSAGE => g,
+                    _ => {
+                        close_all(fds);
+                        return None;
+                    }
+                };
+                crate::fs::vfs::increment_ref(global);
+                fds.push(global);
+            }
+        }
+        offset += (len + 7) & !7;
+    }
+    Some(fds)
+}
+
+fn write_control_fds(hdr: &mut MsgHdr, fds: Vec<usize>) {
+    let capacity = if hdr.control == 0 { 0 } else { (hdr.controllen as usize).saturating_sub(CMSG_HDR_LEN) / 4 };
+    hdr.controllen = 0;
+    if fds.is_empty() {
+        return;
+    }
+
+    let mut locals = Vec::new();
+    for (i, global) in fds.into_iter().enumerate() {
+        let local = if i < capacity { super::fs::assign_local_fd(global) } else { u64::MAX };
+        if local == u64::MAX {
+            crate::fs::vfs::close_file(global);
+            hdr.flags |= MSG_CTRUNC;
+        } else {
+            locals.push(local as i32);
+        }
+    }
+
+    if !locals.is_empty() {
+        let len = CMSG_HDR_LEN + locals.len() * 4;
+        let cmsg = CmsgHdr { len: len as u64, level: SOL_SOCKET, ty: SCM_RIGHTS };
+        unsafe {
+            core::ptr::write_unaligned(hdr.control as *mut CmsgHdr, cmsg);
+            let data = (hdr.control as usize + CMSG_HDR_LEN) as *mut i32;
+            for (i, fd) in locals.iter().enumerate() {
+                core::ptr::write_unaligned(data.add(i), *fd);
+            }
+        }
+        hdr.controllen = len as u64;
+    }
+}
+
+fn iovecs(hdr: &MsgHdr) -> &'static [IoVec] {
+    if hdr.iov == 0 || hdr.iovlen == 0 {
+        return &[];
+    }
+    unsafe { core::slice::from_raw_parts(hdr.iov as *const IoVec, hdr.iovlen as usize) }
+}
+
+pub fn handle_sendmsg(context: &mut CPUState) {
+    let hdr_ptr = context.rsi as *const MsgHdr;
+    if hdr_ptr.is_null() {
+        context.rax = u64::MAX;
+        return;
+    }
+    let hdr = unsafe { core::ptr::read_unaligned(hdr_ptr) };
+
+    let mut data = Vec::new();
+    for iov in iovecs(&hdr) {
+        data.extend_from_slice(unsafe { core::slice::from_raw_parts(iov.base as *const u8, iov.len as usize) });
+    }
+
+    context.rax = match lookup(context.rdi) {
+        Some(SocketRef::Inet(id)) => {
+            let dest = if hdr.name != 0 { read_sockaddr(hdr.name, hdr.namelen as u64) } else { None };
+            send_blocking(id, &data, dest)
+        }
+        Some(SocketRef::Unix(id)) => match read_control_fds(&hdr) {
+            Some(fds) => {
+                let dest = if hdr.name != 0 { read_unix_path(hdr.name, hdr.namelen as u64) } else { None };
+                unix_send_blocking(id, &data, fds, dest.as_deref())
+            }
+            None => u64::MAX,
+        },
+        None => u64::MAX,
+    };
+}
+
+pub fn handle_recvmsg(context: &mut CPUState) {
+    let hdr_ptr = context.rsi as *mut MsgHdr;
+    if hdr_ptr.is_null() {
+        context.rax = u64::MAX;
+        return;
+    }
+    let mut hdr = unsafe { core::ptr::read_unaligned(hdr_ptr) };
+    let iovs = iovecs(&hdr);
+    let total: usize = iovs.iter().map(|iov| iov.len as usize).sum();
+    let mut data = alloc::vec![0u8; total];
+    let namelen_ptr = hdr_ptr as u64 + core::mem::offset_of!(MsgHdr, namelen) as u64;
+    hdr.flags = 0;
+
+    let received = match lookup(context.rdi) {
+        Some(SocketRef::Inet(id)) => match block_on(|stack| stack.socket_recv(id, &mut data)) {
+            Ok((n, addr)) => {
+                if let (true, Some(addr)) = (hdr.name != 0, addr) {
+                    write_sockaddr(hdr.name, namelen_ptr, addr);
+                }
+                write_control_fds(&mut hdr, Vec::new());
+                Some(n)
+            }
+            Err(_) => None,
+        },
+        Some(SocketRef::Unix(id)) => match unix_recv_blocking(id, &mut data) {
+            Ok(received) => {
+                if hdr.name != 0 {
+                    write_unix_path(hdr.name, namelen_ptr, received.from.as_deref());
+                }
+                if received.truncated {
+                    hdr.flags |= MSG_TRUNC;
+                }
+                write_control_fds(&mut hdr, received.fds);
+                Some(received.len)
+            }
+            Err(_) => None,
+        },
+        None => None,
+    };
+
+    let n = match received {
+        Some(n) => n,
+        None => { context.rax = u64::MAX; return; }
+    };
+
+    let mut copied = 0;
+    for iov in iovs {
+        if copied >= n {
+            break;
+        }
+        let chunk = (iov.len as usize).min(n - copied);
+        unsafe { core::ptr::copy_nonoverlapping(data.as_ptr().add(copied), iov.base as *mut u8, chunk) };
+        copied += chunk;
+    }
+
+    unsafe {
+        (*hdr_ptr).controllen = hdr.controllen;
+        (*hdr_ptr).flags = hdr.flags;
+    }
+    context.rax = n as u64;
+}
+
+pub fn handle_getsockname(context: &mut CPUState) {
+    context.rax = match lookup(context.rdi) {
+        Some(SocketRef::Inet(id)) => match with_stack(|stack| stack.socket_local(id).ok_or(SocketError::InvalidArgument)) {
+            Ok(addr) => { write_sockaddr(context.rsi, context.rdx, addr); 0 }
+            Err(_) => u64::MAX,
+        },
+        Some(SocketRef::Unix(id)) => {
+            let path = UNIX.int_lock().local_path(id);
+            write_unix_path(context.rsi, context.rdx, path.as_deref());
+            0
+        }
+        None => u64::MAX,
+    };
+}
+
+pub fn handle_getpeername(context: &mut CPUState) {
+    context.rax = match lookup(context.rdi) {
+        Some(SocketRef::Inet(id)) => match with_stack(|stack| stack.socket_peer(id).ok_or(SocketError::NotConnected)) {
+            Ok(addr) => { write_sockaddr(context.rsi, context.rdx, addr); 0 }
+            Err(_) => u64::MAX,
+        },
+        Some(SocketRef::Unix(id)) => {
+            let path = UNIX.int_lock().peer_path(id);
+            write_unix_path(context.rsi, context.rdx, path.as_deref());
+            0
+        }
+        None => u64::MAX,
+    };
+}
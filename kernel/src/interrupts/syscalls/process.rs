@@ -305,16 +305,8 @@ pub fn handle_get_process_list(context: &mut CPUState) {
 }
 
 pub fn handle_sleep(context: &mut CPUState) {
-    let duration = context.rdi;
-    let mut tm = crate::interrupts::task::TASK_MANAGER.int_lock();
-    let current = tm.current_task;
-
-    if current >= 0 {
-        if let Some(task) = tm.tasks[current as usize].as_mut() {
-            task.wake_ticks = unsafe { crate::interrupts::task::SYSTEM_TICKS } + duration;
-            task.state = crate::interrupts::task::TaskState::Sleeping;
-        }
-    }
+    let duration_ms = context.rdi;
+    super::clock::sleep_until(crate::time::monotonic_ns().saturating_add(duration_ms.saturating_mul(1_000_000)));
 }
 
 pub fn handle_spawn_thread(context: &mut CPUState) {
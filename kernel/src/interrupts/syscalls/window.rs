@@ -1,8 +1,13 @@
 use crate::interrupts::task::CPUState;
 use crate::window_manager::composer::COMPOSER;
-use crate::window_manager::display::DISPLAY_SERVER;
+use crate::window_manager::cursor::{CursorImage, CursorShape, CURSORS, MAX_CURSOR_SIZE};
+use crate::window_manager::display::{ScanoutMode, DISPLAY_SERVER};
+use crate::window_manager::events::{Event, EVENT_QUEUES};
 use crate::window_manager::input::MOUSE;
-use crate::window_manager::window::Window;
+use crate::window_manager::theme::{self, MAX_THEME_NAME};
+use crate::window_manager::tiling::Layout;
+use crate::window_manager::window::{Window, WindowInfo};
+use crate::window_manager::workspace::{WORKSPACES, WORKSPACE_COUNT};
 
 #[derive(Debug, Clone, Copy)]
 struct Mapping {
@@ -118,43 +123,127 @@ pub fn handle_update_window(context: &mut CPUState) {
 
 pub fn handle_update_window_area(context: &mut CPUState) {
     let wid = context.rdi as usize;
-    let x = context.rsi as i32;
-    let y = context.rdx as i32;
-    let w = context.r10 as u32;
-    let h = context.r8 as u32;
+    let x = context.rsi as usize;
+    let y = context.rdx as usize;
+    let w = context.r10 as usize;
+    let h = context.r8 as usize;
 
     unsafe {
         let composer = &mut *(&raw mut COMPOSER);
         if let Some(win) = composer.find_window_id(wid) {
-            let global_x = win.x as i32 + x;
-            let global_y = win.y as i32 + y;
-            composer.update_window_area_rect(global_x, global_y, w, h);
+            // Keep to the window, so a stale or bogus area can't repaint its neighbours.
+            let w = w.min(win.width.saturating_sub(x));
+            let h = h.min(win.height.saturating_sub(y));
+            if w > 0 && h > 0 {
+                let global_x = win.x as i32 + x as i32;
+                let global_y = win.y as i32 + y as i32;
+                composer.update_window_area_rect(global_x, global_y, w as u32, h as u32);
+            }
         }
     }
     context.rax = 1;
 }
 
+fn copy_events(events: alloc::vec::Vec<Event>, buf_ptr: *mut Event, max_events: usize) -> u64 {
+    if buf_ptr.is_null() {
+        return 0;
+    }
+    let user_slice = unsafe { core::slice::from_raw_parts_mut(buf_ptr, max_events) };
+    let mut count = 0;
+    for (i, evt) in events.into_iter().enumerate() {
+        if i < max_events {
+            user_slice[i] = evt;
+            count += 1;
+        }
+    }
+    count as u64
+}
+
 pub fn handle_get_events(context: &mut CPUState) {
     let wid = context.rdi as u32;
-    let buf_ptr = context.rsi as *mut crate::window_manager::events::Event;
+    let buf_ptr = context.rsi as *mut Event;
     let max_events = context.rdx as usize;
 
-    unsafe {
-        use crate::window_manager::events::GLOBAL_EVENT_QUEUE;
-        let events = GLOBAL_EVENT_QUEUE.lock().get_and_remove_events(wid, max_events);
+    let events = EVENT_QUEUES.lock().get_and_remove_events(wid, max_events);
+    context.rax = copy_events(events, buf_ptr, max_events);
+}
+
+/// rdi window, rsi/rdx event buffer, r10 timeout in ns (`u64::MAX` for none). With rdx 0
+/// nothing is consumed and the pending count is returned.
+pub fn handle_wait_events(context: &mut CPUState) {
+    let wid = context.rdi as u32;
+    let buf_ptr = context.rsi as *mut Event;
+    let max_events = context.rdx as usize;
+    let timeout_ns = context.r10;
+
+    let deadline = match timeout_ns {
+        u64::MAX => u64::MAX,
+        t => crate::time::monotonic_ns().saturating_add(t),
+    };
+
+    loop {
+        {
+            let mut queues = EVENT_QUEUES.lock();
+            if !queues.has_queue(wid) {
+                context.rax = u64::MAX;
+                return;
+            }
 
-        if !events.is_empty() {}
+            let pending = queues.pending(wid);
+            if pending > 0 || crate::time::monotonic_ns() >= deadline {
+                if max_events == 0 {
+                    context.rax = pending as u64;
+                } else {
+                    let events = queues.get_and_remove_events(wid, max_events);
+                    context.rax = copy_events(events, buf_ptr, max_events);
+                }
+                return;
+            }
 
-        let user_slice = core::slice::from_raw_parts_mut(buf_ptr, max_events);
-        let mut count = 0;
-        for (i, evt) in events.into_iter().enumerate() {
-            if i < max_events {
-                user_slice[i] = evt;
-                count += 1;
+            let current = crate::interrupts::task::TASK_MANAGER.int_lock().current_task_idx();
+            match current {
+                Some(thread) => {
+                    queues.set_waiter(wid, thread);
+                }
+                None => {
+                    context.rax = 0;
+                    return;
+                }
             }
         }
-        context.rax = count as u64;
+
+        super::clock::park(deadline);
+    }
+}
+
+pub(super) fn owns_window(wid: usize) -> bool {
+    let pid = {
+        let tm = crate::interrupts::task::TASK_MANAGER.int_lock();
+        tm.current_task_idx()
+            .and_then(|idx| tm.tasks[idx].as_ref())
+            .and_then(|thread| thread.process.as_ref())
+            .map(|proc| proc.pid)
+    };
+
+    let owner = unsafe { (*(&raw mut COMPOSER)).find_window_id(wid).map(|w| w.pid) };
+    matches!((owner, pid), (Some(owner), Some(pid)) if owner == pid)
+}
+
+pub fn handle_remove_window(context: &mut CPUState) {
+    let wid = context.rdi as usize;
+
+    if !owns_window(wid) {
+        context.rax = u64::MAX;
+        return;
+    }
+
+    unsafe {
+        (*(&raw mut COMPOSER)).remove_window(wid);
+        if wid < 256 {
+            WINDOW_MAPPINGS[wid] = Mapping { user_addr: 0, kernel_addr: 0 };
+        }
     }
+    context.rax = 0;
 }
 
 pub fn handle_get_width(context: &mut CPUState) {
@@ -174,4 +263,229 @@ pub fn handle_get_mouse(context: &mut CPUState) {
         let mouse = &*(&raw const MOUSE);
         context.rax = ((mouse.x as u64) << 32) | (mouse.y as u64);
     }
-}
\ No newline at end of file
+}
+
+pub fn handle_set_tiling(context: &mut CPUState) {
+    let layout = context.rdi;
+    let gap = context.rsi as usize;
+    let master_ratio = context.rdx as usize;
+
+    context.rax = match Layout::from_u64(layout) {
+        Some(layout) => {
+            unsafe { (*(&raw mut COMPOSER)).configure_tiling(layout, gap, master_ratio) };
+            0
+        }
+        None => u64::MAX,
+    };
+}
+
+/// rdi width, rsi height (both 0 for the host's preferred size), rdx scanout mode:
+/// 0 primary only, 1 mirrored, 2 spanned.
+pub fn handle_set_display_mode(context: &mut CPUState) {
+    let width = context.rdi;
+    let height = context.rsi;
+
+    context.rax = match ScanoutMode::from_u64(context.rdx) {
+        Some(mode) => match crate::window_manager::display::change_mode(width, height, mode) {
+            Ok(()) => 0,
+            Err(e) => {
+                crate::debugln!("Display mode: {}", e);
+                u64::MAX
+            }
+        },
+        None => u64::MAX,
+    };
+}
+
+/// rdi/rsi `WindowInfo` buffer, topmost first. Returns how many windows exist.
+pub fn handle_list_windows(context: &mut CPUState) {
+    let buf = context.rdi as *mut WindowInfo;
+    let max = context.rsi as usize;
+
+    let list = unsafe { (*(&raw const COMPOSER)).window_list() };
+    if !buf.is_null() {
+        let count = list.len().min(max);
+        unsafe { core::ptr::copy_nonoverlapping(list.as_ptr(), buf, count) };
+    }
+    context.rax = list.len() as u64;
+}
+
+/// Turns window list notifications for window rdi on (rsi != 0) or off.
+pub fn handle_watch_windows(context: &mut CPUState) {
+    let wid = context.rdi as usize;
+    let enable = context.rsi != 0;
+
+    context.rax = if owns_window(wid) && EVENT_QUEUES.lock().watch(wid as u32, enable) { 0 } else { u64::MAX };
+}
+
+pub const WINDOW_ACTIVATE: u64 = 0;
+pub const WINDOW_MINIMIZE: u64 = 1;
+pub const WINDOW_CLOSE: u64 = 2;
+pub const WINDOW_MOVE_TO_WORKSPACE: u64 = 3;
+
+/// rdi window, of any process, rsi action, rdx workspace for `WINDOW_MOVE_TO_WORKSPACE`.
+pub fn handle_window_action(context: &mut CPUState) {
+    let wid = context.rdi as usize;
+    let action = context.rsi;
+    let arg = context.rdx as usize;
+
+    unsafe {
+        let composer = &mut *(&raw mut COMPOSER);
+        if composer.find_window_id(wid).is_none() {
+            context.rax = u64::MAX;
+            return;
+        }
+
+        context.rax = match action {
+            WINDOW_ACTIVATE if composer.activate(wid) => 0,
+            WINDOW_MINIMIZE => {
+                composer.minimize_window(wid);
+                0
+            }
+            WINDOW_CLOSE => {
+                composer.request_close(wid);
+                0
+            }
+            WINDOW_MOVE_TO_WORKSPACE if composer.move_to_workspace(wid, arg) => 0,
+            _ => u64::MAX,
+        };
+    }
+}
+
+/// rdi workspace to show, or `u64::MAX` to only report the current one.
+pub fn handle_workspace(context: &mut CPUState) {
+    let target = context.rdi;
+
+    unsafe {
+        if target != u64::MAX {
+            if target as usize >= WORKSPACE_COUNT {
+                context.rax = u64::MAX;
+                return;
+            }
+            (*(&raw mut COMPOSER)).switch_workspace(target as usize);
+        }
+        context.rax = (*(&raw const WORKSPACES)).current as u64;
+    }
+}
+
+/// rdi window (0 for the screen), rsi/rdx pixel buffer. Returns `width << 32 | height` and
+/// copies nothing when the buffer is too small.
+pub fn handle_capture(context: &mut CPUState) {
+    let wid = context.rdi as usize;
+    let buf = context.rsi as *mut u32;
+    let capacity = context.rdx as usize;
+
+    unsafe {
+        let (src, width, height, stride, opaque) = if wid == 0 {
+            let ds = &*(&raw const DISPLAY_SERVER);
+            if ds.double_buffer == 0 {
+                context.rax = u64::MAX;
+                return;
+            }
+            (ds.double_buffer as *const u32, ds.width as usize, ds.height as usize, ds.pitch as usize / 4, true)
+        } else {
+            match (*(&raw mut COMPOSER)).find_window_id(wid) {
+                Some(w) if w.buffer != 0 => (w.buffer as *const u32, w.width, w.height, w.width, false),
+                _ => {
+                    context.rax = u64::MAX;
+                    return;
+                }
+            }
+        };
+
+        if !buf.is_null() && capacity >= width * height {
+            for y in 0..height {
+                let row = src.add(y * stride);
+                let dst = buf.add(y * width);
+                core::ptr::copy_nonoverlapping(row, dst, width);
+                if opaque {
+                    for x in 0..width {
+                        *dst.add(x) |= 0xFF000000;
+                    }
+                }
+            }
+        }
+
+        context.rax = ((width as u64) << 32) | height as u64;
+    }
+}
+
+pub const CURSOR_CUSTOM: u64 = 255;
+
+/// rdi window, rsi `CursorShape` or `CURSOR_CUSTOM` for an ARGB image at rdx, r10 wide and
+/// r8 high, hotspot in r9 as x << 32 | y.
+pub fn handle_set_cursor(context: &mut CPUState) {
+    let wid = context.rdi as usize;
+    let shape = context.rsi;
+
+    if !owns_window(wid) {
+        context.rax = u64::MAX;
+        return;
+    }
+
+    let image = if shape == CURSOR_CUSTOM {
+        let (width, height) = (context.r10 as usize, context.r8 as usize);
+        let (hot_x, hot_y) = ((context.r9 >> 32) as usize, (context.r9 & 0xFFFF_FFFF) as usize);
+        if context.rdx == 0 || width > MAX_CURSOR_SIZE || height > MAX_CURSOR_SIZE {
+            context.rax = u64::MAX;
+            return;
+        }
+        let pixels = unsafe { core::slice::from_raw_parts(context.rdx as *const u32, width * height) };
+        match CursorImage::new(width, height, hot_x, hot_y, pixels) {
+            Ok(image) => Some(image),
+            Err(e) => {
+                crate::debugln!("Cursor: {}", e);
+                context.rax = u64::MAX;
+                return;
+            }
+        }
+    } else {
+        match CursorShape::from_u64(shape) {
+            Some(CursorShape::Arrow) => None,
+            Some(shape) => Some(CursorImage::builtin(shape)),
+            None => {
+                context.rax = u64::MAX;
+                return;
+            }
+        }
+    };
+
+    unsafe {
+        (*(&raw mut CURSORS)).set_window(wid, image);
+        (*(&raw const MOUSE)).update_cursor_shape();
+    }
+    context.rax = 0;
+}
+
+/// rdi/rsi theme name. Every window gets `ThemeChanged`.
+pub fn handle_set_theme(context: &mut CPUState) {
+    let (ptr, len) = (context.rdi, context.rsi as usize);
+    if ptr == 0 || len > MAX_THEME_NAME {
+        context.rax = u64::MAX;
+        return;
+    }
+    let bytes = unsafe { core::slice::from_raw_parts(ptr as *const u8, len) };
+    let Ok(name) = core::str::from_utf8(bytes) else {
+        context.rax = u64::MAX;
+        return;
+    };
+
+    context.rax = match theme::set(name) {
+        Ok(()) => 0,
+        Err(e) => {
+            crate::debugln!("Theme: {}", e);
+            u64::MAX
+        }
+    };
+}
+
+/// Copies the theme name into the rdi/rsi buffer and returns its full length.
+pub fn handle_get_theme(context: &mut CPUState) {
+    let (buf, buf_len) = (context.rdi, context.rsi as usize);
+    let name = theme::current();
+    if buf != 0 {
+        let n = name.len().min(buf_len);
+        unsafe { core::ptr::copy_nonoverlapping(name.as_ptr(), buf as *mut u8, n) };
+    }
+    context.rax = name.len() as u64;
+}
@@ -39,7 +39,7 @@ pub struct Thread {
     pub user_stack: u64,
     pub cpu_state_ptr: u64,
     pub state: ThreadState,
-    pub wake_ticks: u64,
+    pub wake_ns: u64,
     pub exit_code: u64,
     pub name: [u8; 32],
     pub process: Option<Arc<Process>>,
@@ -108,6 +108,15 @@ pub static TASK_MANAGER: Mutex<TaskManager> = Mutex::new(TaskManager {
     tasks: [const { None }; MAX_THREADS],
 });
 
+static PENDING_WAKES: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+
+pub fn wake_thread(idx: usize) {
+    match TASK_MANAGER.try_int_lock() {
+        Some(mut tm) => tm.wake(idx),
+        None => PENDING_WAKES.int_lock().push(idx),
+    }
+}
+
 #[unsafe(no_mangle)]
 pub static mut KERNEL_STACK_PTR: u64 = 0;
 
@@ -131,7 +140,7 @@ impl Thread {
             user_stack: 0,
             cpu_state_ptr: 0,
             state: ThreadState::Null,
-            wake_ticks: 0,
+            wake_ns: 0,
             exit_code: 0,
             name: t_name,
             process: None,
@@ -182,14 +191,36 @@ impl TaskManager {
         }
     }
 
+    fn wake(&mut self, idx: usize) {
+        if let Some(Some(thread)) = self.tasks.get_mut(idx) {
+            if thread.state == ThreadState::Sleeping {
+                thread.state = ThreadState::Ready;
+            }
+        }
+    }
+
     pub fn schedule(&mut self, cpu_state: *mut CPUState) -> (*mut CPUState, u64, u64) {
+        let pending: Vec<usize> = PENDING_WAKES.int_lock().drain(..).collect();
+        for idx in pending {
+            self.wake(idx);
+        }
+
+        let now = crate::time::monotonic_ns();
+        let mut next_wake = u64::MAX;
         for i in 0..MAX_THREADS {
             if let Some(thread) = &mut self.tasks[i] {
-                if thread.state == ThreadState::Sleeping && unsafe { SYSTEM_TICKS } >= thread.wake_ticks {
-                    thread.state = ThreadState::Ready;
+                if thread.state == ThreadState::Sleeping {
+                    if now >= thread.wake_ns {
+                        thread.state = ThreadState::Ready;
+                    } else {
+                        next_wake = next_wake.min(thread.wake_ns);
+                    }
                 }
             }
         }
+        if next_wake != u64::MAX {
+            crate::time::arm_wakeup(next_wake);
+        }
 
         if self.current_task >= 0 {
             if let Some(thread) = &mut self.tasks[self.current_task as usize] {
@@ -428,6 +459,20 @@ pub extern "C" fn yield_handler() {
     }
 }
 
+#[unsafe(naked)]
+pub extern "C" fn deadline_handler() {
+    unsafe {
+        naked_asm!(
+            "push rbp", "push rax", "push rbx", "push rcx", "push rdx", "push rsi", "push rdi",
+            "push r8", "push r9", "push r10", "push r11", "push r12", "push r13", "push r14", "push r15",
+            "mov rdi, rsp", "call switch_deadline", "mov rsp, rax",
+            "pop r15", "pop r14", "pop r13", "pop r12", "pop r11", "pop r10", "pop r9", "pop r8",
+            "pop rdi", "pop rsi", "pop rdx", "pop rcx", "pop rbx", "pop rax", "pop rbp",
+            "iretq",
+        );
+    }
+}
+
 #[unsafe(no_mangle)]
 pub static mut SYSTEM_TICKS: u64 = 0;
 
@@ -441,10 +486,28 @@ pub extern "C" fn switch_yield(rsp: u64) -> u64 {
     unsafe { common_switch(rsp, false) }
 }
 
+#[unsafe(no_mangle)]
+pub extern "C" fn switch_deadline(rsp: u64) -> u64 {
+    unsafe {
+        if let Some(lapic) = (*(&raw const crate::drivers::lapic::LAPIC)).as_ref() {
+            lapic.eoi();
+        }
+        common_switch(rsp, false)
+    }
+}
+
 unsafe fn common_switch(rsp: u64, is_timer: bool) -> u64 {
     unsafe {
         if is_timer {
             SYSTEM_TICKS = SYSTEM_TICKS.wrapping_add(10);
+            if SYSTEM_TICKS % 100 == 0 {
+                crate::net::poll();
+            }
+            if SYSTEM_TICKS % 500 == 0 {
+                crate::window_manager::display::poll_display_events();
+            }
+            crate::audio::poll();
+            crate::drivers::input::virtio::poll();
         }
         let mut tm = TASK_MANAGER.lock();
 
@@ -15,6 +15,9 @@ mod tss;
 pub mod debug;
 pub mod window_manager;
 pub mod sync;
+mod net;
+mod audio;
+mod time;
 
 use crate::boot::{BootInfo, BOOT_INFO};
 use crate::fs::ext2::fs::Ext2;
@@ -80,14 +83,19 @@ pub extern "C" fn _start(bootinfo_ptr: u64) -> ! {
     crate::fs::virtio::init();
     crate::fs::vfs::init();
 
-    window_manager::events::GLOBAL_EVENT_QUEUE.lock().init();
+    window_manager::events::EVENT_QUEUES.lock().init();
     interrupts::task::TASK_MANAGER.lock().init();
 
     unsafe { (*(&raw mut DISPLAY_SERVER)).init(); }
 
+    net::init();
+    audio::init();
+
     debugln!("SIGNPOST: Drivers initialized.");
 
     drivers::periferics::mouse::init_mouse();
+    drivers::input::virtio::init();
+    time::init();
     drivers::periferics::timer::init_pit(100);
 
     crate::debugln!("Mounting Ext2...");
@@ -130,7 +138,7 @@ fn init_pat() {
     }
 }
 
-unsafe fn rdmsr(msr: u32) -> u64 {
+pub(crate) unsafe fn rdmsr(msr: u32) -> u64 {
     let (low, high): (u32, u32);
     unsafe { asm!("rdmsr", in("ecx") msr, out("eax") low, out("edx") high) };
     ((high as u64) << 32) | (low as u64)
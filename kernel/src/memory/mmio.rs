@@ -25,3 +25,7 @@ pub unsafe fn write_32(addr: *mut u8, val: u32) {
 pub unsafe fn write_64(addr: *mut u8, val: u64) {
     core::ptr::write_volatile(addr as *mut u64, val);
 }
+
+pub unsafe fn read_64(addr: *mut u8) -> u64 {
+    core::ptr::read_volatile(addr as *mut u64)
+}
\ No newline at end of file
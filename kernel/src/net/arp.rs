@@ -0,0 +1,149 @@
+use super::ethernet::{BROADCAST_MAC, ETHERTYPE_ARP, ETHERTYPE_IPV4};
+use super::{Ipv4Addr, NetStack};
+use alloc::vec::Vec;
+
+const ARP_REQUEST: u16 = 1;
+const ARP_REPLY: u16 = 2;
+
+const CACHE_SIZE: usize = 32;
+const ENTRY_TTL_MS: u64 = 300_000;
+const REQUEST_RETRY_MS: u64 = 1000;
+const REQUEST_GIVE_UP_MS: u64 = 5000;
+
+struct ArpEntry {
+    ip: Ipv4Addr,
+    mac: [u8; 6],
+    updated: u64,
+}
+
+struct PendingPacket {
+    next_hop: Ipv4Addr,
+    packet: Vec<u8>,
+    queued: u64,
+    last_request: u64,
+}
+
+pub struct ArpCache {
+    entries: Vec<ArpEntry>,
+    pending: Vec<PendingPacket>,
+}
+
+impl ArpCache {
+    pub fn new() -> Self {
+        ArpCache {
+            entries: Vec::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn lookup(&self, ip: Ipv4Addr) -> Option<[u8; 6]> {
+        let now = super::now();
+        self.entries
+            .iter()
+            .find(|e| e.ip == ip && now.saturating_sub(e.updated) < ENTRY_TTL_MS)
+            .map(|e| e.mac)
+    }
+
+    pub fn insert(&mut self, ip: Ipv4Addr, mac: [u8; 6]) {
+        let now = super::now();
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.ip == ip) {
+            entry.mac = mac;
+            entry.updated = now;
+            return;
+        }
+
+        if self.entries.len() >= CACHE_SIZE {
+            if let Some((oldest, _)) = self.entries.iter().enumerate().min_by_key(|(_, e)| e.updated) {
+                self.entries.remove(oldest);
+            }
+        }
+        self.entries.push(ArpEntry { ip, mac, updated: now });
+    }
+
+    pub fn queue(&mut self, next_hop: Ipv4Addr, packet: Vec<u8>) {
+        let now = super::now();
+        self.pending.push(PendingPacket { next_hop, packet, queued: now, last_request: now });
+    }
+
+    fn take_resolved(&mut self, ip: Ipv4Addr) -> Vec<Vec<u8>> {
+        let mut resolved = Vec::new();
+        let mut i = 0;
+        while i < self.pending.len() {
+            if self.pending[i].next_hop == ip {
+                resolved.push(self.pending.remove(i).packet);
+            } else {
+                i += 1;
+            }
+        }
+        resolved
+    }
+}
+
+pub fn handle(stack: &mut NetStack, packet: &[u8]) {
+    if packet.len() < 28 {
+        return;
+    }
+
+    let htype = u16::from_be_bytes([packet[0], packet[1]]);
+    let ptype = u16::from_be_bytes([packet[2], packet[3]]);
+    if htype != 1 || ptype != ETHERTYPE_IPV4 || packet[4] != 6 || packet[5] != 4 {
+        return;
+    }
+
+    let op = u16::from_be_bytes([packet[6], packet[7]]);
+    let mut sender_mac = [0u8; 6];
+    sender_mac.copy_from_slice(&packet[8..14]);
+    let sender_ip: Ipv4Addr = [packet[14], packet[15], packet[16], packet[17]];
+    let target_ip: Ipv4Addr = [packet[24], packet[25], packet[26], packet[27]];
+
+    if sender_ip != super::UNSPECIFIED {
+        stack.arp.insert(sender_ip, sender_mac);
+        for queued in stack.arp.take_resolved(sender_ip) {
+            stack.transmit_frame(sender_mac, ETHERTYPE_IPV4, &queued);
+        }
+    }
+
+    if op == ARP_REQUEST && target_ip == stack.iface.ip && stack.iface.ip != super::UNSPECIFIED {
+        let reply = build(ARP_REPLY, stack.iface.mac, stack.iface.ip, sender_mac, sender_ip);
+        stack.transmit_frame(sender_mac, ETHERTYPE_ARP, &reply);
+    }
+}
+
+pub fn request(stack: &mut NetStack, ip: Ipv4Addr) {
+    let req = build(ARP_REQUEST, stack.iface.mac, stack.iface.ip, [0; 6], ip);
+    stack.transmit_frame(BROADCAST_MAC, ETHERTYPE_ARP, &req);
+}
+
+fn build(op: u16, sender_mac: [u8; 6], sender_ip: Ipv4Addr, target_mac: [u8; 6], target_ip: Ipv4Addr) -> Vec<u8> {
+    let mut p = Vec::with_capacity(28);
+    p.extend_from_slice(&1u16.to_be_bytes());
+    p.extend_from_slice(&ETHERTYPE_IPV4.to_be_bytes());
+    p.push(6);
+    p.push(4);
+    p.extend_from_slice(&op.to_be_bytes());
+    p.extend_from_slice(&sender_mac);
+    p.extend_from_slice(&sender_ip);
+    p.extend_from_slice(&target_mac);
+    p.extend_from_slice(&target_ip);
+    p
+}
+
+impl NetStack {
+    pub fn arp_tick(&mut self, now: u64) {
+        self.arp.pending.retain(|p| now.saturating_sub(p.queued) < REQUEST_GIVE_UP_MS);
+
+        let mut retry: Vec<Ipv4Addr> = Vec::new();
+        for p in self.arp.pending.iter_mut() {
+            if now.saturating_sub(p.last_request) >= REQUEST_RETRY_MS {
+                p.last_request = now;
+                if !retry.contains(&p.next_hop) {
+                    retry.push(p.next_hop);
+                }
+            }
+        }
+
+        for ip in retry {
+            request(self, ip);
+        }
+    }
+}
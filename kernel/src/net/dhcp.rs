@@ -0,0 +1,214 @@
+use super::{Ipv4Addr, NetStack, BROADCAST, UNSPECIFIED};
+use crate::debugln;
+use alloc::vec::Vec;
+
+pub const CLIENT_PORT: u16 = 68;
+pub const SERVER_PORT: u16 = 67;
+
+const MAGIC_COOKIE: [u8; 4] = [99, 130, 83, 99];
+
+const DHCP_DISCOVER: u8 = 1;
+const DHCP_OFFER: u8 = 2;
+const DHCP_REQUEST: u8 = 3;
+const DHCP_ACK: u8 = 5;
+const DHCP_NAK: u8 = 6;
+
+const OPT_SUBNET_MASK: u8 = 1;
+const OPT_ROUTER: u8 = 3;
+const OPT_DNS: u8 = 6;
+const OPT_REQUESTED_IP: u8 = 50;
+const OPT_LEASE_TIME: u8 = 51;
+const OPT_MESSAGE_TYPE: u8 = 53;
+const OPT_SERVER_ID: u8 = 54;
+const OPT_PARAM_LIST: u8 = 55;
+const OPT_END: u8 = 255;
+
+const RETRY_MS: u64 = 4000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DhcpState {
+    Idle,
+    Selecting,
+    Requesting,
+    Bound,
+}
+
+pub struct DhcpClient {
+    pub state: DhcpState,
+    xid: u32,
+    offered: Ipv4Addr,
+    server: Ipv4Addr,
+    retry_at: u64,
+    renew_at: u64,
+}
+
+impl DhcpClient {
+    pub fn new() -> Self {
+        DhcpClient {
+            state: DhcpState::Idle,
+            xid: 0,
+            offered: UNSPECIFIED,
+            server: UNSPECIFIED,
+            retry_at: 0,
+            renew_at: 0,
+        }
+    }
+}
+
+struct DhcpReply {
+    msg_type: u8,
+    yiaddr: Ipv4Addr,
+    server: Ipv4Addr,
+    netmask: Option<Ipv4Addr>,
+    router: Option<Ipv4Addr>,
+    dns: Option<Ipv4Addr>,
+    lease_secs: Option<u32>,
+}
+
+fn option_addr(data: &[u8]) -> Option<Ipv4Addr> {
+    if data.len() >= 4 { Some([data[0], data[1], data[2], data[3]]) } else { None }
+}
+
+fn parse(packet: &[u8], xid: u32) -> Option<DhcpReply> {
+    if packet.len() < 240 || packet[0] != 2 {
+        return None;
+    }
+    if u32::from_be_bytes([packet[4], packet[5], packet[6], packet[7]]) != xid {
+        return None;
+    }
+    if packet[236..240] != MAGIC_COOKIE {
+        return None;
+    }
+
+    let mut reply = DhcpReply {
+        msg_type: 0,
+        yiaddr: [packet[16], packet[17], packet[18], packet[19]],
+        server: [packet[20], packet[21], packet[22], packet[23]],
+        netmask: None,
+        router: None,
+        dns: None,
+        lease_secs: None,
+    };
+
+    let mut i = 240;
+    while i < packet.len() {
+        let code = packet[i];
+        if code == OPT_END { break; }
+        if code == 0 { i += 1; continue; }
+        if i + 1 >= packet.len() { break; }
+        let len = packet[i + 1] as usize;
+        if i + 2 + len > packet.len() { break; }
+        let data = &packet[i + 2..i + 2 + len];
+
+        match code {
+            OPT_MESSAGE_TYPE if len == 1 => reply.msg_type = data[0],
+            OPT_SUBNET_MASK => reply.netmask = option_addr(data),
+            OPT_ROUTER => reply.router = option_addr(data),
+            OPT_DNS => reply.dns = option_addr(data),
+            OPT_SERVER_ID => {
+                if let Some(addr) = option_addr(data) { reply.server = addr; }
+            }
+            OPT_LEASE_TIME if len == 4 => reply.lease_secs = Some(u32::from_be_bytes([data[0], data[1], data[2], data[3]])),
+            _ => {}
+        }
+        i += 2 + len;
+    }
+
+    Some(reply)
+}
+
+fn build(msg_type: u8, xid: u32, mac: [u8; 6], requested: Option<(Ipv4Addr, Ipv4Addr)>) -> Vec<u8> {
+    let mut p = Vec::with_capacity(300);
+    p.extend_from_slice(&[1, 1, 6, 0]);
+    p.extend_from_slice(&xid.to_be_bytes());
+    p.extend_from_slice(&[0, 0]);
+    p.extend_from_slice(&0x8000u16.to_be_bytes());
+    p.extend_from_slice(&[0; 16]);
+    p.extend_from_slice(&mac);
+    p.extend_from_slice(&[0; 10]);
+    p.extend_from_slice(&[0; 192]);
+    p.extend_from_slice(&MAGIC_COOKIE);
+
+    p.extend_from_slice(&[OPT_MESSAGE_TYPE, 1, msg_type]);
+    if let Some((ip, server)) = requested {
+        p.extend_from_slice(&[OPT_REQUESTED_IP, 4]);
+        p.extend_from_slice(&ip);
+        p.extend_from_slice(&[OPT_SERVER_ID, 4]);
+        p.extend_from_slice(&server);
+    }
+    p.extend_from_slice(&[OPT_PARAM_LIST, 3, OPT_SUBNET_MASK, OPT_ROUTER, OPT_DNS]);
+    p.push(OPT_END);
+
+    while p.len() < 300 {
+        p.push(0);
+    }
+    p
+}
+
+impl NetStack {
+    pub fn dhcp_start(&mut self) {
+        let now = super::now();
+        self.dhcp.xid = u32::from_be_bytes([self.iface.mac[2], self.iface.mac[3], self.iface.mac[4], self.iface.mac[5]])
+            ^ (now as u32).wrapping_mul(2654435761);
+        self.dhcp.state = DhcpState::Selecting;
+        self.dhcp.retry_at = now + RETRY_MS;
+
+        let discover = build(DHCP_DISCOVER, self.dhcp.xid, self.iface.mac, None);
+        super::udp::send(self, UNSPECIFIED, CLIENT_PORT, BROADCAST, SERVER_PORT, &discover);
+        debugln!("DHCP: Sent DISCOVER (xid {:#x})", self.dhcp.xid);
+    }
+
+    fn dhcp_request(&mut self) {
+        let request = build(DHCP_REQUEST, self.dhcp.xid, self.iface.mac, Some((self.dhcp.offered, self.dhcp.server)));
+        super::udp::send(self, UNSPECIFIED, CLIENT_PORT, BROADCAST, SERVER_PORT, &request);
+    }
+
+    pub fn dhcp_handle(&mut self, packet: &[u8]) {
+        let reply = match parse(packet, self.dhcp.xid) {
+            Some(r) => r,
+            None => return,
+        };
+
+        match (self.dhcp.state, reply.msg_type) {
+            (DhcpState::Selecting, DHCP_OFFER) => {
+                self.dhcp.offered = reply.yiaddr;
+                self.dhcp.server = reply.server;
+                self.dhcp.state = DhcpState::Requesting;
+                self.dhcp.retry_at = super::now() + RETRY_MS;
+                self.dhcp_request();
+            }
+            (DhcpState::Requesting, DHCP_ACK) => {
+                self.iface.ip = reply.yiaddr;
+                self.iface.netmask = reply.netmask.unwrap_or([255, 255, 255, 0]);
+                self.iface.gateway = reply.router.unwrap_or(UNSPECIFIED);
+                self.iface.dns = reply.dns.unwrap_or(UNSPECIFIED);
+
+                let lease_ms = reply.lease_secs.unwrap_or(3600) as u64 * 1000;
+                self.dhcp.state = DhcpState::Bound;
+                self.dhcp.renew_at = super::now() + lease_ms / 2;
+
+                let ip = self.iface.ip;
+                let gw = self.iface.gateway;
+                debugln!("DHCP: Bound to {}.{}.{}.{}, gateway {}.{}.{}.{}", ip[0], ip[1], ip[2], ip[3], gw[0], gw[1], gw[2], gw[3]);
+            }
+            (DhcpState::Requesting, DHCP_NAK) => {
+                debugln!("DHCP: Received NAK, restarting.");
+                self.dhcp_start();
+            }
+            _ => {}
+        }
+    }
+
+    pub fn dhcp_tick(&mut self, now: u64) {
+        match self.dhcp.state {
+            DhcpState::Selecting | DhcpState::Requesting if now >= self.dhcp.retry_at => self.dhcp_start(),
+            DhcpState::Bound if now >= self.dhcp.renew_at => {
+                self.dhcp.state = DhcpState::Requesting;
+                self.dhcp.offered = self.iface.ip;
+                self.dhcp.retry_at = now + RETRY_MS;
+                self.dhcp_request();
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use alloc::vec::Vec;
+
+pub const ETHERTYPE_IPV4: u16 = 0x0800;
+pub const ETHERTYPE_ARP: u16 = 0x0806;
+
+pub const BROADCAST_MAC: [u8; 6] = [0xFF; 6];
+
+pub struct EthernetFrame<'a> {
+    pub dst: [u8; 6],
+    pub src: [u8; 6],
+    pub ethertype: u16,
+    pub payload: &'a [u8],
+}
+
+pub fn parse(frame: &[u8]) -> Option<EthernetFrame<'_>> {
+    if frame.len() < 14 {
+        return None;
+    }
+
+    let mut dst = [0u8; 6];
+    let mut src = [0u8; 6];
+    dst.copy_from_slice(&frame[0..6]);
+    src.copy_from_slice(&frame[6..12]);
+
+    Some(EthernetFrame {
+        dst,
+        src,
+        ethertype: u16::from_be_bytes([frame[12], frame[13]]),
+        payload: &frame[14..],
+    })
+}
+
+pub fn build(dst: [u8; 6], src: [u8; 6], ethertype: u16, payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(14 + payload.len().max(46));
+    frame.extend_from_slice(&dst);
+    frame.extend_from_slice(&src);
+    frame.extend_from_slice(&ethertype.to_be_bytes());
+    frame.extend_from_slice(payload);
+    while frame.len() < 60 {
+        frame.push(0);
+    }
+    frame
+}
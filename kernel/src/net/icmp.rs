@@ -0,0 +1,27 @@
+use super::ipv4::PROTO_ICMP;
+use super::{Ipv4Addr, NetStack};
+use alloc::vec::Vec;
+
+const ICMP_ECHO_REPLY: u8 = 0;
+const ICMP_ECHO_REQUEST: u8 = 8;
+
+pub fn handle(stack: &mut NetStack, src: Ipv4Addr, dst: Ipv4Addr, packet: &[u8]) {
+    if packet.len() < 8 || super::checksum(packet, 0) != 0 {
+        return;
+    }
+
+    if packet[0] == ICMP_ECHO_REQUEST && packet[1] == 0 {
+        if !stack.is_local(dst) {
+            return;
+        }
+
+        let mut reply = Vec::from(packet);
+        reply[0] = ICMP_ECHO_REPLY;
+        reply[2] = 0;
+        reply[3] = 0;
+        let csum = super::checksum(&reply, 0);
+        reply[2..4].copy_from_slice(&csum.to_be_bytes());
+
+        stack.send_ipv4(dst, src, PROTO_ICMP, &reply);
+    }
+}
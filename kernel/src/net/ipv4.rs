@@ -0,0 +1,88 @@
+use super::{Ipv4Addr, NetStack};
+use alloc::vec::Vec;
+
+pub const PROTO_ICMP: u8 = 1;
+pub const PROTO_TCP: u8 = 6;
+pub const PROTO_UDP: u8 = 17;
+
+const DEFAULT_TTL: u8 = 64;
+
+pub struct Ipv4Packet<'a> {
+    pub src: Ipv4Addr,
+    pub dst: Ipv4Addr,
+    pub protocol: u8,
+    pub payload: &'a [u8],
+}
+
+pub fn parse(packet: &[u8]) -> Option<Ipv4Packet<'_>> {
+    if packet.len() < 20 || (packet[0] >> 4) != 4 {
+        return None;
+    }
+
+    let ihl = ((packet[0] & 0x0F) as usize) * 4;
+    let total_len = u16::from_be_bytes([packet[2], packet[3]]) as usize;
+    if ihl < 20 || total_len < ihl || total_len > packet.len() {
+        return None;
+    }
+
+    if super::checksum(&packet[..ihl], 0) != 0 {
+        return None;
+    }
+
+    let flags_frag = u16::from_be_bytes([packet[6], packet[7]]);
+    let more_fragments = (flags_frag & 0x2000) != 0;
+    let frag_offset = flags_frag & 0x1FFF;
+    if more_fragments || frag_offset != 0 {
+        return None;
+    }
+
+    Some(Ipv4Packet {
+        src: [packet[12], packet[13], packet[14], packet[15]],
+        dst: [packet[16], packet[17], packet[18], packet[19]],
+        protocol: packet[9],
+        payload: &packet[ihl..total_len],
+    })
+}
+
+pub fn build(src: Ipv4Addr, dst: Ipv4Addr, protocol: u8, id: u16, payload: &[u8]) -> Vec<u8> {
+    let total_len = 20 + payload.len();
+    let mut p = Vec::with_capacity(total_len);
+    p.push(0x45);
+    p.push(0);
+    p.extend_from_slice(&(total_len as u16).to_be_bytes());
+    p.extend_from_slice(&id.to_be_bytes());
+    p.extend_from_slice(&0x4000u16.to_be_bytes());
+    p.push(DEFAULT_TTL);
+    p.push(protocol);
+    p.extend_from_slice(&[0, 0]);
+    p.extend_from_slice(&src);
+    p.extend_from_slice(&dst);
+
+    let csum = super::checksum(&p[..20], 0);
+    p[10..12].copy_from_slice(&csum.to_be_bytes());
+
+    p.extend_from_slice(payload);
+    p
+}
+
+pub fn handle(stack: &mut NetStack, packet: &[u8]) {
+    let ip = match parse(packet) {
+        Some(p) => p,
+        None => return,
+    };
+
+    let for_us = stack.is_local(ip.dst)
+        || ip.dst == super::BROADCAST
+        || stack.iface.ip == super::UNSPECIFIED
+        || stack.is_subnet_broadcast(ip.dst);
+    if !for_us {
+        return;
+    }
+
+    match ip.protocol {
+        PROTO_ICMP => super::icmp::handle(stack, ip.src, ip.dst, ip.payload),
+        PROTO_UDP => super::udp::handle(stack, ip.src, ip.dst, ip.payload),
+        PROTO_TCP => super::tcp::handle(stack, ip.src, ip.dst, ip.payload),
+        _ => {}
+    }
+}
@@ -0,0 +1,239 @@
+pub mod ethernet;
+pub mod arp;
+pub mod ipv4;
+pub mod icmp;
+pub mod udp;
+pub mod tcp;
+pub mod dhcp;
+pub mod socket;
+pub mod unix;
+
+use crate::debugln;
+use crate::drivers::net::virtio::VIRTIO_NET;
+use crate::sync::Mutex;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+pub type Ipv4Addr = [u8; 4];
+
+pub const UNSPECIFIED: Ipv4Addr = [0, 0, 0, 0];
+pub const BROADCAST: Ipv4Addr = [255, 255, 255, 255];
+pub const LOCALHOST: Ipv4Addr = [127, 0, 0, 1];
+
+pub struct Interface {
+    pub mac: [u8; 6],
+    pub ip: Ipv4Addr,
+    pub netmask: Ipv4Addr,
+    pub gateway: Ipv4Addr,
+    pub dns: Ipv4Addr,
+    pub link_up: bool,
+}
+
+pub struct NetStack {
+    pub iface: Interface,
+    pub arp: arp::ArpCache,
+    pub loopback: VecDeque<Vec<u8>>,
+    pub sockets: socket::SocketTable,
+    pub dhcp: dhcp::DhcpClient,
+    ip_id: u16,
+}
+
+pub static NET: Mutex<Option<NetStack>> = Mutex::new(None);
+
+pub fn init() {
+    let link_up = crate::drivers::net::virtio::init();
+    let mac = unsafe {
+        match (*(&raw const VIRTIO_NET)).as_ref() {
+            Some(dev) => dev.mac,
+            None => [0; 6],
+        }
+    };
+
+    let mut stack = NetStack {
+        iface: Interface {
+            mac,
+            ip: UNSPECIFIED,
+            netmask: UNSPECIFIED,
+            gateway: UNSPECIFIED,
+            dns: UNSPECIFIED,
+            link_up,
+        },
+        arp: arp::ArpCache::new(),
+        loopback: VecDeque::new(),
+        sockets: socket::SocketTable::new(),
+        dhcp: dhcp::DhcpClient::new(),
+        ip_id: 1,
+    };
+
+    if link_up {
+        unsafe {
+            if let Some(dev) = (*(&raw const VIRTIO_NET)).as_ref() {
+                if dev.irq < 16 {
+                    let vector = 32 + dev.irq as usize;
+                    (*(&raw mut crate::interrupts::idt::IDT)).add(vector, crate::interrupts::exceptions::net_handler as u64);
+                    let pics = &*(&raw const crate::interrupts::pic::PICS);
+                    if dev.irq < 8 {
+                        pics.master.unmask_irq(dev.irq);
+                    } else {
+                        pics.slave.unmask_irq(dev.irq - 8);
+                    }
+                }
+            }
+        }
+        stack.dhcp_start();
+    }
+
+    *NET.lock() = Some(stack);
+    debugln!("Net: Stack initialized (link {}).", if link_up { "up" } else { "down, loopback only" });
+}
+
+pub fn now() -> u64 {
+    unsafe { crate::interrupts::task::SYSTEM_TICKS }
+}
+
+pub fn poll() {
+    let mut guard = NET.int_lock();
+    if let Some(stack) = guard.as_mut() {
+        stack.poll();
+    }
+}
+
+pub fn handle_irq() -> u8 {
+    let irq = unsafe {
+        match (*(&raw const VIRTIO_NET)).as_ref() {
+            Some(dev) => {
+                dev.ack_interrupt();
+                dev.irq
+            }
+            None => return 0xFF,
+        }
+    };
+
+    let mut guard = NET.lock();
+    if let Some(stack) = guard.as_mut() {
+        stack.poll();
+    }
+    irq
+}
+
+impl NetStack {
+    pub fn poll(&mut self) {
+        let frames = unsafe {
+            match (*(&raw mut VIRTIO_NET)).as_mut() {
+                Some(dev) => dev.receive(),
+                None => Vec::new(),
+            }
+        };
+
+        for frame in frames {
+            self.handle_frame(&frame);
+        }
+
+        let mut budget = 64;
+        while let Some(packet) = self.loopback.pop_front() {
+            ipv4::handle(self, &packet);
+            budget -= 1;
+            if budget == 0 { break; }
+        }
+
+        let now = now();
+        tcp::tick(self, now);
+        self.dhcp_tick(now);
+        self.arp_tick(now);
+    }
+
+    fn handle_frame(&mut self, frame: &[u8]) {
+        if let Some(eth) = ethernet::parse(frame) {
+            if eth.dst != self.iface.mac && eth.dst != ethernet::BROADCAST_MAC {
+                return;
+            }
+            match eth.ethertype {
+                ethernet::ETHERTYPE_ARP => arp::handle(self, eth.payload),
+                ethernet::ETHERTYPE_IPV4 => ipv4::handle(self, eth.payload),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn transmit_frame(&mut self, dst_mac: [u8; 6], ethertype: u16, payload: &[u8]) -> bool {
+        let frame = ethernet::build(dst_mac, self.iface.mac, ethertype, payload);
+        unsafe {
+            match (*(&raw mut VIRTIO_NET)).as_mut() {
+                Some(dev) => dev.transmit(&frame),
+                None => false,
+            }
+        }
+    }
+
+    pub fn is_local(&self, ip: Ipv4Addr) -> bool {
+        ip[0] == 127 || (ip == self.iface.ip && ip != UNSPECIFIED)
+    }
+
+    pub fn source_for(&self, dst: Ipv4Addr) -> Ipv4Addr {
+        if dst[0] == 127 { LOCALHOST } else { self.iface.ip }
+    }
+
+    pub fn send_ipv4(&mut self, src: Ipv4Addr, dst: Ipv4Addr, protocol: u8, payload: &[u8]) -> bool {
+        let id = self.ip_id;
+        self.ip_id = self.ip_id.wrapping_add(1);
+        let packet = ipv4::build(src, dst, protocol, id, payload);
+
+        if self.is_local(dst) {
+            self.loopback.push_back(packet);
+            return true;
+        }
+
+        if !self.iface.link_up {
+            return false;
+        }
+
+        if dst == BROADCAST || self.is_subnet_broadcast(dst) {
+            return self.transmit_frame(ethernet::BROADCAST_MAC, ethernet::ETHERTYPE_IPV4, &packet);
+        }
+
+        let next_hop = if self.on_link(dst) || self.iface.gateway == UNSPECIFIED { dst } else { self.iface.gateway };
+
+        match self.arp.lookup(next_hop) {
+            Some(mac) => self.transmit_frame(mac, ethernet::ETHERTYPE_IPV4, &packet),
+            None => {
+                self.arp.queue(next_hop, packet);
+                arp::request(self, next_hop);
+                true
+            }
+        }
+    }
+
+    fn on_link(&self, ip: Ipv4Addr) -> bool {
+        (0..4).all(|i| (ip[i] & self.iface.netmask[i]) == (self.iface.ip[i] & self.iface.netmask[i]))
+    }
+
+    pub fn is_subnet_broadcast(&self, ip: Ipv4Addr) -> bool {
+        self.iface.netmask != UNSPECIFIED
+            && self.on_link(ip)
+            && (0..4).all(|i| (ip[i] | self.iface.netmask[i]) == 0xFF)
+    }
+}
+
+pub fn checksum(data: &[u8], initial: u32) -> u16 {
+    let mut sum = initial;
+    let mut chunks = data.chunks_exact(2);
+    for chunk in &mut chunks {
+        sum += u16::from_be_bytes([chunk[0], chunk[1]]) as u32;
+    }
+    if let [last] = chunks.remainder() {
+        sum += (*last as u32) << 8;
+    }
+    while (sum >> 16) != 0 {
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+pub fn pseudo_header_sum(src: Ipv4Addr, dst: Ipv4Addr, protocol: u8, length: usize) -> u32 {
+    u16::from_be_bytes([src[0], src[1]]) as u32
+        + u16::from_be_bytes([src[2], src[3]]) as u32
+        + u16::from_be_bytes([dst[0], dst[1]]) as u32
+        + u16::from_be_bytes([dst[2], dst[3]]) as u32
+        + protocol as u32
+        + length as u32
+}
@@ -0,0 +1,463 @@
+use super::tcp::{Tcb, TcpState};
+use super::{Ipv4Addr, NetStack, UNSPECIFIED};
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+pub const AF_INET: u64 = 2;
+pub const SOCK_STREAM: u64 = 1;
+pub const SOCK_DGRAM: u64 = 2;
+
+const MAX_SOCKETS: usize = 64;
+const EPHEMERAL_START: u16 = 49152;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SockAddr {
+    pub ip: Ipv4Addr,
+    pub port: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SocketType {
+    Stream,
+    Datagram,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SocketError {
+    WouldBlock,
+    InvalidArgument,
+    AddrInUse,
+    NotConnected,
+    ConnectionRefused,
+    ConnectionReset,
+    Unreachable,
+}
+
+pub struct Datagram {
+    pub src: Ipv4Addr,
+    pub src_port: u16,
+    pub data: Vec<u8>,
+}
+
+pub enum SocketState {
+    Unbound,
+    Udp { rx: VecDeque<Datagram>, peer: Option<SockAddr> },
+    Listening { backlog: usize, ready: VecDeque<usize> },
+    Tcp(Tcb),
+}
+
+pub struct Socket {
+    pub sock_type: SocketType,
+    pub local: SockAddr,
+    pub state: SocketState,
+    pub orphaned: bool,
+}
+
+pub struct SocketTable {
+    sockets: Vec<Option<Socket>>,
+    next_ephemeral: u16,
+}
+
+impl SocketTable {
+    pub fn new() -> Self {
+        let mut sockets = Vec::with_capacity(MAX_SOCKETS);
+        for _ in 0..MAX_SOCKETS {
+            sockets.push(None);
+        }
+        SocketTable { sockets, next_ephemeral: EPHEMERAL_START }
+    }
+
+    pub fn insert(&mut self, socket: Socket) -> Option<usize> {
+        let slot = self.sockets.iter().position(|s| s.is_none())?;
+        self.sockets[slot] = Some(socket);
+        Some(slot)
+    }
+
+    pub fn remove(&mut self, id: usize) {
+        if id < MAX_SOCKETS {
+            self.sockets[id] = None;
+        }
+    }
+
+    pub fn get(&self, id: usize) -> Option<&Socket> {
+        self.sockets.get(id)?.as_ref()
+    }
+
+    pub fn get_mut(&mut self, id: usize) -> Option<&mut Socket> {
+        self.sockets.get_mut(id)?.as_mut()
+    }
+
+    pub fn tcp_ids(&self) -> Vec<usize> {
+        self.sockets
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| matches!(s, Some(Socket { state: SocketState::Tcp(_), .. })))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    pub fn find_udp(&mut self, dst: Ipv4Addr, port: u16) -> Option<&mut Socket> {
+        self.sockets.iter_mut().flatten().find(|s| {
+            s.sock_type == SocketType::Datagram
+                && s.local.port == port
+                && (s.local.ip == UNSPECIFIED || s.local.ip == dst || dst == super::BROADCAST)
+        })
+    }
+
+    pub fn find_tcp(&self, local: SockAddr, remote: SockAddr) -> Option<usize> {
+        self.sockets.iter().position(|s| match s {
+            Some(Socket { state: SocketState::Tcp(tcb), .. }) => {
+                tcb.local == local && tcb.remote == remote && tcb.state != TcpState::Closed
+            }
+            _ => false,
+        })
+    }
+
+    pub fn find_listener(&self, dst: Ipv4Addr, port: u16) -> Option<usize> {
+        self.sockets.iter().position(|s| match s {
+            Some(sock @ Socket { state: SocketState::Listening { .. }, .. }) => {
+                sock.local.port == port && (sock.local.ip == UNSPECIFIED || sock.local.ip == dst)
+            }
+            _ => false,
+        })
+    }
+
+    pub fn has_backlog_room(&self, listener: usize) -> bool {
+        let (backlog, ready) = match self.get(listener) {
+            Some(Socket { state: SocketState::Listening { backlog, ready }, .. }) => (*backlog, ready.len()),
+            _ => return false,
+        };
+
+        let half_open = self.sockets.iter().flatten().filter(|s| match &s.state {
+            SocketState::Tcp(tcb) => tcb.parent == Some(listener) && tcb.state == TcpState::SynReceived,
+            _ => false,
+        }).count();
+
+        ready + half_open < backlog
+    }
+
+    pub fn notify_accept(&mut self, child: usize) {
+        let parent = match self.get(child) {
+            Some(Socket { state: SocketState::Tcp(tcb), .. }) => tcb.parent,
+            _ => None,
+        };
+
+        if let Some(parent) = parent {
+            if let Some(Socket { state: SocketState::Listening { ready, .. }, .. }) = self.get_mut(parent) {
+                ready.push_back(child);
+                return;
+            }
+            if let Some(Socket { state: SocketState::Tcp(tcb), .. }) = self.get_mut(child) {
+                tcb.abort();
+            }
+        }
+    }
+
+    fn port_in_use(&self, sock_type: SocketType, addr: SockAddr) -> bool {
+        self.sockets.iter().flatten().any(|s| {
+            s.sock_type == sock_type
+                && s.local.port == addr.port
+                && (s.local.ip == addr.ip || s.local.ip == UNSPECIFIED || addr.ip == UNSPECIFIED)
+                && match &s.state {
+                    SocketState::Tcp(tcb) => tcb.parent.is_none() && tcb.state != TcpState::Closed,
+                    _ => true,
+                }
+        })
+    }
+
+    fn ephemeral_port(&mut self, sock_type: SocketType) -> u16 {
+        for _ in 0..(u16::MAX - EPHEMERAL_START) {
+            let port = self.next_ephemeral;
+            self.next_ephemeral = if port == u16::MAX { EPHEMERAL_START } else { port + 1 };
+            if !self.port_in_use(sock_type, SockAddr { ip: UNSPECIFIED, port }) {
+                return port;
+            }
+        }
+        0
+    }
+}
+
+impl NetStack {
+    pub fn socket_create(&mut self, sock_type: SocketType) -> Result<usize, SocketError> {
+        let state = match sock_type {
+            SocketType::Stream => SocketState::Unbound,
+            SocketType::Datagram => SocketState::Udp { rx: VecDeque::new(), peer: None },
+        };
+
+        self.sockets
+            .insert(Socket {
+                sock_type,
+                local: SockAddr { ip: UNSPECIFIED, port: 0 },
+                state,
+                orphaned: false,
+            })
+            .ok_or(SocketError::InvalidArgument)
+    }
+
+    pub fn socket_bind(&mut self, id: usize, addr: SockAddr) -> Result<(), SocketError> {
+        let sock_type = match self.sockets.get(id) {
+            Some(s) if s.local.port == 0 => s.sock_type,
+            Some(_) => return Err(SocketError::InvalidArgument),
+            None => return Err(SocketError::InvalidArgument),
+        };
+
+        if addr.ip != UNSPECIFIED && !self.is_local(addr.ip) {
+            return Err(SocketError::InvalidArgument);
+        }
+
+        let port = if addr.port == 0 {
+            self.sockets.ephemeral_port(sock_type)
+        } else if self.sockets.port_in_use(sock_type, addr) {
+            return Err(SocketError::AddrInUse);
+        } else {
+            addr.port
+        };
+
+        if let Some(s) = self.sockets.get_mut(id) {
+            s.local = SockAddr { ip: addr.ip, port };
+        }
+        Ok(())
+    }
+
+    pub fn socket_listen(&mut self, id: usize, backlog: usize) -> Result<(), SocketError> {
+        let needs_bind = match self.sockets.get(id) {
+            Some(Socket { sock_type: SocketType::Stream, state: SocketState::Unbound, local, .. }) => local.port == 0,
+            Some(Socket { state: SocketState::Listening { .. }, .. }) => return Ok(()),
+            _ => return Err(SocketError::InvalidArgument),
+        };
+
+        if needs_bind {
+            self.socket_bind(id, SockAddr { ip: UNSPECIFIED, port: 0 })?;
+        }
+
+        if let Some(s) = self.sockets.get_mut(id) {
+            s.state = SocketState::Listening { backlog: backlog.clamp(1, 16), ready: VecDeque::new() };
+        }
+        Ok(())
+    }
+
+    pub fn socket_accept(&mut self, id: usize) -> Result<(usize, SockAddr), SocketError> {
+        loop {
+            let child = match self.sockets.get_mut(id) {
+                Some(Socket { state: SocketState::Listening { ready, .. }, .. }) => ready.pop_front(),
+                _ => return Err(SocketError::InvalidArgument),
+            };
+
+            let child = match child {
+                Some(c) => c,
+                None => return Err(SocketError::WouldBlock),
+            };
+
+            if let Some(Socket { state: SocketState::Tcp(tcb), orphaned, .. }) = self.sockets.get_mut(child) {
+                if tcb.parent == Some(id) {
+                    tcb.parent = None;
+                    *orphaned = false;
+                    return Ok((child, tcb.remote));
+                }
+            }
+        }
+    }
+
+    pub fn socket_connect(&mut self, id: usize, addr: SockAddr) -> Result<(), SocketError> {
+        if addr.port == 0 || addr.ip == UNSPECIFIED {
+            return Err(SocketError::InvalidArgument);
+        }
+
+        let (sock_type, local) = match self.sockets.get(id) {
+            Some(s) => (s.sock_type, s.local),
+            None => return Err(SocketError::InvalidArgument),
+        };
+
+        if local.port == 0 {
+            self.socket_bind(id, SockAddr { ip: UNSPECIFIED, port: 0 })?;
+        }
+
+        let src_ip = match self.sockets.get(id) {
+            Some(s) if s.local.ip != UNSPECIFIED => s.local.ip,
+            _ => self.source_for(addr.ip),
+        };
+
+        match sock_type {
+            SocketType::Datagram => {
+                if let Some(Socket { state: SocketState::Udp { peer, .. }, .. }) = self.sockets.get_mut(id) {
+                    *peer = Some(addr);
+                }
+                Ok(())
+            }
+            SocketType::Stream => {
+                if src_ip == UNSPECIFIED {
+                    return Err(SocketError::Unreachable);
+                }
+
+                let sock = self.sockets.get_mut(id).ok_or(SocketError::InvalidArgument)?;
+                if !matches!(sock.state, SocketState::Unbound) {
+                    return Err(SocketError::InvalidArgument);
+                }
+
+                let local = SockAddr { ip: src_ip, port: sock.local.port };
+                sock.state = SocketState::Tcp(Tcb::connect(local, addr, super::now()));
+                super::tcp::flush(self, id);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn socket_connect_status(&self, id: usize) -> Result<(), SocketError> {
+        match self.sockets.get(id) {
+            Some(Socket { state: SocketState::Tcp(tcb), .. }) => match tcb.state {
+                TcpState::SynSent | TcpState::SynReceived => Err(SocketError::WouldBlock),
+                TcpState::Closed => Err(SocketError::ConnectionRefused),
+                _ => Ok(()),
+            },
+            Some(Socket { state: SocketState::Udp { .. }, .. }) => Ok(()),
+            _ => Err(SocketError::InvalidArgument),
+        }
+    }
+
+    pub fn socket_send(&mut self, id: usize, data: &[u8], dest: Option<SockAddr>) -> Result<usize, SocketError> {
+        let (sock_type, local) = match self.sockets.get(id) {
+            Some(s) => (s.sock_type, s.local),
+            None => return Err(SocketError::InvalidArgument),
+        };
+
+        match sock_type {
+            SocketType::Datagram => {
+                let target = match (dest, self.sockets.get(id)) {
+                    (Some(d), _) => d,
+                    (None, Some(Socket { state: SocketState::Udp { peer: Some(p), .. }, .. })) => *p,
+                    _ => return Err(SocketError::NotConnected),
+                };
+
+                if local.port == 0 {
+                    self.socket_bind(id, SockAddr { ip: UNSPECIFIED, port: 0 })?;
+                }
+                let local = self.sockets.get(id).map(|s| s.local).ok_or(SocketError::InvalidArgument)?;
+                let src_ip = if local.ip != UNSPECIFIED { local.ip } else { self.source_for(target.ip) };
+
+                if super::udp::send(self, src_ip, local.port, target.ip, target.port, data) {
+                    Ok(data.len())
+                } else {
+                    Err(SocketError::Unreachable)
+                }
+            }
+            SocketType::Stream => {
+                let now = super::now();
+                let sent = match self.sockets.get_mut(id) {
+                    Some(Socket { state: SocketState::Tcp(tcb), .. }) => {
+                        if tcb.reset {
+                            return Err(SocketError::ConnectionReset);
+                        }
+                        if !tcb.can_send() {
+                            return Err(SocketError::NotConnected);
+                        }
+                        if tcb.send_space() == 0 {
+                            return Err(SocketError::WouldBlock);
+                        }
+                        tcb.send(data, now)
+                    }
+                    _ => return Err(SocketError::NotConnected),
+                };
+                super::tcp::flush(self, id);
+                Ok(sent)
+            }
+        }
+    }
+
+    pub fn socket_recv(&mut self, id: usize, buf: &mut [u8]) -> Result<(usize, Option<SockAddr>), SocketError> {
+        let result = match self.sockets.get_mut(id) {
+            Some(Socket { state: SocketState::Udp { rx, .. }, .. }) => match rx.pop_front() {
+                Some(dgram) => {
+                    let n = buf.len().min(dgram.data.len());
+                    buf[..n].copy_from_slice(&dgram.data[..n]);
+                    return Ok((n, Some(SockAddr { ip: dgram.src, port: dgram.src_port })));
+                }
+                None => return Err(SocketError::WouldBlock),
+            },
+            Some(Socket { state: SocketState::Tcp(tcb), .. }) => {
+                if !tcb.recv_buf.is_empty() {
+                    let n = tcb.recv(buf);
+                    Ok((n, Some(tcb.remote)))
+                } else if tcb.reset {
+                    Err(SocketError::ConnectionReset)
+                } else if tcb.peer_closed || tcb.state == TcpState::Closed || buf.is_empty() {
+                    Ok((0, Some(tcb.remote)))
+                } else {
+                    Err(SocketError::WouldBlock)
+                }
+            }
+            _ => return Err(SocketError::NotConnected),
+        };
+
+        super::tcp::flush(self, id);
+        result
+    }
+
+    pub fn socket_close(&mut self, id: usize) {
+        let now = super::now();
+        let remove = match self.sockets.get_mut(id) {
+            Some(Socket { state: SocketState::Tcp(tcb), orphaned, .. }) => {
+                if tcb.recv_buf.is_empty() {
+                    tcb.close(now);
+                } else {
+                    tcb.abort();
+                }
+                *orphaned = true;
+                tcb.state == TcpState::Closed
+            }
+            Some(Socket { state: SocketState::Listening { ready, .. }, .. }) => {
+                let pending: Vec<usize> = ready.drain(..).collect();
+                for child in pending {
+                    if let Some(Socket { state: SocketState::Tcp(tcb), .. }) = self.sockets.get_mut(child) {
+                        tcb.abort();
+                    }
+                    super::tcp::flush(self, child);
+                    self.sockets.remove(child);
+                }
+                true
+            }
+            Some(_) => true,
+            None => false,
+        };
+
+        super::tcp::flush(self, id);
+        if remove {
+            self.sockets.remove(id);
+        }
+    }
+
+    pub fn socket_readiness(&self, id: usize) -> (bool, bool) {
+        match self.sockets.get(id) {
+            Some(Socket { state: SocketState::Udp { rx, .. }, .. }) => (!rx.is_empty(), true),
+            Some(Socket { state: SocketState::Listening { ready, .. }, .. }) => (!ready.is_empty(), false),
+            Some(Socket { state: SocketState::Tcp(tcb), .. }) => {
+                let readable = !tcb.recv_buf.is_empty() || tcb.peer_closed || tcb.reset || tcb.state == TcpState::Closed;
+                let writable = (tcb.can_send() && tcb.send_space() > 0) || tcb.reset;
+                (readable, writable)
+            }
+            _ => (false, false),
+        }
+    }
+
+    pub fn socket_peer(&self, id: usize) -> Option<SockAddr> {
+        match self.sockets.get(id) {
+            Some(Socket { state: SocketState::Tcp(tcb), .. }) => Some(tcb.remote),
+            Some(Socket { state: SocketState::Udp { peer, .. }, .. }) => *peer,
+            _ => None,
+        }
+    }
+
+    pub fn socket_local(&self, id: usize) -> Option<SockAddr> {
+        match self.sockets.get(id) {
+            Some(Socket { state: SocketState::Tcp(tcb), .. }) => Some(tcb.local),
+            Some(s) => Some(s.local),
+            None => None,
+        }
+    }
+}
+
+pub fn release(id: usize) {
+    let mut guard = super::NET.int_lock();
+    if let Some(stack) = guard.as_mut() {
+        stack.socket_close(id);
+    }
+}
@@ -0,0 +1,687 @@
+use super::ipv4::PROTO_TCP;
+use super::socket::{SockAddr, Socket, SocketState, SocketType};
+use super::{Ipv4Addr, NetStack};
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+pub const TCP_FIN: u8 = 0x01;
+pub const TCP_SYN: u8 = 0x02;
+pub const TCP_RST: u8 = 0x04;
+pub const TCP_PSH: u8 = 0x08;
+pub const TCP_ACK: u8 = 0x10;
+
+pub const RECV_BUF_SIZE: usize = 32 * 1024;
+pub const SEND_BUF_SIZE: usize = 32 * 1024;
+
+const DEFAULT_MSS: usize = 536;
+const LOCAL_MSS: usize = 1460;
+const INITIAL_RTO_MS: u64 = 1000;
+const MIN_RTO_MS: u64 = 200;
+const MAX_RTO_MS: u64 = 60_000;
+const MAX_RETRIES: u32 = 8;
+const TIME_WAIT_MS: u64 = 4000;
+
+static mut ISS_COUNTER: u32 = 0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TcpState {
+    Closed,
+    SynSent,
+    SynReceived,
+    Established,
+    FinWait1,
+    FinWait2,
+    CloseWait,
+    Closing,
+    LastAck,
+    TimeWait,
+}
+
+pub struct Tcb {
+    pub state: TcpState,
+    pub local: SockAddr,
+    pub remote: SockAddr,
+    pub parent: Option<usize>,
+
+    iss: u32,
+    snd_una: u32,
+    snd_nxt: u32,
+    snd_wnd: u32,
+    data_seq: u32,
+    send_buf: VecDeque<u8>,
+    mss: usize,
+
+    rcv_nxt: u32,
+    pub recv_buf: VecDeque<u8>,
+    last_adv_window: usize,
+
+    fin_queued: bool,
+    fin_sent: bool,
+    fin_seq: u32,
+    pub peer_closed: bool,
+    pub reset: bool,
+
+    rto: u64,
+    srtt: u64,
+    rttvar: u64,
+    rtt_seq: Option<(u32, u64)>,
+    retransmit_at: u64,
+    retries: u32,
+    force_probe: bool,
+    time_wait_until: u64,
+
+    outbox: Vec<Vec<u8>>,
+}
+
+fn seq_lt(a: u32, b: u32) -> bool {
+    (a.wrapping_sub(b) as i32) < 0
+}
+
+fn seq_le(a: u32, b: u32) -> bool {
+    (a.wrapping_sub(b) as i32) <= 0
+}
+
+fn generate_iss(now: u64) -> u32 {
+    unsafe {
+        ISS_COUNTER = ISS_COUNTER.wrapping_add(64_000);
+        (now as u32).wrapping_mul(250).wrapping_add(ISS_COUNTER)
+    }
+}
+
+fn parse_mss(options: &[u8]) -> Option<usize> {
+    let mut i = 0;
+    while i < options.len() {
+        match options[i] {
+            0 => break,
+            1 => i += 1,
+            kind => {
+                if i + 1 >= options.len() { break; }
+                let len = options[i + 1] as usize;
+                if len < 2 || i + len > options.len() { break; }
+                if kind == 2 && len == 4 {
+                    return Some(u16::from_be_bytes([options[i + 2], options[i + 3]]) as usize);
+                }
+                i += len;
+            }
+        }
+    }
+    None
+}
+
+pub fn build_segment(local: SockAddr, remote: SockAddr, seq: u32, ack: u32, flags: u8, window: u16, options: &[u8], payload: &[u8]) -> Vec<u8> {
+    let header_len = 20 + options.len();
+    let mut s = Vec::with_capacity(header_len + payload.len());
+    s.extend_from_slice(&local.port.to_be_bytes());
+    s.extend_from_slice(&remote.port.to_be_bytes());
+    s.extend_from_slice(&seq.to_be_bytes());
+    s.extend_from_slice(&ack.to_be_bytes());
+    s.push(((header_len / 4) as u8) << 4);
+    s.push(flags);
+    s.extend_from_slice(&window.to_be_bytes());
+    s.extend_from_slice(&[0, 0, 0, 0]);
+    s.extend_from_slice(options);
+    s.extend_from_slice(payload);
+
+    let csum = super::checksum(&s, super::pseudo_header_sum(local.ip, remote.ip, PROTO_TCP, s.len()));
+    s[16..18].copy_from_slice(&csum.to_be_bytes());
+    s
+}
+
+impl Tcb {
+    fn new(local: SockAddr, remote: SockAddr, state: TcpState, now: u64) -> Self {
+        let iss = generate_iss(now);
+        Tcb {
+            state,
+            local,
+            remote,
+            parent: None,
+            iss,
+            snd_una: iss,
+            snd_nxt: iss,
+            snd_wnd: DEFAULT_MSS as u32,
+            data_seq: iss.wrapping_add(1),
+            send_buf: VecDeque::new(),
+            mss: DEFAULT_MSS,
+            rcv_nxt: 0,
+            recv_buf: VecDeque::new(),
+            last_adv_window: RECV_BUF_SIZE,
+            fin_queued: false,
+            fin_sent: false,
+            fin_seq: 0,
+            peer_closed: false,
+            reset: false,
+            rto: INITIAL_RTO_MS,
+            srtt: 0,
+            rttvar: 0,
+            rtt_seq: None,
+            retransmit_at: 0,
+            retries: 0,
+            force_probe: false,
+            time_wait_until: 0,
+            outbox: Vec::new(),
+        }
+    }
+
+    pub fn connect(local: SockAddr, remote: SockAddr, now: u64) -> Self {
+        let mut tcb = Tcb::new(local, remote, TcpState::SynSent, now);
+        tcb.output(now);
+        tcb
+    }
+
+    fn accept_syn(local: SockAddr, remote: SockAddr, parent: usize, seg: &Segment, now: u64) -> Self {
+        let mut tcb = Tcb::new(local, remote, TcpState::SynReceived, now);
+        tcb.parent = Some(parent);
+        tcb.rcv_nxt = seg.seq.wrapping_add(1);
+        tcb.snd_wnd = seg.window as u32;
+        if let Some(mss) = parse_mss(seg.options) {
+            tcb.mss = mss.min(LOCAL_MSS);
+        }
+        tcb.output(now);
+        tcb
+    }
+
+    fn window(&self) -> usize {
+        (RECV_BUF_SIZE - self.recv_buf.len()).min(65535)
+    }
+
+    fn push_segment(&mut self, seq: u32, flags: u8, options: &[u8], payload: &[u8]) {
+        let window = self.window();
+        self.last_adv_window = window;
+        let ack = if (flags & TCP_ACK) != 0 { self.rcv_nxt } else { 0 };
+        let seg = build_segment(self.local, self.remote, seq, ack, flags, window as u16, options, payload);
+        self.outbox.push(seg);
+    }
+
+    fn send_ack(&mut self) {
+        self.push_segment(self.snd_nxt, TCP_ACK, &[], &[]);
+    }
+
+    fn arm_timer(&mut self, now: u64) {
+        if self.retransmit_at == 0 {
+            self.retransmit_at = now + self.rto;
+        }
+    }
+
+    fn output(&mut self, now: u64) {
+        let mss_option = (LOCAL_MSS as u16).to_be_bytes();
+        let syn_options = [2, 4, mss_option[0], mss_option[1]];
+
+        match self.state {
+            TcpState::SynSent | TcpState::SynReceived => {
+                if self.snd_nxt == self.iss {
+                    let flags = if self.state == TcpState::SynSent { TCP_SYN } else { TCP_SYN | TCP_ACK };
+                    self.push_segment(self.iss, flags, &syn_options, &[]);
+                    self.snd_nxt = self.iss.wrapping_add(1);
+                    self.arm_timer(now);
+                }
+            }
+            TcpState::Established | TcpState::CloseWait | TcpState::FinWait1 | TcpState::Closing | TcpState::LastAck => {
+                let window = if self.force_probe { self.snd_wnd.max(1) } else { self.snd_wnd };
+                self.force_probe = false;
+
+                while !self.fin_sent {
+                    let offset = self.snd_nxt.wrapping_sub(self.data_seq) as usize;
+                    if offset >= self.send_buf.len() { break; }
+
+                    let in_flight = self.snd_nxt.wrapping_sub(self.snd_una);
+                    if in_flight >= window { break; }
+
+                    let len = self.mss
+                        .min(self.send_buf.len() - offset)
+                        .min((window - in_flight) as usize);
+                    let payload: Vec<u8> = self.send_buf.range(offset..offset + len).copied().collect();
+                    let flags = if offset + len == self.send_buf.len() { TCP_ACK | TCP_PSH } else { TCP_ACK };
+
+                    self.push_segment(self.snd_nxt, flags, &[], &payload);
+                    if self.rtt_seq.is_none() {
+                        self.rtt_seq = Some((self.snd_nxt.wrapping_add(len as u32), now));
+                    }
+                    self.snd_nxt = self.snd_nxt.wrapping_add(len as u32);
+                    self.arm_timer(now);
+                }
+
+                let all_sent = self.snd_nxt == self.data_seq.wrapping_add(self.send_buf.len() as u32);
+                if self.fin_queued && !self.fin_sent && all_sent {
+                    self.push_segment(self.snd_nxt, TCP_FIN | TCP_ACK, &[], &[]);
+                    self.fin_seq = self.snd_nxt;
+                    self.snd_nxt = self.snd_nxt.wrapping_add(1);
+                    self.fin_sent = true;
+                    self.arm_timer(now);
+
+                    self.state = match self.state {
+                        TcpState::Established => TcpState::FinWait1,
+                        TcpState::CloseWait => TcpState::LastAck,
+                        other => other,
+                    };
+                }
+
+                if self.snd_wnd == 0 && self.snd_una == self.snd_nxt && !self.send_buf.is_empty() {
+                    self.arm_timer(now);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn on_timeout(&mut self, now: u64) {
+        self.retransmit_at = 0;
+
+        if self.snd_una == self.snd_nxt && self.snd_wnd == 0 && !self.send_buf.is_empty() {
+            self.force_probe = true;
+            self.rto = (self.rto * 2).min(MAX_RTO_MS);
+            self.output(now);
+            return;
+        }
+
+        self.retries += 1;
+        if self.retries > MAX_RETRIES {
+            self.reset = true;
+            self.state = TcpState::Closed;
+            return;
+        }
+
+        self.rto = (self.rto * 2).min(MAX_RTO_MS);
+        self.rtt_seq = None;
+
+        self.snd_nxt = match self.state {
+            TcpState::SynSent | TcpState::SynReceived => self.iss,
+            _ => self.snd_una,
+        };
+        if self.fin_sent && seq_le(self.snd_nxt, self.fin_seq) {
+            self.fin_sent = false;
+        }
+        if self.snd_wnd == 0 {
+            self.force_probe = true;
+        }
+
+        self.output(now);
+    }
+
+    fn update_rtt(&mut self, ack: u32, now: u64) {
+        if let Some((seq, sent)) = self.rtt_seq {
+            if seq_le(seq, ack) {
+                let sample = now.saturating_sub(sent).max(1);
+                if self.srtt == 0 {
+                    self.srtt = sample;
+                    self.rttvar = sample / 2;
+                } else {
+                    let diff = if self.srtt > sample { self.srtt - sample } else { sample - self.srtt };
+                    self.rttvar = (3 * self.rttvar + diff) / 4;
+                    self.srtt = (7 * self.srtt + sample) / 8;
+                }
+                self.rto = (self.srtt + (4 * self.rttvar).max(10)).clamp(MIN_RTO_MS, MAX_RTO_MS);
+                self.rtt_seq = None;
+            }
+        }
+    }
+
+    fn on_ack(&mut self, ack: u32, window: u16, now: u64) -> bool {
+        if seq_lt(self.snd_nxt, ack) {
+            self.send_ack();
+            return false;
+        }
+
+        if seq_lt(self.snd_una, ack) {
+            if seq_lt(self.data_seq, ack) {
+                let acked = (ack.wrapping_sub(self.data_seq) as usize).min(self.send_buf.len());
+                self.send_buf.drain(..acked);
+                self.data_seq = self.data_seq.wrapping_add(acked as u32);
+            }
+            self.snd_una = ack;
+            self.update_rtt(ack, now);
+            self.retransmit_at = 0;
+            if self.snd_una != self.snd_nxt {
+                self.arm_timer(now);
+            }
+        }
+
+        if seq_le(self.snd_una, ack) {
+            self.retries = 0;
+            self.snd_wnd = window as u32;
+        }
+        true
+    }
+
+    fn enter_time_wait(&mut self, now: u64) {
+        self.state = TcpState::TimeWait;
+        self.retransmit_at = 0;
+        self.time_wait_until = now + TIME_WAIT_MS;
+    }
+
+    fn process(&mut self, seg: &Segment, now: u64) -> bool {
+        if self.state == TcpState::SynSent {
+            let ack_ok = (seg.flags & TCP_ACK) == 0 || seg.ack == self.iss.wrapping_add(1);
+            if !ack_ok {
+                if (seg.flags & TCP_RST) == 0 {
+                    let rst = build_segment(self.local, self.remote, seg.ack, 0, TCP_RST, 0, &[], &[]);
+                    self.outbox.push(rst);
+                }
+                return false;
+            }
+            if (seg.flags & TCP_RST) != 0 {
+                if (seg.flags & TCP_ACK) != 0 {
+                    self.reset = true;
+                    self.state = TcpState::Closed;
+                }
+                return false;
+            }
+            if (seg.flags & TCP_SYN) != 0 {
+                self.rcv_nxt = seg.seq.wrapping_add(1);
+                if let Some(mss) = parse_mss(seg.options) {
+                    self.mss = mss.min(LOCAL_MSS);
+                }
+                if (seg.flags & TCP_ACK) != 0 {
+                    self.snd_una = seg.ack;
+                    self.snd_wnd = seg.window as u32;
+                    self.state = TcpState::Established;
+                    self.retransmit_at = 0;
+                    self.retries = 0;
+                    self.update_rtt(seg.ack, now);
+                    self.send_ack();
+                    self.output(now);
+                } else {
+                    self.state = TcpState::SynReceived;
+                    self.snd_nxt = self.iss;
+                    self.output(now);
+                }
+            }
+            return false;
+        }
+
+        let seg_len = seg.payload.len() as u32
+            + if (seg.flags & TCP_SYN) != 0 { 1 } else { 0 }
+            + if (seg.flags & TCP_FIN) != 0 { 1 } else { 0 };
+        let wnd = self.window() as u32;
+        let seq_end = seg.seq.wrapping_add(seg_len.max(1)).wrapping_sub(1);
+        let acceptable = if seg_len == 0 {
+            seg.seq == self.rcv_nxt || (seq_le(self.rcv_nxt, seg.seq) && seq_lt(seg.seq, self.rcv_nxt.wrapping_add(wnd.max(1))))
+        } else {
+            (seq_le(self.rcv_nxt, seg.seq) && seq_lt(seg.seq, self.rcv_nxt.wrapping_add(wnd.max(1))))
+                || (seq_le(self.rcv_nxt, seq_end) && seq_lt(seq_end, self.rcv_nxt.wrapping_add(wnd.max(1))))
+        };
+
+        if (seg.flags & TCP_RST) != 0 {
+            if acceptable || seg.seq == self.rcv_nxt {
+                self.reset = true;
+                self.state = TcpState::Closed;
+            }
+            return false;
+        }
+
+        if !acceptable {
+            if self.state == TcpState::SynReceived && (seg.flags & TCP_SYN) != 0 && seg.seq.wrapping_add(1) == self.rcv_nxt {
+                self.snd_nxt = self.iss;
+                self.output(now);
+            } else {
+                self.send_ack();
+            }
+            return false;
+        }
+
+        if (seg.flags & TCP_SYN) != 0 {
+            self.send_ack();
+            return false;
+        }
+
+        if (seg.flags & TCP_ACK) == 0 {
+            return false;
+        }
+
+        let mut became_established = false;
+        if self.state == TcpState::SynReceived {
+            if seg.ack != self.iss.wrapping_add(1) {
+                let rst = build_segment(self.local, self.remote, seg.ack, 0, TCP_RST, 0, &[], &[]);
+                self.outbox.push(rst);
+                return false;
+            }
+            self.snd_una = seg.ack;
+            self.snd_wnd = seg.window as u32;
+            self.retransmit_at = 0;
+            self.retries = 0;
+            self.update_rtt(seg.ack, now);
+            self.state = TcpState::Established;
+            became_established = true;
+        } else if !self.on_ack(seg.ack, seg.window, now) {
+            return false;
+        }
+
+        if self.fin_sent && seq_lt(self.fin_seq, self.snd_una) {
+            match self.state {
+                TcpState::FinWait1 => self.state = TcpState::FinWait2,
+                TcpState::Closing => self.enter_time_wait(now),
+                TcpState::LastAck => {
+                    self.state = TcpState::Closed;
+                    self.retransmit_at = 0;
+                }
+                _ => {}
+            }
+        }
+
+        let mut need_ack = false;
+        let receiving = matches!(self.state, TcpState::Established | TcpState::FinWait1 | TcpState::FinWait2);
+
+        if !seg.payload.is_empty() && receiving {
+            let skip = self.rcv_nxt.wrapping_sub(seg.seq) as usize;
+            if seq_le(seg.seq, self.rcv_nxt) && skip < seg.payload.len() {
+                let data = &seg.payload[skip..];
+                let room = RECV_BUF_SIZE - self.recv_buf.len();
+                let take = data.len().min(room);
+                self.recv_buf.extend(&data[..take]);
+                self.rcv_nxt = self.rcv_nxt.wrapping_add(take as u32);
+            }
+            need_ack = true;
+        }
+
+        if (seg.flags & TCP_FIN) != 0 {
+            let fin_seq = seg.seq.wrapping_add(seg.payload.len() as u32);
+            if fin_seq == self.rcv_nxt && !self.peer_closed {
+                self.rcv_nxt = self.rcv_nxt.wrapping_add(1);
+                self.peer_closed = true;
+                match self.state {
+                    TcpState::Established => self.state = TcpState::CloseWait,
+                    TcpState::FinWait1 => self.state = TcpState::Closing,
+                    TcpState::FinWait2 => self.enter_time_wait(now),
+                    _ => {}
+                }
+            } else if self.state == TcpState::TimeWait {
+                self.enter_time_wait(now);
+            }
+            need_ack = true;
+        }
+
+        let before = self.outbox.len();
+        self.output(now);
+        if need_ack && self.outbox.len() == before {
+            self.send_ack();
+        }
+
+        became_established
+    }
+
+    pub fn send(&mut self, data: &[u8], now: u64) -> usize {
+        let room = SEND_BUF_SIZE - self.send_buf.len();
+        let n = data.len().min(room);
+        self.send_buf.extend(&data[..n]);
+        self.output(now);
+        n
+    }
+
+    pub fn recv(&mut self, buf: &mut [u8]) -> usize {
+        let n = buf.len().min(self.recv_buf.len());
+        for (i, byte) in self.recv_buf.drain(..n).enumerate() {
+            buf[i] = byte;
+        }
+
+        let window = self.window();
+        if n > 0 && self.state != TcpState::Closed && window >= self.last_adv_window + self.mss.min(RECV_BUF_SIZE / 2) {
+            self.send_ack();
+        }
+        n
+    }
+
+    pub fn close(&mut self, now: u64) {
+        match self.state {
+            TcpState::SynSent => self.state = TcpState::Closed,
+            TcpState::SynReceived => self.fin_queued = true,
+            TcpState::Established | TcpState::CloseWait => {
+                self.fin_queued = true;
+                self.output(now);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn abort(&mut self) {
+        if matches!(self.state, TcpState::SynReceived | TcpState::Established | TcpState::FinWait1 | TcpState::FinWait2 | TcpState::CloseWait) {
+            self.push_segment(self.snd_nxt, TCP_RST | TCP_ACK, &[], &[]);
+        }
+        self.state = TcpState::Closed;
+    }
+
+    pub fn send_space(&self) -> usize {
+        SEND_BUF_SIZE - self.send_buf.len()
+    }
+
+    pub fn can_send(&self) -> bool {
+        matches!(self.state, TcpState::Established | TcpState::CloseWait) && !self.fin_queued
+    }
+}
+
+struct Segment<'a> {
+    src_port: u16,
+    dst_port: u16,
+    seq: u32,
+    ack: u32,
+    flags: u8,
+    window: u16,
+    options: &'a [u8],
+    payload: &'a [u8],
+}
+
+fn parse(src: Ipv4Addr, dst: Ipv4Addr, packet: &[u8]) -> Option<Segment<'_>> {
+    if packet.len() < 20 {
+        return None;
+    }
+    if super::checksum(packet, super::pseudo_header_sum(src, dst, PROTO_TCP, packet.len())) != 0 {
+        return None;
+    }
+
+    let data_offset = ((packet[12] >> 4) as usize) * 4;
+    if data_offset < 20 || data_offset > packet.len() {
+        return None;
+    }
+
+    Some(Segment {
+        src_port: u16::from_be_bytes([packet[0], packet[1]]),
+        dst_port: u16::from_be_bytes([packet[2], packet[3]]),
+        seq: u32::from_be_bytes([packet[4], packet[5], packet[6], packet[7]]),
+        ack: u32::from_be_bytes([packet[8], packet[9], packet[10], packet[11]]),
+        flags: packet[13],
+        window: u16::from_be_bytes([packet[14], packet[15]]),
+        options: &packet[20..data_offset],
+        payload: &packet[data_offset..],
+    })
+}
+
+pub fn flush(stack: &mut NetStack, id: usize) {
+    let (segments, local, remote) = match stack.sockets.get_mut(id) {
+        Some(Socket { state: SocketState::Tcp(tcb), .. }) => {
+            (core::mem::take(&mut tcb.outbox), tcb.local, tcb.remote)
+        }
+        _ => return,
+    };
+
+    for seg in segments {
+        stack.send_ipv4(local.ip, remote.ip, PROTO_TCP, &seg);
+    }
+}
+
+fn send_reset(stack: &mut NetStack, local: SockAddr, remote: SockAddr, seg: &Segment) {
+    if (seg.flags & TCP_RST) != 0 {
+        return;
+    }
+
+    let rst = if (seg.flags & TCP_ACK) != 0 {
+        build_segment(local, remote, seg.ack, 0, TCP_RST, 0, &[], &[])
+    } else {
+        let mut len = seg.payload.len() as u32;
+        if (seg.flags & TCP_SYN) != 0 { len += 1; }
+        if (seg.flags & TCP_FIN) != 0 { len += 1; }
+        build_segment(local, remote, 0, seg.seq.wrapping_add(len), TCP_RST | TCP_ACK, 0, &[], &[])
+    };
+    stack.send_ipv4(local.ip, remote.ip, PROTO_TCP, &rst);
+}
+
+pub fn handle(stack: &mut NetStack, src: Ipv4Addr, dst: Ipv4Addr, packet: &[u8]) {
+    let seg = match parse(src, dst, packet) {
+        Some(s) => s,
+        None => return,
+    };
+
+    let local = SockAddr { ip: dst, port: seg.dst_port };
+    let remote = SockAddr { ip: src, port: seg.src_port };
+    let now = super::now();
+
+    if let Some(id) = stack.sockets.find_tcp(local, remote) {
+        let established = match stack.sockets.get_mut(id) {
+            Some(Socket { state: SocketState::Tcp(tcb), .. }) => tcb.process(&seg, now),
+            _ => false,
+        };
+        flush(stack, id);
+
+        if established {
+            stack.sockets.notify_accept(id);
+        }
+        return;
+    }
+
+    if let Some(listener) = stack.sockets.find_listener(dst, seg.dst_port) {
+        if (seg.flags & (TCP_RST | TCP_ACK)) != 0 || (seg.flags & TCP_SYN) == 0 {
+            send_reset(stack, local, remote, &seg);
+            return;
+        }
+
+        if !stack.sockets.has_backlog_room(listener) {
+            return;
+        }
+
+        let tcb = Tcb::accept_syn(local, remote, listener, &seg, now);
+        let child = Socket {
+            sock_type: SocketType::Stream,
+            local,
+            state: SocketState::Tcp(tcb),
+            orphaned: true,
+        };
+        if let Some(id) = stack.sockets.insert(child) {
+            flush(stack, id);
+        }
+        return;
+    }
+
+    send_reset(stack, local, remote, &seg);
+}
+
+pub fn tick(stack: &mut NetStack, now: u64) {
+    for id in stack.sockets.tcp_ids() {
+        let finished = match stack.sockets.get_mut(id) {
+            Some(Socket { state: SocketState::Tcp(tcb), orphaned, .. }) => {
+                if tcb.state == TcpState::TimeWait && now >= tcb.time_wait_until {
+                    tcb.state = TcpState::Closed;
+                }
+                if tcb.retransmit_at != 0 && now >= tcb.retransmit_at {
+                    tcb.on_timeout(now);
+                } else {
+                    tcb.output(now);
+                }
+                *orphaned && tcb.state == TcpState::Closed
+            }
+            _ => false,
+        };
+
+        flush(stack, id);
+
+        if finished {
+            stack.sockets.remove(id);
+        }
+    }
+}
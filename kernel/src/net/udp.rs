@@ -0,0 +1,68 @@
+use super::ipv4::PROTO_UDP;
+use super::socket::{Datagram, SocketState};
+use super::{Ipv4Addr, NetStack};
+use alloc::vec::Vec;
+
+pub const UDP_RECV_QUEUE: usize = 32;
+
+pub fn handle(stack: &mut NetStack, src: Ipv4Addr, dst: Ipv4Addr, packet: &[u8]) {
+    if packet.len() < 8 {
+        return;
+    }
+
+    let src_port = u16::from_be_bytes([packet[0], packet[1]]);
+    let dst_port = u16::from_be_bytes([packet[2], packet[3]]);
+    let length = u16::from_be_bytes([packet[4], packet[5]]) as usize;
+    let csum = u16::from_be_bytes([packet[6], packet[7]]);
+
+    if length < 8 || length > packet.len() {
+        return;
+    }
+    let packet = &packet[..length];
+
+    if csum != 0 && super::checksum(packet, super::pseudo_header_sum(src, dst, PROTO_UDP, length)) != 0 {
+        return;
+    }
+
+    let data = &packet[8..];
+
+    if dst_port == super::dhcp::CLIENT_PORT {
+        stack.dhcp_handle(data);
+        return;
+    }
+
+    if let Some(socket) = stack.sockets.find_udp(dst, dst_port) {
+        if let SocketState::Udp { rx, peer } = &mut socket.state {
+            if let Some(p) = peer {
+                if p.ip != src || p.port != src_port {
+                    return;
+                }
+            }
+            if rx.len() < UDP_RECV_QUEUE {
+                rx.push_back(Datagram { src, src_port, data: Vec::from(data) });
+            }
+        }
+    }
+}
+
+pub fn send(stack: &mut NetStack, src: Ipv4Addr, src_port: u16, dst: Ipv4Addr, dst_port: u16, data: &[u8]) -> bool {
+    let length = 8 + data.len();
+    if length > 1480 {
+        return false;
+    }
+
+    let mut p = Vec::with_capacity(length);
+    p.extend_from_slice(&src_port.to_be_bytes());
+    p.extend_from_slice(&dst_port.to_be_bytes());
+    p.extend_from_slice(&(length as u16).to_be_bytes());
+    p.extend_from_slice(&[0, 0]);
+    p.extend_from_slice(data);
+
+    let mut csum = super::checksum(&p, super::pseudo_header_sum(src, dst, PROTO_UDP, length));
+    if csum == 0 {
+        csum = 0xFFFF;
+    }
+    p[6..8].copy_from_slice(&csum.to_be_bytes());
+
+    stack.send_ipv4(src, dst, PROTO_UDP, &p)
+}
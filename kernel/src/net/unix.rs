@@ -0,0 +1,367 @@
+use super::socket::{SocketError, SocketType};
+use crate::sync::Mutex;
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+pub const AF_UNIX: u64 = 1;
+pub const MAX_FDS_PER_MESSAGE: usize = 16;
+
+const MAX_SOCKETS: usize = 64;
+const STREAM_BUF_SIZE: usize = 64 * 1024;
+const DGRAM_QUEUE_LEN: usize = 32;
+
+pub struct UnixMessage {
+    data: Vec<u8>,
+    offset: usize,
+    fds: Vec<usize>,
+    from: Option<String>,
+}
+
+pub enum UnixState {
+    Unbound,
+    Listening { backlog: usize, pending: VecDeque<usize> },
+    Connected { peer: Option<usize> },
+    Datagram { peer: Option<usize> },
+}
+
+pub struct UnixSocket {
+    pub sock_type: SocketType,
+    pub path: Option<String>,
+    pub state: UnixState,
+    rx: VecDeque<UnixMessage>,
+    rx_bytes: usize,
+    peer_closed: bool,
+}
+
+pub struct UnixReceived {
+    pub len: usize,
+    pub fds: Vec<usize>,
+    pub from: Option<String>,
+    pub truncated: bool,
+}
+
+pub struct UnixTable {
+    sockets: Vec<Option<UnixSocket>>,
+}
+
+pub static UNIX: Mutex<UnixTable> = Mutex::new(UnixTable::new());
+
+impl UnixSocket {
+    fn new(sock_type: SocketType, state: UnixState) -> Self {
+        UnixSocket { sock_type, path: None, state, rx: VecDeque::new(), rx_bytes: 0, peer_closed: false }
+    }
+
+    fn push(&mut self, message: UnixMessage) {
+        self.rx_bytes += message.data.len();
+        self.rx.push_back(message);
+    }
+
+    fn drain_fds(&mut self) -> Vec<usize> {
+        self.rx_bytes = 0;
+        self.rx.drain(..).flat_map(|m| m.fds).collect()
+    }
+}
+
+impl UnixTable {
+    pub const fn new() -> Self {
+        UnixTable { sockets: Vec::new() }
+    }
+
+    fn insert(&mut self, socket: UnixSocket) -> Result<usize, SocketError> {
+        if let Some(i) = self.sockets.iter().position(|s| s.is_none()) {
+            self.sockets[i] = Some(socket);
+            return Ok(i);
+        }
+        if self.sockets.len() < MAX_SOCKETS {
+            self.sockets.push(Some(socket));
+            return Ok(self.sockets.len() - 1);
+        }
+        Err(SocketError::InvalidArgument)
+    }
+
+    fn get(&self, id: usize) -> Option<&UnixSocket> {
+        self.sockets.get(id).and_then(|s| s.as_ref())
+    }
+
+    fn get_mut(&mut self, id: usize) -> Option<&mut UnixSocket> {
+        self.sockets.get_mut(id).and_then(|s| s.as_mut())
+    }
+
+    fn find_bound(&self, path: &str, sock_type: SocketType) -> Option<usize> {
+        self.sockets.iter().position(|s| match s {
+            Some(s) => s.sock_type == sock_type && s.path.as_deref() == Some(path),
+            None => false,
+        })
+    }
+
+    pub fn create(&mut self, sock_type: SocketType) -> Result<usize, SocketError> {
+        let state = match sock_type {
+            SocketType::Stream => UnixState::Unbound,
+            SocketType::Datagram => UnixState::Datagram { peer: None },
+        };
+        self.insert(UnixSocket::new(sock_type, state))
+    }
+
+    pub fn pair(&mut self, sock_type: SocketType) -> Result<(usize, usize), SocketError> {
+        let a = self.create(sock_type)?;
+        let b = match self.create(sock_type) {
+            Ok(b) => b,
+            Err(e) => {
+                self.sockets[a] = None;
+                return Err(e);
+            }
+        };
+
+        let (state_a, state_b) = match sock_type {
+            SocketType::Stream => (UnixState::Connected { peer: Some(b) }, UnixState::Connected { peer: Some(a) }),
+            SocketType::Datagram => (UnixState::Datagram { peer: Some(b) }, UnixState::Datagram { peer: Some(a) }),
+        };
+        if let Some(s) = self.get_mut(a) { s.state = state_a; }
+        if let Some(s) = self.get_mut(b) { s.state = state_b; }
+        Ok((a, b))
+    }
+
+    pub fn bind(&mut self, id: usize, path: String) -> Result<(), SocketError> {
+        if self.sockets.iter().flatten().any(|s| s.path.as_deref() == Some(path.as_str())) {
+            return Err(SocketError::AddrInUse);
+        }
+        match self.get_mut(id) {
+            Some(s) if s.path.is_none() => {
+                s.path = Some(path);
+                Ok(())
+            }
+            _ => Err(SocketError::InvalidArgument),
+        }
+    }
+
+    pub fn listen(&mut self, id: usize, backlog: usize) -> Result<(), SocketError> {
+        match self.get_mut(id) {
+            Some(s) if s.sock_type == SocketType::Stream && s.path.is_some() => match s.state {
+                UnixState::Unbound => {
+                    s.state = UnixState::Listening { backlog: backlog.clamp(1, 16), pending: VecDeque::new() };
+                    Ok(())
+                }
+                UnixState::Listening { .. } => Ok(()),
+                _ => Err(SocketError::InvalidArgument),
+            },
+            _ => Err(SocketError::InvalidArgument),
+        }
+    }
+
+    pub fn connect(&mut self, id: usize, path: &str) -> Result<(), SocketError> {
+        let sock_type = match self.get(id) {
+            Some(s) => s.sock_type,
+            None => return Err(SocketError::InvalidArgument),
+        };
+        let target = self.find_bound(path, sock_type).ok_or(SocketError::ConnectionRefused)?;
+
+        match sock_type {
+            SocketType::Datagram => {
+                if let Some(UnixSocket { state: UnixState::Datagram { peer }, .. }) = self.get_mut(id) {
+                    *peer = Some(target);
+                }
+                Ok(())
+            }
+            SocketType::Stream => {
+                if !matches!(self.get(id).map(|s| &s.state), Some(UnixState::Unbound)) {
+                    return Err(SocketError::InvalidArgument);
+                }
+                match self.get(target).map(|s| &s.state) {
+                    Some(UnixState::Listening { backlog, pending }) if pending.len() >= *backlog => return Err(SocketError::WouldBlock),
+                    Some(UnixState::Listening { .. }) => {}
+                    _ => return Err(SocketError::ConnectionRefused),
+                }
+
+                let server = self.insert(UnixSocket::new(SocketType::Stream, UnixState::Connected { peer: Some(id) }))?;
+                if let Some(s) = self.get_mut(server) {
+                    s.path = Some(String::from(path));
+                }
+                if let Some(UnixSocket { state: UnixState::Listening { pending, .. }, .. }) = self.get_mut(target) {
+                    pending.push_back(server);
+                }
+                if let Some(s) = self.get_mut(id) {
+                    s.state = UnixState::Connected { peer: Some(server) };
+                }
+                Ok(())
+            }
+        }
+    }
+
+    pub fn accept(&mut self, id: usize) -> Result<usize, SocketError> {
+        match self.get_mut(id) {
+            Some(UnixSocket { state: UnixState::Listening { pending, .. }, .. }) => pending.pop_front().ok_or(SocketError::WouldBlock),
+            _ => Err(SocketError::InvalidArgument),
+        }
+    }
+
+    pub fn send(&mut self, id: usize, data: &[u8], fds: &mut Vec<usize>, dest: Option<&str>) -> Result<usize, SocketError> {
+        let (sock_type, from) = match self.get(id) {
+            Some(s) => (s.sock_type, s.path.clone()),
+            None => return Err(SocketError::InvalidArgument),
+        };
+
+        match sock_type {
+            SocketType::Stream => {
+                let peer = match self.get(id).map(|s| &s.state) {
+                    Some(UnixState::Connected { peer: Some(p) }) => *p,
+                    Some(UnixState::Connected { peer: None }) => return Err(SocketError::ConnectionReset),
+                    _ => return Err(SocketError::NotConnected),
+                };
+                if data.is_empty() {
+                    return if fds.is_empty() { Ok(0) } else { Err(SocketError::InvalidArgument) };
+                }
+
+                let target = self.get_mut(peer).ok_or(SocketError::ConnectionReset)?;
+                let space = STREAM_BUF_SIZE.saturating_sub(target.rx_bytes);
+                if space == 0 {
+                    return Err(SocketError::WouldBlock);
+                }
+
+                let n = data.len().min(space);
+                target.push(UnixMessage { data: data[..n].to_vec(), offset: 0, fds: core::mem::take(fds), from: None });
+                Ok(n)
+            }
+            SocketType::Datagram => {
+                let target = match dest {
+                    Some(path) => self.find_bound(path, SocketType::Datagram).ok_or(SocketError::ConnectionRefused)?,
+                    None => match self.get(id).map(|s| &s.state) {
+                        Some(UnixState::Datagram { peer: Some(p) }) => *p,
+                        _ => return Err(SocketError::NotConnected),
+                    },
+                };
+
+                let target = self.get_mut(target).ok_or(SocketError::ConnectionRefused)?;
+                if target.rx.len() >= DGRAM_QUEUE_LEN {
+                    return Err(SocketError::WouldBlock);
+                }
+                target.push(UnixMessage { data: data.to_vec(), offset: 0, fds: core::mem::take(fds), from });
+                Ok(data.len())
+            }
+        }
+    }
+
+    pub fn recv(&mut self, id: usize, buf: &mut [u8]) -> Result<UnixReceived, SocketError> {
+        let sock = self.get_mut(id).ok_or(SocketError::InvalidArgument)?;
+
+        if sock.rx.is_empty() {
+            return match (&sock.state, sock.peer_closed) {
+                (UnixState::Connected { .. }, true) => Ok(UnixReceived { len: 0, fds: Vec::new(), from: None, truncated: false }),
+                (UnixState::Connected { .. }, false) | (UnixState::Datagram { .. }, _) => Err(SocketError::WouldBlock),
+                _ => Err(SocketError::NotConnected),
+            };
+        }
+
+        if sock.sock_type == SocketType::Datagram {
+            let message = sock.rx.pop_front().unwrap();
+            sock.rx_bytes -= message.data.len();
+            let n = buf.len().min(message.data.len());
+            buf[..n].copy_from_slice(&message.data[..n]);
+            return Ok(UnixReceived { len: n, fds: message.fds, from: message.from, truncated: n < message.data.len() });
+        }
+
+        // Stream reads never merge across a message that carries descriptors, so the
+        // receiver sees them alongside the bytes they were sent with.
+        let mut received = UnixReceived { len: 0, fds: Vec::new(), from: None, truncated: false };
+        while received.len < buf.len() {
+            let message = match sock.rx.front_mut() {
+                Some(m) => m,
+                None => break,
+            };
+            if received.len > 0 && !message.fds.is_empty() {
+                break;
+            }
+
+            received.fds.append(&mut message.fds);
+            let n = (buf.len() - received.len).min(message.data.len() - message.offset);
+            buf[received.len..received.len + n].copy_from_slice(&message.data[message.offset..message.offset + n]);
+            message.offset += n;
+            received.len += n;
+            sock.rx_bytes -= n;
+
+            if message.offset == message.data.len() {
+                sock.rx.pop_front();
+            }
+            if !received.fds.is_empty() {
+                break;
+            }
+        }
+        Ok(received)
+    }
+
+    pub fn readiness(&self, id: usize) -> (bool, bool) {
+        let sock = match self.get(id) {
+            Some(s) => s,
+            None => return (false, false),
+        };
+        match &sock.state {
+            UnixState::Listening { pending, .. } => (!pending.is_empty(), false),
+            UnixState::Connected { peer } => {
+                let writable = match peer.and_then(|p| self.get(p)) {
+                    Some(p) => p.rx_bytes < STREAM_BUF_SIZE,
+                    None => true,
+                };
+                (!sock.rx.is_empty() || sock.peer_closed, writable)
+            }
+            UnixState::Datagram { peer } => {
+                let writable = match peer.and_then(|p| self.get(p)) {
+                    Some(p) => p.rx.len() < DGRAM_QUEUE_LEN,
+                    None => true,
+                };
+                (!sock.rx.is_empty(), writable)
+            }
+            UnixState::Unbound => (false, false),
+        }
+    }
+
+    pub fn local_path(&self, id: usize) -> Option<String> {
+        self.get(id).and_then(|s| s.path.clone())
+    }
+
+    pub fn peer_path(&self, id: usize) -> Option<String> {
+        let peer = match self.get(id).map(|s| &s.state) {
+            Some(UnixState::Connected { peer: Some(p) }) | Some(UnixState::Datagram { peer: Some(p) }) => *p,
+            _ => return None,
+        };
+        self.get(peer).and_then(|s| s.path.clone())
+    }
+
+    pub fn close(&mut self, id: usize) -> Vec<usize> {
+        let mut sock = match self.sockets.get_mut(id).and_then(|s| s.take()) {
+            Some(s) => s,
+            None => return Vec::new(),
+        };
+        let mut fds = sock.drain_fds();
+
+        match &mut sock.state {
+            UnixState::Listening { pending, .. } => {
+                for child in pending.drain(..) {
+                    fds.extend(self.close(child));
+                }
+            }
+            UnixState::Connected { peer: Some(peer) } => {
+                if let Some(p) = self.get_mut(*peer) {
+                    p.state = UnixState::Connected { peer: None };
+                    p.peer_closed = true;
+                }
+            }
+            _ => {}
+        }
+
+        for other in self.sockets.iter_mut().flatten() {
+            if let UnixState::Datagram { peer } = &mut other.state {
+                if *peer == Some(id) {
+                    *peer = None;
+                }
+            }
+        }
+        fds
+    }
+}
+
+pub fn release(id: usize) {
+    let fds = UNIX.int_lock().close(id);
+    for fd in fds {
+        crate::fs::vfs::close_file(fd);
+    }
+}
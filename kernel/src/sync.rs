@@ -56,6 +56,26 @@ impl<T> Mutex<T> {
             rflags,
         }
     }
+
+    pub fn try_int_lock(&self) -> Option<IntMutexGuard<'_, T>> {
+        let rflags: u64;
+        unsafe {
+            core::arch::asm!("pushfq; pop {}", out(reg) rflags);
+            core::arch::asm!("cli");
+        }
+
+        if self.lock.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed).is_err() {
+            if (rflags & 0x200) != 0 {
+                unsafe { core::arch::asm!("sti") };
+            }
+            return None;
+        }
+        Some(IntMutexGuard {
+            lock: &self.lock,
+            data: unsafe { &mut *self.data.get() },
+            rflags,
+        })
+    }
 }
 
 impl<'a, T> core::ops::Deref for MutexGuard<'a, T> {
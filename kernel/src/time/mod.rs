@@ -0,0 +1,164 @@
+use crate::debugln;
+use crate::drivers::hpet::HPET;
+use crate::drivers::lapic::LAPIC;
+use crate::drivers::port::{inb, outb};
+use core::arch::x86_64::{__cpuid, _rdtsc};
+
+pub const CLOCK_REALTIME: u64 = 0;
+pub const CLOCK_MONOTONIC: u64 = 1;
+
+const PIT_FREQUENCY: u64 = 1193182;
+const PIT_CHANNEL_2: u16 = 0x42;
+const PIT_COMMAND: u16 = 0x43;
+const PIT_GATE: u16 = 0x61;
+
+const CALIBRATION_MS: u64 = 50;
+const NS_PER_SEC: u64 = 1_000_000_000;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ClockSource {
+    Tsc,
+    Hpet,
+}
+
+static mut SOURCE: ClockSource = ClockSource::Tsc;
+static mut TSC_KHZ: u64 = 0;
+static mut BASE: u64 = 0;
+static mut BOOT_EPOCH_NS: u64 = 0;
+
+pub fn init() {
+    let hpet = crate::drivers::hpet::init();
+
+    let start = unsafe { _rdtsc() };
+    wait_reference(CALIBRATION_MS);
+    let tsc_khz = (unsafe { _rdtsc() } - start) / CALIBRATION_MS;
+
+    let invariant_tsc = __cpuid(0x8000_0000).eax >= 0x8000_0007 && __cpuid(0x8000_0007).edx & (1 << 8) != 0;
+    let wide_hpet = hpet && unsafe { (*(&raw const HPET)).as_ref().is_some_and(|h| h.wide) };
+
+    let source = if invariant_tsc || !wide_hpet { ClockSource::Tsc } else { ClockSource::Hpet };
+    unsafe {
+        TSC_KHZ = tsc_khz.max(1);
+        SOURCE = source;
+        BASE = raw_counter();
+        BOOT_EPOCH_NS = rtc_epoch_seconds() * NS_PER_SEC;
+    }
+    debugln!("Time: {:?} clocksource, TSC at {} kHz{}", source, tsc_khz, if invariant_tsc { " (invariant)" } else { "" });
+
+    crate::drivers::lapic::init();
+}
+
+pub fn wait_reference(ms: u64) {
+    unsafe {
+        if let Some(hpet) = (*(&raw const HPET)).as_ref() {
+            let start = hpet.counter();
+            while hpet.ticks_to_ns(hpet.counter().wrapping_sub(start)) < ms * 1_000_000 {
+                core::hint::spin_loop();
+            }
+            return;
+        }
+
+        let mut remaining = ms;
+        while remaining > 0 {
+            let chunk = remaining.min(50);
+            let count = (PIT_FREQUENCY * chunk / 1000) as u16;
+
+            outb(PIT_GATE, (inb(PIT_GATE) & !0x02) | 0x01);
+            outb(PIT_COMMAND, 0xB0);
+            outb(PIT_CHANNEL_2, (count & 0xFF) as u8);
+            outb(PIT_CHANNEL_2, (count >> 8) as u8);
+
+            let gate = inb(PIT_GATE) & !0x01;
+            outb(PIT_GATE, gate);
+            outb(PIT_GATE, gate | 0x01);
+
+            while inb(PIT_GATE) & 0x20 == 0 {
+                core::hint::spin_loop();
+            }
+            remaining -= chunk;
+        }
+    }
+}
+
+fn raw_counter() -> u64 {
+    unsafe {
+        match SOURCE {
+            ClockSource::Tsc => _rdtsc(),
+            ClockSource::Hpet => (*(&raw const HPET)).as_ref().map_or(0, |h| h.counter()),
+        }
+    }
+}
+
+pub fn monotonic_ns() -> u64 {
+    unsafe {
+        let elapsed = raw_counter().wrapping_sub(BASE);
+        match SOURCE {
+            ClockSource::Tsc => (elapsed as u128 * 1_000_000 / TSC_KHZ as u128) as u64,
+            ClockSource::Hpet => (*(&raw const HPET)).as_ref().map_or(0, |h| h.ticks_to_ns(elapsed)),
+        }
+    }
+}
+
+pub fn realtime_ns() -> u64 {
+    unsafe { BOOT_EPOCH_NS + monotonic_ns() }
+}
+
+pub fn now(clock: u64) -> Option<u64> {
+    match clock {
+        CLOCK_REALTIME => Some(realtime_ns()),
+        CLOCK_MONOTONIC => Some(monotonic_ns()),
+        _ => None,
+    }
+}
+
+pub fn to_monotonic(clock: u64, ns: u64) -> Option<u64> {
+    match clock {
+        CLOCK_REALTIME => Some(ns.saturating_sub(unsafe { BOOT_EPOCH_NS })),
+        CLOCK_MONOTONIC => Some(ns),
+        _ => None,
+    }
+}
+
+pub fn resolution_ns() -> u64 {
+    unsafe {
+        match SOURCE {
+            ClockSource::Tsc => (1_000_000 / TSC_KHZ).max(1),
+            ClockSource::Hpet => (*(&raw const HPET)).as_ref().map_or(1, |h| h.ticks_to_ns(1).max(1)),
+        }
+    }
+}
+
+pub fn arm_wakeup(deadline_ns: u64) {
+    unsafe {
+        if let Some(lapic) = (*(&raw const LAPIC)).as_ref() {
+            lapic.arm_oneshot(deadline_ns.saturating_sub(monotonic_ns()));
+        }
+    }
+}
+
+fn rtc_epoch_seconds() -> u64 {
+    let (mut date, mut time) = (crate::drivers::rtc::get_date(), crate::drivers::rtc::get_time());
+    loop {
+        let (d, t) = (crate::drivers::rtc::get_date(), crate::drivers::rtc::get_time());
+        if d == date && t == time {
+            break;
+        }
+        date = d;
+        time = t;
+    }
+
+    let (day, month, year) = date;
+    let (hour, minute, second) = time;
+    let days = days_from_civil(year as i64, month as i64, day as i64);
+    (days.max(0) as u64) * 86400 + hour as u64 * 3600 + minute as u64 * 60 + second as u64
+}
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
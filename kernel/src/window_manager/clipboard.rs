@@ -0,0 +1,75 @@
+use crate::sync::Mutex;
+use crate::window_manager::events::{ClipboardEvent, Event, EVENT_QUEUES};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+pub const MAX_MIME_LEN: usize = 128;
+pub const MAX_DATA_LEN: usize = 4 * 1024 * 1024;
+
+pub struct Selection {
+    pub owner: usize,
+    pub mime: String,
+    pub data: Vec<u8>,
+}
+
+pub struct Clipboard {
+    selection: Option<Selection>,
+}
+
+pub static CLIPBOARD: Mutex<Clipboard> = Mutex::new(Clipboard { selection: None });
+
+impl Clipboard {
+    pub fn set(&mut self, owner: usize, mime: &str, data: &[u8]) -> Result<(), String> {
+        if mime.is_empty() || mime.len() > MAX_MIME_LEN {
+            return Err(String::from("Invalid MIME type"));
+        }
+        if data.len() > MAX_DATA_LEN {
+            return Err(String::from("Selection too large"));
+        }
+
+        self.selection = Some(Selection {
+            owner,
+            mime: String::from(mime),
+            data: data.to_vec(),
+        });
+        Ok(())
+    }
+
+    pub fn clear(&mut self, owner: usize) -> bool {
+        match &self.selection {
+            Some(sel) if sel.owner == owner => {
+                self.selection = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn mime(&self) -> Option<&str> {
+        self.selection.as_ref().map(|sel| sel.mime.as_str())
+    }
+
+    pub fn get(&self, mime: &str) -> Option<&[u8]> {
+        let sel = self.selection.as_ref()?;
+        if mime.is_empty() || mime_matches(&sel.mime, mime) {
+            Some(&sel.data)
+        } else {
+            None
+        }
+    }
+}
+
+fn mime_matches(offered: &str, requested: &str) -> bool {
+    if offered.eq_ignore_ascii_case(requested) {
+        return true;
+    }
+    !requested.contains(';') && essence(offered).eq_ignore_ascii_case(essence(requested))
+}
+
+fn essence(mime: &str) -> &str {
+    mime.split(';').next().unwrap_or("").trim()
+}
+
+pub fn notify(owner: usize) {
+    EVENT_QUEUES.lock().broadcast(|wid| Event::SelectionChanged(ClipboardEvent { wid, owner: owner as u32 }));
+}
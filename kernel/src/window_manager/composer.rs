@@ -1,48 +1,84 @@
-use super::window::{Items, Window, NULL_WINDOW};
+use super::cursor::CURSORS;
+use super::decorations::{self, TITLE_HEIGHT};
+use super::events::{CloseRequestedEvent, Event, FocusEvent, ResizeEvent, ScreenEvent, WindowListEvent, EVENT_QUEUES};
+use super::tiling::{Layout, MAX_GAP, MAX_MASTER_RATIO, MIN_MASTER_RATIO, TILING};
+use super::window::{Items, Window, WindowInfo, NULL_WINDOW};
+use super::workspace::{WORKSPACES, WORKSPACE_COUNT};
 use crate::debugln;
-use crate::window_manager::display::DISPLAY_SERVER;
+use crate::window_manager::display::{DisplayServer, DISPLAY_SERVER};
 use crate::window_manager::input::CLICKED_WINDOW_ID;
 
 #[derive(Debug, Clone)]
 pub struct Composer {
     pub windows: [Window; 16],
+    switch_target: Option<usize>,
 }
 
 pub static mut COMPOSER: Composer = Composer {
     windows: [NULL_WINDOW; 16],
+    switch_target: None,
 };
 
+fn notify_watchers(kind: fn(WindowListEvent) -> Event, subject: usize) {
+    EVENT_QUEUES.int_lock().notify_watchers(|wid| kind(WindowListEvent { wid, subject: subject as u32 }));
+}
+
+fn border_color(w: &Window) -> Option<u32> {
+    if w.w_type == Items::Window {
+        if w.id == unsafe { CLICKED_WINDOW_ID } {
+            Some(0xFFFFFFFF)
+        } else {
+            Some(0xFF9070FF)
+        }
+    } else {
+        None
+    }
+}
+
+pub fn draw_to_db(ds: &mut DisplayServer, w: &Window, clip: Option<(i32, i32, u32, u32)>) {
+    if !w.is_visible() {
+        return;
+    }
+    let border_color = border_color(w);
+
+    if w.has_title_bar() {
+        let focused = w.id == unsafe { CLICKED_WINDOW_ID };
+        let bar = decorations::title_bar(w, focused);
+        let bar_y = (w.y - TITLE_HEIGHT as isize) as i32;
+        match clip {
+            Some((cx, cy, cw, ch)) => ds.copy_to_db_clipped(w.width as u32, TITLE_HEIGHT as u32, bar, w.x as i32, bar_y, cx, cy, cw, ch, border_color, false),
+            None => ds.copy_to_db(w.width as u32, TITLE_HEIGHT as u32, bar, w.x as i32, bar_y, border_color, false),
+        }
+    }
+
+    match clip {
+        Some((cx, cy, cw, ch)) => ds.copy_to_db_clipped(w.width as u32, w.height as u32, w.buffer, w.x as i32, w.y as i32, cx, cy, cw, ch, border_color, w.treat_as_transparent),
+        None => ds.copy_to_db(w.width as u32, w.height as u32, w.buffer, w.x as i32, w.y as i32, border_color, w.treat_as_transparent),
+    }
+}
+
+pub fn draw_to_fb(ds: &mut DisplayServer, w: &Window) {
+    draw_to_fb_with_border(ds, w, border_color(w));
+}
+
+pub fn draw_to_fb_with_border(ds: &mut DisplayServer, w: &Window, border_color: Option<u32>) {
+    if !w.is_visible() {
+        return;
+    }
+    if w.has_title_bar() {
+        let focused = w.id == unsafe { CLICKED_WINDOW_ID };
+        let bar = decorations::title_bar(w, focused);
+        ds.copy_to_fb_a(w.width as u32, TITLE_HEIGHT as u32, bar, w.x as i32, (w.y - TITLE_HEIGHT as isize) as i32, border_color, false);
+    }
+    ds.copy_to_fb_a(w.width as u32, w.height as u32, w.buffer, w.x as i32, w.y as i32, border_color, w.treat_as_transparent);
+}
+
 impl Composer {
     pub fn copy_window(&mut self, id: usize) {
         for i in 0..self.windows.len() {
             if id == self.windows[i].id {
-                let border_color = if self.windows[i].w_type == Items::Window {
-                    unsafe {
-                        if self.windows[i].id == CLICKED_WINDOW_ID {
-                            Some(0xFFFFFFFF)
-                        } else {
-                            Some(0xFF9070FF)
-                        }
-                    }
-                } else {
-                    None
-                };
-
-                match self.windows[i].w_type {
-                    Items::Null => {}
-                    _ => unsafe {
-                        let ds = &mut *(&raw mut DISPLAY_SERVER);
-                        ds.copy_to_db(
-                            self.windows[i].width as u32,
-                            self.windows[i].height as u32,
-                            self.windows[i].buffer,
-                            self.windows[i].x as i32,
-                            self.windows[i].y as i32,
-                            border_color,
-                            self.windows[i].treat_as_transparent,
-                        )
-                    },
-                }
+                let ds = unsafe { &mut *(&raw mut DISPLAY_SERVER) };
+                draw_to_db(ds, &self.windows[i], None);
             }
         }
     }
@@ -50,33 +86,8 @@ impl Composer {
     pub fn copy_window_fb(&mut self, id: usize) {
         for i in 0..self.windows.len() {
             if id == self.windows[i].id {
-                let border_color = if self.windows[i].w_type == Items::Window {
-                    unsafe {
-                        if self.windows[i].id == CLICKED_WINDOW_ID {
-                            Some(0xFFFFFFFF)
-                        } else {
-                            Some(0xFF9070FF)
-                        }
-                    }
-                } else {
-                    None
-                };
-
-                match self.windows[i].w_type {
-                    Items::Null => {}
-                    _ => unsafe {
-                        let ds = &mut *(&raw mut DISPLAY_SERVER);
-                        ds.copy_to_fb_a(
-                            self.windows[i].width as u32,
-                            self.windows[i].height as u32,
-                            self.windows[i].buffer,
-                            self.windows[i].x as i32,
-                            self.windows[i].y as i32,
-                            border_color,
-                            self.windows[i].treat_as_transparent,
-                        )
-                    },
-                }
+                let ds = unsafe { &mut *(&raw mut DISPLAY_SERVER) };
+                draw_to_fb(ds, &self.windows[i]);
             }
         }
     }
@@ -86,15 +97,14 @@ impl Composer {
         let my = y as isize;
 
         for i in 0..self.windows.len() {
-            if mx >= self.windows[i].x
-                && mx <= (self.windows[i].x + self.windows[i].width as isize)
-                && my >= self.windows[i].y
-                && my <= (self.windows[i].y + self.windows[i].height as isize)
+            let (fx, fy, fw, fh) = self.windows[i].frame();
+            if mx >= fx
+                && mx <= (fx + fw as isize)
+                && my >= fy
+                && my <= (fy + fh as isize)
+                && self.windows[i].is_visible()
             {
-                match self.windows[i].w_type {
-                    Items::Null => {}
-                    _ => return Some(&mut self.windows[i]),
-                }
+                return Some(&mut self.windows[i]);
             }
         }
         None
@@ -112,6 +122,36 @@ impl Composer {
         None
     }
 
+    pub fn set_focus(&mut self, id: usize) {
+        let old = unsafe { CLICKED_WINDOW_ID };
+        if old == id {
+            return;
+        }
+
+        unsafe { CLICKED_WINDOW_ID = id };
+        {
+            let mut queues = EVENT_QUEUES.int_lock();
+            if old != 0 {
+                queues.add_event(Event::FocusOut(FocusEvent { wid: old as u32 }));
+            }
+            if id != 0 {
+                queues.add_event(Event::FocusIn(FocusEvent { wid: id as u32 }));
+            }
+        }
+        notify_watchers(Event::WindowFocused, id);
+
+        if let Some(w) = self.find_window_id(id) {
+            if w.w_type == Items::Window {
+                let workspace = w.workspace;
+                unsafe { (*(&raw mut WORKSPACES)).focused(workspace, id) };
+            }
+        }
+
+        if id != 0 {
+            self.focus_window(id);
+        }
+    }
+
     pub fn focus_window(&mut self, id: usize) {
         let mut target_idx = None;
         for i in 0..self.windows.len() {
@@ -155,11 +195,32 @@ impl Composer {
         let (screen_w, screen_h) = unsafe {
             ((*(&raw mut DISPLAY_SERVER)).width as usize, (*(&raw mut DISPLAY_SERVER)).height as usize)
         };
+        let tiling = unsafe { &mut *(&raw mut TILING) };
 
         for i in 0..self.windows.len() {
             if self.windows[i].w_type == Items::Window {
-                self.windows[i].can_move = true;
-                self.windows[i].can_resize = true;
+                let tiled = tiling.is_tiled(self.windows[i].id);
+                self.windows[i].can_move = !tiled;
+                self.windows[i].can_resize = !tiled;
+            }
+        }
+
+        if tiling.is_active() {
+            let windows = &self.windows;
+            let ids = tiling.tiled(|id| windows.iter().any(|w| w.id == id && w.w_type == Items::Window && w.is_visible()));
+            let rects = tiling.rects(self.work_area(), ids.len());
+
+            for (&id, &(x, y, width, height)) in ids.iter().zip(rects.iter()) {
+                if let Some(w) = self.find_window_id(id) {
+                    let th = w.title_height();
+                    let height = height.saturating_sub(th);
+                    w.x = x;
+                    w.y = y + th as isize;
+
+                    if (w.width, w.height) != (width, height) && w.event_handler != 0 && tiling.note_request(id, width, height) {
+                        EVENT_QUEUES.int_lock().add_event(Event::Resize(ResizeEvent { wid: id as u32, width, height }));
+                    }
+                }
             }
         }
 
@@ -190,12 +251,20 @@ impl Composer {
         }
 
         w.id = self.check_id(w.buffer as u64);
+        w.minimized = false;
+        w.workspace = unsafe { (*(&raw const WORKSPACES)).current };
+        EVENT_QUEUES.int_lock().open(w.id as u32);
+        if wtype != Items::Window {
+            w.decorated = false;
+        } else {
+            unsafe { (*(&raw mut TILING)).track(w.id) };
+        }
 
 
         if wtype == Items::Window {
             let mut count = 0;
             for i in 0..self.windows.len() {
-                if self.windows[i].w_type == Items::Window {
+                if self.windows[i].w_type == Items::Window && self.windows[i].workspace == w.workspace {
                     count += 1;
                 }
             }
@@ -205,7 +274,7 @@ impl Composer {
             let start_y = 50;
 
             w.x = (start_x + (count * offset)) as isize;
-            w.y = (start_y + (count * offset)) as isize;
+            w.y = (start_y + w.title_height() + (count * offset)) as isize;
 
 
             w.can_move = true;
@@ -244,6 +313,7 @@ impl Composer {
         debugln!("add_window: sorted, updating tiling...");
         self.update_tiling();
         debugln!("add_window: tiling updated, returning ID.");
+        notify_watchers(Event::WindowCreated, w.id);
         w.id
     }
 
@@ -271,13 +341,18 @@ impl Composer {
 
                 self.windows[i].transparent = w.transparent;
                 self.windows[i].treat_as_transparent = w.treat_as_transparent;
+                if self.windows[i].title != w.title {
+                    self.windows[i].title = w.title;
+                    notify_watchers(Event::WindowChanged, w.id);
+                }
 
 
                 let current_x = self.windows[i].x;
                 let current_y = self.windows[i].y;
+                let th = self.windows[i].title_height() as isize;
 
                 let min_x = old_x.min(current_x);
-                let min_y = old_y.min(current_y);
+                let min_y = old_y.min(current_y) - th;
                 let max_x = (old_x + old_w as isize).max(current_x + w.width as isize);
                 let max_y = (old_y + old_h as isize).max(current_y + w.height as isize);
 
@@ -305,7 +380,7 @@ impl Composer {
             let mut occluded = false;
             for i in 0..self.windows.len() {
                 let w = &self.windows[i];
-                if w.w_type == Items::Null { continue; }
+                if !w.is_visible() { continue; }
 
                 if !w.treat_as_transparent &&
                     w.x as i32 <= dirty_x &&
@@ -339,32 +414,7 @@ impl Composer {
             }
 
             for i in (0..=start_index).rev() {
-                match self.windows[i].w_type {
-                    Items::Null => {}
-                    _ => {
-                        let w = &self.windows[i];
-                        let border_color = if w.w_type == Items::Window {
-                            if w.id == CLICKED_WINDOW_ID {
-                                Some(0xFFFFFFFF)
-                            } else {
-                                Some(0xFF9070FF)
-                            }
-                        } else {
-                            None
-                        };
-
-                        display_server.copy_to_db_clipped(
-                            w.width as u32,
-                            w.height as u32,
-                            w.buffer,
-                            w.x as i32,
-                            w.y as i32,
-                            dirty_x, dirty_y, dirty_w, dirty_h,
-                            border_color,
-                            w.treat_as_transparent,
-                        );
-                    }
-                }
+                draw_to_db(display_server, &self.windows[i], Some((dirty_x, dirty_y, dirty_w, dirty_h)));
             }
 
             display_server.present_rect(dirty_x, dirty_y, dirty_w, dirty_h);
@@ -384,30 +434,7 @@ impl Composer {
 
             for i in (0..self.windows.len()).rev() {
                 if self.windows[i].id != except_id {
-                    match self.windows[i].w_type {
-                        Items::Null => {}
-                        _ => {
-                            let border_color = if self.windows[i].w_type == Items::Window {
-                                if self.windows[i].id == CLICKED_WINDOW_ID {
-                                    Some(0xFFFFFFFF)
-                                } else {
-                                    Some(0xFF9070FF)
-                                }
-                            } else {
-                                None
-                            };
-
-                            display_server.copy_to_db(
-                                self.windows[i].width as u32,
-                                self.windows[i].height as u32,
-                                self.windows[i].buffer,
-                                self.windows[i].x as i32,
-                                self.windows[i].y as i32,
-                                border_color,
-                                self.windows[i].treat_as_transparent,
-                            );
-                        }
-                    }
+                    draw_to_db(display_server, &self.windows[i], None);
                 }
             }
         }
@@ -418,8 +445,8 @@ impl Composer {
             let mut found = None;
             for i in 0..self.windows.len() {
                 if self.windows[i].id == id {
-                    let w = &self.windows[i];
-                    found = Some((w.x as i32, w.y as i32, w.width as u32, w.height as u32));
+                    let (x, y, width, height) = self.windows[i].frame();
+                    found = Some((x as i32, y as i32, width as u32, height as u32));
                     break;
                 }
             }
@@ -436,7 +463,7 @@ impl Composer {
             let mut occluded = false;
             for i in 0..self.windows.len() {
                 let w = &self.windows[i];
-                if w.w_type == Items::Null { continue; }
+                if !w.is_visible() { continue; }
 
                 if !w.treat_as_transparent &&
                     w.x as i32 <= dirty_x &&
@@ -470,32 +497,7 @@ impl Composer {
             }
 
             for i in (0..=start_index).rev() {
-                match self.windows[i].w_type {
-                    Items::Null => {}
-                    _ => {
-                        let w = &self.windows[i];
-                        let border_color = if w.w_type == Items::Window {
-                            if w.id == CLICKED_WINDOW_ID {
-                                Some(0xFFFFFFFF)
-                            } else {
-                                Some(0xFF9070FF)
-                            }
-                        } else {
-                            None
-                        };
-
-                        display_server.copy_to_db_clipped(
-                            w.width as u32,
-                            w.height as u32,
-                            w.buffer,
-                            w.x as i32,
-                            w.y as i32,
-                            dirty_x, dirty_y, dirty_w, dirty_h,
-                            border_color,
-                            w.treat_as_transparent,
-                        );
-                    }
-                }
+                draw_to_db(display_server, &self.windows[i], Some((dirty_x, dirty_y, dirty_w, dirty_h)));
             }
 
             display_server.present_rect(dirty_x, dirty_y, dirty_w, dirty_h);
@@ -509,6 +511,21 @@ impl Composer {
                 self.windows[i].z = 255;
             }
         }
+        decorations::forget(wid);
+        unsafe { (*(&raw mut TILING)).forget(wid) };
+        unsafe { (*(&raw mut WORKSPACES)).forget(wid) };
+        unsafe { (*(&raw mut CURSORS)).forget(wid) };
+        EVENT_QUEUES.int_lock().close(wid as u32);
+        notify_watchers(Event::WindowDestroyed, wid);
+        unsafe {
+            if CLICKED_WINDOW_ID == wid {
+                CLICKED_WINDOW_ID = 0;
+                notify_watchers(Event::WindowFocused, 0);
+            }
+        }
+        if self.switch_target == Some(wid) {
+            self.switch_target = None;
+        }
 
         self.windows.sort_by_key(|w| w.z);
 
@@ -523,30 +540,7 @@ impl Composer {
             }
 
             for j in (0..self.windows.len()).rev() {
-                match self.windows[j].w_type {
-                    Items::Null => {}
-                    _ => {
-                        let border_color = if self.windows[j].w_type == Items::Window {
-                            if self.windows[j].id == CLICKED_WINDOW_ID {
-                                Some(0xFFFFFFFF)
-                            } else {
-                                Some(0xFF9070FF)
-                            }
-                        } else {
-                            None
-                        };
-
-                        display_server.copy_to_db(
-                            self.windows[j].width as u32,
-                            self.windows[j].height as u32,
-                            self.windows[j].buffer,
-                            self.windows[j].x as i32,
-                            self.windows[j].y as i32,
-                            border_color,
-                            self.windows[j].treat_as_transparent,
-                        );
-                    }
-                }
+                draw_to_db(display_server, &self.windows[j], None);
             }
 
             display_server.mark_dirty(0, 0, display_server.width as u32, display_server.height as u32);
@@ -561,6 +555,15 @@ impl Composer {
             if self.windows[i].pid == pid && self.windows[i].w_type != Items::Null {
                 self.windows[i].w_type = Items::Null;
                 self.windows[i].z = 255;
+                decorations::forget(self.windows[i].id);
+                unsafe { (*(&raw mut TILING)).forget(self.windows[i].id) };
+                unsafe { (*(&raw mut WORKSPACES)).forget(self.windows[i].id) };
+                unsafe { (*(&raw mut CURSORS)).forget(self.windows[i].id) };
+                EVENT_QUEUES.int_lock().discard(self.windows[i].id as u32);
+                notify_watchers(Event::WindowDestroyed, self.windows[i].id);
+                if self.switch_target == Some(self.windows[i].id) {
+                    self.switch_target = None;
+                }
                 removed = true;
             }
         }
@@ -578,27 +581,7 @@ impl Composer {
                 }
 
                 for j in (0..self.windows.len()).rev() {
-                    if self.windows[j].w_type != Items::Null {
-                        let border_color = if self.windows[j].w_type == Items::Window {
-                            if self.windows[j].id == CLICKED_WINDOW_ID {
-                                Some(0xFFFFFFFF)
-                            } else {
-                                Some(0xFF9070FF)
-                            }
-                        } else {
-                            None
-                        };
-
-                        display_server.copy_to_db(
-                            self.windows[j].width as u32,
-                            self.windows[j].height as u32,
-                            self.windows[j].buffer,
-                            self.windows[j].x as i32,
-                            self.windows[j].y as i32,
-                            border_color,
-                            self.windows[j].treat_as_transparent,
-                        );
-                    }
+                    draw_to_db(display_server, &self.windows[j], None);
                 }
                 display_server.mark_dirty(0, 0, display_server.width as u32, display_server.height as u32);
                 display_server.copy();
@@ -606,4 +589,313 @@ impl Composer {
             self.update_tiling();
         }
     }
-}
\ No newline at end of file
+
+    pub fn request_close(&mut self, id: usize) {
+        let (pid, event_handler) = match self.find_window_id(id) {
+            Some(w) => (w.pid, w.event_handler),
+            None => return,
+        };
+
+        if event_handler != 0 {
+            EVENT_QUEUES.int_lock().add_event(Event::CloseRequested(CloseRequestedEvent { wid: id as u32 }));
+        } else {
+            crate::interrupts::task::TASK_MANAGER.int_lock().kill_process(pid);
+        }
+    }
+
+    pub fn minimize_window(&mut self, id: usize) {
+        let frame = match self.find_window_id(id) {
+            Some(w) if w.w_type == Items::Window => {
+                w.minimized = true;
+                w.frame()
+            }
+            _ => return,
+        };
+        notify_watchers(Event::WindowChanged, id);
+
+        if unsafe { CLICKED_WINDOW_ID } == id {
+            self.set_focus(0);
+        }
+        if unsafe { (*(&raw mut TILING)).is_tiled(id) } {
+            self.update_tiling();
+            return;
+        }
+        let (x, y, width, height) = frame;
+        self.update_window_area_rect(x as i32, y as i32, width as u32, height as u32);
+    }
+
+    pub fn restore_minimized(&mut self) {
+        let mut restored = false;
+        for i in 0..self.windows.len() {
+            if self.windows[i].minimized {
+                self.windows[i].minimized = false;
+                notify_watchers(Event::WindowChanged, self.windows[i].id);
+                restored = true;
+            }
+        }
+
+        if restored {
+            self.update_tiling();
+        }
+    }
+
+    pub fn activate(&mut self, id: usize) -> bool {
+        let (was_minimized, workspace) = match self.find_window_id(id) {
+            Some(w) if w.w_type == Items::Window => (core::mem::replace(&mut w.minimized, false), w.workspace),
+            Some(w) if w.w_type == Items::Popup => {
+                self.set_focus(id);
+                return true;
+            }
+            _ => return false,
+        };
+        self.switch_workspace(workspace);
+
+        if was_minimized {
+            notify_watchers(Event::WindowChanged, id);
+            self.update_tiling();
+        }
+        self.set_focus(id);
+        true
+    }
+
+    pub fn window_list(&self) -> alloc::vec::Vec<WindowInfo> {
+        let focused = unsafe { CLICKED_WINDOW_ID };
+        self.windows.iter().filter(|w| w.w_type != Items::Null).map(|w| w.info(w.id == focused)).collect()
+    }
+
+    pub fn switch_step(&mut self, forward: bool) {
+        let workspace = unsafe { (*(&raw const WORKSPACES)).current };
+        // Focusing raises a window to the top, so z order doubles as recency.
+        let ids: alloc::vec::Vec<usize> = self.windows.iter().filter(|w| w.w_type == Items::Window && w.workspace == workspace).map(|w| w.id).collect();
+        if ids.is_empty() {
+            return;
+        }
+
+        let current = self.switch_target.unwrap_or(unsafe { CLICKED_WINDOW_ID });
+        let next = match ids.iter().position(|&id| id == current) {
+            Some(pos) if forward => ids[(pos + 1) % ids.len()],
+            Some(pos) => ids[(pos + ids.len() - 1) % ids.len()],
+            None => ids[0],
+        };
+
+        self.switch_target = Some(next);
+        notify_watchers(Event::WindowSwitch, next);
+    }
+
+    pub fn switch_finish(&mut self) {
+        if let Some(id) = self.switch_target.take() {
+            notify_watchers(Event::WindowSwitch, 0);
+            self.activate(id);
+        }
+    }
+
+    fn workspace_focus(&self, workspace: usize) -> Option<usize> {
+        let workspaces = unsafe { &*(&raw const WORKSPACES) };
+        workspaces.last_focused(workspace, |id| self.windows.iter().any(|w| w.id == id && w.is_visible()))
+    }
+
+    pub fn switch_workspace(&mut self, n: usize) {
+        let workspaces = unsafe { &mut *(&raw mut WORKSPACES) };
+        if n >= WORKSPACE_COUNT || n == workspaces.current {
+            return;
+        }
+
+        workspaces.current = n;
+        let focus = self.workspace_focus(n).unwrap_or(0);
+        self.set_focus(focus);
+        notify_watchers(Event::WorkspaceChanged, n);
+        self.update_tiling();
+    }
+
+    pub fn move_to_workspace(&mut self, id: usize, n: usize) -> bool {
+        if n >= WORKSPACE_COUNT {
+            return false;
+        }
+        match self.find_window_id(id) {
+            Some(w) if w.w_type == Items::Window => {
+                if w.workspace == n {
+                    return true;
+                }
+                w.workspace = n;
+            }
+            _ => return false,
+        }
+
+        let workspaces = unsafe { &mut *(&raw mut WORKSPACES) };
+        workspaces.forget(id);
+        workspaces.focused(n, id);
+        notify_watchers(Event::WindowChanged, id);
+
+        if unsafe { CLICKED_WINDOW_ID } == id {
+            let focus = self.workspace_focus(workspaces.current).unwrap_or(0);
+            self.set_focus(focus);
+        }
+        self.update_tiling();
+        true
+    }
+
+    fn work_area(&self) -> (isize, isize, usize, usize) {
+        let (sw, sh) = unsafe {
+            ((*(&raw mut DISPLAY_SERVER)).width as usize, (*(&raw mut DISPLAY_SERVER)).height as usize)
+        };
+
+        let mut top = 0;
+        for w in &self.windows {
+            if w.w_type == Items::Bar && w.y <= 0 {
+                top = top.max((w.y + w.height as isize).max(0) as usize);
+            }
+        }
+        (0, top as isize, sw, sh.saturating_sub(top))
+    }
+
+    pub fn toggle_maximize(&mut self, id: usize) {
+        let work_area = self.work_area();
+        let w = match self.find_window_id(id) {
+            Some(w) if w.w_type == Items::Window && w.can_resize => w,
+            _ => return,
+        };
+
+        let old_frame = w.frame();
+        let th = w.title_height();
+        let (x, y, width, height) = match decorations::take_restore(id) {
+            Some(geometry) => geometry,
+            None => {
+                decorations::set_restore(id, (w.x, w.y, w.width, w.height));
+                let (ax, ay, aw, ah) = work_area;
+                (ax, ay + th as isize, aw, ah.saturating_sub(th))
+            }
+        };
+
+        w.x = x;
+        w.y = y;
+        let event_handler = w.event_handler;
+
+        if event_handler != 0 {
+            EVENT_QUEUES.int_lock().add_event(Event::Resize(ResizeEvent { wid: id as u32, width, height }));
+        }
+
+        let (ox, oy, ow, oh) = old_frame;
+        self.update_window_area_rect(ox as i32, oy as i32, ow as u32, oh as u32);
+        self.update_window_area(id);
+    }
+
+    pub fn screen_resized(&mut self) {
+        let (sw, sh) = unsafe {
+            ((*(&raw mut DISPLAY_SERVER)).width as usize, (*(&raw mut DISPLAY_SERVER)).height as usize)
+        };
+        let (ax, ay, aw, ah) = self.work_area();
+
+        for i in 0..self.windows.len() {
+            let w = &mut self.windows[i];
+            if w.w_type != Items::Window {
+                continue;
+            }
+            let th = w.title_height();
+
+            if let Some(geometry) = decorations::take_restore(w.id) {
+                decorations::set_restore(w.id, geometry);
+                w.x = ax;
+                w.y = ay + th as isize;
+                if w.event_handler != 0 {
+                    let (width, height) = (aw, ah.saturating_sub(th));
+                    EVENT_QUEUES.int_lock().add_event(Event::Resize(ResizeEvent { wid: w.id as u32, width, height }));
+                }
+                continue;
+            }
+
+            let max_x = sw.saturating_sub(w.width) as isize;
+            let max_y = (sh.saturating_sub(w.height)).max(th) as isize;
+            w.x = w.x.min(max_x).max(0);
+            w.y = w.y.min(max_y).max(th as isize);
+        }
+
+        EVENT_QUEUES.int_lock().broadcast(|wid| Event::ScreenResized(ScreenEvent { wid, width: sw, height: sh }));
+        self.update_tiling();
+    }
+
+    fn focus_order(&self) -> alloc::vec::Vec<usize> {
+        let tiling = unsafe { &*(&raw const TILING) };
+        let visible = |id: usize| self.windows.iter().any(|w| w.id == id && w.w_type == Items::Window && w.is_visible());
+
+        if tiling.is_active() {
+            tiling.tiled(visible)
+        } else {
+            let mut ids: alloc::vec::Vec<usize> = self.windows.iter().filter(|w| w.w_type == Items::Window && w.is_visible()).map(|w| w.id).collect();
+            ids.sort();
+            ids
+        }
+    }
+
+    pub fn focus_neighbour(&mut self, forward: bool) {
+        let ids = self.focus_order();
+        if ids.is_empty() {
+            return;
+        }
+
+        let current = unsafe { CLICKED_WINDOW_ID };
+        let next = match ids.iter().position(|&id| id == current) {
+            Some(pos) if forward => ids[(pos + 1) % ids.len()],
+            Some(pos) => ids[(pos + ids.len() - 1) % ids.len()],
+            None => ids[0],
+        };
+
+        self.set_focus(next);
+    }
+
+    pub fn swap_focused(&mut self, forward: bool) {
+        let ids = self.focus_order();
+        let tiling = unsafe { &mut *(&raw mut TILING) };
+        if !tiling.is_active() {
+            return;
+        }
+        tiling.swap(unsafe { CLICKED_WINDOW_ID }, &ids, forward);
+        self.update_tiling();
+    }
+
+    pub fn promote_focused(&mut self) {
+        let tiling = unsafe { &mut *(&raw mut TILING) };
+        let id = unsafe { CLICKED_WINDOW_ID };
+        if tiling.is_tiled(id) {
+            tiling.promote(id);
+            self.update_tiling();
+        }
+    }
+
+    pub fn toggle_floating(&mut self, id: usize) {
+        match self.find_window_id(id) {
+            Some(w) if w.w_type == Items::Window => {}
+            _ => return,
+        }
+        let tiling = unsafe { &mut *(&raw mut TILING) };
+        let floating = tiling.is_floating(id);
+        tiling.set_floating(id, !floating);
+        self.update_tiling();
+    }
+
+    pub fn cycle_layout(&mut self) {
+        let tiling = unsafe { &mut *(&raw mut TILING) };
+        tiling.layout = tiling.layout.next();
+        debugln!("Tiling: {:?} layout", tiling.layout);
+        self.update_tiling();
+    }
+
+    pub fn adjust_master_ratio(&mut self, delta: isize) {
+        let tiling = unsafe { &mut *(&raw mut TILING) };
+        tiling.master_ratio = (tiling.master_ratio as isize + delta).clamp(MIN_MASTER_RATIO as isize, MAX_MASTER_RATIO as isize) as usize;
+        self.update_tiling();
+    }
+
+    pub fn adjust_gap(&mut self, delta: isize) {
+        let tiling = unsafe { &mut *(&raw mut TILING) };
+        tiling.gap = (tiling.gap as isize + delta).clamp(0, MAX_GAP as isize) as usize;
+        self.update_tiling();
+    }
+
+    pub fn configure_tiling(&mut self, layout: Layout, gap: usize, master_ratio: usize) {
+        let tiling = unsafe { &mut *(&raw mut TILING) };
+        tiling.layout = layout;
+        tiling.gap = gap.min(MAX_GAP);
+        tiling.master_ratio = master_ratio.clamp(MIN_MASTER_RATIO, MAX_MASTER_RATIO);
+        self.update_tiling();
+    }
+}
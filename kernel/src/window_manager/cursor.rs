@@ -0,0 +1,336 @@
+use super::decorations::{EDGE_BOTTOM, EDGE_LEFT, EDGE_RIGHT, EDGE_TOP};
+use super::display::{DISPLAY_SERVER, VIRTIO_ACTIVE};
+use crate::debugln;
+use crate::drivers::periferics::mouse::{CURSOR_BUFFER, CURSOR_HEIGHT, CURSOR_WIDTH};
+use crate::drivers::video::virtio;
+use crate::drivers::video::virtio::cursor::HW_CURSOR_SIZE;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+pub const MAX_CURSOR_SIZE: usize = HW_CURSOR_SIZE as usize;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(u64)]
+pub enum CursorShape {
+    Arrow = 0,
+    Text = 1,
+    Hand = 2,
+    ResizeHorizontal = 3,
+    ResizeVertical = 4,
+    ResizeDiagonal = 5,
+    Busy = 6,
+}
+
+impl CursorShape {
+    pub fn from_u64(value: u64) -> Option<CursorShape> {
+        match value {
+            0 => Some(CursorShape::Arrow),
+            1 => Some(CursorShape::Text),
+            2 => Some(CursorShape::Hand),
+            3 => Some(CursorShape::ResizeHorizontal),
+            4 => Some(CursorShape::ResizeVertical),
+            5 => Some(CursorShape::ResizeDiagonal),
+            6 => Some(CursorShape::Busy),
+            _ => None,
+        }
+    }
+
+    pub fn for_edges(edges: u8) -> CursorShape {
+        match (edges & (EDGE_LEFT | EDGE_RIGHT) != 0, edges & (EDGE_TOP | EDGE_BOTTOM) != 0) {
+            (true, true) => CursorShape::ResizeDiagonal,
+            (true, false) => CursorShape::ResizeHorizontal,
+            (false, true) => CursorShape::ResizeVertical,
+            (false, false) => CursorShape::Arrow,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CursorImage {
+    pub width: usize,
+    pub height: usize,
+    pub hot_x: usize,
+    pub hot_y: usize,
+    pub pixels: Vec<u32>,
+}
+
+impl CursorImage {
+    pub fn new(width: usize, height: usize, hot_x: usize, hot_y: usize, pixels: &[u32]) -> Result<CursorImage, String> {
+        if width == 0 || height == 0 || width > MAX_CURSOR_SIZE || height > MAX_CURSOR_SIZE {
+            return Err(String::from("Cursor size out of range"));
+        }
+        if hot_x >= width || hot_y >= height {
+            return Err(String::from("Hotspot outside the cursor"));
+        }
+        if pixels.len() < width * height {
+            return Err(String::from("Cursor image too short"));
+        }
+        Ok(CursorImage { width, height, hot_x, hot_y, pixels: pixels[..width * height].to_vec() })
+    }
+
+    pub fn builtin(shape: CursorShape) -> CursorImage {
+        match shape {
+            CursorShape::Arrow => CursorImage {
+                width: CURSOR_WIDTH,
+                height: CURSOR_HEIGHT,
+                hot_x: 0,
+                hot_y: 0,
+                pixels: CURSOR_BUFFER.to_vec(),
+            },
+            CursorShape::Text => from_art(&TEXT_ART, 3, 8),
+            CursorShape::Hand => from_art(&HAND_ART, 5, 0),
+            CursorShape::ResizeHorizontal => from_art(&RESIZE_ART, 8, 3),
+            CursorShape::ResizeVertical => {
+                let mut image = from_art(&RESIZE_ART, 8, 3);
+                image.transpose();
+                image
+            }
+            CursorShape::ResizeDiagonal => from_art(&DIAGONAL_ART, 6, 5),
+            CursorShape::Busy => from_art(&BUSY_ART, 5, 6),
+        }
+    }
+
+    fn transpose(&mut self) {
+        let mut pixels = Vec::with_capacity(self.pixels.len());
+        for x in 0..self.width {
+            for y in 0..self.height {
+                pixels.push(self.pixels[y * self.width + x]);
+            }
+        }
+        self.pixels = pixels;
+        core::mem::swap(&mut self.width, &mut self.height);
+        core::mem::swap(&mut self.hot_x, &mut self.hot_y);
+    }
+}
+
+// Built-in shapes are drawn like the arrow: black body, white outline. `X` is black, `.`
+// white and anything else transparent.
+const TEXT_ART: [&str; 17] = [
+    ".......",
+    ".XX.XX.",
+    "...X...",
+    "  .X.  ",
+    "  .X.  ",
+    "  .X.  ",
+    "  .X.  ",
+    "  .X.  ",
+    "  .X.  ",
+    "  .X.  ",
+    "  .X.  ",
+    "  .X.  ",
+    "  .X.  ",
+    "  .X.  ",
+    "...X...",
+    ".XX.XX.",
+    ".......",
+];
+
+const HAND_ART: [&str; 17] = [
+    "    ..        ",
+    "   .XX.       ",
+    "   .XX.       ",
+    "   .XX.       ",
+    "   .XX...     ",
+    "   .XX.XX...  ",
+    "   .XX.XX.XX. ",
+    ".. .XX.XX.XX..",
+    ".X..XXXXXXXXX.",
+    ".XX.XXXXXXXXX.",
+    " .XXXXXXXXXXX.",
+    "  .XXXXXXXXXX.",
+    "  .XXXXXXXXX. ",
+    "   .XXXXXXXX. ",
+    "   .XXXXXXXX. ",
+    "    .XXXXXX.  ",
+    "    ........  ",
+];
+
+const RESIZE_ART: [&str; 7] = [
+    "   .         .   ",
+    "  .X.       .X.  ",
+    " .XX.........XX. ",
+    ".XXXXXXXXXXXXXXX.",
+    " .XX.........XX. ",
+    "  .X.       .X.  ",
+    "   .         .   ",
+];
+
+const DIAGONAL_ART: [&str; 11] = [
+    "......      ",
+    ".XXXX.      ",
+    ".XXX.       ",
+    ".XXXX.      ",
+    ".X..XX.     ",
+    "..  .XX.  ..",
+    "     .XX..X.",
+    "      .XXXX.",
+    "       .XXX.",
+    "      .XXXX.",
+    "      ......",
+];
+
+const BUSY_ART: [&str; 13] = [
+    "...........",
+    ".XXXXXXXXX.",
+    " .X.....X. ",
+    " .X.....X. ",
+    "  .X...X.  ",
+    "   .X.X.   ",
+    "    .X.    ",
+    "   .X.X.   ",
+    "  .X...X.  ",
+    " .X..X..X. ",
+    " .X.XXX.X. ",
+    ".XXXXXXXXX.",
+    "...........",
+];
+
+fn from_art(art: &[&str], hot_x: usize, hot_y: usize) -> CursorImage {
+    let width = art.iter().map(|row| row.len()).max().unwrap_or(0);
+    let mut pixels = Vec::with_capacity(width * art.len());
+    for row in art {
+        let bytes = row.as_bytes();
+        for x in 0..width {
+            pixels.push(match bytes.get(x) {
+                Some(b'X') => 0xFF000000,
+                Some(b'.') => 0xFFFFFFFF,
+                _ => 0,
+            });
+        }
+    }
+    CursorImage { width, height: art.len(), hot_x, hot_y, pixels }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CursorSource {
+    Shape(CursorShape),
+    Window(usize),
+}
+
+pub struct Cursors {
+    image: Option<CursorImage>,
+    source: CursorSource,
+    generation: u64,
+    shown_generation: u64,
+    windows: Vec<(usize, CursorImage)>,
+    hardware: u64,
+    last_hw_pos: (u16, u16),
+}
+
+pub static mut CURSORS: Cursors = Cursors {
+    image: None,
+    source: CursorSource::Shape(CursorShape::Arrow),
+    generation: 0,
+    shown_generation: 0,
+    windows: Vec::new(),
+    hardware: 0,
+    last_hw_pos: (u16::MAX, u16::MAX),
+};
+
+impl Cursors {
+    pub fn image(&mut self) -> &CursorImage {
+        self.image.get_or_insert_with(|| CursorImage::builtin(CursorShape::Arrow))
+    }
+
+    pub fn is_hardware(&self) -> bool {
+        self.hardware != 0
+    }
+
+    pub fn rect(&mut self, x: u16, y: u16) -> (i32, i32, u32, u32) {
+        if self.is_hardware() {
+            return (x as i32, y as i32, 0, 0);
+        }
+        let image = self.image();
+        (x as i32 - image.hot_x as i32, y as i32 - image.hot_y as i32, image.width as u32, image.height as u32)
+    }
+
+    pub fn set_window(&mut self, wid: usize, image: Option<CursorImage>) {
+        self.windows.retain(|(id, _)| *id != wid);
+        if let Some(image) = image {
+            self.windows.push((wid, image));
+        }
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    pub fn forget(&mut self, wid: usize) {
+        self.windows.retain(|(id, _)| *id != wid);
+    }
+
+    pub fn move_hardware(&mut self, x: u16, y: u16) {
+        if self.last_hw_pos != (x, y) {
+            self.last_hw_pos = (x, y);
+            virtio::cursor::move_cursor(x as u32, y as u32);
+        }
+    }
+
+    pub fn init_hardware(&mut self) {
+        let bytes = (HW_CURSOR_SIZE * HW_CURSOR_SIZE * 4) as usize;
+        match crate::memory::pmm::allocate_frames(bytes.div_ceil(4096), 0) {
+            Some(phys) => {
+                virtio::cursor::setup_cursor(phys);
+                self.hardware = phys + crate::memory::paging::HHDM_OFFSET;
+                self.upload(0, 0);
+                debugln!("DisplayServer: Hardware cursor active.");
+            }
+            None => debugln!("DisplayServer: Hardware cursor buffer alloc failed, drawing it in software."),
+        }
+    }
+
+    fn upload(&mut self, x: u16, y: u16) {
+        let backing = self.hardware as *mut u32;
+        let size = HW_CURSOR_SIZE as usize;
+        let image = self.image().clone();
+        unsafe {
+            core::ptr::write_bytes(backing, 0, size * size);
+            for row in 0..image.height {
+                core::ptr::copy_nonoverlapping(image.pixels.as_ptr().add(row * image.width), backing.add(row * size), image.width);
+            }
+        }
+        self.last_hw_pos = (x, y);
+        virtio::cursor::update_cursor(x as u32, y as u32, image.hot_x as u32, image.hot_y as u32);
+    }
+
+    pub fn show(&mut self, source: CursorSource, x: u16, y: u16) {
+        if source == self.source && (matches!(source, CursorSource::Shape(_)) || self.generation == self.shown_generation) {
+            return;
+        }
+
+        let image = match source {
+            CursorSource::Shape(shape) => CursorImage::builtin(shape),
+            CursorSource::Window(wid) => match self.windows.iter().find(|(id, _)| *id == wid) {
+                Some((_, image)) => image.clone(),
+                None => CursorImage::builtin(CursorShape::Arrow),
+            },
+        };
+        self.source = source;
+        self.shown_generation = self.generation;
+
+        if self.is_hardware() {
+            self.image = Some(image);
+            self.upload(x, y);
+            return;
+        }
+
+        unsafe {
+            let ds = &mut *(&raw mut DISPLAY_SERVER);
+            let old = self.rect(x, y);
+            self.image = Some(image);
+            let new = self.rect(x, y);
+
+            ds.copy_to_fb(old.0, old.1, old.2, old.3);
+            ds.draw_mouse(x, y, false);
+
+            if VIRTIO_ACTIVE {
+                let sw = ds.width as i32;
+                let sh = ds.height as i32;
+                let x0 = old.0.min(new.0).max(0);
+                let y0 = old.1.min(new.1).max(0);
+                let x1 = (old.0 + old.2 as i32).max(new.0 + new.2 as i32).min(sw);
+                let y1 = (old.1 + old.3 as i32).max(new.1 + new.3 as i32).min(sh);
+                if x1 > x0 && y1 > y0 {
+                    virtio::flush(x0 as u32, y0 as u32, (x1 - x0) as u32, (y1 - y0) as u32, sw as u32, ds.active_resource_id);
+                }
+            }
+        }
+    }
+}
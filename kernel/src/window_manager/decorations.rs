@@ -0,0 +1,223 @@
+use super::font::{self, GLYPH_HEIGHT, GLYPH_WIDTH};
+use super::window::{Window, TITLE_LEN};
+use alloc::vec;
+use alloc::vec::Vec;
+
+pub const TITLE_HEIGHT: usize = 24;
+pub const RESIZE_MARGIN: isize = 6;
+
+pub const EDGE_RIGHT: u8 = 1 << 0;
+pub const EDGE_BOTTOM: u8 = 1 << 1;
+pub const EDGE_LEFT: u8 = 1 << 2;
+pub const EDGE_TOP: u8 = 1 << 3;
+
+const BUTTON_SIZE: usize = 16;
+const BUTTON_GAP: usize = 6;
+const BUTTON_Y: usize = (TITLE_HEIGHT - BUTTON_SIZE) / 2;
+const TEXT_SCALE: usize = 2;
+const TEXT_PADDING: usize = 10;
+
+const BAR_FOCUSED: u32 = 0xFF2B2B3A;
+const BAR_UNFOCUSED: u32 = 0xFF1C1C24;
+const TEXT_FOCUSED: u32 = 0xFFF5F5F7;
+const TEXT_UNFOCUSED: u32 = 0xFF9090A0;
+const BUTTON_COLOR: u32 = 0xFF3C3C4E;
+const CLOSE_COLOR: u32 = 0xFFE05561;
+const ICON_COLOR: u32 = 0xFFFFFFFF;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Hit {
+    Client,
+    Title,
+    Minimize,
+    Maximize,
+    Close,
+    Resize(u8),
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Button {
+    Minimize,
+    Maximize,
+    Close,
+}
+
+struct Decoration {
+    wid: usize,
+    width: usize,
+    focused: bool,
+    title: [u8; TITLE_LEN],
+    pixels: Vec<u32>,
+    restore: Option<(isize, isize, usize, usize)>,
+}
+
+static mut DECORATIONS: Vec<Decoration> = Vec::new();
+
+fn entry(wid: usize) -> &'static mut Decoration {
+    let decorations = unsafe { &mut *(&raw mut DECORATIONS) };
+    let idx = match decorations.iter().position(|d| d.wid == wid) {
+        Some(idx) => idx,
+        None => {
+            decorations.push(Decoration {
+                wid,
+                width: 0,
+                focused: false,
+                title: [0; TITLE_LEN],
+                pixels: Vec::new(),
+                restore: None,
+            });
+            decorations.len() - 1
+        }
+    };
+    &mut decorations[idx]
+}
+
+pub fn title_bar(w: &Window, focused: bool) -> usize {
+    let d = entry(w.id);
+    if d.pixels.is_empty() || d.width != w.width || d.focused != focused || d.title != w.title {
+        d.width = w.width;
+        d.focused = focused;
+        d.title = w.title;
+        d.pixels = vec![0; w.width * TITLE_HEIGHT];
+        render(&mut d.pixels, w.width, w.title(), focused);
+    }
+    d.pixels.as_ptr() as usize
+}
+
+pub fn forget(wid: usize) {
+    let decorations = unsafe { &mut *(&raw mut DECORATIONS) };
+    decorations.retain(|d| d.wid != wid);
+}
+
+pub fn take_restore(wid: usize) -> Option<(isize, isize, usize, usize)> {
+    entry(wid).restore.take()
+}
+
+pub fn set_restore(wid: usize, geometry: (isize, isize, usize, usize)) {
+    entry(wid).restore = Some(geometry);
+}
+
+fn button_x(width: usize, button: Button) -> usize {
+    let slot = match button {
+        Button::Close => 1,
+        Button::Maximize => 2,
+        Button::Minimize => 3,
+    };
+    width.saturating_sub(slot * (BUTTON_SIZE + BUTTON_GAP))
+}
+
+pub fn hit_test(w: &Window, x: isize, y: isize) -> Hit {
+    let local_x = x - w.x;
+    let local_y = y - w.y;
+
+    // The top edge is the upper rows of the title bar, above its buttons.
+    if w.has_title_bar() && w.can_resize && local_y < RESIZE_MARGIN / 2 - TITLE_HEIGHT as isize {
+        let mut edges = EDGE_TOP;
+        if local_x < RESIZE_MARGIN {
+            edges |= EDGE_LEFT;
+        } else if local_x >= w.width as isize - RESIZE_MARGIN {
+            edges |= EDGE_RIGHT;
+        }
+        return Hit::Resize(edges);
+    }
+
+    if w.has_title_bar() && local_y < 0 {
+        let bar_y = (local_y + TITLE_HEIGHT as isize) as usize;
+        if local_x >= 0 && bar_y >= BUTTON_Y && bar_y < BUTTON_Y + BUTTON_SIZE {
+            for button in [Button::Close, Button::Maximize, Button::Minimize] {
+                let bx = button_x(w.width, button) as isize;
+                if local_x >= bx && local_x < bx + BUTTON_SIZE as isize {
+                    return match button {
+                        Button::Minimize => Hit::Minimize,
+                        Button::Maximize => Hit::Maximize,
+                        Button::Close => Hit::Close,
+                    };
+                }
+            }
+        }
+        return Hit::Title;
+    }
+
+    if w.has_title_bar() && w.can_resize {
+        let mut edges = 0;
+        if local_x < RESIZE_MARGIN {
+            edges |= EDGE_LEFT;
+        } else if local_x >= w.width as isize - RESIZE_MARGIN {
+            edges |= EDGE_RIGHT;
+        }
+        if local_y >= w.height as isize - RESIZE_MARGIN {
+            edges |= EDGE_BOTTOM;
+        }
+        if edges != 0 {
+            return Hit::Resize(edges);
+        }
+    }
+
+    Hit::Client
+}
+
+fn render(pixels: &mut [u32], width: usize, title: &str, focused: bool) {
+    pixels.fill(if focused { BAR_FOCUSED } else { BAR_UNFOCUSED });
+
+    let text_color = if focused { TEXT_FOCUSED } else { TEXT_UNFOCUSED };
+    let text_end = button_x(width, Button::Minimize).saturating_sub(BUTTON_GAP);
+    let text_y = (TITLE_HEIGHT - GLYPH_HEIGHT * TEXT_SCALE) / 2;
+    let advance = (GLYPH_WIDTH + 1) * TEXT_SCALE;
+
+    let mut pen_x = TEXT_PADDING;
+    for c in title.chars() {
+        if pen_x + GLYPH_WIDTH * TEXT_SCALE > text_end {
+            break;
+        }
+        draw_glyph(pixels, width, pen_x, text_y, c, text_color);
+        pen_x += advance;
+    }
+
+    for button in [Button::Minimize, Button::Maximize, Button::Close] {
+        draw_button(pixels, width, button_x(width, button), button);
+    }
+}
+
+fn draw_glyph(pixels: &mut [u32], width: usize, x: usize, y: usize, c: char, color: u32) {
+    let rows = font::glyph(c);
+    for (row, bits) in rows.iter().enumerate() {
+        for col in 0..GLYPH_WIDTH {
+            if bits & (1 << (GLYPH_WIDTH - 1 - col)) != 0 {
+                fill_rect(pixels, width, x + col * TEXT_SCALE, y + row * TEXT_SCALE, TEXT_SCALE, TEXT_SCALE, color);
+            }
+        }
+    }
+}
+
+fn draw_button(pixels: &mut [u32], width: usize, x: usize, button: Button) {
+    let bg = if button == Button::Close { CLOSE_COLOR } else { BUTTON_COLOR };
+    fill_rect(pixels, width, x, BUTTON_Y, BUTTON_SIZE, BUTTON_SIZE, bg);
+
+    let inset = 4;
+    let size = BUTTON_SIZE - inset * 2;
+    let (ix, iy) = (x + inset, BUTTON_Y + inset);
+
+    match button {
+        Button::Minimize => fill_rect(pixels, width, ix, iy + size - 2, size, 2, ICON_COLOR),
+        Button::Maximize => {
+            fill_rect(pixels, width, ix, iy, size, 2, ICON_COLOR);
+            fill_rect(pixels, width, ix, iy + size - 1, size, 1, ICON_COLOR);
+            fill_rect(pixels, width, ix, iy, 1, size, ICON_COLOR);
+            fill_rect(pixels, width, ix + size - 1, iy, 1, size, ICON_COLOR);
+        }
+        Button::Close => {
+            for i in 0..size {
+                fill_rect(pixels, width, ix + i, iy + i, 1, 1, ICON_COLOR);
+                fill_rect(pixels, width, ix + size - 1 - i, iy + i, 1, 1, ICON_COLOR);
+            }
+        }
+    }
+}
+
+fn fill_rect(pixels: &mut [u32], width: usize, x: usize, y: usize, w: usize, h: usize, color: u32) {
+    for row in y..(y + h).min(TITLE_HEIGHT) {
+        for col in x..(x + w).min(width) {
+            pixels[row * width + col] = color;
+        }
+    }
+}
@@ -1,8 +1,32 @@
 use crate::drivers::video::virtio;
+use crate::drivers::video::virtio::structs::VirtioGpuRect;
+use crate::window_manager::composer::COMPOSER;
+use crate::window_manager::cursor::CURSORS;
 use crate::{debugln, println};
+use alloc::string::String;
+use alloc::vec::Vec;
 use core::arch::x86_64::*;
 
 pub const DEPTH: u8 = 32;
+pub const MAX_DIMENSION: u64 = 8192;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanoutMode {
+    Primary,
+    Mirror,
+    Span,
+}
+
+impl ScanoutMode {
+    pub fn from_u64(value: u64) -> Option<Self> {
+        match value {
+            0 => Some(ScanoutMode::Primary),
+            1 => Some(ScanoutMode::Mirror),
+            2 => Some(ScanoutMode::Span),
+            _ => None,
+        }
+    }
+}
 
 pub struct DisplayServer {
     pub width: u64,
@@ -24,6 +48,9 @@ pub struct DisplayServer {
     pub dirty_max_x: i32,
     pub dirty_max_y: i32,
     pub has_dirty: bool,
+
+    pub scanout_mode: ScanoutMode,
+    pub headless: bool,
 }
 
 pub static mut DISPLAY_SERVER: DisplayServer = DisplayServer {
@@ -43,6 +70,8 @@ pub static mut DISPLAY_SERVER: DisplayServer = DisplayServer {
     dirty_max_x: i32::MIN,
     dirty_max_y: i32::MIN,
     has_dirty: false,
+    scanout_mode: ScanoutMode::Primary,
+    headless: false,
 };
 
 pub static mut VIRTIO_ACTIVE: bool = false;
@@ -126,22 +155,7 @@ impl DisplayServer {
                 virtio::transfer_and_flush(2, self.width as u32, self.height as u32);
 
 
-                use crate::drivers::periferics::mouse::{CURSOR_BUFFER, CURSOR_HEIGHT, CURSOR_WIDTH};
-                let cursor_size_bytes = (CURSOR_WIDTH * CURSOR_HEIGHT * 4) as usize;
-                let cursor_pages = (cursor_size_bytes + 4095) / 4096;
-                if let Some(cursor_phys) = crate::memory::pmm::allocate_frames(cursor_pages, 0) {
-                    let cursor_ptr = (cursor_phys + crate::memory::paging::HHDM_OFFSET) as *mut u32;
-
-                    for i in 0..CURSOR_BUFFER.len() {
-                        *cursor_ptr.add(i) = CURSOR_BUFFER[i];
-                    }
-
-
-                    debugln!("DisplayServer: Hardware cursor is DISABLED by request.");
-                } else {
-                    println!("DisplayServer: Failed to allocate hardware cursor buffer!");
-                    debugln!("DisplayServer: Hardware cursor is NOT ACTIVE (buffer alloc failed).");
-                }
+                (*(&raw mut CURSORS)).init_hardware();
 
                 VIRTIO_ACTIVE = true;
 
@@ -152,6 +166,12 @@ impl DisplayServer {
             }
         }
 
+        if vbe.framebuffer == 0 {
+            println!("DisplayServer: No framebuffer, running headless");
+            self.init_headless(self.width.max(640), self.height.max(480));
+            return;
+        }
+
         println!("DisplayServer: Using VBE fallback");
         self.width = vbe.width as u64;
         self.pitch = vbe.pitch as u64;
@@ -176,6 +196,154 @@ impl DisplayServer {
         }
     }
 
+    pub fn init_headless(&mut self, width: u64, height: u64) {
+        self.width = width;
+        self.height = height;
+        self.pitch = width * 4;
+        self.depth = 32;
+        self.reset_dirty();
+
+        let layout = headless_layout(width, height);
+
+        unsafe {
+            VIRTIO_ACTIVE = false;
+            self.headless = true;
+
+            let framebuffer = alloc::alloc::alloc_zeroed(layout);
+            let double_buffer = alloc::alloc::alloc_zeroed(layout);
+            if framebuffer.is_null() || double_buffer.is_null() {
+                panic!("[DisplayServer] Failed to allocate headless buffers!");
+            }
+
+            self.framebuffer = framebuffer as u64;
+            self.double_buffer = double_buffer as u64;
+        }
+    }
+
+    pub fn set_mode(&mut self, width: u64, height: u64, mode: ScanoutMode) -> Result<bool, String> {
+        if width > MAX_DIMENSION || height > MAX_DIMENSION {
+            return Err(String::from("Mode too large"));
+        }
+
+        if self.headless {
+            let (width, height) = if width == 0 || height == 0 { (self.width, self.height) } else { (width, height) };
+            let changed = (width, height) != (self.width, self.height);
+            if changed {
+                let old = headless_layout(self.width, self.height);
+                unsafe {
+                    alloc::alloc::dealloc(self.framebuffer as *mut u8, old);
+                    alloc::alloc::dealloc(self.double_buffer as *mut u8, old);
+                }
+                self.init_headless(width, height);
+            }
+            self.scanout_mode = mode;
+            return Ok(changed);
+        }
+
+        if !unsafe { VIRTIO_ACTIVE } {
+            return Err(String::from("Mode changes need a VirtIO GPU"));
+        }
+
+        let scanouts = virtio::display_modes();
+        let first = scanouts.first().copied().unwrap_or(virtio::ScanoutInfo {
+            id: 0,
+            width: self.width as u32,
+            height: self.height as u32,
+        });
+        let pick = |w: u64, h: u64| if w == 0 || h == 0 { (first.width, first.height) } else { (w as u32, h as u32) };
+        let rect = |x: u32, width: u32, height: u32| VirtioGpuRect { x, y: 0, width, height };
+
+        let (new_w, new_h, layout) = match mode {
+            ScanoutMode::Primary => {
+                let (w, h) = pick(width, height);
+                let mut layout = Vec::new();
+                layout.push((first.id, rect(0, w, h)));
+                (w, h, layout)
+            }
+            ScanoutMode::Mirror => {
+                let (w, h) = pick(width, height);
+                let mut layout: Vec<_> = scanouts.iter().map(|s| (s.id, rect(0, w, h))).collect();
+                if layout.is_empty() {
+                    layout.push((first.id, rect(0, w, h)));
+                }
+                (w, h, layout)
+            }
+            ScanoutMode::Span => {
+                if width != 0 || height != 0 {
+                    return Err(String::from("Spanned modes follow the scanout sizes"));
+                }
+                let mut layout = Vec::new();
+                let (mut x, mut h) = (0, 0);
+                for s in scanouts.iter() {
+                    layout.push((s.id, rect(x, s.width, s.height)));
+                    x += s.width;
+                    h = h.max(s.height);
+                }
+                (x, h, layout)
+            }
+        };
+
+        if new_w == 0 || new_h == 0 || new_w as u64 > MAX_DIMENSION || new_h as u64 > MAX_DIMENSION {
+            return Err(String::from("No usable scanout"));
+        }
+
+        let changed = (new_w as u64, new_h as u64) != (self.width, self.height);
+        if changed {
+            self.reallocate(new_w as u64, new_h as u64)?;
+        }
+
+        virtio::set_layout(layout);
+        virtio::set_scanout(self.active_resource_id, new_w, new_h);
+        virtio::transfer_and_flush(self.active_resource_id, new_w, new_h);
+        self.scanout_mode = mode;
+
+        debugln!("DisplayServer: Mode {}x{} ({:?})", new_w, new_h, mode);
+        Ok(changed)
+    }
+
+    fn reallocate(&mut self, width: u64, height: u64) -> Result<(), String> {
+        let size_bytes = (width * 4 * height) as usize;
+        let pages = (size_bytes + 4095) / 4096;
+
+        let b1 = crate::memory::pmm::allocate_frames(pages, 0).ok_or_else(|| String::from("Out of memory for display buffers"))?;
+        let b2 = match crate::memory::pmm::allocate_frames(pages, 0) {
+            Some(b2) => b2,
+            None => {
+                crate::memory::pmm::free_frame(b1);
+                return Err(String::from("Out of memory for display buffers"));
+            }
+        };
+
+        virtio::disable_scanouts();
+        virtio::destroy_resource(1);
+        virtio::destroy_resource(2);
+        crate::memory::pmm::free_frame(self.buffer1_phys);
+        crate::memory::pmm::free_frame(self.buffer2_phys);
+
+        let b1_virt = b1 + crate::memory::paging::HHDM_OFFSET;
+        let b2_virt = b2 + crate::memory::paging::HHDM_OFFSET;
+        unsafe {
+            core::ptr::write_bytes(b1_virt as *mut u8, 0, size_bytes);
+            core::ptr::write_bytes(b2_virt as *mut u8, 0, size_bytes);
+        }
+
+        virtio::create_resource(1, width as u32, height as u32, b1);
+        virtio::create_resource(2, width as u32, height as u32, b2);
+
+        self.width = width;
+        self.height = height;
+        self.pitch = width * 4;
+        self.buffer1_phys = b1;
+        self.buffer2_phys = b2;
+        self.buffer1_virt = b1_virt;
+        self.buffer2_virt = b2_virt;
+        self.framebuffer = b1_virt;
+        self.double_buffer = b2_virt;
+        self.active_resource_id = 1;
+        self.reset_dirty();
+        Ok(())
+    }
+
     pub fn copy(&mut self) {
         unsafe {
             if VIRTIO_ACTIVE {
@@ -1058,13 +1226,12 @@ impl DisplayServer {
 
                 let mx = crate::window_manager::input::MOUSE.x;
                 let my = crate::window_manager::input::MOUSE.y;
-                use crate::drivers::periferics::mouse::{CURSOR_HEIGHT, CURSOR_WIDTH};
-
-                let mw = CURSOR_WIDTH as u32;
-                let mh = CURSOR_HEIGHT as u32;
+                let (cx, cy, cw, ch) = (*(&raw mut CURSORS)).rect(mx, my);
+                let (cx0, cy0) = (cx.max(0) as u32, cy.max(0) as u32);
+                let (cx1, cy1) = ((cx + cw as i32).max(0) as u32, (cy + ch as i32).max(0) as u32);
 
-                let overlap_x = (mx as u32) < (sx + sw) && (mx as u32 + mw) > sx;
-                let overlap_y = (my as u32) < (sy + sh) && (my as u32 + mh) > sy;
+                let overlap_x = cx0 < (sx + sw) && cx1 > sx;
+                let overlap_y = cy0 < (sy + sh) && cy1 > sy;
 
                 if overlap_x && overlap_y {
                     self.draw_mouse(mx, my, false);
@@ -1073,11 +1240,12 @@ impl DisplayServer {
                 virtio::flush(sx, sy, sw, sh, self.width as u32, self.active_resource_id);
 
                 if overlap_x && overlap_y {
-                    let mouse_inside = (mx as u32) >= sx && (mx as u32 + mw) <= (sx + sw) &&
-                        (my as u32) >= sy && (my as u32 + mh) <= (sy + sh);
+                    let mouse_inside = cx0 >= sx && cx1 <= (sx + sw) && cy0 >= sy && cy1 <= (sy + sh);
+                    let fw = cx1.min(self.width as u32).saturating_sub(cx0);
+                    let fh = cy1.min(self.height as u32).saturating_sub(cy0);
 
-                    if !mouse_inside {
-                        virtio::flush(mx as u32, my as u32, mw, mh, self.width as u32, self.active_resource_id);
+                    if !mouse_inside && fw > 0 && fh > 0 {
+                        virtio::flush(cx0, cy0, fw, fh, self.width as u32, self.active_resource_id);
                     }
                 }
             } else {
@@ -1085,11 +1253,9 @@ impl DisplayServer {
 
                 let mx = crate::window_manager::input::MOUSE.x;
                 let my = crate::window_manager::input::MOUSE.y;
-                use crate::drivers::periferics::mouse::{CURSOR_HEIGHT, CURSOR_WIDTH};
-                let mw = CURSOR_WIDTH as u32;
-                let mh = CURSOR_HEIGHT as u32;
-                let overlap_x = (mx as u32) < (sx + sw) && (mx as u32 + mw) > sx;
-                let overlap_y = (my as u32) < (sy + sh) && (my as u32 + mh) > sy;
+                let (cx, cy, cw, ch) = (*(&raw mut CURSORS)).rect(mx, my);
+                let overlap_x = cx < (sx + sw) as i32 && cx + cw as i32 > sx as i32;
+                let overlap_y = cy < (sy + sh) as i32 && cy + ch as i32 > sy as i32;
                 if overlap_x && overlap_y {
                     self.draw_mouse(mx, my, false);
                 }
@@ -1098,46 +1264,82 @@ impl DisplayServer {
     }
 
     pub fn draw_mouse(&self, x: u16, y: u16, dragging_window: bool) {
-        use crate::drivers::periferics::mouse::{CURSOR_BUFFER, CURSOR_HEIGHT, CURSOR_WIDTH};
+        let cursors = unsafe { &mut *(&raw mut CURSORS) };
+        if cursors.is_hardware() {
+            cursors.move_hardware(x, y);
+            return;
+        }
+        let (left, top, _, _) = cursors.rect(x, y);
+        let image = cursors.image();
 
-        let pitch_bytes = self.pitch as usize;
         let fb_ptr = self.framebuffer as *mut u32;
         let db_ptr = self.double_buffer as *const u32;
-        let width = self.width as usize;
-        let height = self.height as usize;
-        let mx = x as usize;
-        let my = y as usize;
-
-        let bg_src = if dragging_window { fb_ptr as *const u32 } else { db_ptr };
+        let width = self.width as i32;
+        let height = self.height as i32;
+        let pitch_u32 = self.pitch as usize / 4;
 
         unsafe {
-            let fb_pitch_u32 = pitch_bytes / 4;
-
-            for row in 0..CURSOR_HEIGHT {
-                let screen_y = my + row;
+            for row in 0..image.height {
+                let screen_y = top + row as i32;
+                if screen_y < 0 { continue; }
                 if screen_y >= height { break; }
 
-                let fb_row_start = screen_y * fb_pitch_u32 + mx;
-                let cursor_row_start = row * CURSOR_WIDTH;
-
-                for col in 0..CURSOR_WIDTH {
-                    let screen_x = mx + col;
+                for col in 0..image.width {
+                    let screen_x = left + col as i32;
+                    if screen_x < 0 { continue; }
                     if screen_x >= width { break; }
 
-                    let cursor_color = CURSOR_BUFFER[cursor_row_start + col];
+                    let offset = screen_y as usize * pitch_u32 + screen_x as usize;
+                    let cursor_color = image.pixels[row * image.width + col];
 
-                    if cursor_color != 0 {
-                        *fb_ptr.add(fb_row_start + col) = cursor_color;
-                    } else if !dragging_window {
-                        let bg_color = *bg_src.add(fb_row_start + col);
-                        *fb_ptr.add(fb_row_start + col) = bg_color;
-                    }
+                    let background = if dragging_window { *fb_ptr.add(offset) } else { *db_ptr.add(offset) };
+                    *fb_ptr.add(offset) = match cursor_color >> 24 {
+                        0 => background,
+                        255 => cursor_color,
+                        alpha => blend(cursor_color, background, alpha),
+                    };
                 }
             }
         }
     }
 }
 
+fn blend(src: u32, dst: u32, alpha: u32) -> u32 {
+    let inv_alpha = 255 - alpha;
+    let channel = |shift: u32| ((((src >> shift) & 0xFF) * alpha + ((dst >> shift) & 0xFF) * inv_alpha) / 255) << shift;
+    0xFF00_0000 | channel(16) | channel(8) | channel(0)
+}
+
+fn headless_layout(width: u64, height: u64) -> core::alloc::Layout {
+    let size_bytes = (width * 4 * height).max(4) as usize;
+    core::alloc::Layout::from_size_align(size_bytes, 16).expect("[DisplayServer] Bad headless size")
+}
+
+pub fn change_mode(width: u64, height: u64, mode: ScanoutMode) -> Result<(), String> {
+    unsafe {
+        let ds = &mut *(&raw mut DISPLAY_SERVER);
+        if ds.set_mode(width, height, mode)? {
+            let mouse = &mut *(&raw mut crate::window_manager::input::MOUSE);
+            mouse.x = mouse.x.min(ds.width.saturating_sub(1) as u16);
+            mouse.y = mouse.y.min(ds.height.saturating_sub(1) as u16);
+            (*(&raw mut COMPOSER)).screen_resized();
+        }
+    }
+    Ok(())
+}
+
+pub fn poll_display_events() {
+    unsafe {
+        if !VIRTIO_ACTIVE || !virtio::take_display_event() {
+            return;
+        }
+        let mode = (*(&raw const DISPLAY_SERVER)).scanout_mode;
+        if let Err(e) = change_mode(0, 0, mode) {
+            debugln!("DisplayServer: {}", e);
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(C)]
 pub struct Window {
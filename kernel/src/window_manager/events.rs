@@ -1,4 +1,5 @@
 use crate::sync::Mutex;
+use alloc::collections::VecDeque;
 use alloc::vec::Vec;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -36,6 +37,67 @@ pub struct RedrawEvent {
     pub to_db: bool,
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(C)]
+pub struct CloseRequestedEvent {
+    pub wid: u32,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(C)]
+pub struct FocusEvent {
+    pub wid: u32,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(C)]
+pub struct PointerEvent {
+    pub wid: u32,
+    pub x: usize,
+    pub y: usize,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(C)]
+pub struct CloseEvent {
+    pub wid: u32,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(C)]
+pub struct ClipboardEvent {
+    pub wid: u32,
+    pub owner: u32,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(C)]
+pub struct ScreenEvent {
+    pub wid: u32,
+    pub width: usize,
+    pub height: usize,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(C)]
+pub struct ThemeEvent {
+    pub wid: u32,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(C)]
+pub struct WindowListEvent {
+    pub wid: u32,
+    pub subject: u32,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(C)]
+pub struct OverflowEvent {
+    pub wid: u32,
+    pub dropped: usize,
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 #[repr(C)]
 pub enum Event {
@@ -43,6 +105,22 @@ pub enum Event {
     Keyboard(KeyboardEvent),
     Resize(ResizeEvent),
     Redraw(RedrawEvent),
+    CloseRequested(CloseRequestedEvent),
+    FocusIn(FocusEvent),
+    FocusOut(FocusEvent),
+    PointerEnter(PointerEvent),
+    PointerLeave(PointerEvent),
+    Close(CloseEvent),
+    SelectionChanged(ClipboardEvent),
+    WindowCreated(WindowListEvent),
+    WindowDestroyed(WindowListEvent),
+    WindowFocused(WindowListEvent),
+    WindowChanged(WindowListEvent),
+    WindowSwitch(WindowListEvent),
+    ScreenResized(ScreenEvent),
+    WorkspaceChanged(WindowListEvent),
+    ThemeChanged(ThemeEvent),
+    Overflow(OverflowEvent),
     None,
 }
 
@@ -53,88 +131,198 @@ impl Event {
             Event::Keyboard(event) => event.wid,
             Event::Resize(event) => event.wid,
             Event::Redraw(event) => event.wid,
+            Event::CloseRequested(event) => event.wid,
+            Event::FocusIn(event) | Event::FocusOut(event) => event.wid,
+            Event::PointerEnter(event) | Event::PointerLeave(event) => event.wid,
+            Event::Close(event) => event.wid,
+            Event::SelectionChanged(event) => event.wid,
+            Event::WindowCreated(event)
+            | Event::WindowDestroyed(event)
+            | Event::WindowFocused(event)
+            | Event::WindowChanged(event)
+            | Event::WindowSwitch(event)
+            | Event::WorkspaceChanged(event) => event.wid,
+            Event::ScreenResized(event) => event.wid,
+            Event::ThemeChanged(event) => event.wid,
+            Event::Overflow(event) => event.wid,
             Event::None => 0,
         }
     }
+
+    fn is_hover(&self) -> bool {
+        matches!(self, Event::Mouse(m) if m.buttons == [false; 3] && m.scroll == 0)
+    }
+}
+
+pub const QUEUE_CAPACITY: usize = 128;
+
+pub struct WindowQueue {
+    wid: u32,
+    events: VecDeque<Event>,
+    dropped: usize,
+    closed: bool,
+    waiter: Option<usize>,
+    watching: bool,
 }
 
-pub const QUEUE_SIZE: usize = 256;
+impl WindowQueue {
+    fn push(&mut self, event: Event) -> bool {
+        if self.coalesce(&event) {
+            return true;
+        }
+
+        if self.events.len() >= QUEUE_CAPACITY {
+            self.dropped += 1;
+            if event.is_hover() {
+                return false;
+            }
+            match self.events.iter().position(|e| e.is_hover()) {
+                Some(idx) => {
+                    self.events.remove(idx);
+                }
+                None => return false,
+            }
+        }
+
+        self.events.push_back(event);
+        true
+    }
+
+    fn coalesce(&mut self, event: &Event) -> bool {
+        match (self.events.back_mut(), event) {
+            (Some(Event::Mouse(last)), Event::Mouse(new)) if last.buttons == new.buttons && last.scroll == 0 && new.scroll == 0 => {
+                last.x = new.x;
+                last.y = new.y;
+                true
+            }
+            (Some(Event::Keyboard(last)), Event::Keyboard(new)) if last.key == new.key && last.pressed && new.pressed => {
+                last.repeat = last.repeat.saturating_add(new.repeat);
+                true
+            }
+            _ => false,
+        }
+    }
+}
 
-pub struct EventQueue {
-    pub queue: [Event; QUEUE_SIZE],
-    pub head: usize,
-    pub tail: usize,
-    pub count: usize,
+pub struct EventQueues {
+    queues: Vec<WindowQueue>,
 }
 
-pub static GLOBAL_EVENT_QUEUE: Mutex<EventQueue> = Mutex::new(EventQueue {
-    queue: [Event::None; QUEUE_SIZE],
-    head: 0,
-    tail: 0,
-    count: 0,
-});
+pub static EVENT_QUEUES: Mutex<EventQueues> = Mutex::new(EventQueues { queues: Vec::new() });
 
-impl EventQueue {
+impl EventQueues {
     pub fn init(&mut self) {}
 
-    pub fn add_event(&mut self, mut event: Event) {
+    fn find(&mut self, wid: u32) -> Option<&mut WindowQueue> {
+        self.queues.iter_mut().find(|q| q.wid == wid)
+    }
+
+    pub fn open(&mut self, wid: u32) {
+        if self.find(wid).is_none() {
+            self.queues.push(WindowQueue { wid, events: VecDeque::new(), dropped: 0, closed: false, waiter: None, watching: false });
+        }
+    }
+
+    pub fn add_event(&mut self, mut event: Event) -> bool {
         if let Event::Keyboard(ref mut kb) = event {
             if kb.repeat == 0 { kb.repeat = 1; }
         }
 
-        if self.count >= QUEUE_SIZE {
-            self.tail = (self.tail + 1) % QUEUE_SIZE;
-            self.count -= 1;
-        }
+        let queue = match self.find(event.get_window_id()) {
+            Some(q) if !q.closed => q,
+            _ => return false,
+        };
 
-        self.queue[self.head] = event;
-        self.head = (self.head + 1) % QUEUE_SIZE;
-        self.count += 1;
+        let queued = queue.push(event);
+        if queued {
+            wake(queue.waiter.take());
+        }
+        queued
     }
 
-    pub fn get_and_remove_events(&mut self, window_id: u32, max_events: usize) -> Vec<Event> {
-        let mut result = Vec::with_capacity(max_events);
-
+    pub fn has_queue(&mut self, wid: u32) -> bool {
+        self.find(wid).is_some()
+    }
 
-        let mut processed_count = 0;
-        let initial_count = self.count;
-        let mut current_idx = self.tail;
+    pub fn pending(&mut self, wid: u32) -> usize {
+        self.find(wid).map_or(0, |q| q.events.len() + (q.dropped > 0) as usize)
+    }
 
+    pub fn get_and_remove_events(&mut self, window_id: u32, max_events: usize) -> Vec<Event> {
+        let queue = match self.find(window_id) {
+            Some(q) => q,
+            None => return Vec::new(),
+        };
 
-        if self.count == 0 { return result; }
+        let count = queue.events.len().min(max_events);
+        let mut result: Vec<Event> = queue.events.drain(..count).collect();
 
+        // Tell the client once it has caught up, so it can resync any state the lost events carried.
+        if queue.dropped > 0 && queue.events.is_empty() && count < max_events {
+            result.push(Event::Overflow(OverflowEvent { wid: window_id, dropped: queue.dropped }));
+            queue.dropped = 0;
+        }
 
-        let mut new_count = 0;
-        let mut read_ptr = self.tail;
-        let mut write_ptr = self.tail;
+        if queue.closed && queue.events.is_empty() {
+            self.queues.retain(|q| q.wid != window_id);
+        }
+        result
+    }
 
-        for _ in 0..self.count {
-            let evt = self.queue[read_ptr];
+    pub fn set_waiter(&mut self, wid: u32, thread: usize) -> bool {
+        match self.find(wid) {
+            Some(q) => {
+                q.waiter = Some(thread);
+                true
+            }
+            None => false,
+        }
+    }
 
-            let mut taken = false;
-            if evt.get_window_id() == window_id && result.len() < max_events {
-                result.push(evt);
-                taken = true;
+    pub fn broadcast(&mut self, make: impl Fn(u32) -> Event) {
+        for queue in self.queues.iter_mut().filter(|q| !q.closed) {
+            if queue.push(make(queue.wid)) {
+                wake(queue.waiter.take());
             }
+        }
+    }
 
-            if !taken {
-                self.queue[write_ptr] = evt;
-                write_ptr = (write_ptr + 1) % QUEUE_SIZE;
-                new_count += 1;
+    pub fn watch(&mut self, wid: u32, enable: bool) -> bool {
+        match self.find(wid) {
+            Some(q) if !q.closed => {
+                q.watching = enable;
+                true
             }
+            _ => false,
+        }
+    }
 
-            read_ptr = (read_ptr + 1) % QUEUE_SIZE;
+    pub fn notify_watchers(&mut self, make: impl Fn(u32) -> Event) {
+        for queue in self.queues.iter_mut().filter(|q| q.watching && !q.closed) {
+            if queue.push(make(queue.wid)) {
+                wake(queue.waiter.take());
+            }
         }
+    }
 
-        self.head = write_ptr;
-        self.count = new_count;
+    pub fn close(&mut self, wid: u32) {
+        if let Some(queue) = self.find(wid) {
+            queue.events.push_back(Event::Close(CloseEvent { wid }));
+            queue.closed = true;
+            wake(queue.waiter.take());
+        }
+    }
 
-        result
+    pub fn discard(&mut self, wid: u32) {
+        if let Some(queue) = self.find(wid) {
+            wake(queue.waiter.take());
+        }
+        self.queues.retain(|q| q.wid != wid);
     }
+}
 
-    pub fn reset_queue(&mut self) {
-        self.head = 0;
-        self.tail = 0;
-        self.count = 0;
+fn wake(thread: Option<usize>) {
+    if let Some(idx) = thread {
+        crate::interrupts::task::wake_thread(idx);
     }
 }
@@ -1,5 +1,7 @@
-use super::composer::COMPOSER;
-use super::events::{Event, ResizeEvent, GLOBAL_EVENT_QUEUE};
+use super::composer::{self, COMPOSER};
+use super::cursor::{CursorShape, CursorSource, CURSORS};
+use super::decorations::{self, Hit, EDGE_BOTTOM, EDGE_LEFT, EDGE_RIGHT, EDGE_TOP};
+use super::events::{Event, PointerEvent, ResizeEvent, EVENT_QUEUES};
 use super::window::Items;
 use crate::debugln;
 use crate::drivers::video::virtio;
@@ -31,7 +33,11 @@ pub static mut DRAG: bool = false;
 pub static mut DRAGGING_WINDOW: AtomicU16 = AtomicU16::new(0);
 pub static mut RESIZING_WINDOW: AtomicU16 = AtomicU16::new(0);
 pub static mut CLICK_STARTED_IN_TITLEBAR: bool = false;
+pub static mut RESIZE_EDGES: u8 = 0;
 pub static mut CLICKED_WINDOW_ID: usize = 0;
+pub static mut HOVERED_WINDOW: usize = 0;
+pub static mut W_X: isize = 0;
+pub static mut W_Y: isize = 0;
 pub static mut W_WIDTH: usize = 0;
 pub static mut W_HEIGHT: usize = 0;
 pub static mut MOUSE_PENDING: bool = false;
@@ -45,9 +51,6 @@ pub fn handle_mouse_update() {
 
 impl Mouse {
     pub fn cursor(&mut self, data: [u8; 4]) {
-        let old_x = self.x;
-        let old_y = self.y;
-
         let mut x_rel = data[1] as i16;
         let mut y_rel = data[2] as i16;
 
@@ -59,21 +62,32 @@ impl Mouse {
             y_rel |= 0xFF00u16 as i16;
         }
 
+        self.update(x_rel, y_rel, data[0], data[3] as i8);
+    }
+
+    pub fn move_to(&mut self, x: u16, y: u16, buttons: u8, scroll_val: i8) {
+        let x_rel = x as i16 - self.x as i16;
+        let y_rel = self.y as i16 - y as i16;
+        self.update(x_rel, y_rel, buttons, scroll_val);
+    }
+
+    pub fn update(&mut self, x_rel: i16, y_rel: i16, buttons: u8, scroll_val: i8) {
+        let old_x = self.x;
+        let old_y = self.y;
+
         self.x = self.clamp_mx(x_rel);
         self.y = self.clamp_my(-y_rel);
 
         let prev_left = self.left;
 
-        self.left = (data[0] & 0b00000001) != 0;
-        self.right = (data[0] & 0b00000010) != 0;
-        self.center = (data[0] & 0b00000100) != 0;
+        self.left = (buttons & 0b00000001) != 0;
+        self.right = (buttons & 0b00000010) != 0;
+        self.center = (buttons & 0b00000100) != 0;
 
         unsafe {
-            LAST_INPUT = data[0];
+            LAST_INPUT = buttons;
         }
 
-        let scroll_val = data[3] as i8;
-
         if scroll_val != 0 {
             debugln!("Mouse Scroll: {}", scroll_val);
         }
@@ -83,25 +97,38 @@ impl Mouse {
             if let Some(ws) = w {
                 let is_super = crate::drivers::periferics::keyboard::is_super_active();
 
-                unsafe {
-                    let old_id = CLICKED_WINDOW_ID;
-                    let new_id = ws.id;
+                // Focusing re-sorts the window list, so read everything needed from `ws` first.
+                let hit = decorations::hit_test(ws, self.x as isize, self.y as isize);
+                let (wid, x, y, width, height, can_move) = (ws.id, ws.x, ws.y, ws.width, ws.height, ws.can_move);
 
-
-                    if old_id != new_id {
-                        CLICKED_WINDOW_ID = new_id;
-                        (*(&raw mut COMPOSER)).focus_window(new_id);
-                    }
-                }
+                unsafe { (*(&raw mut COMPOSER)).set_focus(wid) };
 
 
-                if ws.can_move && is_super {
+                if can_move && (is_super || hit == Hit::Title) {
                     unsafe {
                         CLICK_STARTED_IN_TITLEBAR = true;
                     }
                 } else {
                     unsafe { CLICK_STARTED_IN_TITLEBAR = false; }
                 }
+
+                unsafe {
+                    let composer = &mut *(&raw mut COMPOSER);
+                    match hit {
+                        Hit::Close if !is_super => composer.request_close(wid),
+                        Hit::Minimize if !is_super => composer.minimize_window(wid),
+                        Hit::Maximize if !is_super => composer.toggle_maximize(wid),
+                        Hit::Resize(edges) if !is_super => {
+                            RESIZE_EDGES = edges;
+                            W_X = x;
+                            W_Y = y;
+                            W_WIDTH = width;
+                            W_HEIGHT = height;
+                            (*(&raw mut RESIZING_WINDOW)).store(wid as u16, Ordering::Relaxed);
+                        }
+                        _ => {}
+                    }
+                }
             } else {
                 unsafe { CLICK_STARTED_IN_TITLEBAR = false; }
             }
@@ -120,22 +147,35 @@ impl Mouse {
                 DRAG = false;
 
                 if (*(&raw mut RESIZING_WINDOW)).load(Ordering::Relaxed) != 0 {
-                    let w = (*(&raw mut COMPOSER))
-                        .find_window_id((*(&raw mut RESIZING_WINDOW)).load(Ordering::Relaxed) as usize)
-                        .unwrap();
+                    let composer = &mut *(&raw mut COMPOSER);
+                    let wid = (*(&raw mut RESIZING_WINDOW)).load(Ordering::Relaxed) as usize;
 
                     (*(&raw mut DRAGGING_WINDOW)).store(0, Ordering::Relaxed);
                     (*(&raw mut RESIZING_WINDOW)).store(0, Ordering::Relaxed);
 
-                    if w.event_handler != 0 {
-                        GLOBAL_EVENT_QUEUE.int_lock().add_event(Event::Resize(ResizeEvent {
-                            wid: w.id as u32,
-                            width: W_WIDTH,
-                            height: W_HEIGHT,
-                        }));
+                    if let Some(w) = composer.find_window_id(wid) {
+                        // The outline is repainted from the old frame, so take it before moving.
+                        let (fx, fy, _, _) = w.frame();
+                        w.x = W_X;
+                        w.y = W_Y;
+                        if w.event_handler != 0 {
+                            EVENT_QUEUES.int_lock().add_event(Event::Resize(ResizeEvent {
+                                wid: w.id as u32,
+                                width: W_WIDTH,
+                                height: W_HEIGHT,
+                            }));
+                        }
+
+                        // The outline was drawn straight to the framebuffer; repaint everything it may have touched.
+                        let (fx, fy) = (fx.min(W_X), fy.min(W_Y - w.title_height() as isize));
+                        let display_server = &*(&raw const DISPLAY_SERVER);
+                        let sw = (display_server.width as i32 - fx as i32).max(0) as u32;
+                        let sh = (display_server.height as i32 - fy as i32).max(0) as u32;
+                        composer.update_window_area_rect(fx as i32, fy as i32, sw, sh);
                     }
                     W_WIDTH = 0;
                     W_HEIGHT = 0;
+                    RESIZE_EDGES = 0;
                 } else if (*(&raw mut DRAGGING_WINDOW)).load(Ordering::Relaxed) != 0 {
                     let wid = (*(&raw mut DRAGGING_WINDOW)).load(Ordering::Relaxed) as usize;
                     let composer = &mut *(&raw mut COMPOSER);
@@ -145,14 +185,12 @@ impl Mouse {
                     if w.is_none() { return; }
 
                     let w = w.unwrap();
-                    let win_x = w.x;
-                    let win_y = w.y;
-                    let win_width = w.width;
-                    let win_height = w.height;
+                    let (win_x, win_y, win_width, win_height) = w.frame();
 
                     composer.copy_window(wid);
 
-                    display_server.copy_to_fb(old_x as i32, old_y as i32, 32, 32);
+                    let (cx, cy, cw, ch) = cursor_rect(old_x, old_y);
+                    display_server.copy_to_fb(cx, cy, cw, ch);
 
                     display_server.copy_to_fb(win_x as i32, win_y as i32, win_width as u32, win_height as u32);
 
@@ -181,10 +219,8 @@ impl Mouse {
         let _w = unsafe { (*(&raw mut COMPOSER)).find_window(self.x as usize, self.y as usize) };
 
         if unsafe { (*(&raw mut RESIZING_WINDOW)).load(Ordering::Relaxed) != 0 } {
-            let x_vec = x_rel;
-            let y_vec = y_rel;
-            let dx = x_vec;
-            let dy = y_vec * -1;
+            let dx = x_rel as isize;
+            let dy = -(y_rel as isize);
 
             let w = unsafe {
                 (*(&raw mut COMPOSER))
@@ -192,38 +228,62 @@ impl Mouse {
                     .unwrap()
             };
 
-            let final_width = self.rem_sign(unsafe { W_WIDTH } as i16 + dx) as usize;
-            let final_height = self.rem_sign(unsafe { W_HEIGHT } as i16 + dy) as usize;
+            let edges = unsafe { RESIZE_EDGES };
+            let min_width = w.min_width.max(MIN_RESIZE) as isize;
+            let min_height = w.min_height.max(MIN_RESIZE) as isize;
+            let th = w.title_height() as isize;
 
             unsafe {
-                let wx = w.x.max(0) as usize;
-                let wy = w.y.max(0) as usize;
-                W_WIDTH = cap(final_width, ((*(&raw mut DISPLAY_SERVER)).width as usize).saturating_sub(wx));
-                W_HEIGHT = cap(final_height, ((*(&raw mut DISPLAY_SERVER)).height as usize).saturating_sub(wy));
+                let (sw, sh) = ((*(&raw mut DISPLAY_SERVER)).width as isize, (*(&raw mut DISPLAY_SERVER)).height as isize);
+                let (mut width, mut height) = (W_WIDTH as isize, W_HEIGHT as isize);
+                if edges & EDGE_RIGHT != 0 {
+                    width = (width + dx).max(min_width).min(sw - W_X.max(0));
+                }
+                if edges & EDGE_BOTTOM != 0 {
+                    height = (height + dy).max(min_height).min(sh - W_Y.max(0));
+                }
+                // The left and top edges move the window so the opposite edge stays put.
+                if edges & EDGE_LEFT != 0 {
+                    let right = W_X + width;
+                    width = (width - dx).max(min_width).min(right);
+                    W_X = right - width;
+                }
+                if edges & EDGE_TOP != 0 {
+                    let bottom = W_Y + height;
+                    height = (height - dy).max(min_height).min(bottom - th);
+                    W_Y = bottom - height;
+                }
+                W_WIDTH = width as usize;
+                W_HEIGHT = height as usize;
             }
 
+            let th = th as usize;
+            let outline_x = unsafe { W_X.max(0) };
+            let outline_y = unsafe { (W_Y - th as isize).max(0) };
+
             self.draw_square_outline(
-                w.y as u16,
-                w.x as u16,
-                unsafe { W_HEIGHT as u16 },
+                outline_y as u16,
+                outline_x as u16,
+                unsafe { (W_HEIGHT + th) as u16 },
                 unsafe { W_WIDTH as u16 },
                 Color::rgb(245, 245, 247),
             );
 
             unsafe {
                 if VIRTIO_ACTIVE {
-                    virtio::flush(w.x as u32, w.y as u32, W_WIDTH as u32, W_HEIGHT as u32, (*(&raw mut DISPLAY_SERVER)).width as u32, (*(&raw mut DISPLAY_SERVER)).active_resource_id);
+                    virtio::flush(outline_x as u32, outline_y as u32, W_WIDTH as u32, (W_HEIGHT + th) as u32, (*(&raw mut DISPLAY_SERVER)).width as u32, (*(&raw mut DISPLAY_SERVER)).active_resource_id);
                 }
 
                 (*(&raw mut DISPLAY_SERVER)).draw_mouse(self.x, self.y, false);
 
                 if VIRTIO_ACTIVE {
-                    let mx = self.x as u32;
-                    let my = self.y as u32;
+                    let (cx, cy, cw, ch) = cursor_rect(self.x, self.y);
+                    let mx = cx.max(0) as u32;
+                    let my = cy.max(0) as u32;
                     let sw = (*(&raw mut DISPLAY_SERVER)).width as u32;
                     let sh = (*(&raw mut DISPLAY_SERVER)).height as u32;
-                    let fw = (32 as u32).min(sw.saturating_sub(mx));
-                    let fh = (32 as u32).min(sh.saturating_sub(my));
+                    let fw = ((cx + cw as i32).max(0) as u32).min(sw).saturating_sub(mx);
+                    let fh = ((cy + ch as i32).max(0) as u32).min(sh).saturating_sub(my);
 
                     if fw > 0 && fh > 0 {
                         virtio::flush(mx, my, fw, fh, sw, (*(&raw mut DISPLAY_SERVER)).active_resource_id);
@@ -245,12 +305,12 @@ impl Mouse {
                 None => return,
             };
 
+            let th = w.title_height() as i32;
             let old_win_x = w.x;
             let old_win_y = w.y;
             let width = w.width;
             let height = w.height;
             let id = w.id;
-            let buffer = w.buffer;
 
             let target_mx = old_x as i32 + x_vec as i32;
             let target_my = old_y as i32 - y_vec as i32;
@@ -274,7 +334,7 @@ impl Mouse {
 
             let min_visible_x = -(width as i32) + margin;
             let max_visible_x = screen_w - margin;
-            let min_visible_y = -(height as i32) + margin;
+            let min_visible_y = if th > 0 { th } else { -(height as i32) + margin };
             let max_visible_y = screen_h - margin;
 
             let clamped_win_x = target_win_x.max(min_visible_x).min(max_visible_x);
@@ -292,10 +352,10 @@ impl Mouse {
             w.x = new_x;
             w.y = new_y;
 
-            display_server.copy_to_fb(old_win_x as i32, old_win_y as i32, width as u32, height as u32);
+            display_server.copy_to_fb(old_win_x as i32, old_win_y as i32 - th, width as u32, height as u32 + th as u32);
 
 
-            display_server.copy_to_fb_a(width as u32, height as u32, buffer, new_x as i32, new_y as i32, Some(0xFFFFFFFF), w.treat_as_transparent);
+            composer::draw_to_fb_with_border(display_server, w, Some(0xFFFFFFFF));
 
 
             for i in 0..composer.windows.len() {
@@ -308,7 +368,7 @@ impl Mouse {
                             w.buffer,
                             w.x as i32,
                             w.y as i32,
-                            new_x as i32, new_y as i32, width as u32, height as u32,
+                            new_x as i32, new_y as i32 - th, width as u32, height as u32 + th as u32,
                             None,
                             w.treat_as_transparent,
                         );
@@ -318,11 +378,12 @@ impl Mouse {
             }
 
             let old_x_clamped = (old_win_x as i32).max(0) as u32;
-            let old_y_clamped = (old_win_y as i32).max(0) as u32;
+            let old_y_clamped = (old_win_y as i32 - th).max(0) as u32;
             let new_x_clamped = (new_x as i32).max(0) as u32;
-            let new_y_clamped = (new_y as i32).max(0) as u32;
-            let mouse_x = self.x as u32;
-            let mouse_y = self.y as u32;
+            let new_y_clamped = (new_y as i32 - th).max(0) as u32;
+            let (cx, cy, cw, ch) = cursor_rect(self.x, self.y);
+            let mouse_x = cx.max(0) as u32;
+            let mouse_y = cy.max(0) as u32;
 
             let screen_w_u32 = screen_w as u32;
             let screen_h_u32 = screen_h as u32;
@@ -331,8 +392,8 @@ impl Mouse {
             let old_y_end = (old_win_y as i32 + height as i32).max(0).min(screen_h).max(0) as u32;
             let new_x_end = (new_x as i32 + width as i32).max(0).min(screen_w).max(0) as u32;
             let new_y_end = (new_y as i32 + height as i32).max(0).min(screen_h).max(0) as u32;
-            let mouse_x_end = (mouse_x + 32).min(screen_w_u32);
-            let mouse_y_end = (mouse_y + 32).min(screen_h_u32);
+            let mouse_x_end = ((cx + cw as i32).max(0) as u32).min(screen_w_u32);
+            let mouse_y_end = ((cy + ch as i32).max(0) as u32).min(screen_h_u32);
 
             let min_x = old_x_clamped.min(new_x_clamped).min(mouse_x);
             let min_y = old_y_clamped.min(new_y_clamped).min(mouse_y);
@@ -358,20 +419,18 @@ impl Mouse {
 
         unsafe {
             let display_server = &mut *(&raw mut DISPLAY_SERVER);
-            display_server.copy_to_fb(old_x as i32, old_y as i32, 32, 32);
+            let (ox, oy, ow, oh) = cursor_rect(old_x, old_y);
+            display_server.copy_to_fb(ox, oy, ow, oh);
 
             display_server.draw_mouse(self.x, self.y, false);
 
             if VIRTIO_ACTIVE {
-                let u_old_x = old_x as u32;
-                let u_old_y = old_y as u32;
-                let u_new_x = self.x as u32;
-                let u_new_y = self.y as u32;
+                let (nx, ny, nw, nh) = cursor_rect(self.x, self.y);
 
-                let min_x = u_old_x.min(u_new_x);
-                let min_y = u_old_y.min(u_new_y);
-                let max_x = (u_old_x + 32).max(u_new_x + 32);
-                let max_y = (u_old_y + 32).max(u_new_y + 32);
+                let min_x = ox.min(nx).max(0) as u32;
+                let min_y = oy.min(ny).max(0) as u32;
+                let max_x = (ox + ow as i32).max(nx + nw as i32).max(0) as u32;
+                let max_y = (oy + oh as i32).max(ny + nh as i32).max(0) as u32;
 
                 let screen_w = display_server.width as u32;
                 let screen_h = display_server.height as u32;
@@ -390,16 +449,24 @@ impl Mouse {
                 crate::debugln!("Input: Click at {},{}", self.x, self.y);
             }
 
+            let hovered = (*(&raw mut COMPOSER))
+                .find_window(self.x as usize, self.y as usize)
+                .filter(|w| reaches_client(decorations::hit_test(w, self.x as isize, self.y as isize)))
+                .map(|w| w.id);
+            self.update_hover(hovered.unwrap_or(0));
+            self.update_cursor_shape();
+
             if let Some(w) = (*(&raw mut COMPOSER)).find_window(self.x as usize, self.y as usize) {
                 if self.left {
                     crate::debugln!("Input: Found window ID {} at {},{}", w.id, w.x, w.y);
                 }
 
-                if w.event_handler != 0 {
+                let over_client = reaches_client(decorations::hit_test(w, self.x as isize, self.y as isize));
+                if w.event_handler != 0 && over_client {
                     let local_x = (self.x as isize - w.x).max(0) as usize;
                     let local_y = (self.y as isize - w.y).max(0) as usize;
 
-                    use crate::window_manager::events::{Event, MouseEvent, GLOBAL_EVENT_QUEUE};
+                    use crate::window_manager::events::{Event, MouseEvent, EVENT_QUEUES};
 
                     static mut LAST_X: usize = 9999;
                     static mut LAST_Y: usize = 9999;
@@ -421,7 +488,7 @@ impl Mouse {
                             scroll: scroll_val,
                         });
 
-                        GLOBAL_EVENT_QUEUE.int_lock().add_event(event);
+                        EVENT_QUEUES.int_lock().add_event(event);
 
                         if self.left {
                             crate::debugln!("Input: Dispatching Mouse Event to {}", w.id);
@@ -432,8 +499,44 @@ impl Mouse {
         };
     }
 
-    fn rem_sign(&self, n: i16) -> u16 {
-        if n < 0 { (n * -1) as u16 } else { n as u16 }
+    pub fn update_cursor_shape(&self) {
+        unsafe {
+            let source = if (*(&raw mut RESIZING_WINDOW)).load(Ordering::Relaxed) != 0 {
+                CursorSource::Shape(CursorShape::for_edges(RESIZE_EDGES))
+            } else {
+                match (*(&raw mut COMPOSER)).find_window(self.x as usize, self.y as usize) {
+                    Some(w) => match decorations::hit_test(w, self.x as isize, self.y as isize) {
+                        Hit::Resize(edges) => CursorSource::Shape(CursorShape::for_edges(edges)),
+                        Hit::Client => CursorSource::Window(w.id),
+                        _ => CursorSource::Shape(CursorShape::Arrow),
+                    },
+                    None => CursorSource::Shape(CursorShape::Arrow),
+                }
+            };
+            (*(&raw mut CURSORS)).show(source, self.x, self.y);
+        }
+    }
+
+    fn update_hover(&self, hovered: usize) {
+        unsafe {
+            if hovered == HOVERED_WINDOW {
+                return;
+            }
+
+            let composer = &mut *(&raw mut COMPOSER);
+            let mut queues = EVENT_QUEUES.int_lock();
+            for (id, leaving) in [(HOVERED_WINDOW, true), (hovered, false)] {
+                if let Some(w) = composer.find_window_id(id) {
+                    let pointer = PointerEvent {
+                        wid: id as u32,
+                        x: (self.x as isize - w.x).max(0) as usize,
+                        y: (self.y as isize - w.y).max(0) as usize,
+                    };
+                    queues.add_event(if leaving { Event::PointerLeave(pointer) } else { Event::PointerEnter(pointer) });
+                }
+            }
+            HOVERED_WINDOW = hovered;
+        }
     }
 
     fn is_bottom_right(
@@ -511,6 +614,17 @@ impl Mouse {
     }
 }
 
-fn cap(n: usize, value: usize) -> usize {
-    if n > value { value } else { n }
+const MIN_RESIZE: usize = 64;
+
+fn cursor_rect(x: u16, y: u16) -> (i32, i32, u32, u32) {
+    unsafe { (*(&raw mut CURSORS)).rect(x, y) }
+}
+
+// The top edge sits over the title bar, so only the others pass pointer events on to the client.
+fn reaches_client(hit: Hit) -> bool {
+    match hit {
+        Hit::Client => true,
+        Hit::Resize(edges) => edges & EDGE_TOP == 0,
+        _ => false,
+    }
 }
\ No newline at end of file
@@ -1,5 +1,12 @@
+pub mod clipboard;
 pub mod composer;
+pub mod cursor;
+pub mod decorations;
 pub mod events;
+pub mod font;
 pub mod window;
 pub mod input;
+pub mod tiling;
 pub mod display;
+pub mod theme;
+pub mod workspace;
@@ -0,0 +1,35 @@
+use crate::sync::Mutex;
+use crate::window_manager::events::{Event, ThemeEvent, EVENT_QUEUES};
+use alloc::format;
+use alloc::string::String;
+
+pub const MAX_THEME_NAME: usize = 64;
+pub const DEFAULT_THEME: &str = "light";
+
+static THEME: Mutex<String> = Mutex::new(String::new());
+
+pub fn current() -> String {
+    let theme = THEME.lock();
+    if theme.is_empty() {
+        String::from(DEFAULT_THEME)
+    } else {
+        theme.clone()
+    }
+}
+
+pub fn set(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > MAX_THEME_NAME {
+        return Err(String::from("Invalid theme name"));
+    }
+    // The name becomes part of a path.
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(String::from("Invalid theme name"));
+    }
+    if crate::fs::vfs::open(0, &format!("@0xE0/sys/themes/{}.theme", name)).is_err() {
+        return Err(String::from("No such theme"));
+    }
+
+    *THEME.lock() = String::from(name);
+    EVENT_QUEUES.int_lock().broadcast(|wid| Event::ThemeChanged(ThemeEvent { wid }));
+    Ok(())
+}
@@ -0,0 +1,195 @@
+use alloc::vec::Vec;
+
+pub type Rect = (isize, isize, usize, usize);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(u64)]
+pub enum Layout {
+    Floating = 0,
+    MasterStack = 1,
+    Grid = 2,
+}
+
+impl Layout {
+    pub fn from_u64(value: u64) -> Option<Layout> {
+        match value {
+            0 => Some(Layout::Floating),
+            1 => Some(Layout::MasterStack),
+            2 => Some(Layout::Grid),
+            _ => None,
+        }
+    }
+
+    pub fn next(self) -> Layout {
+        match self {
+            Layout::Floating => Layout::MasterStack,
+            Layout::MasterStack => Layout::Grid,
+            Layout::Grid => Layout::Floating,
+        }
+    }
+}
+
+pub const MAX_GAP: usize = 64;
+pub const MIN_MASTER_RATIO: usize = 20;
+pub const MAX_MASTER_RATIO: usize = 80;
+
+pub struct Tiling {
+    pub layout: Layout,
+    pub gap: usize,
+    pub master_ratio: usize,
+    order: Vec<usize>,
+    floating: Vec<usize>,
+    requested: Vec<(usize, usize, usize)>,
+}
+
+pub static mut TILING: Tiling = Tiling {
+    layout: Layout::Floating,
+    gap: 8,
+    master_ratio: 55,
+    order: Vec::new(),
+    floating: Vec::new(),
+    requested: Vec::new(),
+};
+
+impl Tiling {
+    pub fn is_active(&self) -> bool {
+        self.layout != Layout::Floating
+    }
+
+    pub fn track(&mut self, wid: usize) {
+        if !self.order.contains(&wid) {
+            self.order.push(wid);
+        }
+    }
+
+    pub fn forget(&mut self, wid: usize) {
+        self.order.retain(|&id| id != wid);
+        self.floating.retain(|&id| id != wid);
+        self.requested.retain(|&(id, _, _)| id != wid);
+    }
+
+    pub fn is_floating(&self, wid: usize) -> bool {
+        self.floating.contains(&wid)
+    }
+
+    pub fn is_tiled(&self, wid: usize) -> bool {
+        self.is_active() && self.order.contains(&wid) && !self.is_floating(wid)
+    }
+
+    pub fn set_floating(&mut self, wid: usize, floating: bool) {
+        self.floating.retain(|&id| id != wid);
+        if floating {
+            self.floating.push(wid);
+        }
+    }
+
+    pub fn tiled(&self, visible: impl Fn(usize) -> bool) -> Vec<usize> {
+        self.order.iter().copied().filter(|&id| !self.is_floating(id) && visible(id)).collect()
+    }
+
+    pub fn swap(&mut self, wid: usize, tiled: &[usize], forward: bool) {
+        let pos = match tiled.iter().position(|&id| id == wid) {
+            Some(pos) => pos,
+            None => return,
+        };
+        if tiled.len() < 2 {
+            return;
+        }
+        let other = if forward { tiled[(pos + 1) % tiled.len()] } else { tiled[(pos + tiled.len() - 1) % tiled.len()] };
+
+        let a = self.order.iter().position(|&id| id == wid);
+        let b = self.order.iter().position(|&id| id == other);
+        if let (Some(a), Some(b)) = (a, b) {
+            self.order.swap(a, b);
+        }
+    }
+
+    pub fn promote(&mut self, wid: usize) {
+        if let Some(pos) = self.order.iter().position(|&id| id == wid) {
+            let id = self.order.remove(pos);
+            self.order.insert(0, id);
+        }
+    }
+
+    pub fn note_request(&mut self, wid: usize, width: usize, height: usize) -> bool {
+        if self.requested.iter().any(|&r| r == (wid, width, height)) {
+            return false;
+        }
+        self.requested.retain(|&(id, _, _)| id != wid);
+        self.requested.push((wid, width, height));
+        true
+    }
+
+    pub fn rects(&self, area: Rect, count: usize) -> Vec<Rect> {
+        match self.layout {
+            Layout::Floating => Vec::new(),
+            Layout::MasterStack => master_stack(area, count, self.gap, self.master_ratio),
+            Layout::Grid => grid(area, count, self.gap),
+        }
+    }
+}
+
+fn split(start: isize, total: usize, parts: usize, gap: usize) -> Vec<(isize, usize)> {
+    let usable = total.saturating_sub(gap * parts.saturating_sub(1));
+    let base = usable / parts.max(1);
+    let extra = usable % parts.max(1);
+
+    let mut spans = Vec::with_capacity(parts);
+    let mut pos = start;
+    for i in 0..parts {
+        let len = base + if i < extra { 1 } else { 0 };
+        spans.push((pos, len));
+        pos += (len + gap) as isize;
+    }
+    spans
+}
+
+fn inset(area: Rect, gap: usize) -> Rect {
+    let (x, y, w, h) = area;
+    (x + gap as isize, y + gap as isize, w.saturating_sub(gap * 2), h.saturating_sub(gap * 2))
+}
+
+fn master_stack(area: Rect, count: usize, gap: usize, ratio: usize) -> Vec<Rect> {
+    let (x, y, w, h) = inset(area, gap);
+    if count == 0 {
+        return Vec::new();
+    }
+    if count == 1 {
+        return alloc::vec![(x, y, w, h)];
+    }
+
+    let usable = w.saturating_sub(gap);
+    let master_w = usable * ratio / 100;
+    let stack_w = usable - master_w;
+    let stack_x = x + (master_w + gap) as isize;
+
+    let mut rects = Vec::with_capacity(count);
+    rects.push((x, y, master_w, h));
+    for (sy, sh) in split(y, h, count - 1, gap) {
+        rects.push((stack_x, sy, stack_w, sh));
+    }
+    rects
+}
+
+fn grid(area: Rect, count: usize, gap: usize) -> Vec<Rect> {
+    let (x, y, w, h) = inset(area, gap);
+    if count == 0 {
+        return Vec::new();
+    }
+
+    let mut cols = 1;
+    while cols * cols < count {
+        cols += 1;
+    }
+    let rows = count.div_ceil(cols);
+
+    let mut rects = Vec::with_capacity(count);
+    for (row, (ry, rh)) in split(y, h, rows, gap).into_iter().enumerate() {
+        // The last row may be short; its windows share the full width.
+        let in_row = if row == rows - 1 { count - cols * (rows - 1) } else { cols };
+        for (cx, cw) in split(x, w, in_row, gap) {
+            rects.push((cx, ry, cw, rh));
+        }
+    }
+    rects
+}
@@ -1,3 +1,8 @@
+use super::decorations::TITLE_HEIGHT;
+use super::workspace;
+
+pub const TITLE_LEN: usize = 64;
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 #[repr(C)]
 pub enum Items {
@@ -28,6 +33,26 @@ pub struct Window {
     pub min_height: usize,
     pub event_handler: usize,
     pub w_type: Items,
+    pub decorated: bool,
+    pub minimized: bool,
+    pub workspace: usize,
+    pub title: [u8; TITLE_LEN],
+}
+
+#[derive(Debug, Copy, Clone)]
+#[repr(C)]
+pub struct WindowInfo {
+    pub id: usize,
+    pub pid: u64,
+    pub x: isize,
+    pub y: isize,
+    pub width: usize,
+    pub height: usize,
+    pub w_type: Items,
+    pub focused: bool,
+    pub minimized: bool,
+    pub workspace: usize,
+    pub title: [u8; TITLE_LEN],
 }
 
 pub static NULL_WINDOW: Window = Window {
@@ -47,4 +72,51 @@ pub static NULL_WINDOW: Window = Window {
     min_height: 0,
     event_handler: 0,
     w_type: Items::Null,
+    decorated: false,
+    minimized: false,
+    workspace: 0,
+    title: [0; TITLE_LEN],
 };
+
+impl Window {
+    pub fn has_title_bar(&self) -> bool {
+        self.decorated && self.w_type == Items::Window
+    }
+
+    pub fn title_height(&self) -> usize {
+        if self.has_title_bar() { TITLE_HEIGHT } else { 0 }
+    }
+
+    pub fn frame(&self) -> (isize, isize, usize, usize) {
+        let th = self.title_height();
+        (self.x, self.y - th as isize, self.width, self.height + th)
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.w_type != Items::Null && !self.minimized && workspace::is_shown(self.w_type, self.workspace)
+    }
+
+    pub fn info(&self, focused: bool) -> WindowInfo {
+        WindowInfo {
+            id: self.id,
+            pid: self.pid,
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+            w_type: self.w_type,
+            focused,
+            minimized: self.minimized,
+            workspace: self.workspace,
+            title: self.title,
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        let len = self.title.iter().position(|&b| b == 0).unwrap_or(TITLE_LEN);
+        match core::str::from_utf8(&self.title[..len]) {
+            Ok(s) => s,
+            Err(e) => core::str::from_utf8(&self.title[..e.valid_up_to()]).unwrap_or(""),
+        }
+    }
+}
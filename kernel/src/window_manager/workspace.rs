@@ -0,0 +1,36 @@
+use alloc::vec::Vec;
+
+pub const WORKSPACE_COUNT: usize = 9;
+
+pub struct Workspaces {
+    pub current: usize,
+    history: [Vec<usize>; WORKSPACE_COUNT],
+}
+
+pub static mut WORKSPACES: Workspaces = Workspaces {
+    current: 0,
+    history: [const { Vec::new() }; WORKSPACE_COUNT],
+};
+
+impl Workspaces {
+    pub fn focused(&mut self, workspace: usize, wid: usize) {
+        if let Some(history) = self.history.get_mut(workspace) {
+            history.retain(|&id| id != wid);
+            history.insert(0, wid);
+        }
+    }
+
+    pub fn forget(&mut self, wid: usize) {
+        for history in self.history.iter_mut() {
+            history.retain(|&id| id != wid);
+        }
+    }
+
+    pub fn last_focused(&self, workspace: usize, usable: impl Fn(usize) -> bool) -> Option<usize> {
+        self.history.get(workspace)?.iter().copied().find(|&id| usable(id))
+    }
+}
+
+pub fn is_shown(w_type: super::window::Items, workspace: usize) -> bool {
+    w_type != super::window::Items::Window || workspace == unsafe { (*(&raw const WORKSPACES)).current }
+}
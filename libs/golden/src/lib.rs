@@ -0,0 +1,35 @@
+use std::path::Path;
+
+/// The pixels, alpha dropped, as a binary PPM.
+fn to_ppm(width: usize, height: usize, pixels: &[u32]) -> Vec<u8> {
+    let mut out = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+    for &p in pixels {
+        out.extend_from_slice(&[(p >> 16) as u8, (p >> 8) as u8, p as u8]);
+    }
+    out
+}
+
+/// Compares `pixels` with `<dir>/<name>.ppm`. Run with `UPDATE_GOLDEN=1` to rewrite the
+/// images after an intended change, and look at the diff before committing.
+pub fn assert_golden(dir: &Path, name: &str, width: usize, height: usize, pixels: &[u32]) {
+    let path = dir.join(format!("{}.ppm", name));
+    let actual = to_ppm(width, height, pixels);
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, &actual).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read(&path).unwrap_or_else(|_| panic!("missing {}, run with UPDATE_GOLDEN=1", path.display()));
+    if expected != actual {
+        let dump = std::env::temp_dir().join(format!("{}.actual.ppm", name));
+        std::fs::write(&dump, &actual).unwrap();
+
+        let header = actual.len() - width * height * 3;
+        let first = expected.iter().zip(&actual).skip(header).position(|(a, b)| a != b).map(|i| i / 3);
+        match first {
+            Some(i) => panic!("{} differs from golden at ({}, {}), output saved to {}", name, i % width, i / width, dump.display()),
+            None => panic!("{} differs from golden in size, output saved to {}", name, dump.display()),
+        }
+    }
+}
@@ -1,2 +1,18 @@
+#ifndef _SYS_TIME_H
+#define _SYS_TIME_H
 
-dummy
+#include <time.h>
+
+struct timeval {
+    long tv_sec;
+    long tv_usec;
+};
+
+struct timezone {
+    int tz_minuteswest;
+    int tz_dsttime;
+};
+
+extern int gettimeofday(struct timeval *tv, struct timezone *tz);
+
+#endif
@@ -6,6 +6,17 @@ typedef long clock_t;
 
 #define CLOCKS_PER_SEC 1000
 
+typedef int clockid_t;
+
+#define CLOCK_REALTIME 0
+#define CLOCK_MONOTONIC 1
+#define TIMER_ABSTIME 1
+
+struct timespec {
+    long tv_sec;
+    long tv_nsec;
+};
+
 struct tm {
     int tm_sec;
     int tm_min;
@@ -25,5 +36,10 @@ extern struct tm *gmtime(const time_t *timep);
 extern struct tm *localtime(const time_t *timep);
 extern size_t strftime(char *s, size_t max, const char *format, const struct tm *tm);
 extern double difftime(time_t time1, time_t time0);
+extern struct tm *gmtime_r(const time_t *timep, struct tm *result);
+extern int clock_gettime(clockid_t clock_id, struct timespec *tp);
+extern int clock_getres(clockid_t clock_id, struct timespec *res);
+extern int clock_nanosleep(clockid_t clock_id, int flags, const struct timespec *req, struct timespec *rem);
+extern int nanosleep(const struct timespec *req, struct timespec *rem);
 
 #endif
@@ -20,6 +20,7 @@ pub mod dirent;
 pub mod locale;
 pub mod time;
 pub mod pthread;
+pub mod socket;
 
 #[cfg(feature = "userland")]
 pub use std::runtime::*;
@@ -0,0 +1,161 @@
+use core::ffi::{c_char, c_int, c_void};
+
+pub const AF_UNIX: c_int = 1;
+pub const AF_INET: c_int = 2;
+pub const SOCK_STREAM: c_int = 1;
+pub const SOCK_DGRAM: c_int = 2;
+pub const INADDR_ANY: u32 = 0;
+pub const SOL_SOCKET: c_int = 1;
+pub const SCM_RIGHTS: c_int = 1;
+pub const MSG_CTRUNC: c_int = 0x08;
+pub const MSG_TRUNC: c_int = 0x20;
+
+pub type socklen_t = u32;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct in_addr {
+    pub s_addr: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct sockaddr_in {
+    pub sin_family: u16,
+    pub sin_port: u16,
+    pub sin_addr: in_addr,
+    pub sin_zero: [u8; 8],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct sockaddr_un {
+    pub sun_family: u16,
+    pub sun_path: [c_char; 108],
+}
+
+#[repr(C)]
+pub struct iovec {
+    pub iov_base: *mut c_void,
+    pub iov_len: usize,
+}
+
+#[repr(C)]
+pub struct msghdr {
+    pub msg_name: *mut c_void,
+    pub msg_namelen: socklen_t,
+    pub msg_iov: *mut iovec,
+    pub msg_iovlen: usize,
+    pub msg_control: *mut c_void,
+    pub msg_controllen: usize,
+    pub msg_flags: c_int,
+}
+
+#[repr(C)]
+pub struct cmsghdr {
+    pub cmsg_len: usize,
+    pub cmsg_level: c_int,
+    pub cmsg_type: c_int,
+}
+
+#[repr(C)]
+pub struct sockaddr {
+    pub sa_family: u16,
+    pub sa_data: [c_char; 14],
+}
+
+fn ret(res: u64) -> c_int {
+    if res == u64::MAX { -1 } else { res as c_int }
+}
+
+fn ret_size(res: u64) -> isize {
+    if res == u64::MAX { -1 } else { res as isize }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn socket(domain: c_int, ty: c_int, protocol: c_int) -> c_int {
+    ret(std::os::syscall(41, domain as u64, ty as u64, protocol as u64))
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn bind(fd: c_int, addr: *const sockaddr, len: socklen_t) -> c_int {
+    ret(std::os::syscall(49, fd as u64, addr as u64, len as u64))
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn listen(fd: c_int, backlog: c_int) -> c_int {
+    ret(std::os::syscall(50, fd as u64, backlog as u64, 0))
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn accept(fd: c_int, addr: *mut sockaddr, len: *mut socklen_t) -> c_int {
+    ret(std::os::syscall(43, fd as u64, addr as u64, len as u64))
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn connect(fd: c_int, addr: *const sockaddr, len: socklen_t) -> c_int {
+    ret(std::os::syscall(42, fd as u64, addr as u64, len as u64))
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sendto(fd: c_int, buf: *const c_void, len: usize, flags: c_int, addr: *const sockaddr, addrlen: socklen_t) -> isize {
+    ret_size(std::os::syscall6(44, fd as u64, buf as u64, len as u64, flags as u64, addr as u64, addrlen as u64))
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn recvfrom(fd: c_int, buf: *mut c_void, len: usize, flags: c_int, addr: *mut sockaddr, addrlen: *mut socklen_t) -> isize {
+    ret_size(std::os::syscall6(45, fd as u64, buf as u64, len as u64, flags as u64, addr as u64, addrlen as u64))
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn send(fd: c_int, buf: *const c_void, len: usize, flags: c_int) -> isize {
+    sendto(fd, buf, len, flags, core::ptr::null(), 0)
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn recv(fd: c_int, buf: *mut c_void, len: usize, flags: c_int) -> isize {
+    recvfrom(fd, buf, len, flags, core::ptr::null_mut(), core::ptr::null_mut())
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sendmsg(fd: c_int, msg: *const msghdr, flags: c_int) -> isize {
+    ret_size(std::os::syscall(46, fd as u64, msg as u64, flags as u64))
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn recvmsg(fd: c_int, msg: *mut msghdr, flags: c_int) -> isize {
+    ret_size(std::os::syscall(47, fd as u64, msg as u64, flags as u64))
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn socketpair(domain: c_int, ty: c_int, protocol: c_int, sv: *mut c_int) -> c_int {
+    ret(std::os::syscall4(53, domain as u64, ty as u64, protocol as u64, sv as u64))
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn getsockname(fd: c_int, addr: *mut sockaddr, len: *mut socklen_t) -> c_int {
+    ret(std::os::syscall(51, fd as u64, addr as u64, len as u64))
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn getpeername(fd: c_int, addr: *mut sockaddr, len: *mut socklen_t) -> c_int {
+    ret(std::os::syscall(52, fd as u64, addr as u64, len as u64))
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn htons(v: u16) -> u16 { v.to_be() }
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ntohs(v: u16) -> u16 { u16::from_be(v) }
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn htonl(v: u32) -> u32 { v.to_be() }
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ntohl(v: u32) -> u32 { u32::from_be(v) }
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn inet_addr(cp: *const c_char) -> u32 {
+    let s = core::ffi::CStr::from_ptr(cp).to_string_lossy();
+    match s.parse::<std::net::Ipv4Addr>() {
+        Ok(ip) => u32::from_ne_bytes(ip.octets()),
+        Err(_) => u32::MAX,
+    }
+}
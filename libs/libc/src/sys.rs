@@ -21,6 +21,23 @@ pub unsafe extern "C" fn krake_sleep(ms: usize) {
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn krake_get_time_ms() -> usize { std::os::syscall(109, 0, 0, 0) as usize }
 
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn krake_audio_open(rate: u32, channels: c_int) -> c_int {
+    let res = krake_syscall(116, rate as u64, channels as u64, 0, 0);
+    if res == u64::MAX { -1 } else { res as c_int }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn krake_audio_write(fd: c_int, samples: *const i16, count: usize) -> isize {
+    let res = krake_syscall(1, fd as i64 as u64, samples as u64, (count * 2) as u64, 0);
+    if res == u64::MAX { -1 } else { (res / 2) as isize }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn krake_audio_volume(fd: c_int, percent: c_int) -> c_int {
+    if krake_syscall(117, fd as i64 as u64, percent as u64, 0, 0) == u64::MAX { -1 } else { 0 }
+}
+
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn krake_get_event(wid: usize, out_event: *mut u32) -> c_int {
     let mut buf = [0u64; 8];
@@ -84,6 +101,10 @@ struct RawWindow {
     min_height: usize,
     event_handler: usize,
     w_type: u32,
+    decorated: bool,
+    minimized: bool,
+    workspace: usize,
+    title: [u8; 64],
 }
 
 static mut DOOM_WINDOW_BUFFER: usize = 0;
@@ -120,6 +141,10 @@ pub unsafe extern "C" fn krake_window_create(width: usize, height: usize, _trans
         min_height: 0,
         event_handler: 1,
         w_type: 3,
+        decorated: true,
+        minimized: false,
+        workspace: 0,
+        title: [0; 64],
     };
     krake_syscall(100, &w as *const _ as u64, 0, 0, 0) as usize
 }
@@ -146,6 +171,10 @@ pub unsafe extern "C" fn krake_window_draw(wid: usize) {
         min_height: 0,
         event_handler: 0,
         w_type: 3,
+        decorated: true,
+        minimized: false,
+        workspace: 0,
+        title: [0; 64],
     };
     krake_syscall(102, &w as *const _ as u64, 0, 0, 0);
 }
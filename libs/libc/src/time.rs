@@ -1,5 +1,28 @@
-use crate::sys::krake_get_time_ms;
-use core::ffi::c_long;
+use core::ffi::{c_int, c_long};
+
+pub const CLOCK_REALTIME: c_int = 0;
+pub const CLOCK_MONOTONIC: c_int = 1;
+pub const TIMER_ABSTIME: c_int = 1;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct timespec {
+    pub tv_sec: c_long,
+    pub tv_nsec: c_long,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct timeval {
+    pub tv_sec: c_long,
+    pub tv_usec: c_long,
+}
+
+#[repr(C)]
+pub struct timezone {
+    pub tz_minuteswest: c_int,
+    pub tz_dsttime: c_int,
+}
 
 #[repr(C)]
 pub struct tm {
@@ -14,9 +37,71 @@ pub struct tm {
     pub tm_isdst: i32,
 }
 
+fn ret(res: u64) -> c_int {
+    if res == u64::MAX { -1 } else { 0 }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn clock_gettime(clock_id: c_int, tp: *mut timespec) -> c_int {
+    ret(std::os::syscall(228, clock_id as u64, tp as u64, 0))
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn clock_getres(clock_id: c_int, res: *mut timespec) -> c_int {
+    ret(std::os::syscall(229, clock_id as u64, res as u64, 0))
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn clock_nanosleep(clock_id: c_int, flags: c_int, req: *const timespec, rem: *mut timespec) -> c_int {
+    if std::os::syscall(230, clock_id as u64, flags as u64, req as u64) == u64::MAX {
+        return 22; // EINVAL
+    }
+    if !rem.is_null() {
+        *rem = timespec { tv_sec: 0, tv_nsec: 0 };
+    }
+    0
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nanosleep(req: *const timespec, rem: *mut timespec) -> c_int {
+    if clock_nanosleep(CLOCK_MONOTONIC, 0, req, rem) == 0 { 0 } else { -1 }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gettimeofday(tv: *mut timeval, tz: *mut timezone) -> c_int {
+    if !tv.is_null() {
+        let mut ts = timespec { tv_sec: 0, tv_nsec: 0 };
+        if clock_gettime(CLOCK_REALTIME, &mut ts) != 0 {
+            return -1;
+        }
+        *tv = timeval { tv_sec: ts.tv_sec, tv_usec: ts.tv_nsec / 1000 };
+    }
+    if !tz.is_null() {
+        *tz = timezone { tz_minuteswest: 0, tz_dsttime: 0 };
+    }
+    0
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn time(t: *mut c_long) -> c_long {
+    let mut ts = timespec { tv_sec: 0, tv_nsec: 0 };
+    if clock_gettime(CLOCK_REALTIME, &mut ts) != 0 {
+        return -1;
+    }
+    if !t.is_null() {
+        *t = ts.tv_sec;
+    }
+    ts.tv_sec
+}
+
+/// Milliseconds of monotonic time, matching `CLOCKS_PER_SEC`.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn clock() -> c_long {
-    krake_get_time_ms() as c_long
+    let mut ts = timespec { tv_sec: 0, tv_nsec: 0 };
+    if clock_gettime(CLOCK_MONOTONIC, &mut ts) != 0 {
+        return -1;
+    }
+    ts.tv_sec * 1000 + ts.tv_nsec / 1_000_000
 }
 
 static mut STATIC_TM: tm = tm {
@@ -31,19 +116,77 @@ static mut STATIC_TM: tm = tm {
     tm_isdst: 0,
 };
 
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Normalises out-of-range fields the way C requires, treating the time as UTC.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mktime(tm: *mut tm) -> c_long {
+    if tm.is_null() {
+        return -1;
+    }
+    let t = &mut *tm;
+    let months = t.tm_year as i64 * 12 + t.tm_mon as i64;
+    let days = days_from_civil(1900 + months.div_euclid(12), months.rem_euclid(12) + 1, 1) + t.tm_mday as i64 - 1;
+    let secs = days * 86400 + t.tm_hour as i64 * 3600 + t.tm_min as i64 * 60 + t.tm_sec as i64;
+
+    let normalized = secs as c_long;
+    gmtime_r(&normalized, tm);
+    normalized
+}
+
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn mktime(_tm: *mut tm) -> c_long {
-    0 // Stub
+pub unsafe extern "C" fn gmtime_r(timep: *const c_long, result: *mut tm) -> *mut tm {
+    if timep.is_null() || result.is_null() {
+        return core::ptr::null_mut();
+    }
+    let secs = *timep as i64;
+    let days = secs.div_euclid(86400);
+    let rem = secs.rem_euclid(86400);
+    let (year, month, day) = civil_from_days(days);
+
+    *result = tm {
+        tm_sec: (rem % 60) as i32,
+        tm_min: (rem / 60 % 60) as i32,
+        tm_hour: (rem / 3600) as i32,
+        tm_mday: day as i32,
+        tm_mon: (month - 1) as i32,
+        tm_year: (year - 1900) as i32,
+        tm_wday: (days + 4).rem_euclid(7) as i32,
+        tm_yday: (days - days_from_civil(year, 1, 1)) as i32,
+        tm_isdst: 0,
+    };
+    result
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn gmtime(_timep: *const c_long) -> *mut tm {
-    &raw mut STATIC_TM
+pub unsafe extern "C" fn gmtime(timep: *const c_long) -> *mut tm {
+    gmtime_r(timep, &raw mut STATIC_TM)
 }
 
+/// There is no timezone database, so local time is UTC.
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn localtime(_timep: *const c_long) -> *mut tm {
-    &raw mut STATIC_TM
+pub unsafe extern "C" fn localtime(timep: *const c_long) -> *mut tm {
+    gmtime(timep)
 }
 
 #[unsafe(no_mangle)]
@@ -1,23 +1,11 @@
-use crate::sys::krake_sleep;
 use core::ffi::{c_char, c_int, c_long, c_uint, c_ulong, c_void};
 
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn usleep(usec: c_uint) -> c_int {
-    let ms = (usec + 999) / 1000;
-    krake_sleep(ms as usize);
+    std::os::sleep_ns(usec as u64 * 1000);
     0
 }
 
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn time(t: *mut c_long) -> c_long {
-    let ticks = std::os::syscall(109, 0, 0, 0); // SYS_GET_TICKS
-    let seconds = (ticks / 1000) as c_long;
-    if !t.is_null() {
-        *t = seconds;
-    }
-    seconds
-}
-
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn open(path: *const c_char, _flags: c_int, _mode: c_int) -> c_int {
     let path_str = core::ffi::CStr::from_ptr(path).to_string_lossy();
@@ -40,6 +40,12 @@ copy "target\bits64pie\release\cat" "tree\sys\bin\cat.elf"
 cargo build --package=taskbar --target=bits64pie.json --release
 copy "target\bits64pie\release\taskbar" "tree\sys\bin\taskbar.elf"
 
+cargo build --package=screenshot --target=bits64pie.json --release
+copy "target\bits64pie\release\screenshot" "tree\sys\bin\screenshot.elf"
+
+cargo build --package=tone --target=bits64pie.json --release
+copy "target\bits64pie\release\tone" "tree\sys\bin\tone.elf"
+
 cargo build --package=wasm_runner --target=bits64pie.json --release
 copy "target\bits64pie\release\wasm_runner" "tree\sys\bin\wasm_runner.elf"
 
@@ -50,6 +56,6 @@ wsl dd if=build/kernel.bin of=build/disk.img seek=6144 bs=512 conv=notrunc
 wsl genext2fs -d tree -b 262144 -B 1024 build/disk2.img
 wsl dd if=build/disk2.img of=build/disk.img seek=16384 bs=512 conv=notrunc
 
-qemu-system-x86_64 -drive file=build/disk.img,format=raw,if=virtio -serial stdio --no-reboot -device virtio-gpu-pci,xres=1024,yres=576 -display sdl -vga none -m 4G -accel whpx -machine kernel_irqchip=off
+qemu-system-x86_64 -drive file=build/disk.img,format=raw,if=virtio -serial stdio --no-reboot -device virtio-gpu-pci,xres=1024,yres=576 -display sdl -vga none -m 4G -audiodev wav,id=snd0,path=build/audio.wav -device AC97,audiodev=snd0 -device virtio-keyboard-pci -device virtio-tablet-pci -netdev user,id=n0 -device virtio-net-pci,netdev=n0 -accel whpx -machine kernel_irqchip=off
 
 REM pause
\ No newline at end of file
@@ -0,0 +1,74 @@
+use crate::io::{Error, Result, Write};
+use crate::os::syscall;
+
+const SYS_AUDIO_OPEN: u64 = 116;
+const SYS_AUDIO_VOLUME: u64 = 117;
+
+/// A PCM output stream mixed by the kernel. Samples are signed 16-bit, interleaved
+/// when `channels` is 2.
+pub struct AudioStream {
+    fd: usize,
+    rate: u32,
+    channels: usize,
+}
+
+impl AudioStream {
+    pub fn open(rate: u32, channels: usize) -> Result<Self> {
+        let res = unsafe { syscall(SYS_AUDIO_OPEN, rate as u64, channels as u64, 0) };
+        if res == u64::MAX {
+            Err(Error::from_raw_os_error(22)) // EINVAL
+        } else {
+            Ok(AudioStream { fd: res as usize, rate, channels })
+        }
+    }
+
+    pub fn rate(&self) -> u32 {
+        self.rate
+    }
+
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Blocks until every sample has been queued.
+    pub fn write_samples(&mut self, samples: &[i16]) -> Result<()> {
+        let bytes = unsafe { core::slice::from_raw_parts(samples.as_ptr() as *const u8, samples.len() * 2) };
+        self.write_all(bytes)
+    }
+
+    pub fn set_volume(&self, percent: u32) -> Result<()> {
+        let res = unsafe { syscall(SYS_AUDIO_VOLUME, self.fd as u64, percent as u64, 0) };
+        if res == u64::MAX { Err(Error::from_raw_os_error(9)) } else { Ok(()) }
+    }
+
+    pub fn as_raw_fd(&self) -> usize {
+        self.fd
+    }
+}
+
+impl Write for AudioStream {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let res = unsafe { syscall(1, self.fd as u64, buf.as_ptr() as u64, buf.len() as u64) };
+        if res == u64::MAX {
+            Err(Error::from_raw_os_error(5)) // EIO
+        } else {
+            Ok(res as usize)
+        }
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for AudioStream {
+    fn drop(&mut self) {
+        crate::os::file_close(self.fd);
+    }
+}
+
+pub fn set_master_volume(percent: u32) {
+    unsafe {
+        syscall(SYS_AUDIO_VOLUME, u64::MAX, percent as u64, 0);
+    }
+}
@@ -1,4 +1,8 @@
 use crate::os::syscall;
+use rust_alloc::string::String;
+use rust_alloc::vec::Vec;
+
+pub const TITLE_LEN: usize = 64;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 #[repr(C)]
@@ -32,6 +36,13 @@ pub struct Window {
 
     pub event_handler: usize,
     pub w_type: Items,
+
+    /// Ignored for anything but `Items::Window`.
+    pub decorated: bool,
+    pub minimized: bool,
+    /// Set by the compositor; new windows open on the workspace being shown.
+    pub workspace: usize,
+    pub title: [u8; TITLE_LEN],
 }
 
 impl Window {
@@ -53,8 +64,51 @@ impl Window {
             min_height: 0,
             event_handler: 0,
             w_type: Items::Window,
+            decorated: true,
+            minimized: false,
+            workspace: 0,
+            title: [0; TITLE_LEN],
         }
     }
+
+    pub fn set_title(&mut self, title: &str) {
+        self.title = title_bytes(title);
+    }
+}
+
+/// Encodes `title` for `Window::title`, truncated on a char boundary and NUL-terminated.
+pub fn title_bytes(title: &str) -> [u8; TITLE_LEN] {
+    let mut bytes = [0; TITLE_LEN];
+    let mut len = title.len().min(TITLE_LEN - 1);
+    while !title.is_char_boundary(len) {
+        len -= 1;
+    }
+    bytes[..len].copy_from_slice(&title.as_bytes()[..len]);
+    bytes
+}
+
+/// A window as reported by `list_windows`.
+#[derive(Debug, Copy, Clone)]
+#[repr(C)]
+pub struct WindowInfo {
+    pub id: usize,
+    pub pid: u64,
+    pub x: isize,
+    pub y: isize,
+    pub width: usize,
+    pub height: usize,
+    pub w_type: Items,
+    pub focused: bool,
+    pub minimized: bool,
+    pub workspace: usize,
+    pub title: [u8; TITLE_LEN],
+}
+
+impl WindowInfo {
+    pub fn title(&self) -> &str {
+        let len = self.title.iter().position(|&b| b == 0).unwrap_or(TITLE_LEN);
+        core::str::from_utf8(&self.title[..len]).unwrap_or("")
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -95,6 +149,11 @@ pub fn add_window(window: &Window) -> usize {
     }
 }
 
+/// Removes a window owned by the calling process.
+pub fn remove_window(wid: usize) -> bool {
+    unsafe { syscall(101, wid as u64, 0, 0) != u64::MAX }
+}
+
 pub fn update_window(window: &Window) {
     unsafe {
         syscall(102, window as *const _ as u64, 0, 0);
@@ -107,6 +166,112 @@ pub fn update_window_area(wid: usize, x: usize, y: usize, w: usize, h: usize) {
     }
 }
 
+/// Every live window, topmost first.
+pub fn list_windows() -> Vec<WindowInfo> {
+    let mut list: Vec<WindowInfo> = Vec::new();
+    loop {
+        let total = unsafe { syscall(123, list.as_mut_ptr() as u64, list.capacity() as u64, 0) } as usize;
+        if total <= list.capacity() {
+            unsafe { list.set_len(total) };
+            return list;
+        }
+        list.reserve(total);
+    }
+}
+
+/// Subscribes `wid` to `WindowCreated`, `WindowDestroyed`, `WindowFocused`,
+/// `WindowChanged`, `WindowSwitch` and `WorkspaceChanged` events.
+pub fn watch_windows(wid: usize, enable: bool) -> bool {
+    unsafe { syscall(124, wid as u64, enable as u64, 0) != u64::MAX }
+}
+
+/// Restores `wid` if minimized and focuses it.
+pub fn activate_window(wid: usize) -> bool {
+    unsafe { syscall(125, wid as u64, 0, 0) != u64::MAX }
+}
+
+pub fn minimize_window(wid: usize) -> bool {
+    unsafe { syscall(125, wid as u64, 1, 0) != u64::MAX }
+}
+
+/// Asks the app owning `wid` to close it, as its close button would.
+pub fn close_window(wid: usize) -> bool {
+    unsafe { syscall(125, wid as u64, 2, 0) != u64::MAX }
+}
+
+pub const WORKSPACE_COUNT: usize = 9;
+
+pub fn move_to_workspace(wid: usize, workspace: usize) -> bool {
+    unsafe { syscall(125, wid as u64, 3, workspace as u64) != u64::MAX }
+}
+
+pub fn current_workspace() -> usize {
+    unsafe { syscall(128, u64::MAX, 0, 0) as usize }
+}
+
+pub fn switch_workspace(workspace: usize) -> bool {
+    unsafe { syscall(128, workspace as u64, 0, 0) != u64::MAX }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(u64)]
+pub enum Layout {
+    Floating = 0,
+    MasterStack = 1,
+    Grid = 2,
+}
+
+/// Switches the compositor layout. `gap` is in pixels and `master_ratio` is the percentage
+/// of the screen width given to the master window.
+pub fn set_tiling(layout: Layout, gap: usize, master_ratio: usize) -> bool {
+    unsafe { syscall(118, layout as u64, gap as u64, master_ratio as u64) != u64::MAX }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(u64)]
+pub enum ScanoutMode {
+    Primary = 0,
+    Mirror = 1,
+    Span = 2,
+}
+
+/// Changes the screen resolution; 0x0 picks the host's preferred size. Spanned modes
+/// always use each scanout's preferred size. Windows get `ScreenResized` afterwards.
+pub fn set_display_mode(width: usize, height: usize, mode: ScanoutMode) -> bool {
+    unsafe { syscall(127, width as u64, height as u64, mode as u64) != u64::MAX }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(u64)]
+pub enum CursorShape {
+    Arrow = 0,
+    Text = 1,
+    Hand = 2,
+    ResizeHorizontal = 3,
+    ResizeVertical = 4,
+    ResizeDiagonal = 5,
+    Busy = 6,
+}
+
+pub const MAX_CURSOR_SIZE: usize = 64;
+
+/// Sets the cursor shown while the pointer is over the client area of `wid`.
+pub fn set_cursor(wid: usize, shape: CursorShape) -> bool {
+    unsafe { syscall(129, wid as u64, shape as u64, 0) != u64::MAX }
+}
+
+/// Like `set_cursor` with an ARGB image of up to `MAX_CURSOR_SIZE` square; `hot_x`, `hot_y`
+/// is the pixel that points.
+pub fn set_custom_cursor(wid: usize, width: usize, height: usize, hot_x: usize, hot_y: usize, pixels: &[u32]) -> bool {
+    if pixels.len() < width * height {
+        return false;
+    }
+    let hotspot = ((hot_x as u64) << 32) | hot_y as u64;
+    unsafe {
+        crate::os::syscall6(129, wid as u64, 255, pixels.as_ptr() as u64, width as u64, height as u64, hotspot) != u64::MAX
+    }
+}
+
 pub fn get_screen_width() -> usize {
     unsafe { syscall(106, 0, 0, 0) as usize }
 }
@@ -125,3 +290,106 @@ pub fn get_mouse_pos() -> (usize, usize) {
 pub fn get_events(window_id: usize, events_ptr: usize, max_events: usize) {
     unsafe { syscall(104, window_id as u64, events_ptr as u64, max_events as u64); }
 }
+
+/// Sleeps until `window_id` has events or `timeout_ns` passes (`None` waits forever), then
+/// copies up to `max_events` into `events_ptr`. With `max_events` of 0 the events are left
+/// queued and only their count is returned. Returns `None` once the window is gone.
+pub fn wait_events(window_id: usize, events_ptr: usize, max_events: usize, timeout_ns: Option<u64>) -> Option<usize> {
+    let timeout = timeout_ns.unwrap_or(u64::MAX);
+    let res = unsafe { crate::os::syscall4(119, window_id as u64, events_ptr as u64, max_events as u64, timeout) };
+    if res == u64::MAX { None } else { Some(res as usize) }
+}
+
+pub const MIME_TEXT: &str = "text/plain;charset=utf-8";
+
+/// Offers `data` as the system selection on behalf of `wid`.
+pub fn set_clipboard(wid: usize, mime: &str, data: &[u8]) -> bool {
+    unsafe {
+        crate::os::syscall5(120, wid as u64, mime.as_ptr() as u64, mime.len() as u64, data.as_ptr() as u64, data.len() as u64) != u64::MAX
+    }
+}
+
+/// Gives up the selection if `wid` still owns it.
+pub fn clear_clipboard(wid: usize) -> bool {
+    unsafe { crate::os::syscall5(120, wid as u64, 0, 0, 0, 0) != u64::MAX }
+}
+
+/// Reads the selection if it is available as `mime`; an empty `mime` accepts any type.
+pub fn get_clipboard(mime: &str) -> Option<Vec<u8>> {
+    let mut data = Vec::new();
+    loop {
+        let len = unsafe {
+            crate::os::syscall4(121, mime.as_ptr() as u64, mime.len() as u64, data.as_mut_ptr() as u64, data.capacity() as u64)
+        };
+        if len == u64::MAX {
+            return None;
+        }
+        if len as usize <= data.capacity() {
+            unsafe { data.set_len(len as usize) };
+            return Some(data);
+        }
+        // The selection may change between calls, so size up and ask again.
+        data.reserve(len as usize);
+    }
+}
+
+pub fn get_clipboard_text() -> Option<String> {
+    get_clipboard("text/plain").and_then(|data| String::from_utf8(data).ok())
+}
+
+/// MIME type of the current selection, if there is one.
+pub fn clipboard_type() -> Option<String> {
+    let mut buf = [0u8; 128];
+    let len = unsafe { syscall(122, buf.as_mut_ptr() as u64, buf.len() as u64, 0) };
+    if len == u64::MAX {
+        return None;
+    }
+    let len = (len as usize).min(buf.len());
+    core::str::from_utf8(&buf[..len]).ok().map(String::from)
+}
+
+/// A captured frame in ARGB, `width * height` pixels with no padding.
+pub struct Capture {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u32>,
+}
+
+impl Capture {
+    pub fn to_png(&self, alpha: bool) -> Vec<u8> {
+        crate::png::encode(self.width, self.height, &self.pixels, alpha)
+    }
+}
+
+/// Grabs the composed screen, or the client area of window `wid` when given.
+pub fn capture(wid: Option<usize>) -> Option<Capture> {
+    let wid = wid.unwrap_or(0) as u64;
+    let mut pixels: Vec<u32> = Vec::new();
+    loop {
+        let res = unsafe { syscall(126, wid, pixels.as_mut_ptr() as u64, pixels.capacity() as u64) };
+        if res == u64::MAX {
+            return None;
+        }
+
+        let (width, height) = ((res >> 32) as usize, (res & 0xFFFFFFFF) as usize);
+        if width * height <= pixels.capacity() {
+            unsafe { pixels.set_len(width * height) };
+            return Some(Capture { width, height, pixels });
+        }
+        pixels.reserve(width * height);
+    }
+}
+
+/// Switches every running app to the theme in `/sys/themes/<name>.theme`. Windows get
+/// `ThemeChanged` afterwards.
+pub fn set_theme(name: &str) -> bool {
+    unsafe { syscall(130, name.as_ptr() as u64, name.len() as u64, 0) != u64::MAX }
+}
+
+/// Name of the current system theme.
+pub fn get_theme() -> String {
+    let mut buf = [0u8; 64];
+    let len = unsafe { syscall(131, buf.as_mut_ptr() as u64, buf.len() as u64, 0) };
+    let len = (len as usize).min(buf.len());
+    core::str::from_utf8(&buf[..len]).map(String::from).unwrap_or_default()
+}
@@ -8,8 +8,11 @@ pub mod alloc;
 pub mod os;
 pub mod sys;
 pub mod graphics;
+pub mod png;
 pub mod sync;
 pub mod fs;
+pub mod net;
+pub mod audio;
 pub mod math;
 pub mod time;
 pub mod thread;
@@ -0,0 +1,315 @@
+mod unix;
+
+use crate::io::{Error, Read, Result, Write};
+use crate::os::{syscall, syscall6};
+use core::fmt;
+use core::str::FromStr;
+
+pub use unix::{UnixDatagram, UnixListener, UnixStream};
+
+const AF_INET: u64 = 2;
+const SOCK_STREAM: u64 = 1;
+const SOCK_DGRAM: u64 = 2;
+
+const SYS_SOCKET: u64 = 41;
+const SYS_CONNECT: u64 = 42;
+const SYS_ACCEPT: u64 = 43;
+const SYS_SENDTO: u64 = 44;
+const SYS_RECVFROM: u64 = 45;
+const SYS_BIND: u64 = 49;
+const SYS_LISTEN: u64 = 50;
+const SYS_GETSOCKNAME: u64 = 51;
+const SYS_GETPEERNAME: u64 = 52;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ipv4Addr {
+    octets: [u8; 4],
+}
+
+impl Ipv4Addr {
+    pub const UNSPECIFIED: Ipv4Addr = Ipv4Addr::new(0, 0, 0, 0);
+    pub const LOCALHOST: Ipv4Addr = Ipv4Addr::new(127, 0, 0, 1);
+    pub const BROADCAST: Ipv4Addr = Ipv4Addr::new(255, 255, 255, 255);
+
+    pub const fn new(a: u8, b: u8, c: u8, d: u8) -> Self {
+        Ipv4Addr { octets: [a, b, c, d] }
+    }
+
+    pub fn octets(&self) -> [u8; 4] {
+        self.octets
+    }
+
+    pub fn is_loopback(&self) -> bool {
+        self.octets[0] == 127
+    }
+}
+
+impl fmt::Display for Ipv4Addr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let [a, b, c, d] = self.octets;
+        write!(f, "{}.{}.{}.{}", a, b, c, d)
+    }
+}
+
+impl FromStr for Ipv4Addr {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut octets = [0u8; 4];
+        let mut parts = s.split('.');
+        for octet in octets.iter_mut() {
+            *octet = parts.next().and_then(|p| p.parse().ok()).ok_or(Error::from_raw_os_error(22))?;
+        }
+        if parts.next().is_some() {
+            return Err(Error::from_raw_os_error(22)); // EINVAL
+        }
+        Ok(Ipv4Addr { octets })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SocketAddrV4 {
+    ip: Ipv4Addr,
+    port: u16,
+}
+
+impl SocketAddrV4 {
+    pub const fn new(ip: Ipv4Addr, port: u16) -> Self {
+        SocketAddrV4 { ip, port }
+    }
+
+    pub fn ip(&self) -> &Ipv4Addr {
+        &self.ip
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+}
+
+impl fmt::Display for SocketAddrV4 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.ip, self.port)
+    }
+}
+
+impl FromStr for SocketAddrV4 {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (ip, port) = s.rsplit_once(':').ok_or(Error::from_raw_os_error(22))?;
+        let port = port.parse().map_err(|_| Error::from_raw_os_error(22))?;
+        Ok(SocketAddrV4::new(ip.parse()?, port))
+    }
+}
+
+#[repr(C)]
+struct SockAddrIn {
+    family: u16,
+    port: u16,
+    addr: [u8; 4],
+    zero: [u8; 8],
+}
+
+impl SockAddrIn {
+    fn from_addr(addr: &SocketAddrV4) -> Self {
+        SockAddrIn { family: AF_INET as u16, port: addr.port.to_be(), addr: addr.ip.octets, zero: [0; 8] }
+    }
+
+    fn empty() -> Self {
+        SockAddrIn { family: 0, port: 0, addr: [0; 4], zero: [0; 8] }
+    }
+
+    fn to_addr(&self) -> SocketAddrV4 {
+        let [a, b, c, d] = self.addr;
+        SocketAddrV4::new(Ipv4Addr::new(a, b, c, d), u16::from_be(self.port))
+    }
+}
+
+fn check(res: u64, code: i32) -> Result<u64> {
+    if res == u64::MAX { Err(Error::from_raw_os_error(code)) } else { Ok(res) }
+}
+
+struct Socket {
+    fd: usize,
+}
+
+impl Socket {
+    fn new(sock_type: u64) -> Result<Self> {
+        let fd = check(unsafe { syscall(SYS_SOCKET, AF_INET, sock_type, 0) }, 24)?; // EMFILE
+        Ok(Socket { fd: fd as usize })
+    }
+
+    fn bind(&self, addr: &SocketAddrV4) -> Result<()> {
+        let raw = SockAddrIn::from_addr(addr);
+        let res = unsafe { syscall(SYS_BIND, self.fd as u64, &raw as *const _ as u64, core::mem::size_of::<SockAddrIn>() as u64) };
+        check(res, 98).map(|_| ()) // EADDRINUSE
+    }
+
+    fn connect(&self, addr: &SocketAddrV4) -> Result<()> {
+        let raw = SockAddrIn::from_addr(addr);
+        let res = unsafe { syscall(SYS_CONNECT, self.fd as u64, &raw as *const _ as u64, core::mem::size_of::<SockAddrIn>() as u64) };
+        check(res, 111).map(|_| ()) // ECONNREFUSED
+    }
+
+    fn name(&self, num: u64) -> Result<SocketAddrV4> {
+        let mut raw = SockAddrIn::empty();
+        let mut len = core::mem::size_of::<SockAddrIn>() as u32;
+        let res = unsafe { syscall(num, self.fd as u64, &mut raw as *mut _ as u64, &mut len as *mut u32 as u64) };
+        check(res, 107).map(|_| raw.to_addr()) // ENOTCONN
+    }
+
+    fn send_to(&self, buf: &[u8], dest: Option<&SocketAddrV4>) -> Result<usize> {
+        let raw = dest.map(SockAddrIn::from_addr);
+        let (ptr, len) = match &raw {
+            Some(r) => (r as *const _ as u64, core::mem::size_of::<SockAddrIn>() as u64),
+            None => (0, 0),
+        };
+        let res = unsafe { syscall6(SYS_SENDTO, self.fd as u64, buf.as_ptr() as u64, buf.len() as u64, 0, ptr, len) };
+        check(res, 32).map(|n| n as usize) // EPIPE
+    }
+
+    fn recv_from(&self, buf: &mut [u8]) -> Result<(usize, SocketAddrV4)> {
+        let mut raw = SockAddrIn::empty();
+        let mut len = core::mem::size_of::<SockAddrIn>() as u32;
+        let res = unsafe {
+            syscall6(SYS_RECVFROM, self.fd as u64, buf.as_mut_ptr() as u64, buf.len() as u64, 0, &mut raw as *mut _ as u64, &mut len as *mut u32 as u64)
+        };
+        check(res, 104).map(|n| (n as usize, raw.to_addr())) // ECONNRESET
+    }
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        crate::os::file_close(self.fd);
+    }
+}
+
+pub struct TcpStream {
+    socket: Socket,
+}
+
+impl TcpStream {
+    pub fn connect(addr: SocketAddrV4) -> Result<TcpStream> {
+        let socket = Socket::new(SOCK_STREAM)?;
+        socket.connect(&addr)?;
+        Ok(TcpStream { socket })
+    }
+
+    pub fn peer_addr(&self) -> Result<SocketAddrV4> {
+        self.socket.name(SYS_GETPEERNAME)
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddrV4> {
+        self.socket.name(SYS_GETSOCKNAME)
+    }
+
+    pub fn as_raw_fd(&self) -> usize {
+        self.socket.fd
+    }
+}
+
+impl Read for TcpStream {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.socket.recv_from(buf).map(|(n, _)| n)
+    }
+}
+
+impl Write for TcpStream {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.socket.send_to(buf, None)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+pub struct TcpListener {
+    socket: Socket,
+}
+
+impl TcpListener {
+    pub fn bind(addr: SocketAddrV4) -> Result<TcpListener> {
+        let socket = Socket::new(SOCK_STREAM)?;
+        socket.bind(&addr)?;
+        check(unsafe { syscall(SYS_LISTEN, socket.fd as u64, 16, 0) }, 22)?;
+        Ok(TcpListener { socket })
+    }
+
+    pub fn accept(&self) -> Result<(TcpStream, SocketAddrV4)> {
+        let mut raw = SockAddrIn::empty();
+        let mut len = core::mem::size_of::<SockAddrIn>() as u32;
+        let res = unsafe { syscall(SYS_ACCEPT, self.socket.fd as u64, &mut raw as *mut _ as u64, &mut len as *mut u32 as u64) };
+        let fd = check(res, 24)?;
+        Ok((TcpStream { socket: Socket { fd: fd as usize } }, raw.to_addr()))
+    }
+
+    pub fn incoming(&self) -> Incoming<'_> {
+        Incoming { listener: self }
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddrV4> {
+        self.socket.name(SYS_GETSOCKNAME)
+    }
+
+    pub fn as_raw_fd(&self) -> usize {
+        self.socket.fd
+    }
+}
+
+pub struct Incoming<'a> {
+    listener: &'a TcpListener,
+}
+
+impl<'a> Iterator for Incoming<'a> {
+    type Item = Result<TcpStream>;
+
+    fn next(&mut self) -> Option<Result<TcpStream>> {
+        Some(self.listener.accept().map(|(stream, _)| stream))
+    }
+}
+
+pub struct UdpSocket {
+    socket: Socket,
+}
+
+impl UdpSocket {
+    pub fn bind(addr: SocketAddrV4) -> Result<UdpSocket> {
+        let socket = Socket::new(SOCK_DGRAM)?;
+        socket.bind(&addr)?;
+        Ok(UdpSocket { socket })
+    }
+
+    pub fn connect(&self, addr: SocketAddrV4) -> Result<()> {
+        self.socket.connect(&addr)
+    }
+
+    pub fn send_to(&self, buf: &[u8], addr: SocketAddrV4) -> Result<usize> {
+        self.socket.send_to(buf, Some(&addr))
+    }
+
+    pub fn recv_from(&self, buf: &mut [u8]) -> Result<(usize, SocketAddrV4)> {
+        self.socket.recv_from(buf)
+    }
+
+    pub fn send(&self, buf: &[u8]) -> Result<usize> {
+        self.socket.send_to(buf, None)
+    }
+
+    pub fn recv(&self, buf: &mut [u8]) -> Result<usize> {
+        self.socket.recv_from(buf).map(|(n, _)| n)
+    }
+
+    pub fn peer_addr(&self) -> Result<SocketAddrV4> {
+        self.socket.name(SYS_GETPEERNAME)
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddrV4> {
+        self.socket.name(SYS_GETSOCKNAME)
+    }
+
+    pub fn as_raw_fd(&self) -> usize {
+        self.socket.fd
+    }
+}
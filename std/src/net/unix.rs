@@ -0,0 +1,304 @@
+use crate::io::{Error, Read, Result, Write};
+use crate::os::syscall;
+use rust_alloc::string::String;
+
+const AF_UNIX: u64 = 1;
+const SOCK_STREAM: u64 = 1;
+const SOCK_DGRAM: u64 = 2;
+
+const SYS_SOCKET: u64 = 41;
+const SYS_CONNECT: u64 = 42;
+const SYS_ACCEPT: u64 = 43;
+const SYS_SENDMSG: u64 = 46;
+const SYS_RECVMSG: u64 = 47;
+const SYS_BIND: u64 = 49;
+const SYS_LISTEN: u64 = 50;
+const SYS_SOCKETPAIR: u64 = 53;
+
+const SOL_SOCKET: i32 = 1;
+const SCM_RIGHTS: i32 = 1;
+const MAX_FDS: usize = 16;
+
+#[repr(C)]
+struct SockAddrUn {
+    family: u16,
+    path: [u8; 108],
+}
+
+impl SockAddrUn {
+    fn new(path: &str) -> Result<Self> {
+        let mut raw = SockAddrUn { family: AF_UNIX as u16, path: [0; 108] };
+        if path.is_empty() || path.len() >= raw.path.len() {
+            return Err(Error::from_raw_os_error(22)); // EINVAL
+        }
+        raw.path[..path.len()].copy_from_slice(path.as_bytes());
+        Ok(raw)
+    }
+
+    fn empty() -> Self {
+        SockAddrUn { family: 0, path: [0; 108] }
+    }
+
+    fn path(&self) -> Option<String> {
+        let end = self.path.iter().position(|&b| b == 0).unwrap_or(self.path.len());
+        if end == 0 { None } else { Some(String::from_utf8_lossy(&self.path[..end]).into_owned()) }
+    }
+}
+
+#[repr(C)]
+struct IoVec {
+    base: u64,
+    len: u64,
+}
+
+#[repr(C)]
+struct MsgHdr {
+    name: u64,
+    namelen: u32,
+    iov: u64,
+    iovlen: u64,
+    control: u64,
+    controllen: u64,
+    flags: i32,
+}
+
+#[repr(C)]
+struct Control {
+    len: u64,
+    level: i32,
+    ty: i32,
+    fds: [i32; MAX_FDS],
+}
+
+fn check(res: u64, code: i32) -> Result<u64> {
+    if res == u64::MAX { Err(Error::from_raw_os_error(code)) } else { Ok(res) }
+}
+
+struct Socket {
+    fd: usize,
+}
+
+impl Socket {
+    fn new(sock_type: u64) -> Result<Self> {
+        let fd = check(unsafe { syscall(SYS_SOCKET, AF_UNIX, sock_type, 0) }, 24)?; // EMFILE
+        Ok(Socket { fd: fd as usize })
+    }
+
+    fn pair(sock_type: u64) -> Result<(Socket, Socket)> {
+        let mut fds = [0i32; 2];
+        let res = unsafe { crate::os::syscall4(SYS_SOCKETPAIR, AF_UNIX, sock_type, 0, fds.as_mut_ptr() as u64) };
+        check(res, 24)?;
+        Ok((Socket { fd: fds[0] as usize }, Socket { fd: fds[1] as usize }))
+    }
+
+    fn addr_call(&self, num: u64, path: &str, code: i32) -> Result<()> {
+        let raw = SockAddrUn::new(path)?;
+        let res = unsafe { syscall(num, self.fd as u64, &raw as *const _ as u64, core::mem::size_of::<SockAddrUn>() as u64) };
+        check(res, code).map(|_| ())
+    }
+
+    fn send_msg(&self, buf: &[u8], fds: &[usize], dest: Option<&str>) -> Result<usize> {
+        if fds.len() > MAX_FDS {
+            return Err(Error::from_raw_os_error(22));
+        }
+        let addr = match dest {
+            Some(path) => Some(SockAddrUn::new(path)?),
+            None => None,
+        };
+
+        let mut iov = IoVec { base: buf.as_ptr() as u64, len: buf.len() as u64 };
+        let mut control = Control { len: (16 + fds.len() * 4) as u64, level: SOL_SOCKET, ty: SCM_RIGHTS, fds: [0; MAX_FDS] };
+        for (slot, fd) in control.fds.iter_mut().zip(fds) {
+            *slot = *fd as i32;
+        }
+
+        let hdr = MsgHdr {
+            name: addr.as_ref().map(|a| a as *const _ as u64).unwrap_or(0),
+            namelen: if addr.is_some() { core::mem::size_of::<SockAddrUn>() as u32 } else { 0 },
+            iov: &mut iov as *mut IoVec as u64,
+            iovlen: 1,
+            control: if fds.is_empty() { 0 } else { &control as *const Control as u64 },
+            controllen: if fds.is_empty() { 0 } else { control.len },
+            flags: 0,
+        };
+        let res = unsafe { syscall(SYS_SENDMSG, self.fd as u64, &hdr as *const MsgHdr as u64, 0) };
+        check(res, 32).map(|n| n as usize) // EPIPE
+    }
+
+    fn recv_msg(&self, buf: &mut [u8], fds: &mut [usize]) -> Result<(usize, usize, Option<String>)> {
+        let mut addr = SockAddrUn::empty();
+        let mut iov = IoVec { base: buf.as_mut_ptr() as u64, len: buf.len() as u64 };
+        let mut control = Control { len: 0, level: 0, ty: 0, fds: [0; MAX_FDS] };
+        let capacity = fds.len().min(MAX_FDS);
+
+        let mut hdr = MsgHdr {
+            name: &mut addr as *mut SockAddrUn as u64,
+            namelen: core::mem::size_of::<SockAddrUn>() as u32,
+            iov: &mut iov as *mut IoVec as u64,
+            iovlen: 1,
+            control: &mut control as *mut Control as u64,
+            controllen: (16 + capacity * 4) as u64,
+            flags: 0,
+        };
+        let res = unsafe { syscall(SYS_RECVMSG, self.fd as u64, &mut hdr as *mut MsgHdr as u64, 0) };
+        let n = check(res, 104)? as usize; // ECONNRESET
+
+        let mut count = 0;
+        if hdr.controllen >= 16 && control.level == SOL_SOCKET && control.ty == SCM_RIGHTS {
+            count = ((control.len as usize - 16) / 4).min(capacity);
+            for i in 0..count {
+                fds[i] = control.fds[i] as usize;
+            }
+        }
+        Ok((n, count, addr.path()))
+    }
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        crate::os::file_close(self.fd);
+    }
+}
+
+pub struct UnixStream {
+    socket: Socket,
+}
+
+impl UnixStream {
+    pub fn connect(path: &str) -> Result<UnixStream> {
+        let socket = Socket::new(SOCK_STREAM)?;
+        socket.addr_call(SYS_CONNECT, path, 111)?; // ECONNREFUSED
+        Ok(UnixStream { socket })
+    }
+
+    pub fn pair() -> Result<(UnixStream, UnixStream)> {
+        let (a, b) = Socket::pair(SOCK_STREAM)?;
+        Ok((UnixStream { socket: a }, UnixStream { socket: b }))
+    }
+
+    /// Sends `buf` together with the given descriptors; the receiver gets its own
+    /// copies and the caller's descriptors stay open.
+    pub fn send_with_fds(&self, buf: &[u8], fds: &[usize]) -> Result<usize> {
+        self.socket.send_msg(buf, fds, None)
+    }
+
+    /// Returns the number of bytes and the number of descriptors written to `fds`.
+    pub fn recv_with_fds(&self, buf: &mut [u8], fds: &mut [usize]) -> Result<(usize, usize)> {
+        self.socket.recv_msg(buf, fds).map(|(n, count, _)| (n, count))
+    }
+
+    pub fn as_raw_fd(&self) -> usize {
+        self.socket.fd
+    }
+
+    pub fn from_raw_fd(fd: usize) -> Self {
+        UnixStream { socket: Socket { fd } }
+    }
+}
+
+impl Read for UnixStream {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.socket.recv_msg(buf, &mut []).map(|(n, _, _)| n)
+    }
+}
+
+impl Write for UnixStream {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.socket.send_msg(buf, &[], None)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+pub struct UnixListener {
+    socket: Socket,
+}
+
+impl UnixListener {
+    pub fn bind(path: &str) -> Result<UnixListener> {
+        let socket = Socket::new(SOCK_STREAM)?;
+        socket.addr_call(SYS_BIND, path, 98)?; // EADDRINUSE
+        check(unsafe { syscall(SYS_LISTEN, socket.fd as u64, 16, 0) }, 22)?;
+        Ok(UnixListener { socket })
+    }
+
+    pub fn accept(&self) -> Result<UnixStream> {
+        let fd = check(unsafe { syscall(SYS_ACCEPT, self.socket.fd as u64, 0, 0) }, 24)?;
+        Ok(UnixStream { socket: Socket { fd: fd as usize } })
+    }
+
+    pub fn incoming(&self) -> Incoming<'_> {
+        Incoming { listener: self }
+    }
+
+    pub fn as_raw_fd(&self) -> usize {
+        self.socket.fd
+    }
+}
+
+pub struct Incoming<'a> {
+    listener: &'a UnixListener,
+}
+
+impl<'a> Iterator for Incoming<'a> {
+    type Item = Result<UnixStream>;
+
+    fn next(&mut self) -> Option<Result<UnixStream>> {
+        Some(self.listener.accept())
+    }
+}
+
+pub struct UnixDatagram {
+    socket: Socket,
+}
+
+impl UnixDatagram {
+    pub fn bind(path: &str) -> Result<UnixDatagram> {
+        let socket = Socket::new(SOCK_DGRAM)?;
+        socket.addr_call(SYS_BIND, path, 98)?;
+        Ok(UnixDatagram { socket })
+    }
+
+    pub fn unbound() -> Result<UnixDatagram> {
+        Ok(UnixDatagram { socket: Socket::new(SOCK_DGRAM)? })
+    }
+
+    pub fn pair() -> Result<(UnixDatagram, UnixDatagram)> {
+        let (a, b) = Socket::pair(SOCK_DGRAM)?;
+        Ok((UnixDatagram { socket: a }, UnixDatagram { socket: b }))
+    }
+
+    pub fn connect(&self, path: &str) -> Result<()> {
+        self.socket.addr_call(SYS_CONNECT, path, 111)
+    }
+
+    pub fn send_to(&self, buf: &[u8], path: &str) -> Result<usize> {
+        self.socket.send_msg(buf, &[], Some(path))
+    }
+
+    pub fn recv_from(&self, buf: &mut [u8]) -> Result<(usize, Option<String>)> {
+        self.socket.recv_msg(buf, &mut []).map(|(n, _, from)| (n, from))
+    }
+
+    pub fn send(&self, buf: &[u8]) -> Result<usize> {
+        self.socket.send_msg(buf, &[], None)
+    }
+
+    pub fn recv(&self, buf: &mut [u8]) -> Result<usize> {
+        self.socket.recv_msg(buf, &mut []).map(|(n, _, _)| n)
+    }
+
+    pub fn send_with_fds(&self, buf: &[u8], fds: &[usize]) -> Result<usize> {
+        self.socket.send_msg(buf, fds, None)
+    }
+
+    pub fn recv_with_fds(&self, buf: &mut [u8], fds: &mut [usize]) -> Result<(usize, usize)> {
+        self.socket.recv_msg(buf, fds).map(|(n, count, _)| (n, count))
+    }
+
+    pub fn as_raw_fd(&self) -> usize {
+        self.socket.fd
+    }
+}
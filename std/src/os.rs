@@ -12,12 +12,58 @@ pub fn debug_print(s: &str) {
 }
 
 pub fn sleep(ms: u64) {
-    if ms > 10 {
-        unsafe {
-            syscall(35, ms, 0, 0);
-        }
+    sleep_ns(ms.saturating_mul(1_000_000));
+}
+
+pub const CLOCK_REALTIME: u64 = 0;
+pub const CLOCK_MONOTONIC: u64 = 1;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Timespec {
+    pub tv_sec: i64,
+    pub tv_nsec: i64,
+}
+
+impl Timespec {
+    pub fn from_nanos(ns: u64) -> Self {
+        Timespec { tv_sec: (ns / 1_000_000_000) as i64, tv_nsec: (ns % 1_000_000_000) as i64 }
+    }
+
+    pub fn as_nanos(&self) -> u64 {
+        self.tv_sec as u64 * 1_000_000_000 + self.tv_nsec as u64
+    }
+}
 
+/// Nanoseconds on `clock`, or `None` for an unknown clock id.
+pub fn clock_gettime(clock: u64) -> Option<u64> {
+    let mut ts = Timespec::default();
+    let res = unsafe { syscall(228, clock, &mut ts as *mut Timespec as u64, 0) };
+    if res == u64::MAX { None } else { Some(ts.as_nanos()) }
+}
+
+pub fn clock_getres(clock: u64) -> Option<u64> {
+    let mut ts = Timespec::default();
+    let res = unsafe { syscall(229, clock, &mut ts as *mut Timespec as u64, 0) };
+    if res == u64::MAX { None } else { Some(ts.as_nanos()) }
+}
+
+pub fn sleep_ns(ns: u64) {
+    if ns == 0 {
         yield_task();
+        return;
+    }
+    let ts = Timespec::from_nanos(ns);
+    unsafe {
+        syscall(230, CLOCK_MONOTONIC, 0, &ts as *const Timespec as u64);
+    }
+}
+
+/// Sleeps until `deadline` nanoseconds on `clock` have been reached.
+pub fn sleep_until(clock: u64, deadline: u64) {
+    let ts = Timespec::from_nanos(deadline);
+    unsafe {
+        syscall(230, clock, 1, &ts as *const Timespec as u64);
     }
 }
 
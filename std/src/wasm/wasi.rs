@@ -127,23 +127,23 @@ fn environ_sizes_get<T: Config>(store: &mut Store<'_, T>, args: Vec<Value>) -> R
     Ok(vec![Value::I32(0)])
 }
 
+fn wasi_clock(id: u32) -> u64 {
+    if id == 0 { crate::os::CLOCK_REALTIME } else { crate::os::CLOCK_MONOTONIC }
+}
+
 fn clock_res_get<T: Config>(store: &mut Store<'_, T>, args: Vec<Value>) -> Result<Vec<Value>, HaltExecutionError> {
+    let id = match args.get(0) { Some(Value::I32(v)) => *v, _ => 0 };
     let r_ptr = match args.get(1) { Some(Value::I32(v)) => *v as u32, _ => return Ok(vec![Value::I32(28)]) };
-    let _ = write_u64(store, r_ptr, 1_000_000);
+    let res = crate::os::clock_getres(wasi_clock(id)).unwrap_or(1);
+    if write_u64(store, r_ptr, res).is_err() { return Ok(vec![Value::I32(28)]); }
     Ok(vec![Value::I32(0)])
 }
 
 fn clock_time_get<T: Config>(store: &mut Store<'_, T>, args: Vec<Value>) -> Result<Vec<Value>, HaltExecutionError> {
     let id = match args.get(0) { Some(Value::I32(v)) => *v, _ => 0 };
     let t_ptr = match args.get(2) { Some(Value::I32(v)) => *v as u32, _ => return Ok(vec![Value::I32(28)]) };
-    let nanos = if id == 1 { crate::os::get_system_ticks() * 1_000_000 } else {
-        let (d, m, y) = crate::os::get_date();
-        let (h, min, s) = crate::os::get_time();
-        let yrs = if y >= 1970 { (y - 1970) as u64 } else { 0 };
-        let mut secs = yrs * 31_536_000 + (m as u64).saturating_sub(1) * 2_592_000 + (d as u64).saturating_sub(1) * 86_400 + (h as u64) * 3600 + (min as u64) * 60 + s as u64;
-        (secs * 1_000_000_000) + (crate::os::get_system_ticks() % 1000) * 1_000_000
-    };
-    let _ = write_u64(store, t_ptr, nanos);
+    let nanos = match crate::os::clock_gettime(wasi_clock(id)) { Some(ns) => ns, None => return Ok(vec![Value::I32(28)]) };
+    if write_u64(store, t_ptr, nanos).is_err() { return Ok(vec![Value::I32(28)]); }
     Ok(vec![Value::I32(0)])
 }
 
@@ -2,7 +2,7 @@
 #![no_main]
 
 extern crate alloc;
-use inkui::{Color, Size, Widget, Window};
+use inkui::{Color, Event, Size, Widget, Window};
 use std::fs::File;
 use std::graphics::Items;
 use std::io::Read;
@@ -58,7 +58,11 @@ pub extern "C" fn main() -> i32 {
     test_wasm();
 
     loop {
-        std::os::yield_task();
+        for event in win_wallpaper.wait_events(None) {
+            if let Event::ScreenResized(e) = event {
+                win_wallpaper.set_bounds(0, 0, e.width, e.height);
+            }
+        }
     }
 
     0